@@ -1,46 +1,363 @@
+use ckb_types::packed;
+use ckb_types::prelude::{IntoTransactionView, Pack};
 use common::traits::{
     api::APIAdapter,
     async_trait,
+    axon_rpc_client::AxonRpc,
+    ckb_rpc_client::CkbRpc,
     query::TransactionStorage,
-    smt::{DelegateSmtStorage, RewardSmtStorage, StakeSmtStorage},
+    smt::{DelegateSmtStorage, ProposalSmtStorage, RewardSmtStorage, SmtSnapshot, StakeSmtStorage},
+    tx_builder::{
+        IBatchTxBuilder, IDelegateTxBuilder, IRequirementTxBuilder, IRewardTxBuilder,
+        IStakeTxBuilder, IWithdrawTxBuilder,
+    },
 };
-use common::types::{relation_db::transaction::Model, smt::Address};
-use common::Result;
+use common::types::api::{
+    AccountPortfolio, AddressAmount, BatchOperation, ChainState, DelegationAmount,
+    EpochRewardEstimate, OperationStatus, Page, PendingDelta, RewardEstimate, RewardSmtLeaf,
+    RewardSmtProof, RewardState, SmtAmountLeaf, SmtProof, UnclaimedReward, ValidatorRewardEstimate,
+    WithdrawalAmount,
+};
+use common::types::axon_rpc_client::Header;
+use common::types::tx_builder::{
+    Amount, BatchItems, ChainContext, DelegateItem, Epoch, RewardTypeIds, StakeItem, StakeTypeIds,
+};
+use common::types::{
+    axon_types::checkpoint::CheckpointCellData,
+    relation_db::transaction::{decode_amount, Model},
+    smt::{Address, Root},
+    JsonBytes, OutputsValidator, Status, Transaction, TransactionView, H256,
+};
+use common::utils::convert::{to_ckb_h160, to_eth_h160, to_u32};
+use common::{AnyError, Result};
+use futures::stream::{BoxStream, StreamExt};
+use molecule::prelude::Entity;
+use query::{delegate_deltas, stake_delta, withdraw_infos, TxParser};
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+use tx_builder::ckb::{
+    batch::BatchTxBuilder,
+    delegate::DelegateTxBuilder,
+    helper::{
+        reward_calculator::{
+            claimable_epochs, fetch_commission_rates, fetch_metadata, proposed_validators,
+            EpochReward,
+        },
+        Checkpoint, Delegate, RewardCalculator, Stake, Withdraw, Xudt,
+    },
+    requirement::RequirementTxBuilder,
+    reward::RewardTxBuilder,
+    stake::StakeTxBuilder,
+    withdraw::WithdrawTxBuilder,
+    CkbTxErr, INAUGURATION,
+};
+
+const TX_POLL_INTERVAL: Duration = Duration::from_secs(3);
+const TX_POLL_MAX_TRY: u64 = 200;
+// about one Axon block
+const CHAIN_STATE_TTL: Duration = Duration::from_secs(3);
+const MAX_ESTIMATE_EPOCHS: u64 = 100;
+const SCRATCH_DIR: &str = "spark-api-scratch";
 
 #[derive(Clone)]
-pub struct DefaultAPIAdapter<T, S> {
+pub struct DefaultAPIAdapter<T, S, C, A> {
     relation_storage: Arc<T>,
-    _smt_storage:     Arc<S>,
+    smt_storage:      Arc<S>,
+    ckb_rpc:          C,
+    axon_rpc:         A,
+    ctx:              ChainContext,
+    chain_state:      Arc<Mutex<Option<(Instant, ChainState)>>>,
+    // where the scratch copies of the smts are made
+    scratch_dir:      PathBuf,
+    scratch_count:    Arc<AtomicU64>,
 }
 
-impl<T, S> DefaultAPIAdapter<T, S>
+impl<T, S, C, A> DefaultAPIAdapter<T, S, C, A>
 where
     T: TransactionStorage + 'static,
-    S: StakeSmtStorage + DelegateSmtStorage + RewardSmtStorage + 'static,
+    S: StakeSmtStorage + DelegateSmtStorage + RewardSmtStorage + ProposalSmtStorage + 'static,
+    C: CkbRpc + 'static,
+    A: AxonRpc + 'static,
 {
-    pub fn new(relation_storage: Arc<T>, smt_storage: Arc<S>) -> Self {
+    pub fn new(
+        relation_storage: Arc<T>,
+        smt_storage: Arc<S>,
+        ckb_rpc: C,
+        axon_rpc: A,
+        ctx: ChainContext,
+    ) -> Self {
         Self {
             relation_storage,
-            _smt_storage: smt_storage,
+            smt_storage,
+            ckb_rpc,
+            axon_rpc,
+            ctx,
+            chain_state: Arc::new(Mutex::new(None)),
+            scratch_dir: std::env::temp_dir()
+                .join(SCRATCH_DIR)
+                .join(std::process::id().to_string()),
+            scratch_count: Arc::new(AtomicU64::new(0)),
         }
     }
+
+    pub fn with_scratch_dir(mut self, scratch_dir: PathBuf) -> Self {
+        self.scratch_dir = scratch_dir;
+        self
+    }
+
+    /// Keep the block number of the cached chain state up to date with the new
+    /// Axon headers.
+    pub fn follow_headers(&self, mut headers: BoxStream<'static, Header>) {
+        let chain_state = Arc::clone(&self.chain_state);
+        tokio::spawn(async move {
+            while let Some(header) = headers.next().await {
+                if let Some((_, state)) = chain_state.lock().await.as_mut() {
+                    state.block_number = state.block_number.max(header.number);
+                }
+            }
+        });
+    }
+
+    fn stake_type_ids(&self) -> StakeTypeIds {
+        (&self.ctx.type_ids).into()
+    }
+
+    fn reward_type_ids(&self) -> RewardTypeIds {
+        (&self.ctx.type_ids).into()
+    }
+
+    async fn current_epoch(&self) -> Result<Epoch> {
+        Checkpoint::get_epoch(
+            &self.ckb_rpc,
+            &self.ctx,
+            &self.ctx.type_ids.checkpoint_type_id,
+        )
+        .await
+    }
+
+    async fn fetch_chain_state(&self) -> Result<ChainState> {
+        let metadata = self.axon_rpc.get_current_metadata().await?;
+        let block = self.axon_rpc.get_block(None).await?;
+
+        let checkpoint = Checkpoint::get_cell(
+            &self.ckb_rpc,
+            Checkpoint::type_(&self.ctx, &self.ctx.type_ids.checkpoint_type_id),
+        )
+        .await?;
+        let checkpoint = checkpoint
+            .output_data
+            .ok_or(CkbTxErr::MalformedCellData("checkpoint"))?
+            .into_bytes();
+        let checkpoint = CheckpointCellData::from_compatible_slice(&checkpoint)
+            .map_err(|_| CkbTxErr::MalformedCellData("checkpoint"))?;
+
+        let smt = &*self.smt_storage;
+        let stakers = StakeSmtStorage::get_sub_leaves(smt, metadata.epoch).await?;
+        let mut total_delegate_amount: Amount = 0;
+        for staker in stakers.keys() {
+            total_delegate_amount +=
+                DelegateSmtStorage::get_sub_leaves(smt, metadata.epoch, *staker)
+                    .await?
+                    .values()
+                    .sum::<Amount>();
+        }
+
+        Ok(ChainState {
+            epoch:                 metadata.epoch,
+            period:                to_u32(&checkpoint.period()),
+            block_number:          block.header.number,
+            validators:            metadata.verifier_list.iter().map(|v| v.address).collect(),
+            total_stake_amount:    stakers.values().sum::<Amount>().to_string(),
+            total_delegate_amount: total_delegate_amount.to_string(),
+        })
+    }
+
+    async fn pending_stake(
+        &self,
+        addr: Address,
+        current_epoch: Epoch,
+    ) -> Result<Option<PendingDelta>> {
+        let cell = Stake::get_cell(
+            &self.ckb_rpc,
+            Stake::lock(
+                &self.ctx,
+                &self.ctx.type_ids.metadata_type_id,
+                &to_ckb_h160(&addr),
+            ),
+            Xudt::type_(&self.ctx, &self.ctx.type_ids.xudt_owner.pack()),
+        )
+        .await?;
+        let cell = match cell {
+            Some(cell) => cell,
+            None => return Ok(None),
+        };
+
+        let data = cell.output_data.unwrap_or_default().into_bytes();
+        let item = stake_delta(&data).ok_or(CkbTxErr::MalformedCellData("stake AT"))?;
+        if !is_pending(item.amount, item.inauguration_epoch, current_epoch) {
+            return Ok(None);
+        }
+
+        Ok(Some(PendingDelta {
+            staker:             addr,
+            is_increase:        item.is_increase,
+            amount:             item.amount.to_string(),
+            inauguration_epoch: item.inauguration_epoch,
+        }))
+    }
+
+    async fn pending_delegations(
+        &self,
+        addr: Address,
+        current_epoch: Epoch,
+    ) -> Result<Vec<PendingDelta>> {
+        let cell = Delegate::get_cell(
+            &self.ckb_rpc,
+            Delegate::lock(
+                &self.ctx,
+                &self.ctx.type_ids.metadata_type_id,
+                &to_ckb_h160(&addr),
+            ),
+            Xudt::type_(&self.ctx, &self.ctx.type_ids.xudt_owner.pack()),
+        )
+        .await?;
+        let cell = match cell {
+            Some(cell) => cell,
+            None => return Ok(vec![]),
+        };
+
+        let data = cell.output_data.unwrap_or_default().into_bytes();
+        let items = delegate_deltas(&data).ok_or(CkbTxErr::MalformedCellData("delegate AT"))?;
+        Ok(items
+            .into_iter()
+            .filter(|item| is_pending(item.amount, item.inauguration_epoch, current_epoch))
+            .map(|item| PendingDelta {
+                staker:             to_eth_h160(&item.staker),
+                is_increase:        item.is_increase,
+                amount:             item.amount.to_string(),
+                inauguration_epoch: item.inauguration_epoch,
+            })
+            .collect())
+    }
+
+    async fn withdrawals(
+        &self,
+        addr: Address,
+        current_epoch: Epoch,
+    ) -> Result<Vec<WithdrawalAmount>> {
+        let cell = Withdraw::get_cell(
+            &self.ckb_rpc,
+            Withdraw::lock(
+                &self.ctx,
+                &self.ctx.type_ids.metadata_type_id,
+                &to_ckb_h160(&addr),
+            ),
+            Xudt::type_(&self.ctx, &self.ctx.type_ids.xudt_owner.pack()),
+        )
+        .await?;
+        let cell = match cell {
+            Some(cell) => cell,
+            None => return Ok(vec![]),
+        };
+
+        let data = cell.output_data.unwrap_or_default().into_bytes();
+        let infos = withdraw_infos(&data).ok_or(CkbTxErr::MalformedCellData("withdraw AT"))?;
+        Ok(infos
+            .into_iter()
+            .map(|(unlock_epoch, amount)| WithdrawalAmount {
+                unlock_epoch,
+                amount: amount.to_string(),
+                withdrawable: unlock_epoch <= current_epoch,
+            })
+            .collect())
+    }
+
+    async fn calc_rewards(
+        &self,
+        addr: Address,
+        epochs: RangeInclusive<Epoch>,
+        current_epoch: Epoch,
+    ) -> Result<Vec<EpochReward>> {
+        let smt = &*self.smt_storage;
+        let metadata = fetch_metadata(
+            &self.ckb_rpc,
+            &self.ctx,
+            &self.ctx.type_ids.metadata_type_id,
+        )
+        .await?;
+        let commission_rates = fetch_commission_rates(
+            &self.ckb_rpc,
+            &self.ctx,
+            &self.ctx.type_ids.metadata_type_id,
+            &self.ctx.type_ids.xudt_owner,
+            proposed_validators(smt, epochs.clone()).await?,
+        )
+        .await?;
+
+        RewardCalculator::from_metadata(smt, &metadata, current_epoch, commission_rates)
+            .calc_rewards(addr, epochs)
+            .await
+    }
+
+    async fn unclaimed_reward(
+        &self,
+        addr: Address,
+        current_epoch: Epoch,
+    ) -> Result<Option<UnclaimedReward>> {
+        let smt = &*self.smt_storage;
+        let not_claimed_epoch = RewardSmtStorage::get_epoch(smt, addr).await?;
+        let epochs = match claimable_epochs(current_epoch, not_claimed_epoch) {
+            Some(epochs) => epochs,
+            None => return Ok(None),
+        };
+
+        let rewards = self
+            .calc_rewards(addr, epochs.clone(), current_epoch)
+            .await?;
+
+        Ok(Some(UnclaimedReward {
+            start_epoch: *epochs.start(),
+            end_epoch:   *epochs.end(),
+            amount:      rewards.iter().map(|r| r.amount).sum::<Amount>().to_string(),
+        }))
+    }
+}
+
+// A delta is applied to the SMTs by the kicker once its inauguration epoch is
+// reached, a zero amount means there is no delta.
+fn is_pending(amount: Amount, inauguration_epoch: Epoch, current_epoch: Epoch) -> bool {
+    amount != 0 && inauguration_epoch >= current_epoch + INAUGURATION
 }
 
 #[async_trait]
-impl<T, S> APIAdapter for DefaultAPIAdapter<T, S>
+impl<T, S, C, A> APIAdapter for DefaultAPIAdapter<T, S, C, A>
 where
     T: TransactionStorage + Sync + Send + 'static,
-    S: StakeSmtStorage + DelegateSmtStorage + RewardSmtStorage + Sync + Send + 'static,
+    S: StakeSmtStorage
+        + DelegateSmtStorage
+        + RewardSmtStorage
+        + ProposalSmtStorage
+        + SmtSnapshot
+        + Clone
+        + Sync
+        + Send
+        + 'static,
+    C: CkbRpc + 'static,
+    A: AxonRpc + 'static,
 {
     async fn get_records_by_address(
         &self,
         addr: Address,
-        offset: u64,
+        page_token: Option<String>,
         limit: u64,
-    ) -> Result<Vec<Model>> {
+    ) -> Result<Page<Model>> {
         self.relation_storage
-            .get_records_by_address(addr, offset, limit)
+            .get_records_by_address(addr, page_token, limit)
             .await
     }
 
@@ -48,36 +365,558 @@ where
         &self,
         addr: Address,
         operation: u32,
-        offset: u64,
+        event: Option<u32>,
+        page_token: Option<String>,
         limit: u64,
-    ) -> Result<Vec<Model>> {
+    ) -> Result<Page<Model>> {
         self.relation_storage
-            .get_operation_history(addr, operation, offset, limit)
+            .get_operation_history(addr, operation, event, page_token, limit)
             .await
     }
 
     async fn get_stake_amount_by_epoch(
         &self,
         operation: u32,
-        offset: u64,
+        page_token: Option<String>,
         limit: u64,
-    ) -> Result<Vec<Model>> {
+    ) -> Result<Page<Model>> {
         self.relation_storage
-            .get_stake_amount_by_epoch(operation, offset, limit)
+            .get_stake_amount_by_epoch(operation, page_token, limit)
             .await
     }
 
-    async fn get_top_stake_address(&self, operation: u32) -> Result<Vec<Model>> {
-        self.relation_storage.get_top_stake_address(operation).await
+    async fn get_top_stake_address(
+        &self,
+        operation: u32,
+        page_token: Option<String>,
+        limit: u64,
+    ) -> Result<Page<AddressAmount>> {
+        self.relation_storage
+            .get_top_stake_address(operation, page_token, limit)
+            .await
     }
 
     async fn get_address_state(&self, addr: Address) -> Result<Vec<Model>> {
         self.relation_storage.get_address_state(addr).await
     }
 
-    async fn get_latest_stake_transactions(&self, offset: u64, limit: u64) -> Result<Vec<Model>> {
+    async fn get_latest_stake_transactions(
+        &self,
+        page_token: Option<String>,
+        limit: u64,
+    ) -> Result<Page<Model>> {
         self.relation_storage
-            .get_latest_stake_transactions(offset, limit)
+            .get_latest_stake_transactions(page_token, limit)
             .await
     }
+
+    async fn build_stake_rate_tx(
+        &self,
+        staker: Address,
+        commission_rate: u8,
+    ) -> Result<TransactionView> {
+        let tx = RequirementTxBuilder::new(
+            &self.ckb_rpc,
+            &self.ctx,
+            self.stake_type_ids(),
+            to_ckb_h160(&staker),
+            commission_rate,
+        )
+        .build_tx()
+        .await?;
+
+        Ok(tx.into())
+    }
+
+    async fn build_stake_tx(
+        &self,
+        staker: Address,
+        amount: Amount,
+        is_increase: bool,
+    ) -> Result<TransactionView> {
+        let current_epoch = self.current_epoch().await?;
+
+        let tx = StakeTxBuilder::new(
+            &self.ckb_rpc,
+            &self.ctx,
+            self.stake_type_ids(),
+            to_ckb_h160(&staker),
+            current_epoch,
+            StakeItem {
+                is_increase,
+                amount,
+                inauguration_epoch: current_epoch + INAUGURATION,
+            },
+            None,
+        )
+        .build_tx()
+        .await?;
+
+        Ok(tx.into())
+    }
+
+    async fn build_delegate_tx(
+        &self,
+        delegator: Address,
+        staker: Address,
+        amount: Amount,
+        is_increase: bool,
+    ) -> Result<TransactionView> {
+        let current_epoch = self.current_epoch().await?;
+
+        let tx = DelegateTxBuilder::new(
+            &self.ckb_rpc,
+            &self.ctx,
+            self.stake_type_ids(),
+            to_ckb_h160(&delegator),
+            current_epoch,
+            vec![DelegateItem::new(
+                to_ckb_h160(&staker),
+                is_increase,
+                amount,
+                current_epoch + INAUGURATION,
+            )],
+            (*self.smt_storage).clone(),
+        )
+        .build_tx()
+        .await?;
+
+        Ok(tx.into())
+    }
+
+    async fn build_batch_tx(
+        &self,
+        user: Address,
+        batch: BatchOperation,
+    ) -> Result<TransactionView> {
+        let current_epoch = self.current_epoch().await?;
+        let inauguration_epoch = current_epoch + INAUGURATION;
+
+        let stake = batch
+            .stake
+            .map(|stake| -> Result<StakeItem> {
+                Ok(StakeItem {
+                    is_increase: stake.is_increase,
+                    amount: decode_amount(&stake.amount)?,
+                    inauguration_epoch,
+                })
+            })
+            .transpose()?;
+        let delegates = batch
+            .delegates
+            .into_iter()
+            .map(|delegate| -> Result<DelegateItem> {
+                Ok(DelegateItem::new(
+                    to_ckb_h160(&delegate.staker),
+                    delegate.is_increase,
+                    decode_amount(&delegate.amount)?,
+                    inauguration_epoch,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let tx = BatchTxBuilder::new(
+            &self.ckb_rpc,
+            &self.ctx,
+            self.stake_type_ids(),
+            to_ckb_h160(&user),
+            current_epoch,
+            BatchItems {
+                stake,
+                delegates,
+                withdraw: batch.withdraw,
+            },
+            (*self.smt_storage).clone(),
+        )
+        .build_tx()
+        .await?;
+
+        Ok(tx.into())
+    }
+
+    async fn build_withdraw_tx(&self, user: Address) -> Result<TransactionView> {
+        let current_epoch = self.current_epoch().await?;
+
+        let tx = WithdrawTxBuilder::new(
+            &self.ckb_rpc,
+            &self.ctx,
+            self.stake_type_ids(),
+            to_ckb_h160(&user),
+            current_epoch,
+        )
+        .build_tx()
+        .await?;
+
+        Ok(tx.into())
+    }
+
+    // The builder writes the claimed epoch into the reward smt it is given, so
+    // the tx is built on a scratch copy of the smts. The served smts are only
+    // updated by the kicker from the committed txs.
+    async fn build_reward_tx(&self, user: Address) -> Result<TransactionView> {
+        let current_epoch = self.current_epoch().await?;
+
+        let scratch_dir = self.scratch_dir.join(format!(
+            "reward-{}",
+            self.scratch_count.fetch_add(1, Ordering::Relaxed)
+        ));
+        let smt = Arc::clone(&self.smt_storage);
+        let dir = scratch_dir.clone();
+        let scratch = tokio::task::spawn_blocking(move || smt.scratch_copy(&dir)).await??;
+
+        // Claim the rewards of all the epochs which have not been claimed yet.
+        let tx = async {
+            RewardTxBuilder::new(
+                &self.ckb_rpc,
+                &self.ctx,
+                self.reward_type_ids(),
+                scratch,
+                to_ckb_h160(&user),
+                current_epoch,
+                current_epoch,
+            )
+            .await?
+            .build_tx()
+            .await
+        }
+        .await;
+
+        // the scratch copy is dropped with the builder
+        if let Err(e) = std::fs::remove_dir_all(&scratch_dir) {
+            log::warn!("[api] remove scratch smt {:?} failed: {}", scratch_dir, e);
+        }
+
+        Ok(tx?.into())
+    }
+
+    async fn send_transaction(&self, tx: Transaction) -> Result<H256> {
+        // Everything which may fail is done before the tx is broadcast, so an
+        // error returned means the tx is not submitted. The records are decoded
+        // from the tx itself, in the same way as the indexer does once it is
+        // committed.
+        let view: TransactionView = packed::Transaction::from(tx.clone()).into_view().into();
+        let records = TxParser::new(&self.ckb_rpc, &self.ctx).parse(&view).await?;
+        let timestamp = u32::try_from(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())?;
+
+        let tx_hash = self
+            .ckb_rpc
+            .send_transaction(&tx, Some(OutputsValidator::Passthrough))
+            .await?;
+
+        // The tx is broadcast, so its hash is returned even if it can not be
+        // recorded. It is recorded by the indexer once it is committed.
+        let models = records
+            .into_iter()
+            .flat_map(|(_, records)| records)
+            .enumerate()
+            .map(|(item_index, record)| {
+                record.into_model(
+                    &tx_hash,
+                    None,
+                    None,
+                    item_index,
+                    timestamp,
+                    OperationStatus::Pending,
+                )
+            });
+        for model in models {
+            if let Err(e) = self.relation_storage.insert(model).await {
+                log::error!("[api] record transaction 0x{} failed: {}", tx_hash, e);
+                break;
+            }
+        }
+
+        let ckb_rpc = self.ckb_rpc.clone();
+        let relation_storage = Arc::clone(&self.relation_storage);
+        let hash = tx_hash.clone();
+        tokio::spawn(async move {
+            if let Err(e) = track_transaction(
+                ckb_rpc,
+                relation_storage,
+                hash,
+                TX_POLL_INTERVAL,
+                TX_POLL_MAX_TRY,
+            )
+            .await
+            {
+                log::error!("[api] track transaction error: {}", e);
+            }
+        });
+
+        Ok(H256::from_slice(tx_hash.as_bytes()))
+    }
+
+    async fn get_stake_smt_proof(&self, epoch: Epoch, stakers: Vec<Address>) -> Result<SmtProof> {
+        let smt = &*self.smt_storage;
+        let sub_root = StakeSmtStorage::get_sub_root(smt, epoch)
+            .await?
+            .ok_or_else(|| AnyError::msg(format!("stake smt of epoch {} not found", epoch)))?;
+        let sub_proof = StakeSmtStorage::generate_sub_proof(smt, epoch, stakers.clone()).await?;
+        let top_proof = StakeSmtStorage::generate_top_proof(smt, vec![epoch]).await?;
+        let leaves = StakeSmtStorage::get_sub_leaves(smt, epoch).await?;
+
+        Ok(SmtProof {
+            epoch,
+            top_root: to_root_hash(StakeSmtStorage::get_top_root(smt).await?),
+            top_proof: JsonBytes::from_vec(top_proof),
+            sub_root: to_root_hash(sub_root),
+            sub_proof: JsonBytes::from_vec(sub_proof),
+            leaves: to_amount_leaves(stakers, &leaves),
+        })
+    }
+
+    async fn get_delegate_smt_proof(
+        &self,
+        staker: Address,
+        epoch: Epoch,
+        delegators: Vec<Address>,
+    ) -> Result<SmtProof> {
+        let smt = &*self.smt_storage;
+        let sub_root = DelegateSmtStorage::get_sub_root(smt, epoch, staker)
+            .await?
+            .ok_or_else(|| {
+                AnyError::msg(format!(
+                    "delegate smt of {} at epoch {} not found",
+                    staker, epoch
+                ))
+            })?;
+        let sub_proof =
+            DelegateSmtStorage::generate_sub_proof(smt, staker, epoch, delegators.clone()).await?;
+        let top_proof = DelegateSmtStorage::generate_top_proof(smt, vec![epoch], staker).await?;
+        let leaves = DelegateSmtStorage::get_sub_leaves(smt, epoch, staker).await?;
+
+        Ok(SmtProof {
+            epoch,
+            top_root: to_root_hash(DelegateSmtStorage::get_top_root(smt, staker).await?),
+            top_proof: JsonBytes::from_vec(top_proof),
+            sub_root: to_root_hash(sub_root),
+            sub_proof: JsonBytes::from_vec(sub_proof),
+            leaves: to_amount_leaves(delegators, &leaves),
+        })
+    }
+
+    async fn get_reward_smt_proof(&self, addresses: Vec<Address>) -> Result<RewardSmtProof> {
+        let smt = &*self.smt_storage;
+        let mut leaves = Vec::with_capacity(addresses.len());
+        for address in addresses.iter() {
+            leaves.push(RewardSmtLeaf {
+                address: *address,
+                epoch:   RewardSmtStorage::get_epoch(smt, *address).await?,
+            });
+        }
+
+        Ok(RewardSmtProof {
+            root: to_root_hash(RewardSmtStorage::get_root(smt).await?),
+            proof: JsonBytes::from_vec(RewardSmtStorage::generate_proof(smt, addresses).await?),
+            leaves,
+        })
+    }
+
+    async fn get_stake_smt_root(&self) -> Result<ckb_types::H256> {
+        Ok(to_root_hash(
+            StakeSmtStorage::get_top_root(&*self.smt_storage).await?,
+        ))
+    }
+
+    async fn get_delegate_smt_root(&self, staker: Address) -> Result<ckb_types::H256> {
+        Ok(to_root_hash(
+            DelegateSmtStorage::get_top_root(&*self.smt_storage, staker).await?,
+        ))
+    }
+
+    async fn get_reward_smt_root(&self) -> Result<ckb_types::H256> {
+        Ok(to_root_hash(
+            RewardSmtStorage::get_root(&*self.smt_storage).await?,
+        ))
+    }
+
+    async fn get_chain_state(&self) -> Result<ChainState> {
+        // Holding the lock while fetching makes the concurrent requests wait for
+        // one fetch instead of flooding the nodes.
+        let mut cache = self.chain_state.lock().await;
+        if let Some((fetched_at, state)) = cache.as_ref() {
+            if fetched_at.elapsed() < CHAIN_STATE_TTL {
+                return Ok(state.clone());
+            }
+        }
+
+        let state = self.fetch_chain_state().await?;
+        *cache = Some((Instant::now(), state.clone()));
+        Ok(state)
+    }
+
+    async fn estimate_rewards(
+        &self,
+        addr: Address,
+        from_epoch: Epoch,
+        to_epoch: Epoch,
+    ) -> Result<RewardEstimate> {
+        if from_epoch > to_epoch || to_epoch - from_epoch >= MAX_ESTIMATE_EPOCHS {
+            return Err(AnyError::msg(format!(
+                "invalid epochs from {} to {}, at most {} epochs",
+                from_epoch, to_epoch, MAX_ESTIMATE_EPOCHS
+            )));
+        }
+
+        let current_epoch = self.current_epoch().await?;
+        let not_claimed_epoch = RewardSmtStorage::get_epoch(&*self.smt_storage, addr).await?;
+        let claimable = claimable_epochs(current_epoch, not_claimed_epoch);
+        let rewards = self
+            .calc_rewards(addr, from_epoch..=to_epoch, current_epoch)
+            .await?;
+
+        let is_claimable = |epoch: &Epoch| {
+            claimable
+                .as_ref()
+                .map_or(false, |epochs| epochs.contains(epoch))
+        };
+        let total_amount = rewards.iter().map(|r| r.amount).sum::<Amount>();
+        let claimable_amount = rewards
+            .iter()
+            .filter(|r| is_claimable(&r.epoch))
+            .map(|r| r.amount)
+            .sum::<Amount>();
+
+        let epochs = rewards
+            .into_iter()
+            .map(|reward| EpochRewardEstimate {
+                epoch:      reward.epoch,
+                amount:     reward.amount.to_string(),
+                claimable:  is_claimable(&reward.epoch),
+                validators: reward
+                    .validators
+                    .into_iter()
+                    .map(|v| ValidatorRewardEstimate {
+                        validator:             v.validator,
+                        propose_count:         v.propose_count,
+                        commission_rate:       v.commission_rate,
+                        stake_amount:          v.stake_amount.to_string(),
+                        total_delegate_amount: v.total_delegate_amount.to_string(),
+                        total_reward:          v.total_reward.to_string(),
+                        reward:                v.reward.to_string(),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Ok(RewardEstimate {
+            current_epoch,
+            total_amount: total_amount.to_string(),
+            claimable_amount: claimable_amount.to_string(),
+            epochs,
+        })
+    }
+
+    async fn get_account_portfolio(&self, addr: Address) -> Result<AccountPortfolio> {
+        let current_epoch = self.current_epoch().await?;
+        let smt = &*self.smt_storage;
+
+        let mut delegations = Vec::new();
+        for staker in StakeSmtStorage::get_sub_leaves(smt, current_epoch)
+            .await?
+            .into_keys()
+        {
+            if let Some(amount) =
+                DelegateSmtStorage::get_amount(smt, current_epoch, staker, addr).await?
+            {
+                delegations.push(DelegationAmount {
+                    staker,
+                    amount: amount.to_string(),
+                });
+            }
+        }
+
+        Ok(AccountPortfolio {
+            epoch: current_epoch,
+            stake_amount: StakeSmtStorage::get_amount(smt, current_epoch, addr)
+                .await?
+                .unwrap_or_default()
+                .to_string(),
+            delegations,
+            pending_stake: self.pending_stake(addr, current_epoch).await?,
+            pending_delegations: self.pending_delegations(addr, current_epoch).await?,
+            withdrawals: self.withdrawals(addr, current_epoch).await?,
+            unclaimed_reward: self.unclaimed_reward(addr, current_epoch).await?,
+        })
+    }
+
+    async fn get_reward_state(&self, addr: Address) -> Result<RewardState> {
+        let current_epoch = self.current_epoch().await?;
+        let lock_amount = self
+            .unclaimed_reward(addr, current_epoch)
+            .await?
+            .map(|reward| reward.amount)
+            .unwrap_or_else(|| 0.to_string());
+
+        let mut unlock_amount: Amount = 0;
+        for claim in self.relation_storage.get_reward_claims(addr).await? {
+            unlock_amount += decode_amount(&claim.amount)?;
+        }
+
+        Ok(RewardState {
+            lock_amount,
+            unlock_amount: unlock_amount.to_string(),
+        })
+    }
+}
+
+fn to_root_hash(root: Root) -> ckb_types::H256 {
+    ckb_types::H256::from_slice(root.as_slice()).unwrap()
+}
+
+fn to_amount_leaves(users: Vec<Address>, leaves: &HashMap<Address, Amount>) -> Vec<SmtAmountLeaf> {
+    users
+        .into_iter()
+        .map(|address| SmtAmountLeaf {
+            address,
+            amount: leaves
+                .get(&address)
+                .copied()
+                .unwrap_or_default()
+                .to_string(),
+        })
+        .collect()
+}
+
+/// Poll the status of the transaction until it is committed or rejected, and
+/// write the final status back to the relation database. A failed poll is
+/// tried again, and a transaction which is neither committed nor rejected
+/// after all the tries is marked as timed out.
+pub(crate) async fn track_transaction<T: TransactionStorage, C: CkbRpc>(
+    ckb_rpc: C,
+    relation_storage: Arc<T>,
+    tx_hash: ckb_types::H256,
+    poll_interval: Duration,
+    max_try: u64,
+) -> Result<()> {
+    let mut status = OperationStatus::TimedOut;
+
+    for _ in 0..max_try {
+        tokio::time::sleep(poll_interval).await;
+
+        let tx_status = match ckb_rpc.get_transaction(tx_hash.clone()).await {
+            Ok(Some(tx)) => tx.tx_status.status,
+            Ok(None) => continue,
+            Err(e) => {
+                log::warn!("[api] poll transaction 0x{} failed: {}", tx_hash, e);
+                continue;
+            }
+        };
+
+        match tx_status {
+            Status::Committed => {
+                status = OperationStatus::Success;
+                break;
+            }
+            Status::Rejected => {
+                status = OperationStatus::Failed;
+                break;
+            }
+            _ => continue,
+        }
+    }
+
+    relation_storage
+        .update_status(tx_hash.to_string(), status as u32)
+        .await
 }
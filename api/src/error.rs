@@ -10,6 +10,10 @@ pub enum ApiError {
     HttpServer(String),
     #[error("invalid method (expected {expected:?}, found {found:?})")]
     InvalidMethod { expected: String, found: String },
+    #[error("invalid params {0}")]
+    InvalidParams(String),
+    #[error("serialize error {0}")]
+    Serialize(String),
     #[error(transparent)]
     Other(#[from] AnyError),
 }
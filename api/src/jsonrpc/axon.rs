@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use crate::jsonrpc::AxonStatusRpcServer;
+use crate::{error::ApiError, jsonrpc::AxonStatusRpcServer};
 use common::{
     traits::{api::APIAdapter, async_trait},
     types::api::ChainState,
@@ -8,19 +8,22 @@ use common::{
 use jsonrpsee::core::RpcResult;
 
 pub struct AxonStatusRpc<Adapter> {
-    _adapter: Arc<Adapter>,
+    adapter: Arc<Adapter>,
 }
 
 impl<Adapter: APIAdapter> AxonStatusRpc<Adapter> {
-    // #[warn(dead_code)]
-    // pub fn new(_adapter: Arc<Adapter>) -> Self {
-    //     Self { _adapter }
-    // }
+    pub fn new(adapter: Arc<Adapter>) -> Self {
+        Self { adapter }
+    }
 }
 
 #[async_trait]
 impl<Adapter: APIAdapter + 'static> AxonStatusRpcServer for AxonStatusRpc<Adapter> {
     async fn get_chain_state(&self) -> RpcResult<ChainState> {
-        unimplemented!()
+        Ok(self
+            .adapter
+            .get_chain_state()
+            .await
+            .map_err(|e| ApiError::Adapter(e.to_string()))?)
     }
 }
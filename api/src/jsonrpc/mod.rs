@@ -1,13 +1,17 @@
 pub mod axon;
 pub mod operation;
 pub mod query;
+pub mod smt;
 use crate::error::ApiError;
+use crate::jsonrpc::axon::AxonStatusRpc;
 use crate::jsonrpc::operation::OperationRpc;
-use crate::jsonrpc::query::{AxonStatusRpc, StatusRpcModule};
+use crate::jsonrpc::query::StatusRpcModule;
+use crate::jsonrpc::smt::SmtProofRpc;
 
 use common::types::api::{
-    AddressAmount, ChainState, HistoryEvent, OperationType, RewardHistory, RewardState,
-    StakeAmount, StakeHistory, StakeRate, StakeState, StakeTransaction,
+    AccountPortfolio, AddressAmount, BatchOperation, ChainState, HistoryEvent, OperationType, Page,
+    RewardEstimate, RewardHistory, RewardSmtProof, RewardState, SmtProof, StakeAmount,
+    StakeHistory, StakeRate, StakeState, StakeTransaction,
 };
 use common::types::smt::Address;
 use common::types::Transaction;
@@ -30,45 +34,56 @@ pub trait AccountHistoryRpc {
     #[method(name = "getRewardState")]
     async fn get_reward_state(&self, addr: Address) -> RpcResult<RewardState>;
 
+    #[method(name = "getAccountPortfolio")]
+    async fn get_account_portfolio(&self, addr: Address) -> RpcResult<AccountPortfolio>;
+
+    #[method(name = "estimateRewards")]
+    async fn estimate_rewards(
+        &self,
+        addr: Address,
+        from_epoch: u64,
+        to_epoch: u64,
+    ) -> RpcResult<RewardEstimate>;
+
     #[method(name = "getStakeHistory")]
     async fn get_stake_history(
         &self,
         addr: Address,
-        page_number: u64,
+        page_token: Option<String>,
         page_size: u64,
         enent: HistoryEvent,
         operation_type: OperationType,
-    ) -> RpcResult<Vec<StakeHistory>>;
+    ) -> RpcResult<Page<StakeHistory>>;
 
     #[method(name = "getRewardHistory")]
     async fn get_reward_history(
         &self,
         addr: Address,
-        page_number: u64,
+        page_token: Option<String>,
         page_size: u64,
-    ) -> RpcResult<RewardHistory>;
+    ) -> RpcResult<Page<RewardHistory>>;
 
     #[method(name = "getStakeAmountByEpoch")]
     async fn get_stake_amount_by_epoch(
         &self,
         operation_type: OperationType,
-        page_number: u64,
+        page_token: Option<String>,
         page_size: u64,
-    ) -> RpcResult<Vec<StakeAmount>>;
+    ) -> RpcResult<Page<StakeAmount>>;
 
     #[method(name = "getTopStakeAddress")]
     async fn get_top_stake_address(
         &self,
-        page_number: u64,
+        page_token: Option<String>,
         page_size: u64,
-    ) -> RpcResult<Vec<AddressAmount>>;
+    ) -> RpcResult<Page<AddressAmount>>;
 
     #[method(name = "getLatestStakeTransactions")]
     async fn get_latest_stake_transactions(
         &self,
-        page_number: u64,
+        page_token: Option<String>,
         page_size: u64,
-    ) -> RpcResult<Vec<StakeTransaction>>;
+    ) -> RpcResult<Page<StakeTransaction>>;
 }
 
 #[rpc(server)]
@@ -82,46 +97,89 @@ pub trait OperationRpc {
     #[method(name = "setStakeRate")]
     async fn set_stake_rate(
         &self,
-        address: H256,
+        address: Address,
         stake_rate: u64,
         delegate_rate: u64,
     ) -> RpcResult<String>;
 
     #[method(name = "stake")]
-    async fn stake(&self, address: H256, amount: u64) -> RpcResult<String>;
+    async fn stake(&self, address: Address, amount: String) -> RpcResult<String>;
 
     #[method(name = "unstake")]
-    async fn unstake(&self, address: H256, amount: u64) -> RpcResult<String>;
+    async fn unstake(&self, address: Address, amount: String) -> RpcResult<String>;
 
     #[method(name = "delegate")]
-    async fn delegate(&self, address: H256, amount: u64) -> RpcResult<String>;
+    async fn delegate(
+        &self,
+        address: Address,
+        staker: Address,
+        amount: String,
+    ) -> RpcResult<String>;
 
     #[method(name = "undelegate")]
-    async fn undelegate(&self, address: H256, amount: u64) -> RpcResult<String>;
-
-    #[method(name = "withdrawStake")]
-    async fn withdraw_stake(
+    async fn undelegate(
         &self,
-        address: H256,
-        withdraw_type: OperationType,
+        address: Address,
+        staker: Address,
+        amount: String,
     ) -> RpcResult<String>;
 
+    // The unlocked tokens of stake and delegate are kept in the same withdraw
+    // AT cell, so they are withdrawn together.
+    #[method(name = "withdrawStake")]
+    async fn withdraw_stake(&self, address: Address) -> RpcResult<String>;
+
     #[method(name = "withdrawRewards")]
-    async fn withdraw_rewards(&self, address: H256) -> RpcResult<String>;
+    async fn withdraw_rewards(&self, address: Address) -> RpcResult<String>;
+
+    #[method(name = "batchOperations")]
+    async fn batch_operations(
+        &self,
+        address: Address,
+        operation: BatchOperation,
+    ) -> RpcResult<String>;
 
     #[method(name = "sendTransaction")]
     async fn send_transaction(&self, tx: Transaction) -> RpcResult<H256>;
 }
 
+#[rpc(server)]
+pub trait SmtRpc {
+    #[method(name = "getStakeSmtProof")]
+    async fn get_stake_smt_proof(&self, epoch: u64, stakers: Vec<Address>) -> RpcResult<SmtProof>;
+
+    #[method(name = "getDelegateSmtProof")]
+    async fn get_delegate_smt_proof(
+        &self,
+        staker: Address,
+        epoch: u64,
+        delegators: Vec<Address>,
+    ) -> RpcResult<SmtProof>;
+
+    #[method(name = "getRewardSmtProof")]
+    async fn get_reward_smt_proof(&self, addresses: Vec<Address>) -> RpcResult<RewardSmtProof>;
+
+    #[method(name = "getStakeSmtRoot")]
+    async fn get_stake_smt_root(&self) -> RpcResult<ckb_types::H256>;
+
+    #[method(name = "getDelegateSmtRoot")]
+    async fn get_delegate_smt_root(&self, staker: Address) -> RpcResult<ckb_types::H256>;
+
+    #[method(name = "getRewardSmtRoot")]
+    async fn get_reward_smt_root(&self) -> RpcResult<ckb_types::H256>;
+}
+
 pub async fn run_server<Adapter: APIAdapter + 'static>(
     adapter: Arc<Adapter>,
     url: impl ToSocketAddrs,
 ) -> Result<ServerHandle, ApiError> {
     let mut module = StatusRpcModule::new(Arc::clone(&adapter)).into_rpc();
     let axon_rpc = AxonStatusRpc::new(Arc::clone(&adapter)).into_rpc();
-    let op_rpc = OperationRpc::new(adapter).into_rpc();
+    let op_rpc = OperationRpc::new(Arc::clone(&adapter)).into_rpc();
+    let smt_rpc = SmtProofRpc::new(adapter).into_rpc();
     module.merge(axon_rpc).unwrap();
     module.merge(op_rpc).unwrap();
+    module.merge(smt_rpc).unwrap();
     let server = ServerBuilder::new()
         .http_only()
         .build(url)
@@ -1,9 +1,12 @@
 use std::sync::Arc;
 
-use crate::jsonrpc::OperationRpcServer;
+use crate::{error::ApiError, jsonrpc::OperationRpcServer};
 use common::{
     traits::api::APIAdapter,
-    types::{api::OperationType, Transaction, H256},
+    types::{
+        api::BatchOperation, relation_db::transaction::decode_amount, smt::Address, Transaction,
+        TransactionView, H256,
+    },
 };
 use jsonrpsee::core::{async_trait, RpcResult};
 
@@ -19,48 +22,126 @@ impl<Adapter: APIAdapter> OperationRpc<Adapter> {
 
 #[async_trait]
 impl<Adapter: APIAdapter + 'static> OperationRpcServer for OperationRpc<Adapter> {
+    // The stake rate is the commission the staker takes from the rewards of its
+    // delegators, the delegate rate is the share left to them, in percent.
     async fn set_stake_rate(
         &self,
-        _address: H256,
-        _stake_rate: u64,
-        _delegate_rate: u64,
+        address: Address,
+        stake_rate: u64,
+        delegate_rate: u64,
     ) -> RpcResult<String> {
-        let _ = self.adapter;
-        unimplemented!()
-    }
+        if stake_rate.checked_add(delegate_rate) != Some(100) {
+            return Err(ApiError::InvalidParams(format!(
+                "stake rate {} and delegate rate {} should sum to 100",
+                stake_rate, delegate_rate
+            ))
+            .into());
+        }
 
-    async fn stake(&self, _address: H256, _amount: u64) -> RpcResult<String> {
-        let _ = self.adapter;
-        unimplemented!()
+        let tx = self
+            .adapter
+            .build_stake_rate_tx(address, stake_rate as u8)
+            .await
+            .map_err(|e| ApiError::Adapter(e.to_string()))?;
+        to_json(&tx)
     }
 
-    async fn unstake(&self, _address: H256, _amount: u64) -> RpcResult<String> {
-        let _ = self.adapter;
-        unimplemented!()
+    async fn stake(&self, address: Address, amount: String) -> RpcResult<String> {
+        let amount = parse_amount(&amount)?;
+        let tx = self
+            .adapter
+            .build_stake_tx(address, amount, true)
+            .await
+            .map_err(|e| ApiError::Adapter(e.to_string()))?;
+        to_json(&tx)
     }
 
-    async fn delegate(&self, _address: H256, _amount: u64) -> RpcResult<String> {
-        unimplemented!()
+    async fn unstake(&self, address: Address, amount: String) -> RpcResult<String> {
+        let amount = parse_amount(&amount)?;
+        let tx = self
+            .adapter
+            .build_stake_tx(address, amount, false)
+            .await
+            .map_err(|e| ApiError::Adapter(e.to_string()))?;
+        to_json(&tx)
     }
 
-    async fn undelegate(&self, _address: H256, _amount: u64) -> RpcResult<String> {
-        unimplemented!()
+    async fn delegate(
+        &self,
+        address: Address,
+        staker: Address,
+        amount: String,
+    ) -> RpcResult<String> {
+        let amount = parse_amount(&amount)?;
+        let tx = self
+            .adapter
+            .build_delegate_tx(address, staker, amount, true)
+            .await
+            .map_err(|e| ApiError::Adapter(e.to_string()))?;
+        to_json(&tx)
     }
 
-    async fn withdraw_stake(
+    async fn undelegate(
         &self,
-        _address: H256,
-        _withdraw_type: OperationType,
+        address: Address,
+        staker: Address,
+        amount: String,
     ) -> RpcResult<String> {
-        // withdraw_type: stake | delegate
-        unimplemented!()
+        let amount = parse_amount(&amount)?;
+        let tx = self
+            .adapter
+            .build_delegate_tx(address, staker, amount, false)
+            .await
+            .map_err(|e| ApiError::Adapter(e.to_string()))?;
+        to_json(&tx)
     }
 
-    async fn withdraw_rewards(&self, _address: H256) -> RpcResult<String> {
-        unimplemented!()
+    async fn withdraw_stake(&self, address: Address) -> RpcResult<String> {
+        let tx = self
+            .adapter
+            .build_withdraw_tx(address)
+            .await
+            .map_err(|e| ApiError::Adapter(e.to_string()))?;
+        to_json(&tx)
     }
 
-    async fn send_transaction(&self, _tx: Transaction) -> RpcResult<H256> {
-        unimplemented!()
+    async fn withdraw_rewards(&self, address: Address) -> RpcResult<String> {
+        let tx = self
+            .adapter
+            .build_reward_tx(address)
+            .await
+            .map_err(|e| ApiError::Adapter(e.to_string()))?;
+        to_json(&tx)
+    }
+
+    async fn batch_operations(
+        &self,
+        address: Address,
+        operation: BatchOperation,
+    ) -> RpcResult<String> {
+        let tx = self
+            .adapter
+            .build_batch_tx(address, operation)
+            .await
+            .map_err(|e| ApiError::Adapter(e.to_string()))?;
+        to_json(&tx)
     }
+
+    async fn send_transaction(&self, tx: Transaction) -> RpcResult<H256> {
+        let tx_hash = self
+            .adapter
+            .send_transaction(tx)
+            .await
+            .map_err(|e| ApiError::Adapter(e.to_string()))?;
+        Ok(tx_hash)
+    }
+}
+
+// the amounts are decimal strings, which are lossless for u128
+fn parse_amount(amount: &str) -> RpcResult<u128> {
+    decode_amount(amount).map_err(|e| ApiError::InvalidParams(e.to_string()).into())
+}
+
+fn to_json(tx: &TransactionView) -> RpcResult<String> {
+    serde_json::to_string(tx).map_err(|e| ApiError::Serialize(e.to_string()).into())
 }
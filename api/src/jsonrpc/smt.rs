@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use crate::{error::ApiError, jsonrpc::SmtRpcServer};
+use common::{
+    traits::api::APIAdapter,
+    types::{
+        api::{RewardSmtProof, SmtProof},
+        smt::Address,
+    },
+};
+use jsonrpsee::core::{async_trait, RpcResult};
+
+pub struct SmtProofRpc<Adapter> {
+    adapter: Arc<Adapter>,
+}
+
+impl<Adapter: APIAdapter> SmtProofRpc<Adapter> {
+    pub fn new(adapter: Arc<Adapter>) -> Self {
+        Self { adapter }
+    }
+}
+
+#[async_trait]
+impl<Adapter: APIAdapter + 'static> SmtRpcServer for SmtProofRpc<Adapter> {
+    async fn get_stake_smt_proof(&self, epoch: u64, stakers: Vec<Address>) -> RpcResult<SmtProof> {
+        Ok(self
+            .adapter
+            .get_stake_smt_proof(epoch, stakers)
+            .await
+            .map_err(|e| ApiError::Adapter(e.to_string()))?)
+    }
+
+    async fn get_delegate_smt_proof(
+        &self,
+        staker: Address,
+        epoch: u64,
+        delegators: Vec<Address>,
+    ) -> RpcResult<SmtProof> {
+        Ok(self
+            .adapter
+            .get_delegate_smt_proof(staker, epoch, delegators)
+            .await
+            .map_err(|e| ApiError::Adapter(e.to_string()))?)
+    }
+
+    async fn get_reward_smt_proof(&self, addresses: Vec<Address>) -> RpcResult<RewardSmtProof> {
+        Ok(self
+            .adapter
+            .get_reward_smt_proof(addresses)
+            .await
+            .map_err(|e| ApiError::Adapter(e.to_string()))?)
+    }
+
+    async fn get_stake_smt_root(&self) -> RpcResult<ckb_types::H256> {
+        Ok(self
+            .adapter
+            .get_stake_smt_root()
+            .await
+            .map_err(|e| ApiError::Adapter(e.to_string()))?)
+    }
+
+    async fn get_delegate_smt_root(&self, staker: Address) -> RpcResult<ckb_types::H256> {
+        Ok(self
+            .adapter
+            .get_delegate_smt_root(staker)
+            .await
+            .map_err(|e| ApiError::Adapter(e.to_string()))?)
+    }
+
+    async fn get_reward_smt_root(&self) -> RpcResult<ckb_types::H256> {
+        Ok(self
+            .adapter
+            .get_reward_smt_root()
+            .await
+            .map_err(|e| ApiError::Adapter(e.to_string()))?)
+    }
+}
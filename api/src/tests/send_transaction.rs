@@ -0,0 +1,281 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use ckb_types::bytes::Bytes;
+use ckb_types::core::{Capacity, TransactionBuilder};
+use ckb_types::packed::{CellInput, CellOutput, OutPoint};
+use ckb_types::prelude::{Builder, Entity, Pack, Unpack};
+use ckb_types::{h160, H160};
+use sea_orm::ConnectionTrait;
+
+use common::traits::{api::APIAdapter, ckb_rpc_client::CkbRpc, query::TransactionStorage};
+use common::types::api::{HistoryEvent, OperationStatus, OperationType};
+use common::types::axon_types::stake::{StakeAtCellData, StakeAtCellLockData};
+use common::types::relation_db::transaction::decode_amount;
+use common::types::tx_builder::{ChainContext, StakeItem};
+use common::types::Transaction;
+use common::utils::convert::{to_eth_h160, to_identity};
+use common::utils::mock::MockCkbRpc;
+use rpc_client::axon_client::AxonRpcClient;
+use storage::{relation_db::TransactionHistory, SmtManager};
+use tx_builder::ckb::helper::{token_cell_data, Stake, Xudt};
+use tx_builder::ckb::INAUGURATION;
+
+use crate::adapter::{track_transaction, DefaultAPIAdapter};
+
+use super::{mock_chain_context, mock_data, AXON_URL, RELATION_DB_URL};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+const MAX_TRY: u64 = 5;
+
+const USER: H160 = h160!("0x88fc88b65d9a613a1ad53fb2f41e5cb9c741fb6d");
+const STAKE_AMOUNT: u128 = u64::MAX as u128 + 1;
+const EPOCH: u64 = 3;
+
+type Adapter = DefaultAPIAdapter<TransactionHistory, SmtManager, MockCkbRpc, AxonRpcClient>;
+
+async fn adapter(ckb: MockCkbRpc, name: &str) -> (Adapter, Arc<TransactionHistory>) {
+    let storage = Arc::new(TransactionHistory::new(RELATION_DB_URL).await);
+    let smt_path = std::env::temp_dir().join("spark-api-test").join(name);
+    let adapter = DefaultAPIAdapter::new(
+        Arc::clone(&storage),
+        Arc::new(SmtManager::new(smt_path)),
+        ckb,
+        AxonRpcClient::new(AXON_URL, "").await,
+        mock_chain_context(),
+    );
+    (adapter, storage)
+}
+
+// The stake AT cell of the user with an empty delta.
+fn commit_stake_cell(ckb: &MockCkbRpc) -> OutPoint {
+    let ctx = mock_chain_context();
+    let tx = TransactionBuilder::default()
+        .output(stake_output(&ctx))
+        .output_data(stake_data(None).pack())
+        .build();
+    ckb.commit(tx.clone(), 1, 1_000);
+    OutPoint::new(tx.hash(), 0)
+}
+
+fn stake_output(ctx: &ChainContext) -> CellOutput {
+    CellOutput::new_builder()
+        .lock(Stake::lock(ctx, &ctx.type_ids.metadata_type_id, &USER))
+        .type_(Some(Xudt::type_(ctx, &ctx.type_ids.xudt_owner.pack())).pack())
+        .capacity(Capacity::shannons(100).pack())
+        .build()
+}
+
+fn stake_data(delta: Option<StakeItem>) -> Bytes {
+    let lock = StakeAtCellLockData::new_builder().l2_address(to_identity(&USER));
+    let lock = match delta {
+        Some(delta) => lock.delta(delta.into()),
+        None => lock,
+    };
+    let data = StakeAtCellData::new_builder().lock(lock.build()).build();
+    token_cell_data(STAKE_AMOUNT, data.as_bytes())
+}
+
+// The user stakes from the epoch, the client can not tell another amount.
+fn stake_tx(stake_cell: OutPoint) -> Transaction {
+    let ctx = mock_chain_context();
+    TransactionBuilder::default()
+        .input(CellInput::new(stake_cell, 0))
+        .output(stake_output(&ctx))
+        .output_data(
+            stake_data(Some(StakeItem {
+                is_increase:        true,
+                amount:             STAKE_AMOUNT,
+                inauguration_epoch: EPOCH + INAUGURATION,
+            }))
+            .pack(),
+        )
+        .build()
+        .data()
+        .into()
+}
+
+fn user_tx() -> Transaction {
+    TransactionBuilder::default()
+        .output(
+            CellOutput::new_builder()
+                .capacity(Capacity::shannons(100).pack())
+                .build(),
+        )
+        .output_data(Bytes::new().pack())
+        .build()
+        .data()
+        .into()
+}
+
+async fn send(adapter: &Adapter, tx: Transaction) -> common::Result<ckb_types::H256> {
+    let tx_hash = adapter.send_transaction(tx).await?;
+    Ok(ckb_types::H256::from_slice(tx_hash.as_bytes()).unwrap())
+}
+
+#[tokio::test]
+async fn send_records_pending_tx() {
+    let ckb = MockCkbRpc::new();
+    let stake_cell = commit_stake_cell(&ckb);
+    let (adapter, storage) = adapter(ckb.clone(), "send_records_pending_tx").await;
+
+    let tx_hash = send(&adapter, stake_tx(stake_cell)).await.unwrap();
+    let sent: Vec<ckb_types::H256> = ckb
+        .sent_transactions()
+        .iter()
+        .map(|tx| tx.hash().unpack())
+        .collect();
+    assert_eq!(sent, vec![tx_hash.clone()]);
+
+    // the record is decoded from the outputs of the tx
+    let records = storage
+        .get_records_by_tx_hash(tx_hash.to_string())
+        .await
+        .unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].address, to_eth_h160(&USER).to_string());
+    assert_eq!(records[0].operation, OperationType::Stake as u32);
+    assert_eq!(records[0].event, HistoryEvent::Add as u32);
+    assert_eq!(records[0].status, OperationStatus::Pending as u32);
+    assert_eq!(records[0].epoch, EPOCH as u32);
+    assert_eq!(records[0].output_index, None);
+    assert_eq!(
+        decode_amount(&records[0].stake_amount).unwrap(),
+        STAKE_AMOUNT
+    );
+}
+
+#[tokio::test]
+async fn send_tx_without_operations() {
+    let ckb = MockCkbRpc::new();
+    let (adapter, storage) = adapter(ckb.clone(), "send_tx_without_operations").await;
+
+    // the tx is relayed, but there is nothing to record
+    let tx_hash = send(&adapter, user_tx()).await.unwrap();
+    assert_eq!(ckb.sent_transactions()[0].hash().unpack(), tx_hash);
+    assert!(storage
+        .get_records_by_tx_hash(tx_hash.to_string())
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn send_error_is_not_recorded() {
+    let ckb = MockCkbRpc::new();
+    let stake_cell = commit_stake_cell(&ckb);
+    ckb.set_send_error(Some("pool is full"));
+    let (adapter, storage) = adapter(ckb.clone(), "send_error_is_not_recorded").await;
+
+    assert!(send(&adapter, stake_tx(stake_cell)).await.is_err());
+    assert!(storage
+        .get_latest_stake_transactions(None, 10)
+        .await
+        .unwrap()
+        .items
+        .is_empty());
+}
+
+#[tokio::test]
+async fn nothing_sent_without_inputs() {
+    // the stake AT cell spent is not on chain
+    let ckb = MockCkbRpc::new();
+    let (adapter, _) = adapter(ckb.clone(), "nothing_sent_without_inputs").await;
+
+    let stake_cell = OutPoint::new(Default::default(), 0);
+    assert!(send(&adapter, stake_tx(stake_cell)).await.is_err());
+    assert!(ckb.sent_transactions().is_empty());
+}
+
+#[tokio::test]
+async fn record_error_after_broadcast() {
+    let ckb = MockCkbRpc::new();
+    let stake_cell = commit_stake_cell(&ckb);
+    let (adapter, storage) = adapter(ckb.clone(), "record_error_after_broadcast").await;
+    storage
+        .db
+        .execute_unprepared("DROP TABLE \"transaction\"")
+        .await
+        .unwrap();
+
+    // the tx is broadcast, so the hash is returned
+    let tx_hash = send(&adapter, stake_tx(stake_cell)).await.unwrap();
+    assert_eq!(ckb.sent_transactions()[0].hash().unpack(), tx_hash);
+}
+
+async fn track(ckb: MockCkbRpc, tx_hash: &ckb_types::H256) -> OperationStatus {
+    let storage = Arc::new(TransactionHistory::new(RELATION_DB_URL).await);
+    storage
+        .insert(mock_data(tx_hash.to_string(), 100).await.unwrap())
+        .await
+        .unwrap();
+
+    track_transaction(
+        ckb,
+        Arc::clone(&storage),
+        tx_hash.clone(),
+        POLL_INTERVAL,
+        MAX_TRY,
+    )
+    .await
+    .unwrap();
+
+    let records = storage
+        .get_records_by_tx_hash(tx_hash.to_string())
+        .await
+        .unwrap();
+    OperationStatus::from(records[0].status)
+}
+
+async fn sent_tx(ckb: &MockCkbRpc) -> ckb_types::H256 {
+    ckb.send_transaction(&user_tx(), None).await.unwrap()
+}
+
+#[tokio::test]
+async fn track_committed_tx() {
+    let ckb = MockCkbRpc::new();
+    let tx_hash = sent_tx(&ckb).await;
+    ckb.commit_sent(&tx_hash, 2, 2_000);
+
+    assert!(matches!(
+        track(ckb, &tx_hash).await,
+        OperationStatus::Success
+    ));
+}
+
+#[tokio::test]
+async fn track_rejected_tx() {
+    let ckb = MockCkbRpc::new();
+    let tx_hash = sent_tx(&ckb).await;
+    ckb.reject(&tx_hash);
+
+    assert!(matches!(
+        track(ckb, &tx_hash).await,
+        OperationStatus::Failed
+    ));
+}
+
+#[tokio::test]
+async fn track_through_rpc_errors() {
+    let ckb = MockCkbRpc::new();
+    let tx_hash = sent_tx(&ckb).await;
+    ckb.commit_sent(&tx_hash, 2, 2_000);
+    ckb.fail_get_transaction(MAX_TRY as usize - 1);
+
+    assert!(matches!(
+        track(ckb, &tx_hash).await,
+        OperationStatus::Success
+    ));
+}
+
+#[tokio::test]
+async fn track_timed_out_tx() {
+    let ckb = MockCkbRpc::new();
+    // still pending when the tracking stops
+    let tx_hash = sent_tx(&ckb).await;
+
+    assert!(matches!(
+        track(ckb, &tx_hash).await,
+        OperationStatus::TimedOut
+    ));
+}
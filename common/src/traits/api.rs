@@ -1,39 +1,133 @@
 use crate::Result;
 use async_trait::async_trait;
 
-use crate::types::{relation_db::transaction::Model, smt::Address};
+use crate::types::api::{
+    AccountPortfolio, AddressAmount, BatchOperation, ChainState, Page, RewardEstimate,
+    RewardSmtProof, RewardState, SmtProof,
+};
+use crate::types::tx_builder::{Amount, Epoch};
+use crate::types::{
+    relation_db::transaction::Model, smt::Address, Transaction, TransactionView, H256,
+};
 
 #[async_trait]
 pub trait APIAdapter: Send + Sync {
     async fn get_records_by_address(
         &self,
         addr: Address,
-        offset: u64,
+        page_token: Option<String>,
         limit: u64,
-    ) -> Result<Vec<Model>>;
+    ) -> Result<Page<Model>>;
 
     async fn get_operation_history(
         &self,
         addr: Address,
         operation: u32,
-        offset: u64,
+        event: Option<u32>,
+        page_token: Option<String>,
         limit: u64,
-    ) -> Result<Vec<Model>>;
+    ) -> Result<Page<Model>>;
 
     async fn get_stake_amount_by_epoch(
         &self,
         operation: u32,
-        offset: u64,
+        page_token: Option<String>,
         limit: u64,
-    ) -> Result<Vec<Model>>;
+    ) -> Result<Page<Model>>;
 
-    async fn get_top_stake_address(&self, operation: u32) -> Result<Vec<Model>>;
+    /// The addresses with the sums of their amounts of the operation, from the
+    /// largest.
+    async fn get_top_stake_address(
+        &self,
+        operation: u32,
+        page_token: Option<String>,
+        limit: u64,
+    ) -> Result<Page<AddressAmount>>;
 
     async fn get_address_state(&self, addr: Address) -> Result<Vec<Model>>;
 
     async fn get_latest_stake_transactions(
         &self,
-        offset: u64,
-        page_size: u64,
-    ) -> Result<Vec<Model>>;
+        page_token: Option<String>,
+        limit: u64,
+    ) -> Result<Page<Model>>;
+
+    /// Build an unsigned transaction which sets the commission rate of the
+    /// staker in its delegate requirement cell.
+    async fn build_stake_rate_tx(
+        &self,
+        staker: Address,
+        commission_rate: u8,
+    ) -> Result<TransactionView>;
+
+    /// Build an unsigned stake or redeem transaction for the staker.
+    async fn build_stake_tx(
+        &self,
+        staker: Address,
+        amount: Amount,
+        is_increase: bool,
+    ) -> Result<TransactionView>;
+
+    /// Build an unsigned delegate or undelegate transaction from the delegator
+    /// to the staker.
+    async fn build_delegate_tx(
+        &self,
+        delegator: Address,
+        staker: Address,
+        amount: Amount,
+        is_increase: bool,
+    ) -> Result<TransactionView>;
+
+    /// Build an unsigned transaction which sends the stake, delegates and
+    /// withdraw of the user together.
+    async fn build_batch_tx(&self, user: Address, batch: BatchOperation)
+        -> Result<TransactionView>;
+
+    /// Build an unsigned transaction which withdraws the unlocked tokens.
+    async fn build_withdraw_tx(&self, user: Address) -> Result<TransactionView>;
+
+    /// Build an unsigned transaction which claims all the claimable rewards.
+    async fn build_reward_tx(&self, user: Address) -> Result<TransactionView>;
+
+    /// Relay the signed transaction to CKB and record the operations decoded
+    /// from it as pending. The records are updated once the transaction is
+    /// committed or rejected.
+    async fn send_transaction(&self, tx: Transaction) -> Result<H256>;
+
+    /// Prove the amounts of the stakers in the stake SMT of the epoch.
+    async fn get_stake_smt_proof(&self, epoch: Epoch, stakers: Vec<Address>) -> Result<SmtProof>;
+
+    /// Prove the amounts of the delegators in the delegate SMT of the staker at
+    /// the epoch.
+    async fn get_delegate_smt_proof(
+        &self,
+        staker: Address,
+        epoch: Epoch,
+        delegators: Vec<Address>,
+    ) -> Result<SmtProof>;
+
+    /// Prove the last claimed epochs of the addresses in the reward SMT.
+    async fn get_reward_smt_proof(&self, addresses: Vec<Address>) -> Result<RewardSmtProof>;
+
+    async fn get_stake_smt_root(&self) -> Result<ckb_types::H256>;
+
+    async fn get_delegate_smt_root(&self, staker: Address) -> Result<ckb_types::H256>;
+
+    async fn get_reward_smt_root(&self) -> Result<ckb_types::H256>;
+
+    /// The chain state may be cached for a few seconds.
+    async fn get_chain_state(&self) -> Result<ChainState>;
+
+    async fn get_account_portfolio(&self, addr: Address) -> Result<AccountPortfolio>;
+
+    async fn get_reward_state(&self, addr: Address) -> Result<RewardState>;
+
+    /// Calculate the rewards of the epochs from the SMTs without building a
+    /// transaction.
+    async fn estimate_rewards(
+        &self,
+        addr: Address,
+        from_epoch: Epoch,
+        to_epoch: Epoch,
+    ) -> Result<RewardEstimate>;
 }
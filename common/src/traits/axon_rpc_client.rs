@@ -1,21 +1,35 @@
 use async_trait::async_trait;
 
-use crate::types::{api::ChainState, axon_rpc_client::LatestCheckPointInfo, ckb_rpc_client::Cell};
+use crate::types::{
+    axon_rpc_client::{Block, BlockNumber, Header, LatestCheckPointInfo, Metadata},
+    ckb_rpc_client::Cell,
+};
 use anyhow::Result;
+use futures::stream::BoxStream;
 
 #[async_trait]
 pub trait SubmitProcess {
     fn is_closed(&self) -> bool;
     // if false return, it means this cell process should be shutdown
     async fn notify_axon(&mut self, cell: &Cell) -> bool;
+    // called with the first block and the notified cells that are reverted by
+    // a chain reorg, the data derived from them should be dropped
+    async fn rollback(&mut self, from: u64, cells: &[Cell]) -> bool;
 }
 
 #[async_trait]
 pub trait AxonRpc: Send + Sync {
     async fn get_checkpoint_info(&self) -> Result<LatestCheckPointInfo>;
+
+    async fn get_current_metadata(&self) -> Result<Metadata>;
+
+    // get the latest block if number is none
+    async fn get_block(&self, number: Option<BlockNumber>) -> Result<Block>;
 }
 
-#[async_trait]
 pub trait AxonWsRpc: Send + Sync {
-    async fn sub_axon_header(&self) -> Result<ChainState>;
+    // the stream of new headers never ends, the subscription is renewed with a
+    // backoff after the connection is lost, and the headers produced meanwhile
+    // are skipped
+    fn sub_axon_header(&self) -> BoxStream<'static, Header>;
 }
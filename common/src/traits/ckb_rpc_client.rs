@@ -2,10 +2,12 @@ use anyhow::Result;
 use async_trait::async_trait;
 use ckb_types::H256;
 
-use crate::types::ckb_rpc_client::{Cell, IndexerTip, Order, Pagination, RpcSearchKey, SearchKey};
+use crate::types::ckb_rpc_client::{
+    Cell, FeeRateStatistics, IndexerTip, Order, Pagination, RpcSearchKey, SearchKey, Tx,
+};
 use crate::types::{
-    BlockNumber, CellWithStatus, JsonBytes, OutPoint, OutputsValidator, Transaction,
-    TransactionWithStatusResponse, Uint32,
+    BlockNumber, CellWithStatus, HeaderView, JsonBytes, OutPoint, OutputsValidator, Transaction,
+    TransactionWithStatusResponse, Uint32, Uint64,
 };
 
 #[async_trait]
@@ -23,6 +25,15 @@ pub trait CkbRpc: Send + Sync + Clone {
     // ckb indexer `get_indexer_tip`
     async fn get_indexer_tip(&self) -> Result<IndexerTip>;
 
+    // ckb indexer `get_transactions`, the spent cells are included
+    async fn get_transactions(
+        &self,
+        search_key: SearchKey,
+        order: Order,
+        limit: Uint32,
+        after: Option<JsonBytes>,
+    ) -> Result<Pagination<Tx>>;
+
     // Pool
     async fn send_transaction(
         &self,
@@ -30,8 +41,18 @@ pub trait CkbRpc: Send + Sync + Clone {
         outputs_validator: Option<OutputsValidator>,
     ) -> Result<H256>;
 
+    // the fee rates of the recent `target` blocks, in shannons per KB
+    async fn get_fee_rate_statistics(
+        &self,
+        target: Option<Uint64>,
+    ) -> Result<Option<FeeRateStatistics>>;
+
     // Chain
     async fn get_transaction(&self, hash: H256) -> Result<Option<TransactionWithStatusResponse>>;
+
+    async fn get_block_hash(&self, number: BlockNumber) -> Result<Option<H256>>;
+
+    async fn get_header_by_number(&self, number: BlockNumber) -> Result<Option<HeaderView>>;
 }
 
 #[async_trait]
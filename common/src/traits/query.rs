@@ -2,39 +2,130 @@ use crate::Result;
 use async_trait::async_trait;
 
 use crate::types::{
-    relation_db::transaction::{self, Model},
-    smt::Address,
+    api::{AddressAmount, Page},
+    relation_db::{
+        delegation, epoch_snapshot, reward_claim, staker,
+        transaction::{self, Model},
+        withdraw_unlock,
+    },
+    smt::{Address, Epoch},
 };
 
+/// Drops the data derived from the blocks since `from`, which are reverted by a
+/// reorg.
+#[async_trait]
+pub trait ChainRollback: Send + Sync {
+    async fn rollback_blocks(&self, from: u64) -> Result<()>;
+}
+
 #[async_trait]
 pub trait TransactionStorage {
-    async fn insert(&mut self, tx_record: transaction::ActiveModel) -> Result<()>;
+    async fn insert(&self, tx_record: transaction::ActiveModel) -> Result<()>;
+
+    async fn update_status(&self, tx_hash: String, status: u32) -> Result<()>;
+
+    /// Writes the records indexed from the outputs of a committed tx. They
+    /// replace the pending records written when the tx was sent, and a record
+    /// indexed again is updated instead of duplicated.
+    async fn save_indexed_records(
+        &self,
+        tx_hash: String,
+        records: Vec<transaction::ActiveModel>,
+    ) -> Result<()>;
+
+    async fn get_records_by_tx_hash(&self, tx_hash: String) -> Result<Vec<Model>>;
 
     async fn get_records_by_address(
         &self,
         addr: Address,
-        offset: u64,
+        page_token: Option<String>,
         limit: u64,
-    ) -> Result<Vec<Model>>;
+    ) -> Result<Page<Model>>;
 
     async fn get_operation_history(
         &self,
         addr: Address,
         operation: u32,
-        offset: u64,
+        event: Option<u32>,
+        page_token: Option<String>,
         limit: u64,
-    ) -> Result<Vec<Model>>;
+    ) -> Result<Page<Model>>;
 
     async fn get_stake_amount_by_epoch(
         &self,
         operation: u32,
-        offset: u64,
+        page_token: Option<String>,
         limit: u64,
-    ) -> Result<Vec<Model>>;
+    ) -> Result<Page<Model>>;
 
-    async fn get_top_stake_address(&self, operation: u32) -> Result<Vec<Model>>;
+    /// The addresses with the sums of their amounts of the operation, from the
+    /// largest. A redeem is taken off the sum.
+    async fn get_top_stake_address(
+        &self,
+        operation: u32,
+        page_token: Option<String>,
+        limit: u64,
+    ) -> Result<Page<AddressAmount>>;
 
     async fn get_address_state(&self, addr: Address) -> Result<Vec<Model>>;
 
-    async fn get_latest_stake_transactions(&self, offset: u64, limit: u64) -> Result<Vec<Model>>;
+    async fn get_latest_stake_transactions(
+        &self,
+        page_token: Option<String>,
+        limit: u64,
+    ) -> Result<Page<Model>>;
+
+    /// Inserts the staker or replaces its delegate requirement.
+    async fn upsert_staker(&self, staker: staker::ActiveModel) -> Result<()>;
+
+    async fn get_staker(&self, addr: Address) -> Result<Option<staker::Model>>;
+
+    /// Inserts the delegation or replaces the amount of the same staker,
+    /// delegator and epoch.
+    async fn upsert_delegation(&self, delegation: delegation::ActiveModel) -> Result<()>;
+
+    async fn get_delegations_by_staker(
+        &self,
+        staker: Address,
+        epoch: Epoch,
+    ) -> Result<Vec<delegation::Model>>;
+
+    async fn get_delegations_by_delegator(
+        &self,
+        delegator: Address,
+        epoch: Epoch,
+    ) -> Result<Vec<delegation::Model>>;
+
+    /// The latest delegation of the delegator to the staker before the epoch.
+    async fn get_delegation_before(
+        &self,
+        staker: Address,
+        delegator: Address,
+        epoch: Epoch,
+    ) -> Result<Option<delegation::Model>>;
+
+    async fn insert_epoch_snapshot(&self, snapshot: epoch_snapshot::ActiveModel) -> Result<()>;
+
+    async fn get_epoch_snapshot(&self, epoch: Epoch) -> Result<Option<epoch_snapshot::Model>>;
+
+    /// Inserts the reward claim or replaces the one of the same tx.
+    async fn upsert_reward_claim(&self, claim: reward_claim::ActiveModel) -> Result<()>;
+
+    async fn get_reward_claims(&self, addr: Address) -> Result<Vec<reward_claim::Model>>;
+
+    /// Inserts the withdraw unlock or replaces the one of the same tx and
+    /// unlock epoch.
+    async fn upsert_withdraw_unlock(&self, unlock: withdraw_unlock::ActiveModel) -> Result<()>;
+
+    /// The withdraw unlocks of the address which are unlocked at the epoch.
+    async fn get_unlocked_withdraws(
+        &self,
+        addr: Address,
+        epoch: Epoch,
+    ) -> Result<Vec<withdraw_unlock::Model>>;
+
+    /// Drops the transaction records, the stakers and the delegations indexed
+    /// from the blocks since `from`, which are reverted by a reorg. A staker
+    /// who is still delegated to is kept until its stake tx is indexed again.
+    async fn rollback_blocks(&self, from: u64) -> Result<()>;
 }
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use std::collections::HashMap;
+use std::path::Path;
 
 use crate::types::smt::{
     Address, Amount, Delegator, Epoch, Proof, ProposalCount, Root, Staker, UserAmount, Validator,
@@ -65,6 +66,9 @@ pub trait DelegateSmtStorage: Send + Sync {
 
     async fn get_top_roots(&self, stakers: Vec<Staker>) -> Result<HashMap<Staker, Root>>;
 
+    /// The stakers whose delegate SMTs are not empty.
+    async fn get_stakers(&self) -> Result<Vec<Staker>>;
+
     async fn generate_sub_proof(
         &self,
         staker: Staker,
@@ -104,3 +108,26 @@ pub trait ProposalSmtStorage: Send + Sync {
 
     async fn generate_top_proof(&self, epochs: Vec<Epoch>) -> Result<Proof>;
 }
+
+/// Snapshot of all the SMTs, used to bootstrap a new kicker from an existing
+/// one.
+pub trait SmtSnapshot {
+    // the SMTs are read at a point in time, so they may be updated meanwhile
+    fn export_snapshot(&self, epoch: Epoch, path: &Path) -> Result<()>;
+
+    // only an empty SMT database can be imported into, the epoch of the snapshot
+    // is returned
+    fn import_snapshot(&self, path: &Path) -> Result<Epoch>;
+
+    // copies all the SMTs into a new database at the path, which replaces the
+    // one left there, so the copy can be written without touching the original
+    fn scratch_copy(&self, path: &Path) -> Result<Self>
+    where
+        Self: Sized;
+
+    // replaces all the SMTs with the ones of the copy at once, so a scratch copy
+    // is applied after the tx built on it is committed
+    fn replace_with(&self, copy: &Self) -> Result<()>
+    where
+        Self: Sized;
+}
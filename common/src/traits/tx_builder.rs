@@ -13,6 +13,7 @@ use crate::types::tx_builder::*;
 pub trait IStakeTxBuilder<'a, C: CkbRpc> {
     fn new(
         ckb: &'a C,
+        ctx: &'a ChainContext,
         type_ids: StakeTypeIds,
         staker: EthAddress,
         current_epoch: Epoch,
@@ -27,6 +28,7 @@ pub trait IStakeTxBuilder<'a, C: CkbRpc> {
 pub trait IDelegateTxBuilder<'a, C: CkbRpc, D: DelegateSmtStorage> {
     fn new(
         ckb: &'a C,
+        ctx: &'a ChainContext,
         type_ids: StakeTypeIds,
         delegator: EthAddress,
         current_epoch: Epoch,
@@ -37,9 +39,43 @@ pub trait IDelegateTxBuilder<'a, C: CkbRpc, D: DelegateSmtStorage> {
     async fn build_tx(self) -> Result<TransactionView>;
 }
 
+#[async_trait]
+pub trait IBatchTxBuilder<'a, C: CkbRpc, D: DelegateSmtStorage> {
+    fn new(
+        ckb: &'a C,
+        ctx: &'a ChainContext,
+        type_ids: StakeTypeIds,
+        user: EthAddress,
+        current_epoch: Epoch,
+        batch: BatchItems,
+        delegate_smt_storage: D,
+    ) -> Self;
+
+    async fn build_tx(self) -> Result<TransactionView>;
+}
+
 #[async_trait]
 pub trait IWithdrawTxBuilder<'a, C: CkbRpc> {
-    fn new(ckb: &'a C, type_ids: StakeTypeIds, user: EthAddress, current_epoch: Epoch) -> Self;
+    fn new(
+        ckb: &'a C,
+        ctx: &'a ChainContext,
+        type_ids: StakeTypeIds,
+        user: EthAddress,
+        current_epoch: Epoch,
+    ) -> Self;
+
+    async fn build_tx(self) -> Result<TransactionView>;
+}
+
+#[async_trait]
+pub trait IRequirementTxBuilder<'a, C: CkbRpc> {
+    fn new(
+        ckb: &'a C,
+        ctx: &'a ChainContext,
+        type_ids: StakeTypeIds,
+        staker: EthAddress,
+        commission_rate: u8,
+    ) -> Self;
 
     async fn build_tx(self) -> Result<TransactionView>;
 }
@@ -52,12 +88,13 @@ where
 {
     async fn new(
         ckb: &'a C,
+        ctx: &'a ChainContext,
         type_ids: RewardTypeIds,
         smt: S,
         user: EthAddress,
         current_epoch: Epoch,
         epoch_count: u64,
-    ) -> Self;
+    ) -> Result<Self>;
 
     async fn build_tx(mut self) -> Result<TransactionView>;
 }
@@ -69,54 +106,58 @@ where
 {
     async fn new(
         ckb: &'a C,
-        kicker_key: PrivateKey,
+        ctx: &'a ChainContext,
+        kicker: SignerLock,
         type_ids: CheckpointTypeIds,
         epoch_len: u64,
         new_checkpoint: Checkpoint,
         proof: CheckpointProof,
     ) -> Self;
 
-    async fn build_tx(self) -> Result<TransactionView>;
+    async fn build_tx(self) -> Result<UnsignedTx>;
 }
 
 #[async_trait]
 pub trait IMetadataTxBuilder<'a, C, PSmt> {
     async fn new(
         ckb: &'a C,
-        kicker: PrivateKey,
+        ctx: &'a ChainContext,
+        kicker: SignerLock,
         type_ids: MetadataTypeIds,
         last_checkpoint: Cell,
         smt: PSmt,
         path: std::path::PathBuf,
     ) -> Self;
 
-    async fn build_tx(self) -> Result<TransactionView>;
+    async fn build_tx(self) -> Result<UnsignedTx>;
 }
 
 #[async_trait]
 pub trait IStakeSmtTxBuilder<'a, C: CkbRpc, S: StakeSmtStorage> {
     fn new(
         ckb: &'a C,
-        kicker: PrivateKey,
+        ctx: &'a ChainContext,
+        kicker: SignerLock,
         current_epoch: Epoch,
         type_ids: StakeSmtTypeIds,
         stake_cells: Vec<Cell>,
         stake_smt_storage: S,
     ) -> Self;
 
-    async fn build_tx(self) -> Result<(TransactionView, NonTopStakers)>;
+    async fn build_tx(self) -> Result<(UnsignedTx, NonTopStakers)>;
 }
 
 #[async_trait]
 pub trait IDelegateSmtTxBuilder<'a, C: CkbRpc, D: DelegateSmtStorage> {
     fn new(
         ckb: &'a C,
-        kicker: PrivateKey,
+        ctx: &'a ChainContext,
+        kicker: SignerLock,
         current_epoch: Epoch,
         type_ids: DelegateSmtTypeIds,
         delegate_at_cells: Vec<Cell>,
         delegate_smt_storage: D,
     ) -> Self;
 
-    async fn build_tx(mut self) -> Result<(TransactionView, NonTopDelegators)>;
+    async fn build_tx(mut self) -> Result<(UnsignedTx, NonTopDelegators)>;
 }
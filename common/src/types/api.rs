@@ -1,22 +1,44 @@
-use crate::types::H160;
+use crate::types::{JsonBytes, H160};
 use ckb_types::H256;
 use serde::{Deserialize, Serialize};
 
-use crate::types::axon_rpc_client::{Header, Metadata};
-
+/// The period is the one of the latest checkpoint on CKB, the total amounts are
+/// the sums of the stake and delegate SMTs of the current epoch.
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct ChainState {
-    pub epoch:        u64,
-    pub block_number: u64,
+    pub epoch:                 u64,
+    pub period:                u32,
+    pub block_number:          u64,
+    pub validators:            Vec<H160>,
+    pub total_stake_amount:    String,
+    pub total_delegate_amount: String,
+}
+
+/// A page of a history ordered from the newest. The next page token is opaque
+/// and absent on the last page, the total counts all the pages.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Page<T> {
+    pub items:           Vec<T>,
+    pub total:           u64,
+    pub next_page_token: Option<String>,
 }
 
-impl ChainState {
-    pub fn new(h: Header, m: Metadata) -> Self {
-        ChainState {
-            block_number: h.number,
-            epoch:        m.version.end.saturating_sub(m.version.start),
+impl<T> Page<T> {
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items:           self.items.into_iter().map(f).collect(),
+            total:           self.total,
+            next_page_token: self.next_page_token,
         }
     }
+
+    pub fn try_map<U, E>(self, f: impl FnMut(T) -> Result<U, E>) -> Result<Page<U>, E> {
+        Ok(Page {
+            items:           self.items.into_iter().map(f).collect::<Result<_, _>>()?,
+            total:           self.total,
+            next_page_token: self.next_page_token,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Copy, Deserialize, Serialize)]
@@ -40,6 +62,7 @@ pub enum OperationType {
     Stake,
     Delegate,
     Reward,
+    Withdraw,
 }
 
 impl From<u32> for OperationType {
@@ -48,6 +71,7 @@ impl From<u32> for OperationType {
             0 => OperationType::Stake,
             1 => OperationType::Delegate,
             2 => OperationType::Reward,
+            3 => OperationType::Withdraw,
             _ => panic!("Invalid value for OperationType"),
         }
     }
@@ -58,6 +82,8 @@ pub enum OperationStatus {
     Success,
     Pending,
     Failed,
+    // neither committed nor rejected when the tracking stops
+    TimedOut,
 }
 
 impl From<u32> for OperationStatus {
@@ -66,6 +92,7 @@ impl From<u32> for OperationStatus {
             0 => OperationStatus::Success,
             1 => OperationStatus::Pending,
             2 => OperationStatus::Failed,
+            3 => OperationStatus::TimedOut,
             _ => panic!("Invalid value for OperationStatus"),
         }
     }
@@ -108,16 +135,16 @@ pub struct AddressAmount {
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct StakeState {
-    pub total_amount:        u32,
-    pub stake_amount:        u32,
-    pub delegate_amount:     u32,
-    pub withdrawable_amount: u32,
+    pub total_amount:        String,
+    pub stake_amount:        String,
+    pub delegate_amount:     String,
+    pub withdrawable_amount: String,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct StakeHistory {
     pub id:           String,
-    pub amount:       u32,
+    pub amount:       String,
     pub event:        HistoryEvent,
     pub status:       OperationStatus,
     pub transactions: Vec<HistoryTransactions>,
@@ -130,16 +157,18 @@ pub struct HistoryTransactions {
     pub timestamp: u64,
 }
 
+/// The rewards of an address which are not claimed yet and the rewards it has
+/// claimed, in decimal strings.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct RewardState {
-    pub lock_amount:   u32,
-    pub unlock_amount: u32,
+    pub lock_amount:   String,
+    pub unlock_amount: String,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct RewardHistory {
     pub epoch:  u32,
-    pub amount: u32,
+    pub amount: String,
     pub locked: bool,
     pub from:   RewardFrom,
 }
@@ -148,13 +177,146 @@ pub struct RewardHistory {
 pub struct RewardFrom {
     pub reward_type: OperationType,
     pub address:     H160,
-    pub amount:      u64,
+    pub amount:      String,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct StakeTransaction {
     pub timestamp: u64,
     pub hash:      H256,
-    pub amount:    u64,
+    pub amount:    String,
     pub status:    OperationStatus,
 }
+
+/// The tokens of an address, derived from the SMTs of the current epoch and
+/// the AT cells of the address on CKB.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AccountPortfolio {
+    pub epoch:               u64,
+    pub stake_amount:        String,
+    pub delegations:         Vec<DelegationAmount>,
+    pub pending_stake:       Option<PendingDelta>,
+    pub pending_delegations: Vec<PendingDelta>,
+    pub withdrawals:         Vec<WithdrawalAmount>,
+    pub unclaimed_reward:    Option<UnclaimedReward>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DelegationAmount {
+    pub staker: H160,
+    pub amount: String,
+}
+
+/// A stake or delegate change which is not inaugurated yet.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PendingDelta {
+    pub staker:             H160,
+    pub is_increase:        bool,
+    pub amount:             String,
+    pub inauguration_epoch: u64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WithdrawalAmount {
+    pub unlock_epoch: u64,
+    pub amount:       String,
+    pub withdrawable: bool,
+}
+
+/// The rewards of the epochs which can be claimed now.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UnclaimedReward {
+    pub start_epoch: u64,
+    pub end_epoch:   u64,
+    pub amount:      String,
+}
+
+/// The stake, delegates and withdraw of an address which are built into one
+/// transaction. The withdraw goes first, so the unlocked tokens can be staked
+/// or delegated again. The amounts are decimal strings.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct BatchOperation {
+    pub stake:     Option<StakeDelta>,
+    #[serde(default)]
+    pub delegates: Vec<DelegateDelta>,
+    #[serde(default)]
+    pub withdraw:  bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StakeDelta {
+    pub is_increase: bool,
+    pub amount:      String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DelegateDelta {
+    pub staker:      H160,
+    pub is_increase: bool,
+    pub amount:      String,
+}
+
+/// The estimated rewards of an address, the claimable ones are the epochs
+/// which can be claimed now.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RewardEstimate {
+    pub current_epoch:    u64,
+    pub total_amount:     String,
+    pub claimable_amount: String,
+    pub epochs:           Vec<EpochRewardEstimate>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EpochRewardEstimate {
+    pub epoch:      u64,
+    pub amount:     String,
+    pub claimable:  bool,
+    pub validators: Vec<ValidatorRewardEstimate>,
+}
+
+/// The total reward is shared by the validator and its delegators, the reward
+/// is the share of the address.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ValidatorRewardEstimate {
+    pub validator:             H160,
+    pub propose_count:         u64,
+    pub commission_rate:       u8,
+    pub stake_amount:          String,
+    pub total_delegate_amount: String,
+    pub total_reward:          String,
+    pub reward:                String,
+}
+
+/// Proof of some leaves of an epoch in the stake SMT or a delegate SMT. The sub
+/// proof proves the leaves against the sub root of the epoch, and the top
+/// proof proves the sub root against the top root.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SmtProof {
+    pub epoch:     u64,
+    pub top_root:  H256,
+    pub top_proof: JsonBytes,
+    pub sub_root:  H256,
+    pub sub_proof: JsonBytes,
+    pub leaves:    Vec<SmtAmountLeaf>,
+}
+
+/// A missing leaf has a zero amount.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SmtAmountLeaf {
+    pub address: H160,
+    pub amount:  String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RewardSmtProof {
+    pub root:   H256,
+    pub proof:  JsonBytes,
+    pub leaves: Vec<RewardSmtLeaf>,
+}
+
+/// The last epoch whose reward has been claimed, none if never claimed.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RewardSmtLeaf {
+    pub address: H160,
+    pub epoch:   Option<u64>,
+}
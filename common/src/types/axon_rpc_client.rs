@@ -46,6 +46,15 @@ pub struct Header {
     pub chain_id:                 u64,
 }
 
+/// The Web3 style header notified by `eth_subscribe("newHeads")`, only the
+/// fields needed to fetch the Axon header are kept.
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+pub struct NewHead {
+    #[serde(deserialize_with = "deserialize_uint")]
+    pub number: BlockNumber,
+    pub hash:   Hash,
+}
+
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
 pub struct Proof {
     #[serde(deserialize_with = "deserialize_uint")]
@@ -1,3 +1,4 @@
+use std::collections::VecDeque;
 use std::future::Future;
 
 use anyhow::Result;
@@ -10,6 +11,18 @@ use serde::{Deserialize, Serialize};
 
 pub type RPC<T> = dyn Future<Output = Result<T>>;
 
+// Blocks within this depth are not scanned in case of a reorg.
+pub const DEFAULT_CONFIRMATIONS: u64 = 24;
+
+// the minimum fee rate accepted by the nodes, shannons per KB
+pub const MIN_FEE_RATE: u64 = 1000;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FeeRateStatistics {
+    pub mean:   Uint64,
+    pub median: Uint64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct IndexerTip {
     pub block_hash:   H256,
@@ -144,6 +157,9 @@ pub struct RpcSearchKey {
     pub script_type:        ScriptType,
     pub script_search_mode: Option<IndexerScriptSearchMode>,
     pub filter:             Option<RpcSearchKeyFilter>,
+    // the confirmations of the network if not set
+    #[serde(default)]
+    pub confirmations:      Option<u64>,
 }
 
 impl RpcSearchKey {
@@ -183,7 +199,34 @@ impl RpcSearchKeyFilter {
     }
 }
 
+/// A block range scanned by a cell process. The hash of its last block is
+/// checked against the chain to detect a fork.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ScannedRange {
+    pub from:  BlockNumber,
+    pub to:    BlockNumber,
+    pub hash:  H256,
+    pub cells: Vec<Cell>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ScanTipInfo {
+    // the next block to scan
+    pub number: BlockNumber,
+    // the latest scanned ranges, oldest first
+    pub ranges: VecDeque<ScannedRange>,
+}
+
+impl ScanTipInfo {
+    pub fn new(number: BlockNumber) -> Self {
+        ScanTipInfo {
+            number,
+            ranges: VecDeque::new(),
+        }
+    }
+}
+
 pub trait TipState {
-    fn load(&self) -> &BlockNumber;
-    fn update(&mut self, current: BlockNumber);
+    fn load(&self) -> &ScanTipInfo;
+    fn update(&mut self, current: ScanTipInfo);
 }
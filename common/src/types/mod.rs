@@ -14,6 +14,6 @@ pub use ethereum_types::{
 };
 
 pub use ckb_jsonrpc_types::{
-    BlockNumber, CellWithStatus, JsonBytes, OutPoint, OutputsValidator, Transaction,
-    TransactionWithStatusResponse, Uint32,
+    BlockNumber, CellWithStatus, HeaderView, JsonBytes, OutPoint, OutputsValidator, Status,
+    Transaction, TransactionView, TransactionWithStatusResponse, Uint32, Uint64,
 };
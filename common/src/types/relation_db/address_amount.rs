@@ -0,0 +1,23 @@
+//! `SeaORM` Entity of the amounts added and redeemed by the addresses in every
+//! operation.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Deserialize, Serialize)]
+#[sea_orm(table_name = "address_amount")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub address:   String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub operation: u32,
+    pub added:     String,
+    pub redeemed:  String,
+    /// the added amount less the redeemed, zero at least
+    pub amount:    String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
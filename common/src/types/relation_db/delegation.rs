@@ -0,0 +1,36 @@
+//! `SeaORM` Entity of the delegations to the stakers in every epoch.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Deserialize, Serialize)]
+#[sea_orm(table_name = "delegation")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id:           u32,
+    pub staker:       String,
+    pub delegator:    String,
+    pub epoch:        u64,
+    pub amount:       String,
+    pub block_number: Option<u64>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::staker::Entity",
+        from = "Column::Staker",
+        to = "super::staker::Column::Address",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Staker,
+}
+
+impl Related<super::staker::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Staker.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,38 @@
+//! `SeaORM` Entity of the epochs referenced by the reward claims and the
+//! withdraw unlocks.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Deserialize, Serialize)]
+#[sea_orm(table_name = "epoch_snapshot")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub epoch:                 u64,
+    pub total_stake_amount:    String,
+    pub total_delegate_amount: String,
+    pub staker_count:          u32,
+    pub timestamp:             u64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::reward_claim::Entity")]
+    RewardClaim,
+    #[sea_orm(has_many = "super::withdraw_unlock::Entity")]
+    WithdrawUnlock,
+}
+
+impl Related<super::reward_claim::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RewardClaim.def()
+    }
+}
+
+impl Related<super::withdraw_unlock::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::WithdrawUnlock.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
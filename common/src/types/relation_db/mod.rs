@@ -2,4 +2,10 @@
 
 pub mod prelude;
 
+pub mod address_amount;
+pub mod delegation;
+pub mod epoch_snapshot;
+pub mod reward_claim;
+pub mod staker;
 pub mod transaction;
+pub mod withdraw_unlock;
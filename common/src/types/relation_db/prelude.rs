@@ -1,3 +1,9 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.11.3
 
+pub use super::address_amount::Entity as AddressAmount;
+pub use super::delegation::Entity as Delegation;
+pub use super::epoch_snapshot::Entity as EpochSnapshot;
+pub use super::reward_claim::Entity as RewardClaim;
+pub use super::staker::Entity as Staker;
 pub use super::transaction::Entity as Transaction;
+pub use super::withdraw_unlock::Entity as WithdrawUnlock;
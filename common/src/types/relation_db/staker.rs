@@ -0,0 +1,31 @@
+//! `SeaORM` Entity of the stakers and their delegate requirements.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Deserialize, Serialize)]
+#[sea_orm(table_name = "staker")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub address:            String,
+    pub commission_rate:    u32,
+    pub maximum_delegators: u32,
+    pub threshold:          String,
+    pub epoch:              u64,
+    // the block of the stake tx last updating the requirement
+    pub block_number:       Option<u64>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::delegation::Entity")]
+    Delegation,
+}
+
+impl Related<super::delegation::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Delegation.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
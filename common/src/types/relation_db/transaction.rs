@@ -1,5 +1,6 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.11.3
 
+use anyhow::{anyhow, Result};
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -13,17 +14,39 @@ pub struct Model {
     pub operation:           u32,
     pub event:               u32,
     pub tx_hash:             String,
-    pub total_amount:        u32,
-    pub stake_amount:        u32,
-    pub delegate_amount:     u32,
-    pub withdrawable_amount: u32,
+    pub total_amount:        String,
+    pub stake_amount:        String,
+    pub delegate_amount:     String,
+    pub withdrawable_amount: String,
     pub stake_rate:          String,
     pub delegate_rate:       String,
     pub epoch:               u32,
     pub status:              u32,
+    // The output of the record, none for the txs sent by spark until they are
+    // indexed.
+    pub output_index:        Option<u32>,
+    // The index of the record in the output, a delegate cell records the
+    // delegations to several stakers.
+    pub item_index:          u32,
+    // The block of the output, none until the record is indexed.
+    pub block_number:        Option<u64>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {}
 
 impl ActiveModelBehavior for ActiveModel {}
+
+/// The amounts are stored as zero padded decimal strings, which are lossless
+/// for u128 on any database and are ordered the same as the amounts.
+pub fn encode_amount(amount: u128) -> String {
+    format!("{:039}", amount)
+}
+
+/// An amount which does not decode is an error rather than zero, a corrupt
+/// record must not show up as an empty balance.
+pub fn decode_amount(amount: &str) -> Result<u128> {
+    amount
+        .parse()
+        .map_err(|e| anyhow!("invalid amount {:?}: {}", amount, e))
+}
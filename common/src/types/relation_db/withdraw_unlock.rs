@@ -0,0 +1,36 @@
+//! `SeaORM` Entity of the redeemed amounts locked until the unlock epoch.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Deserialize, Serialize)]
+#[sea_orm(table_name = "withdraw_unlock")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id:           u32,
+    pub address:      String,
+    pub amount:       String,
+    pub epoch:        u64,
+    pub unlock_epoch: u64,
+    pub tx_hash:      String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::epoch_snapshot::Entity",
+        from = "Column::Epoch",
+        to = "super::epoch_snapshot::Column::Epoch",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    EpochSnapshot,
+}
+
+impl Related<super::epoch_snapshot::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::EpochSnapshot.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use std::fmt::{self, Formatter};
 use std::str::FromStr;
+use std::sync::Arc;
 
 use axon_types::{
     basic::{Byte20, Byte32, Byte48, Byte65, Identity},
@@ -11,12 +12,19 @@ use axon_types::{
     },
     stake::StakeInfoDelta,
 };
-use ckb_types::{H160, H256};
+use ckb_sdk::ScriptGroup;
+use ckb_types::{
+    bytes::Bytes,
+    core::{DepType, ScriptHashType, TransactionView},
+    packed::{CellDep, OutPoint, Script, WitnessArgs},
+    H160, H256,
+};
 use molecule::prelude::{Builder, Byte, Entity, Reader};
 use rlp::Encodable;
 use rlp_derive::{RlpDecodable, RlpEncodable};
 use serde::de::{self, Deserialize, Deserializer, Visitor};
 
+use crate::types::ckb_rpc_client::{DEFAULT_CONFIRMATIONS, MIN_FEE_RATE};
 use crate::types::primitive::Hasher;
 use crate::utils::convert::*;
 
@@ -39,6 +47,9 @@ pub enum NetworkType {
     Mainnet,
     Testnet,
     Devnet,
+    // a network described in the config, the scripts are all from its
+    // deployment manifest
+    Custom(String),
 }
 
 impl<'a> Deserialize<'a> for NetworkType {
@@ -59,11 +70,7 @@ impl<'a> Visitor<'a> for NetworkTypeVisitor {
     where
         E: de::Error,
     {
-        match v {
-            "mainnet" | "Mainnet" => Ok(NetworkType::Mainnet),
-            "testnet" | "Testnet" => Ok(NetworkType::Testnet),
-            _ => Err(de::Error::custom(format!("invalid network type: {}", v))),
-        }
+        v.parse().map_err(de::Error::custom)
     }
 
     fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
@@ -74,7 +81,35 @@ impl<'a> Visitor<'a> for NetworkTypeVisitor {
     }
 
     fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
-        formatter.write_str("mainnet or testnet")
+        formatter.write_str("mainnet, testnet, devnet or custom:<name>")
+    }
+}
+
+/// How the fee rate of a tx is decided, in shannons per KB.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeStrategy {
+    Fixed(u64),
+    // the median fee rate of the recent blocks, from the node
+    Estimated,
+    // the estimated fee rate, but no more than the cap
+    Capped(u64),
+}
+
+/// How the fee of a tx is paid.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeParams {
+    pub strategy:      FeeStrategy,
+    // how many times a tx is balanced again if its change can not pay the fee
+    pub balance_retry: u32,
+}
+
+impl Default for FeeParams {
+    fn default() -> Self {
+        Self {
+            strategy:      FeeStrategy::Fixed(MIN_FEE_RATE),
+            balance_retry: 0,
+        }
     }
 }
 
@@ -83,9 +118,16 @@ impl FromStr for NetworkType {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "mainnet" => Ok(NetworkType::Mainnet),
-            "testnet" => Ok(NetworkType::Testnet),
-            _ => Err(format!("invalid network type: {}", s)),
+            "mainnet" | "Mainnet" => Ok(NetworkType::Mainnet),
+            "testnet" | "Testnet" => Ok(NetworkType::Testnet),
+            "devnet" | "Devnet" => Ok(NetworkType::Devnet),
+            _ => match s.strip_prefix("custom:") {
+                Some(name) if !name.is_empty() => Ok(NetworkType::Custom(name.to_owned())),
+                _ => Err(format!(
+                    "invalid network type: {}, expected mainnet, testnet, devnet or custom:<name>",
+                    s
+                )),
+            },
         }
     }
 }
@@ -384,12 +426,140 @@ impl From<Validator> for AValidator {
     }
 }
 
+/// Where a contract is deployed.
+#[derive(Clone, Debug)]
+pub struct ScriptInfo {
+    pub code_hash: H256,
+    pub hash_type: ScriptHashType,
+    pub tx_hash:   H256,
+    pub index:     u32,
+    pub dep_type:  DepType,
+}
+
+impl ScriptInfo {
+    pub fn script(&self, args: Bytes) -> Script {
+        use ckb_types::prelude::{Builder as _, Entity as _, Pack};
+
+        Script::new_builder()
+            .code_hash(self.code_hash.pack())
+            .hash_type(self.hash_type.into())
+            .args(args.pack())
+            .build()
+    }
+
+    pub fn cell_dep(&self) -> CellDep {
+        use ckb_types::prelude::{Builder as _, Entity as _, Pack};
+
+        CellDep::new_builder()
+            .out_point(
+                OutPoint::new_builder()
+                    .tx_hash(self.tx_hash.pack())
+                    .index(self.index.pack())
+                    .build(),
+            )
+            .dep_type(self.dep_type.into())
+            .build()
+    }
+}
+
+/// The scripts every helper builds its scripts and cell deps from.
+#[derive(Clone, Debug)]
+pub struct ScriptRegistry {
+    pub omni_lock:                 ScriptInfo,
+    pub secp256k1_blake160:        ScriptInfo,
+    pub xudt_type:                 ScriptInfo,
+    pub always_success_lock:       ScriptInfo,
+    pub selection_lock:            ScriptInfo,
+    pub checkpoint_type:           ScriptInfo,
+    pub metadata_type:             ScriptInfo,
+    pub stake_lock:                ScriptInfo,
+    pub stake_smt_type:            ScriptInfo,
+    pub delegate_requirement_type: ScriptInfo,
+    pub delegate_lock:             ScriptInfo,
+    pub delegate_smt_type:         ScriptInfo,
+    pub withdraw_lock:             ScriptInfo,
+    pub reward_smt_type:           ScriptInfo,
+}
+
+/// The parameters of a network, a custom network describes them in the config.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct NetworkParams {
+    pub address_prefix: String,
+    // how many blocks a cell is confirmed after
+    #[serde(default = "default_confirmations")]
+    pub confirmations:  u64,
+}
+
+fn default_confirmations() -> u64 {
+    DEFAULT_CONFIRMATIONS
+}
+
+impl NetworkParams {
+    /// The parameters of a builtin network, none for a custom network.
+    pub fn builtin(network: &NetworkType) -> Option<Self> {
+        let address_prefix = match network {
+            NetworkType::Mainnet => "ckb",
+            NetworkType::Testnet | NetworkType::Devnet => "ckt",
+            NetworkType::Custom(_) => return None,
+        };
+        Some(NetworkParams {
+            address_prefix: address_prefix.to_owned(),
+            confirmations:  DEFAULT_CONFIRMATIONS,
+        })
+    }
+}
+
+/// The chain a tx is built for. It is passed to every tx builder and helper,
+/// so txs of different chains can be built in one process.
+#[derive(Clone, Debug)]
+pub struct ChainContext {
+    pub network:  NetworkType,
+    pub params:   NetworkParams,
+    pub scripts:  Arc<ScriptRegistry>,
+    pub type_ids: TypeIds,
+    pub fee:      FeeParams,
+}
+
+/// The lock of the cells which pay the fee of a tx. The tx is built with the
+/// placeholder witness and is signed afterwards.
+#[derive(Clone, Debug)]
+pub struct SignerLock {
+    pub lock:                Script,
+    pub cell_deps:           Vec<CellDep>,
+    pub witness_placeholder: WitnessArgs,
+}
+
+impl SignerLock {
+    pub fn add_cell_deps(&self, cell_deps: &mut Vec<CellDep>) {
+        for cell_dep in self.cell_deps.iter() {
+            if !cell_deps.contains(cell_dep) {
+                cell_deps.push(cell_dep.clone());
+            }
+        }
+    }
+}
+
+/// A built tx and the script groups left to be signed.
+#[derive(Clone, Debug)]
+pub struct UnsignedTx {
+    pub tx:            TransactionView,
+    pub script_groups: Vec<ScriptGroup>,
+}
+
 #[derive(Clone, Default, Debug)]
 pub struct CheckpointTypeIds {
     pub metadata_type_id:   H256,
     pub checkpoint_type_id: H256,
 }
 
+/// The stake, delegates and withdraw of a user which are sent in one tx.
+#[derive(Clone, Default, Debug)]
+pub struct BatchItems {
+    pub stake:     Option<StakeItem>,
+    pub delegates: Vec<DelegateItem>,
+    pub withdraw:  bool,
+}
+
 #[derive(Clone, Default, Debug)]
 pub struct StakeTypeIds {
     pub metadata_type_id:   H256,
@@ -431,7 +601,8 @@ pub struct MetadataTypeIds {
     pub xudt_owner:           H256,
 }
 
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
 pub struct TypeIds {
     pub issue_type_id:          H256,
     pub selection_type_id:      H256,
@@ -476,6 +647,97 @@ impl From<TypeIds> for ATypeIds {
     }
 }
 
+impl From<ATypeIds> for TypeIds {
+    fn from(v: ATypeIds) -> Self {
+        TypeIds {
+            issue_type_id:          H256::from_slice(v.issue_type_id().as_slice()).unwrap(),
+            selection_type_id:      H256::from_slice(v.selection_type_id().as_slice()).unwrap(),
+            metadata_code_hash:     H256::from_slice(v.metadata_code_hash().as_slice()).unwrap(),
+            metadata_type_id:       H256::from_slice(v.metadata_type_id().as_slice()).unwrap(),
+            checkpoint_code_hash:   H256::from_slice(v.checkpoint_code_hash().as_slice()).unwrap(),
+            checkpoint_type_id:     H256::from_slice(v.checkpoint_type_id().as_slice()).unwrap(),
+            stake_smt_code_hash:    H256::from_slice(v.stake_smt_code_hash().as_slice()).unwrap(),
+            stake_smt_type_id:      H256::from_slice(v.stake_smt_type_id().as_slice()).unwrap(),
+            delegate_smt_code_hash: H256::from_slice(v.delegate_smt_code_hash().as_slice())
+                .unwrap(),
+            delegate_smt_type_id:   H256::from_slice(v.delegate_smt_type_id().as_slice()).unwrap(),
+            reward_code_hash:       H256::from_slice(v.reward_code_hash().as_slice()).unwrap(),
+            reward_smt_type_id:     H256::from_slice(v.reward_type_id().as_slice()).unwrap(),
+            xudt_type_hash:         H256::from_slice(v.xudt_type_hash().as_slice()).unwrap(),
+            xudt_owner:             H256::from_slice(v.xudt_owner_lock_hash().as_slice()).unwrap(),
+            withdraw_code_hash:     H256::from_slice(v.withdraw_code_hash().as_slice()).unwrap(),
+            stake_code_hash:        H256::from_slice(v.stake_at_code_hash().as_slice()).unwrap(),
+            delegate_code_hash:     H256::from_slice(v.delegate_at_code_hash().as_slice()).unwrap(),
+        }
+    }
+}
+
+impl From<&TypeIds> for CheckpointTypeIds {
+    fn from(v: &TypeIds) -> Self {
+        CheckpointTypeIds {
+            metadata_type_id:   v.metadata_type_id.clone(),
+            checkpoint_type_id: v.checkpoint_type_id.clone(),
+        }
+    }
+}
+
+impl From<&TypeIds> for StakeTypeIds {
+    fn from(v: &TypeIds) -> Self {
+        StakeTypeIds {
+            metadata_type_id:   v.metadata_type_id.clone(),
+            checkpoint_type_id: v.checkpoint_type_id.clone(),
+            xudt_owner:         v.xudt_owner.clone(),
+        }
+    }
+}
+
+impl From<&TypeIds> for RewardTypeIds {
+    fn from(v: &TypeIds) -> Self {
+        RewardTypeIds {
+            selection_type_id:    v.selection_type_id.clone(),
+            metadata_type_id:     v.metadata_type_id.clone(),
+            checkpoint_type_id:   v.checkpoint_type_id.clone(),
+            reward_smt_type_id:   v.reward_smt_type_id.clone(),
+            stake_smt_type_id:    v.stake_smt_type_id.clone(),
+            delegate_smt_type_id: v.delegate_smt_type_id.clone(),
+            xudt_owner:           v.xudt_owner.clone(),
+        }
+    }
+}
+
+impl From<&TypeIds> for StakeSmtTypeIds {
+    fn from(v: &TypeIds) -> Self {
+        StakeSmtTypeIds {
+            metadata_type_id:   v.metadata_type_id.clone(),
+            stake_smt_type_id:  v.stake_smt_type_id.clone(),
+            checkpoint_type_id: v.checkpoint_type_id.clone(),
+            xudt_owner:         v.xudt_owner.clone(),
+        }
+    }
+}
+
+impl From<&TypeIds> for DelegateSmtTypeIds {
+    fn from(v: &TypeIds) -> Self {
+        DelegateSmtTypeIds {
+            metadata_type_id:     v.metadata_type_id.clone(),
+            delegate_smt_type_id: v.delegate_smt_type_id.clone(),
+            checkpoint_type_id:   v.checkpoint_type_id.clone(),
+            xudt_owner:           v.xudt_owner.clone(),
+        }
+    }
+}
+
+impl From<&TypeIds> for MetadataTypeIds {
+    fn from(v: &TypeIds) -> Self {
+        MetadataTypeIds {
+            metadata_type_id:     v.metadata_type_id.clone(),
+            stake_smt_type_id:    v.stake_smt_type_id.clone(),
+            delegate_smt_type_id: v.delegate_smt_type_id.clone(),
+            xudt_owner:           v.xudt_owner.clone(),
+        }
+    }
+}
+
 impl From<Metadata> for AMetadata {
     fn from(metadata: Metadata) -> Self {
         AMetadata::new_builder()
@@ -516,3 +778,24 @@ pub struct MetadataInfo {
     pub epoch0_metadata: Metadata,
     pub epoch1_metadata: Metadata,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_network_type() {
+        assert_eq!("mainnet".parse(), Ok(NetworkType::Mainnet));
+        assert_eq!("Testnet".parse(), Ok(NetworkType::Testnet));
+        assert_eq!("devnet".parse(), Ok(NetworkType::Devnet));
+        assert_eq!(
+            "custom:my-net".parse(),
+            Ok(NetworkType::Custom("my-net".to_owned()))
+        );
+
+        assert!("".parse::<NetworkType>().is_err());
+        assert!("testnett".parse::<NetworkType>().is_err());
+        assert!("my-net".parse::<NetworkType>().is_err());
+        assert!("custom:".parse::<NetworkType>().is_err());
+    }
+}
@@ -0,0 +1,54 @@
+use std::fs::{copy, create_dir_all, remove_file, rename, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::Result;
+use serde::Serialize;
+
+/// Dumps `value` as json to the file `name` of the directory. The file is
+/// written in a temporary sub-directory first and then moved, so that it is
+/// never left half written.
+pub fn dump_json<T: Serialize>(dir: &Path, name: &str, value: &T) -> Result<()> {
+    create_dir_all(dir)?;
+    // dump file to a temporary sub-directory
+    let tmp_dir = dir.join("tmp");
+    create_dir_all(&tmp_dir)?;
+    let tmp_file = tmp_dir.join(name);
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .append(false)
+        .open(&tmp_file)?;
+    file.set_len(0)?;
+    file.write_all(serde_json::to_string(value)?.as_bytes())?;
+    file.sync_all()?;
+    move_file(tmp_file, dir.join(name))?;
+    Ok(())
+}
+
+fn move_file<P: AsRef<Path>>(src: P, dst: P) -> Result<(), std::io::Error> {
+    if rename(&src, &dst).is_err() {
+        copy(&src, &dst)?;
+        remove_file(&src)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dump_json() {
+        let dir = std::env::temp_dir().join("spark-dump-json-test");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        dump_json(&dir, "state", &vec![1u64, 2, 3]).unwrap();
+        // a shorter value replaces the file
+        dump_json(&dir, "state", &vec![4u64]).unwrap();
+
+        let content = std::fs::read_to_string(dir.join("state")).unwrap();
+        assert_eq!(content, "[4]");
+        assert!(!dir.join("tmp").join("state").exists());
+    }
+}
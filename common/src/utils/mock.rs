@@ -0,0 +1,420 @@
+//! An in-memory ckb node, used by the tests of the tx builders, the indexer and
+//! the api. The txs are committed by hand, and the indexer rpcs are answered
+//! from the committed txs.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use ckb_jsonrpc_types::{
+    BlockNumber, CellData, CellInfo, CellWithStatus, HeaderView, JsonBytes, OutPoint,
+    OutputsValidator, Transaction, TransactionView, TransactionWithStatusResponse, Uint32, Uint64,
+};
+use ckb_types::{bytes::Bytes, core, packed, prelude::*, H256};
+use parking_lot::RwLock;
+use serde_json::json;
+
+use crate::traits::ckb_rpc_client::CkbRpc;
+use crate::types::ckb_rpc_client::{
+    Cell, CellType, FeeRateStatistics, IndexerScriptSearchMode, IndexerTip, Order, Pagination,
+    ScriptType, SearchKey, Tx, TxWithCell,
+};
+
+#[derive(Clone, Default)]
+pub struct MockCkbRpc {
+    chain: Arc<RwLock<MockChain>>,
+}
+
+#[derive(Default)]
+struct MockChain {
+    // The committed txs with their block numbers and indices in the block.
+    committed:             Vec<(core::TransactionView, u64, u32)>,
+    headers:               HashMap<u64, core::HeaderView>,
+    spent:                 HashSet<packed::OutPoint>,
+    sent:                  HashMap<H256, core::TransactionView>,
+    rejected:              HashSet<H256>,
+    fee_rate:              Option<u64>,
+    send_error:            Option<String>,
+    get_transaction_fails: usize,
+    // the headers built after a fork differ from the reverted ones
+    forks:                 u64,
+}
+
+impl MockChain {
+    fn find_tx(&self, tx_hash: &packed::Byte32) -> Option<&(core::TransactionView, u64, u32)> {
+        self.committed.iter().find(|(tx, ..)| &tx.hash() == tx_hash)
+    }
+
+    fn output(&self, out_point: &packed::OutPoint) -> Option<(packed::CellOutput, Bytes)> {
+        let (tx, ..) = self.find_tx(&out_point.tx_hash())?;
+        let index: u32 = out_point.index().unpack();
+        tx.output_with_data(index as usize)
+    }
+
+    fn header(&mut self, number: u64) -> core::HeaderView {
+        let nonce = u128::from(self.forks);
+        self.headers
+            .entry(number)
+            .or_insert_with(|| {
+                core::HeaderBuilder::default()
+                    .number(number.pack())
+                    .nonce(nonce.pack())
+                    .build()
+            })
+            .clone()
+    }
+
+    fn tip(&self) -> u64 {
+        self.committed
+            .iter()
+            .map(|(_, number, _)| *number)
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+impl MockCkbRpc {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Commits the tx at the end of the block, whose timestamp is in
+    /// milliseconds.
+    pub fn commit(&self, tx: core::TransactionView, block_number: u64, timestamp: u64) {
+        let mut chain = self.chain.write();
+        let header = chain.header(block_number);
+        if header.timestamp() != timestamp {
+            let header = header
+                .as_advanced_builder()
+                .timestamp(timestamp.pack())
+                .build();
+            chain.headers.insert(block_number, header);
+        }
+        for input in tx.inputs() {
+            chain.spent.insert(input.previous_output());
+        }
+        let tx_index = chain
+            .committed
+            .iter()
+            .filter(|(_, number, _)| *number == block_number)
+            .count() as u32;
+        chain.sent.remove(&tx.hash().unpack());
+        chain.committed.push((tx, block_number, tx_index));
+    }
+
+    /// Commits a tx which was sent by `send_transaction`.
+    pub fn commit_sent(&self, tx_hash: &H256, block_number: u64, timestamp: u64) {
+        let tx = self.chain.read().sent.get(tx_hash).cloned();
+        self.commit(tx.expect("unknown sent tx"), block_number, timestamp);
+    }
+
+    /// Reverts the blocks from `block_number` on with their txs, the blocks
+    /// committed again have other hashes.
+    pub fn fork(&self, block_number: u64) {
+        let mut chain = self.chain.write();
+        chain
+            .committed
+            .retain(|(_, number, _)| *number < block_number);
+        chain.headers.retain(|number, _| *number < block_number);
+        let spent = chain
+            .committed
+            .iter()
+            .flat_map(|(tx, ..)| tx.input_pts_iter())
+            .collect();
+        chain.spent = spent;
+        chain.forks += 1;
+    }
+
+    pub fn reject(&self, tx_hash: &H256) {
+        let mut chain = self.chain.write();
+        chain.sent.remove(tx_hash);
+        chain.rejected.insert(tx_hash.clone());
+    }
+
+    /// The fee rate statistics of the recent blocks, none if the node has not
+    /// any.
+    pub fn set_fee_rate(&self, fee_rate: Option<u64>) {
+        self.chain.write().fee_rate = fee_rate;
+    }
+
+    /// Makes every `send_transaction` fail with the message.
+    pub fn set_send_error(&self, error: Option<&str>) {
+        self.chain.write().send_error = error.map(ToOwned::to_owned);
+    }
+
+    /// Makes the next `count` calls of `get_transaction` fail.
+    pub fn fail_get_transaction(&self, count: usize) {
+        self.chain.write().get_transaction_fails = count;
+    }
+
+    pub fn sent_transactions(&self) -> Vec<core::TransactionView> {
+        self.chain.read().sent.values().cloned().collect()
+    }
+
+    fn search(&self, search_key: &SearchKey) -> Vec<(Cell, CellType, bool)> {
+        let chain = self.chain.read();
+        let mut found = Vec::new();
+        for (tx, number, tx_index) in chain.committed.iter() {
+            if !in_block_range(search_key, *number) {
+                continue;
+            }
+            for (index, input) in tx.inputs().into_iter().enumerate() {
+                let out_point = input.previous_output();
+                if let Some((output, data)) = chain.output(&out_point) {
+                    if cell_matches(search_key, &output, &data) {
+                        let cell = to_cell(output, data, tx.hash(), index, *number, *tx_index);
+                        found.push((cell, CellType::Input, false));
+                    }
+                }
+            }
+            for (index, (output, data)) in tx.outputs_with_data_iter().enumerate() {
+                if cell_matches(search_key, &output, &data) {
+                    let out_point = packed::OutPoint::new(tx.hash(), index as u32);
+                    let live = !chain.spent.contains(&out_point);
+                    let cell = to_cell(output, data, tx.hash(), index, *number, *tx_index);
+                    found.push((cell, CellType::Output, live));
+                }
+            }
+        }
+        found
+    }
+}
+
+fn to_cell(
+    output: packed::CellOutput,
+    data: Bytes,
+    tx_hash: packed::Byte32,
+    index: usize,
+    block_number: u64,
+    tx_index: u32,
+) -> Cell {
+    Cell {
+        output:       output.into(),
+        output_data:  Some(JsonBytes::from_bytes(data)),
+        out_point:    packed::OutPoint::new(tx_hash, index as u32).into(),
+        block_number: block_number.into(),
+        tx_index:     tx_index.into(),
+    }
+}
+
+fn in_block_range(search_key: &SearchKey, number: u64) -> bool {
+    match search_key.filter.as_ref().and_then(|f| f.block_range) {
+        Some([from, to]) => number >= from.value() && number < to.value(),
+        None => true,
+    }
+}
+
+fn script_matches(
+    expected: &packed::Script,
+    actual: Option<packed::Script>,
+    mode: &IndexerScriptSearchMode,
+) -> bool {
+    let actual = match actual {
+        Some(script) => script,
+        None => return false,
+    };
+    if expected.code_hash() != actual.code_hash() || expected.hash_type() != actual.hash_type() {
+        return false;
+    }
+    let expected_args = expected.args().raw_data();
+    let actual_args = actual.args().raw_data();
+    match mode {
+        IndexerScriptSearchMode::Prefix => actual_args.starts_with(&expected_args),
+        IndexerScriptSearchMode::Exact => actual_args == expected_args,
+    }
+}
+
+fn cell_matches(search_key: &SearchKey, output: &packed::CellOutput, data: &Bytes) -> bool {
+    let mode = search_key.script_search_mode.clone().unwrap_or_default();
+    let script: packed::Script = search_key.script.clone().into();
+    let (searched, other) = match search_key.script_type {
+        ScriptType::Lock => (Some(output.lock()), output.type_().to_opt()),
+        ScriptType::Type => (output.type_().to_opt(), Some(output.lock())),
+    };
+    if !script_matches(&script, searched, &mode) {
+        return false;
+    }
+
+    let filter = match search_key.filter.as_ref() {
+        Some(filter) => filter,
+        None => return true,
+    };
+    if let Some(script) = filter.script.clone() {
+        let script: packed::Script = script.into();
+        if !script_matches(&script, other, &IndexerScriptSearchMode::Prefix) {
+            return false;
+        }
+    }
+    if let Some([min, max]) = filter.output_data_len_range {
+        let len = data.len() as u64;
+        if len < min.value() || len >= max.value() {
+            return false;
+        }
+    }
+    if let Some([min, max]) = filter.output_capacity_range {
+        let capacity: u64 = output.capacity().unpack();
+        if capacity < min.value() || capacity >= max.value() {
+            return false;
+        }
+    }
+    true
+}
+
+fn tx_response(
+    tx: Option<&core::TransactionView>,
+    status: &str,
+    block_hash: Option<H256>,
+) -> TransactionWithStatusResponse {
+    serde_json::from_value(json!({
+        "transaction": tx.map(|tx| TransactionView::from(tx.clone())),
+        "cycles": null,
+        "tx_status": { "status": status, "block_hash": block_hash, "reason": null },
+    }))
+    .expect("tx with status")
+}
+
+#[async_trait]
+impl CkbRpc for MockCkbRpc {
+    async fn get_cells(
+        &self,
+        search_key: SearchKey,
+        _order: Order,
+        limit: Uint32,
+        _after: Option<JsonBytes>,
+    ) -> Result<Pagination<Cell>> {
+        let objects = self
+            .search(&search_key)
+            .into_iter()
+            .filter(|(_, io_type, live)| matches!(io_type, CellType::Output) && *live)
+            .map(|(cell, ..)| cell)
+            .take(limit.value() as usize)
+            .collect();
+        Ok(Pagination {
+            objects,
+            last_cursor: JsonBytes::default(),
+        })
+    }
+
+    async fn get_live_cell(&self, out_point: OutPoint, with_data: bool) -> Result<CellWithStatus> {
+        let out_point: packed::OutPoint = out_point.into();
+        let chain = self.chain.read();
+        let cell = if chain.spent.contains(&out_point) {
+            None
+        } else {
+            chain.output(&out_point)
+        };
+        Ok(match cell {
+            Some((output, data)) => CellWithStatus {
+                cell:   Some(CellInfo {
+                    output: output.into(),
+                    data:   with_data.then(|| CellData {
+                        hash:    packed::CellOutput::calc_data_hash(&data).unpack(),
+                        content: JsonBytes::from_bytes(data),
+                    }),
+                }),
+                status: "live".to_owned(),
+            },
+            None => CellWithStatus {
+                cell:   None,
+                status: "unknown".to_owned(),
+            },
+        })
+    }
+
+    async fn get_indexer_tip(&self) -> Result<IndexerTip> {
+        let mut chain = self.chain.write();
+        let tip = chain.tip();
+        Ok(IndexerTip {
+            block_hash:   chain.header(tip).hash().unpack(),
+            block_number: tip.into(),
+        })
+    }
+
+    async fn get_transactions(
+        &self,
+        search_key: SearchKey,
+        _order: Order,
+        limit: Uint32,
+        _after: Option<JsonBytes>,
+    ) -> Result<Pagination<Tx>> {
+        let objects = self
+            .search(&search_key)
+            .into_iter()
+            .map(|(cell, io_type, _)| {
+                Tx::Ungrouped(TxWithCell {
+                    tx_hash: cell.out_point.tx_hash,
+                    block_number: cell.block_number,
+                    tx_index: cell.tx_index,
+                    io_index: cell.out_point.index,
+                    io_type,
+                })
+            })
+            .take(limit.value() as usize)
+            .collect();
+        Ok(Pagination {
+            objects,
+            last_cursor: JsonBytes::default(),
+        })
+    }
+
+    async fn send_transaction(
+        &self,
+        tx: &Transaction,
+        _outputs_validator: Option<OutputsValidator>,
+    ) -> Result<H256> {
+        let mut chain = self.chain.write();
+        if let Some(error) = chain.send_error.clone() {
+            return Err(anyhow!(error));
+        }
+        let tx = packed::Transaction::from(tx.clone()).into_view();
+        let tx_hash: H256 = tx.hash().unpack();
+        chain.sent.insert(tx_hash.clone(), tx);
+        Ok(tx_hash)
+    }
+
+    async fn get_fee_rate_statistics(
+        &self,
+        _target: Option<Uint64>,
+    ) -> Result<Option<FeeRateStatistics>> {
+        Ok(self
+            .chain
+            .read()
+            .fee_rate
+            .map(|fee_rate| FeeRateStatistics {
+                mean:   fee_rate.into(),
+                median: fee_rate.into(),
+            }))
+    }
+
+    async fn get_transaction(&self, hash: H256) -> Result<Option<TransactionWithStatusResponse>> {
+        let mut chain = self.chain.write();
+        if chain.get_transaction_fails > 0 {
+            chain.get_transaction_fails -= 1;
+            return Err(anyhow!("connection refused"));
+        }
+        if let Some((tx, number, _)) = chain.find_tx(&hash.pack()).cloned() {
+            let block_hash = chain.header(number).hash().unpack();
+            return Ok(Some(tx_response(Some(&tx), "committed", Some(block_hash))));
+        }
+        if let Some(tx) = chain.sent.get(&hash) {
+            return Ok(Some(tx_response(Some(tx), "pending", None)));
+        }
+        if chain.rejected.contains(&hash) {
+            return Ok(Some(tx_response(None, "rejected", None)));
+        }
+        Ok(None)
+    }
+
+    async fn get_block_hash(&self, number: BlockNumber) -> Result<Option<H256>> {
+        let number = number.value();
+        let mut chain = self.chain.write();
+        Ok((number <= chain.tip()).then(|| chain.header(number).hash().unpack()))
+    }
+
+    async fn get_header_by_number(&self, number: BlockNumber) -> Result<Option<HeaderView>> {
+        let number = number.value();
+        let mut chain = self.chain.write();
+        Ok((number <= chain.tip()).then(|| chain.header(number).into()))
+    }
+}
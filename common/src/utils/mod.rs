@@ -1,3 +1,6 @@
 pub mod codec;
 pub mod convert;
+pub mod fs;
 pub mod hash;
+#[cfg(feature = "mock")]
+pub mod mock;
@@ -9,7 +9,7 @@ use crate::tx::*;
 pub async fn run_all_tx(ckb: &CkbRpcClient, priv_keys: PrivKeys) {
     remove_smt();
 
-    let seeder_key = priv_keys.seeder_privkey.clone().into_h256().unwrap();
+    let seeder_key = priv_keys.seeder_key().unwrap();
     let kicker_key = priv_keys.staker_privkeys[0].clone().into_h256().unwrap();
     let staker_key = priv_keys.staker_privkeys[0].clone().into_h256().unwrap();
     let delegator_key = priv_keys.delegator_privkeys[0].clone().into_h256().unwrap();
@@ -25,7 +25,7 @@ pub async fn run_delegate_case(ckb: &CkbRpcClient, priv_keys: PrivKeys) {
         panic!("At least one delegator is required");
     }
 
-    let seeder_key = priv_keys.seeder_privkey.clone().into_h256().unwrap();
+    let seeder_key = priv_keys.seeder_key().unwrap();
     let (stakers_key, stakers) = gen_users(priv_keys.staker_privkeys.clone());
     let (delegators_key, delegators) = gen_users(priv_keys.delegator_privkeys.clone());
     let delegator_key = delegators_key[0].clone();
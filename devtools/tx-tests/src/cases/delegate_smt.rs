@@ -19,7 +19,7 @@ pub async fn run_delegate_smt_case(ckb: &CkbRpcClient, priv_keys: PrivKeys) {
         panic!("At least 2 delegators are required");
     }
 
-    let seeder_key = priv_keys.seeder_privkey.clone().into_h256().unwrap();
+    let seeder_key = priv_keys.seeder_key().unwrap();
     let (stakers_key, stakers) = gen_users(priv_keys.staker_privkeys.clone());
     let (delegators_key, _) = gen_users(priv_keys.delegator_privkeys.clone());
     let kicker_key = stakers_key[0].clone();
@@ -16,7 +16,7 @@ pub async fn run_stake_case(ckb: &CkbRpcClient, priv_keys: PrivKeys) {
         panic!("At least one delegator is required");
     }
 
-    let seeder_key = priv_keys.seeder_privkey.clone().into_h256().unwrap();
+    let seeder_key = priv_keys.seeder_key().unwrap();
     let (stakers_key, _) = gen_users(priv_keys.staker_privkeys.clone());
     let staker_key = stakers_key[0].clone();
     let kicker_key = stakers_key[0].clone();
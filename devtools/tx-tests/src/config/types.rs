@@ -4,6 +4,7 @@ use bytes::Bytes;
 use ckb_types::H256;
 use common::types::tx_builder::TypeIds as CTypeIds;
 use serde::{Deserialize, Serialize};
+use tx_builder::ckb::keystore::{KeystoreConfig, SignerKey};
 
 const HEX_PREFIX: &str = "0x";
 const HEX_PREFIX_UPPER: &str = "0X";
@@ -12,11 +13,26 @@ pub type TypeId = Privkey;
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct PrivKeys {
-    pub seeder_privkey:     Privkey,
+    #[serde(default)]
+    pub seeder_privkey:     Option<Privkey>,
+    // preferred to the plaintext seeder key
+    #[serde(default)]
+    pub seeder_keystore:    Option<KeystoreConfig>,
     pub staker_privkeys:    Vec<Privkey>,
     pub delegator_privkeys: Vec<Privkey>,
 }
 
+impl PrivKeys {
+    /// The seeder key, decrypted from the keystore if it is set.
+    pub fn seeder_key(&self) -> Result<SignerKey> {
+        match (&self.seeder_keystore, &self.seeder_privkey) {
+            (Some(keystore), _) => keystore.load(),
+            (None, Some(privkey)) => SignerKey::from_slice(&privkey.clone().inner().as_bytes()),
+            (None, None) => Err(anyhow!("Neither seeder_keystore nor seeder_privkey is set")),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct TypeIds {
     pub selection_type_id:    TypeId,
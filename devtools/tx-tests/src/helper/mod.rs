@@ -1,3 +1,12 @@
 pub mod signer;
 pub mod smt;
 pub mod user;
+
+use common::types::tx_builder::{ChainContext, TypeIds};
+
+/// The chain context of the network switched by `parse_ckb_net`. The type ids
+/// are read from the config file by every tx.
+#[allow(deprecated)]
+pub fn chain_context() -> ChainContext {
+    tx_builder::ckb::default_chain_context(TypeIds::default())
+}
@@ -3,9 +3,9 @@ use common::types::tx_builder::NetworkType;
 use config::types::PrivKeys;
 use rpc_client::ckb_client::ckb_rpc_client::CkbRpcClient;
 use tx_builder::ckb::helper::OmniEth;
-use tx_builder::set_network_type;
 
 use crate::config::{parse_log_config, parse_priv_keys};
+use crate::helper::chain_context;
 use crate::tx::*;
 
 mod cases;
@@ -184,6 +184,33 @@ async fn main() {
                         .required(false)
                         .num_args(0)
                         .help("Test reward tx"),
+                )
+                .arg(
+                    clap::Arg::new("batch")
+                        .short('b')
+                        .required(false)
+                        .num_args(0)
+                        .help("Test batch tx of adding stake and withdrawing"),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("verify")
+                .about("Verify a json tx with the local script verifier")
+                .arg(
+                    clap::Arg::new("net")
+                        .short('n')
+                        .required(false)
+                        .num_args(1)
+                        .value_parser(["dev", "test", "main"])
+                        .default_value("test")
+                        .help("Switch network"),
+                )
+                .arg(
+                    clap::Arg::new("file")
+                        .short('f')
+                        .required(true)
+                        .num_args(1)
+                        .help("Path of the json tx"),
                 ),
         )
         .subcommand(
@@ -216,6 +243,7 @@ async fn main() {
     match matches.subcommand() {
         Some(("cases", matches)) => run_test_cases(matches, priv_keys).await,
         Some(("tx", matches)) => run_single_tx(matches, priv_keys).await,
+        Some(("verify", matches)) => run_verify(matches).await,
         Some(("users", matches)) => view_users(matches, priv_keys),
         _ => unimplemented!(),
     }
@@ -280,10 +308,11 @@ async fn run_single_tx(matches: &clap::ArgMatches, priv_keys: PrivKeys) {
     let withdraw = *matches.get_one::<bool>("withdraw").unwrap();
     let metadata = *matches.get_one::<bool>("metadata").unwrap();
     let reward = *matches.get_one::<bool>("reward").unwrap();
+    let batch = *matches.get_one::<bool>("batch").unwrap();
 
     let ckb = parse_ckb_net(net);
 
-    let seeder_key = priv_keys.seeder_privkey.clone().into_h256().unwrap();
+    let seeder_key = priv_keys.seeder_key().unwrap();
     let kicker_key = priv_keys.staker_privkeys[0].clone().into_h256().unwrap();
     let staker_key = priv_keys.staker_privkeys[0].clone().into_h256().unwrap();
     let delegator_key = priv_keys.delegator_privkeys[0].clone().into_h256().unwrap();
@@ -333,23 +362,34 @@ async fn run_single_tx(matches: &clap::ArgMatches, priv_keys: PrivKeys) {
     } else if reward {
         let user_key = priv_keys.staker_privkeys[0].clone().into_h256().unwrap();
         run_reward_tx(&ckb, user_key, 4).await.unwrap();
+    } else if batch {
+        run_batch_tx(&ckb, staker_key, 10, 2).await.unwrap();
     } else {
         unimplemented!();
     }
 }
 
+async fn run_verify(matches: &clap::ArgMatches) {
+    let net = matches.get_one::<String>("net").unwrap().as_str();
+    let file = matches.get_one::<String>("file").unwrap();
+
+    let ckb = parse_ckb_net(net);
+    verify_tx_file(&ckb, file).await;
+}
+
 fn view_users(matches: &clap::ArgMatches, priv_keys: PrivKeys) {
     let net = matches.get_one::<String>("net").unwrap().as_str();
     let address = matches.get_one::<bool>("address").unwrap();
 
     parse_ckb_net(net);
+    let ctx = chain_context();
 
     if *address {
-        let seeder_key = priv_keys.seeder_privkey.into_h256().unwrap();
-        let omni_eth = OmniEth::new(seeder_key);
+        let seeder_key = priv_keys.seeder_key().unwrap();
+        let omni_eth = seeder_key.with_key(|key| OmniEth::new(key.clone()));
         println!(
             "seeder ckb addres: {}, eth address: {}",
-            omni_eth.ckb_address().unwrap(),
+            omni_eth.ckb_address(&ctx).unwrap(),
             omni_eth.address().unwrap(),
         );
 
@@ -359,7 +399,7 @@ fn view_users(matches: &clap::ArgMatches, priv_keys: PrivKeys) {
             println!(
                 "staker{} ckb addres: {}, eth address: {}",
                 i,
-                omni_eth.ckb_address().unwrap(),
+                omni_eth.ckb_address(&ctx).unwrap(),
                 omni_eth.address().unwrap(),
             );
         }
@@ -370,7 +410,7 @@ fn view_users(matches: &clap::ArgMatches, priv_keys: PrivKeys) {
             println!(
                 "delegator{} ckb addres: {}, eth address: {}",
                 i,
-                omni_eth.ckb_address().unwrap(),
+                omni_eth.ckb_address(&ctx).unwrap(),
                 omni_eth.address().unwrap(),
             );
         }
@@ -390,21 +430,23 @@ fn register_log() {
     );
 }
 
+// The tx builders of the devtools use the deprecated default chain context.
+#[allow(deprecated)]
 fn parse_ckb_net(net: &str) -> CkbRpcClient {
     match net {
         "dev" => {
             println!("dev net");
-            set_network_type(NetworkType::Devnet);
+            tx_builder::set_network_type(NetworkType::Devnet);
             CkbRpcClient::new("http://127.0.0.1:8114")
         }
         "test" => {
             println!("test net");
-            set_network_type(NetworkType::Testnet);
+            tx_builder::set_network_type(NetworkType::Testnet);
             CkbRpcClient::new("https://testnet.ckb.dev")
         }
         "main" => {
             println!("main net");
-            set_network_type(NetworkType::Mainnet);
+            tx_builder::set_network_type(NetworkType::Mainnet);
             CkbRpcClient::new("https://mainnet.ckb.dev")
         }
         _ => unimplemented!(),
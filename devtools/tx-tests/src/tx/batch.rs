@@ -0,0 +1,83 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use ckb_types::H256;
+use rpc_client::ckb_client::ckb_rpc_client::CkbRpcClient;
+
+use common::traits::tx_builder::IBatchTxBuilder;
+use common::types::tx_builder::{BatchItems, StakeItem, StakeTypeIds};
+use storage::SmtManager;
+use tx_builder::ckb::batch::BatchTxBuilder;
+use tx_builder::ckb::helper::{OmniEth, Tx};
+
+use crate::config::parse_type_ids;
+use crate::helper::chain_context;
+use crate::{MAX_TRY, ROCKSDB_PATH, TYPE_IDS_PATH};
+
+// Adds the stake and withdraws the unlocked tokens of the staker in one tx.
+pub async fn run_batch_tx(
+    ckb: &CkbRpcClient,
+    staker_key: H256,
+    amount: u128,
+    current_epoch: u64,
+) -> Result<()> {
+    let ctx = chain_context();
+    let type_ids = parse_type_ids(TYPE_IDS_PATH);
+    let checkpoint_type_id = type_ids.checkpoint_type_id.into_h256().unwrap();
+    let metadata_type_id = type_ids.metadata_type_id.into_h256().unwrap();
+    let xudt_args = type_ids.xudt_owner.into_h256().unwrap();
+
+    let path = PathBuf::from(ROCKSDB_PATH);
+    let smt = SmtManager::new(path);
+
+    let omni_eth = OmniEth::new(staker_key);
+    let token_lock = OmniEth::lock(&ctx, &omni_eth.address().unwrap());
+
+    let tx = BatchTxBuilder::new(
+        ckb,
+        &ctx,
+        StakeTypeIds {
+            metadata_type_id,
+            checkpoint_type_id,
+            xudt_owner: xudt_args,
+        },
+        omni_eth.address().unwrap(),
+        current_epoch,
+        BatchItems {
+            stake:     Some(StakeItem {
+                is_increase: true,
+                amount,
+                inauguration_epoch: current_epoch + 2,
+            }),
+            delegates: vec![],
+            withdraw:  true,
+        },
+        smt,
+    )
+    .build_tx()
+    .await?;
+
+    let mut tx = Tx::new(ckb, tx);
+    let script_groups = tx.gen_script_group().await.unwrap();
+    let signer = omni_eth.signer().unwrap();
+
+    for group in script_groups.lock_groups.values() {
+        if group.script == token_lock {
+            println!("sign; AT cell: {:?}", group.input_indices);
+            tx.sign(&signer, group).unwrap();
+        } else {
+            println!("not sign; locked AT cell: {:?}", group.input_indices);
+        }
+    }
+
+    match tx.send().await {
+        Ok(tx_hash) => println!("batch tx hash: 0x{}", tx_hash),
+        Err(e) => println!("{}", e),
+    }
+
+    println!("batch tx ready");
+    tx.wait_until_committed(1000, MAX_TRY).await.unwrap();
+    println!("batch tx committed");
+
+    Ok(())
+}
@@ -5,8 +5,10 @@ use common::types::tx_builder::{Checkpoint, CheckpointProof, CheckpointTypeIds,
 use rpc_client::ckb_client::ckb_rpc_client::CkbRpcClient;
 use tx_builder::ckb::checkpoint::CheckpointTxBuilder;
 use tx_builder::ckb::helper::{OmniEth, Tx};
+use tx_builder::ckb::signer::{OmniEthSigner, TxSigner};
 
 use crate::config::parse_type_ids;
+use crate::helper::chain_context;
 use crate::mock::{mock_axon_proof_v2, mock_axon_proposal_v2};
 use crate::{MAX_TRY, TYPE_IDS_PATH};
 
@@ -56,13 +58,16 @@ pub async fn checkpoint_tx(
     new_checkpoint: Checkpoint,
     proof: CheckpointProof,
 ) {
+    let ctx = chain_context();
     let type_ids = parse_type_ids(TYPE_IDS_PATH);
     let metadata_type_id = type_ids.metadata_type_id.into_h256().unwrap();
     let checkpoint_type_id = type_ids.checkpoint_type_id.into_h256().unwrap();
 
+    let signer = OmniEthSigner::new(kicker_key.into());
     let tx = CheckpointTxBuilder::new(
         ckb,
-        kicker_key.clone(),
+        &ctx,
+        signer.signer_lock(&ctx).unwrap(),
         CheckpointTypeIds {
             metadata_type_id,
             checkpoint_type_id,
@@ -76,20 +81,9 @@ pub async fn checkpoint_tx(
     .await
     .unwrap();
 
-    let mut tx = Tx::new(ckb, tx);
-    let script_groups = tx.gen_script_group().await.unwrap();
-    let signer = OmniEth::new(kicker_key).signer().unwrap();
-    let mut first_group = true;
+    let tx = signer.sign_unsigned(tx).await.unwrap();
 
-    for group in script_groups.lock_groups.iter() {
-        if !first_group {
-            println!("sign; not checkpoint id: {:?}", group.1.input_indices);
-            tx.sign(&signer, group.1).unwrap();
-        } else {
-            println!("not sign; checkpoint id: {:?}", group.1.input_indices);
-        }
-        first_group = false;
-    }
+    let mut tx = Tx::new(ckb, tx);
 
     match tx.send().await {
         Ok(tx_hash) => println!("checkpoint tx hash: 0x{}", tx_hash),
@@ -11,6 +11,7 @@ use tx_builder::ckb::delegate::DelegateTxBuilder;
 use tx_builder::ckb::helper::{OmniEth, Tx};
 
 use crate::config::parse_type_ids;
+use crate::helper::chain_context;
 use crate::{MAX_TRY, ROCKSDB_PATH, TYPE_IDS_PATH};
 
 pub async fn first_delegate_tx(
@@ -90,6 +91,7 @@ pub async fn delegate_tx(
     current_epoch: u64,
     first_delegate: bool,
 ) -> Result<()> {
+    let ctx = chain_context();
     let type_ids = parse_type_ids(TYPE_IDS_PATH);
     let checkpoint_type_id = type_ids.checkpoint_type_id.into_h256().unwrap();
     let metadata_type_id = type_ids.metadata_type_id.into_h256().unwrap();
@@ -102,6 +104,7 @@ pub async fn delegate_tx(
 
     let tx = DelegateTxBuilder::new(
         ckb,
+        &ctx,
         StakeTypeIds {
             metadata_type_id,
             checkpoint_type_id,
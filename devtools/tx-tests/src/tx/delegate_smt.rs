@@ -14,8 +14,10 @@ use common::types::tx_builder::DelegateSmtTypeIds;
 use common::utils::convert::to_h160;
 use tx_builder::ckb::delegate_smt::DelegateSmtTxBuilder;
 use tx_builder::ckb::helper::{Delegate, OmniEth, Tx, Xudt};
+use tx_builder::ckb::signer::{OmniEthSigner, TxSigner};
 
 use crate::config::parse_type_ids;
+use crate::helper::chain_context;
 use crate::helper::smt::{generate_smt_root, to_root, verify_proof};
 use crate::{MAX_TRY, ROCKSDB_PATH, TYPE_IDS_PATH};
 
@@ -25,6 +27,7 @@ pub async fn delegate_smt_tx(
     delegators_key: Vec<H256>,
     current_epoch: u64,
 ) {
+    let ctx = chain_context();
     let type_ids = parse_type_ids(TYPE_IDS_PATH);
     let metadata_type_id = type_ids.metadata_type_id.into_h256().unwrap();
     let checkpoint_type_id = type_ids.checkpoint_type_id.into_h256().unwrap();
@@ -37,8 +40,8 @@ pub async fn delegate_smt_tx(
         delegate_cells.push(
             Delegate::get_cell(
                 ckb,
-                Delegate::lock(&metadata_type_id, &omni_eth.address().unwrap()),
-                Xudt::type_(&xudt_owner.pack()),
+                Delegate::lock(&ctx, &metadata_type_id, &omni_eth.address().unwrap()),
+                Xudt::type_(&ctx, &xudt_owner.pack()),
             )
             .await
             .unwrap()
@@ -49,9 +52,11 @@ pub async fn delegate_smt_tx(
     let path = PathBuf::from(ROCKSDB_PATH);
     let smt = SmtManager::new(path);
 
+    let signer = OmniEthSigner::new(kicker_key.into());
     let (tx, _) = DelegateSmtTxBuilder::new(
         ckb,
-        kicker_key,
+        &ctx,
+        signer.signer_lock(&ctx).unwrap(),
         current_epoch,
         DelegateSmtTypeIds {
             metadata_type_id,
@@ -66,8 +71,9 @@ pub async fn delegate_smt_tx(
     .await
     .unwrap();
 
-    verify_new_delegate_smt(&tx, current_epoch).await;
+    verify_new_delegate_smt(&tx.tx, current_epoch).await;
 
+    let tx = signer.sign_unsigned(tx).await.unwrap();
     let mut tx = Tx::new(ckb, tx);
     match tx.send().await {
         Ok(tx_hash) => println!("delegate smt tx hash: 0x{}", tx_hash),
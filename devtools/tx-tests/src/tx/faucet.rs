@@ -5,10 +5,12 @@ use tx_builder::ckb::faucet::FaucetTxBuilder;
 use tx_builder::ckb::helper::{OmniEth, Sighash, Tx};
 
 use crate::config::types::PrivKeys;
+use crate::helper::chain_context;
 use crate::MAX_TRY;
 
 pub async fn run_faucet_tx(ckb: &CkbRpcClient, priv_keys: PrivKeys) {
-    let seeder_key = priv_keys.seeder_privkey.into_h256().unwrap();
+    let ctx = chain_context();
+    let seeder_key = priv_keys.seeder_key().unwrap();
 
     let mut users = HashMap::new();
 
@@ -18,7 +20,7 @@ pub async fn run_faucet_tx(ckb: &CkbRpcClient, priv_keys: PrivKeys) {
         println!(
             "transfer to staker{}[{}] 10000 CKB",
             i,
-            omni_eth.ckb_address().unwrap(),
+            omni_eth.ckb_address(&ctx).unwrap(),
         );
         users.insert(omni_eth.address().unwrap(), 10000);
     }
@@ -29,20 +31,20 @@ pub async fn run_faucet_tx(ckb: &CkbRpcClient, priv_keys: PrivKeys) {
         println!(
             "transfer to delegator{}[{}]",
             i,
-            omni_eth.ckb_address().unwrap(),
+            omni_eth.ckb_address(&ctx).unwrap(),
         );
         users.insert(omni_eth.address().unwrap(), 10000);
     }
 
     let users = users.into_iter().collect();
 
-    let sig_hash = Sighash::new(seeder_key.clone());
+    let sig_hash = seeder_key.with_key(|key| Sighash::new(key.clone()));
     println!(
         "seeder secp256k1 ckb addres: {}\n",
-        sig_hash.address().unwrap()
+        sig_hash.address(&ctx).unwrap()
     );
 
-    let tx = FaucetTxBuilder::new(ckb, seeder_key, users)
+    let tx = FaucetTxBuilder::new(ckb, &ctx, seeder_key, users)
         .build_tx()
         .await
         .unwrap();
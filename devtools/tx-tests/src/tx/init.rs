@@ -1,21 +1,21 @@
 use std::collections::HashSet;
 
 use ckb_types::H256;
-use common::types::tx_builder::{
-    Checkpoint, Metadata, MetadataInfo, PrivateKey, ProposeCount, RewardMeta,
-};
+use common::types::tx_builder::{Checkpoint, Metadata, MetadataInfo, ProposeCount, RewardMeta};
 use rpc_client::ckb_client::ckb_rpc_client::CkbRpcClient;
 use tx_builder::ckb::helper::{OmniEth, Tx};
 use tx_builder::ckb::init::InitTxBuilder;
+use tx_builder::ckb::keystore::SignerKey;
 
 use crate::config::types::TypeIds as CTypeIds;
 use crate::config::write_file;
+use crate::helper::chain_context;
 use crate::mock::mock_axon_validators_v2;
 use crate::{MAX_TRY, TYPE_IDS_PATH};
 
 pub async fn run_init_tx(
     ckb: &CkbRpcClient,
-    seeder_key: H256,
+    seeder_key: SignerKey,
     stakers_key: Vec<H256>,
     quorum: u16,
 ) {
@@ -75,16 +75,18 @@ pub async fn run_init_tx(
 
 pub async fn init_tx(
     ckb: &CkbRpcClient,
-    seeder_key: PrivateKey,
+    seeder_key: SignerKey,
     checkpoint: Checkpoint,
     metadata: MetadataInfo,
     stakers: HashSet<ckb_types::H160>,
 ) -> Tx<CkbRpcClient> {
-    let (tx, type_id_args) =
-        InitTxBuilder::new(ckb, seeder_key, 1000000, checkpoint, metadata, stakers)
-            .build_tx()
-            .await
-            .unwrap();
+    let ctx = chain_context();
+    let (tx, type_id_args) = InitTxBuilder::new(
+        ckb, &ctx, seeder_key, 1000000, checkpoint, metadata, stakers,
+    )
+    .build_tx()
+    .await
+    .unwrap();
 
     let mut tx = Tx::new(ckb, tx);
 
@@ -14,12 +14,15 @@ use common::types::tx_builder::MetadataTypeIds;
 use common::utils::convert::{to_h160, to_u128};
 use tx_builder::ckb::helper::{cell_collector::get_live_cell, Checkpoint, Tx};
 use tx_builder::ckb::metadata::MetadataSmtTxBuilder;
+use tx_builder::ckb::signer::{OmniEthSigner, TxSigner};
 
 use crate::config::parse_type_ids;
+use crate::helper::chain_context;
 use crate::helper::smt::{generate_smt_root, to_root, verify_proof};
 use crate::{MAX_TRY, ROCKSDB_PATH, TYPE_IDS_PATH};
 
 pub async fn run_metadata_tx(ckb: &CkbRpcClient, kicker_key: H256, current_epoch: u64) {
+    let ctx = chain_context();
     let type_ids = parse_type_ids(TYPE_IDS_PATH);
 
     let metadata_type_id = type_ids.metadata_type_id.into_h256().unwrap();
@@ -28,7 +31,7 @@ pub async fn run_metadata_tx(ckb: &CkbRpcClient, kicker_key: H256, current_epoch
     let delegate_smt_type_id = type_ids.delegate_smt_type_id.into_h256().unwrap();
     let xudt_owner = type_ids.xudt_owner.into_h256().unwrap();
 
-    let checkpoint_cell = Checkpoint::get_cell(ckb, Checkpoint::type_(&checkpoint_type_id))
+    let checkpoint_cell = Checkpoint::get_cell(ckb, Checkpoint::type_(&ctx, &checkpoint_type_id))
         .await
         .unwrap();
 
@@ -37,9 +40,11 @@ pub async fn run_metadata_tx(ckb: &CkbRpcClient, kicker_key: H256, current_epoch
     // disable load context from file
     let tmp_dir = tempfile::tempdir().unwrap();
 
+    let signer = OmniEthSigner::new(kicker_key.into());
     let tx = MetadataSmtTxBuilder::new(
         ckb,
-        kicker_key,
+        &ctx,
+        signer.signer_lock(&ctx).unwrap(),
         MetadataTypeIds {
             metadata_type_id,
             stake_smt_type_id,
@@ -55,8 +60,9 @@ pub async fn run_metadata_tx(ckb: &CkbRpcClient, kicker_key: H256, current_epoch
     .await
     .unwrap();
 
-    verify_old_delegat_smt(ckb, &tx, current_epoch).await;
+    verify_old_delegat_smt(ckb, &tx.tx, current_epoch).await;
 
+    let tx = signer.sign_unsigned(tx).await.unwrap();
     let mut tx = Tx::new(ckb, tx);
     match tx.send().await {
         Ok(tx_hash) => println!("metadata tx hash: 0x{}", tx_hash),
@@ -6,12 +6,14 @@ use tx_builder::ckb::mint::MintTxBuilder;
 
 use crate::config::parse_type_ids;
 use crate::config::types::PrivKeys;
+use crate::helper::chain_context;
 use crate::{MAX_TRY, TYPE_IDS_PATH};
 
 pub async fn run_mint_tx(ckb: &CkbRpcClient, priv_keys: PrivKeys) {
+    let ctx = chain_context();
     let type_ids = parse_type_ids(TYPE_IDS_PATH);
 
-    let seeder_key = priv_keys.seeder_privkey.into_h256().unwrap();
+    let seeder_key = priv_keys.seeder_key().unwrap();
 
     let mut users = HashMap::new();
 
@@ -32,10 +34,17 @@ pub async fn run_mint_tx(ckb: &CkbRpcClient, priv_keys: PrivKeys) {
     let selection_type_id = type_ids.selection_type_id.into_h256().unwrap();
     let issue_type_id = type_ids.issue_type_id.into_h256().unwrap();
 
-    let tx = MintTxBuilder::new(ckb, seeder_key, users, selection_type_id, issue_type_id)
-        .build_tx()
-        .await
-        .unwrap();
+    let tx = MintTxBuilder::new(
+        ckb,
+        &ctx,
+        seeder_key,
+        users,
+        selection_type_id,
+        issue_type_id,
+    )
+    .build_tx()
+    .await
+    .unwrap();
 
     let mut tx = Tx::new(ckb, tx);
 
@@ -1,3 +1,4 @@
+mod batch;
 mod checkpoint;
 mod delegate;
 mod delegate_smt;
@@ -8,8 +9,10 @@ mod mint;
 mod reward;
 mod stake;
 mod stake_smt;
+mod verify;
 mod withdraw;
 
+pub use batch::run_batch_tx;
 pub use checkpoint::{checkpoint_tx, run_checkpoint_tx};
 pub use delegate::{add_delegate_tx, delegate_tx, first_delegate_tx, redeem_delegate_tx};
 pub use delegate_smt::delegate_smt_tx;
@@ -20,4 +23,5 @@ pub use mint::run_mint_tx;
 pub use reward::run_reward_tx;
 pub use stake::{add_stake_tx, first_stake_tx, redeem_stake_tx};
 pub use stake_smt::stake_smt_tx;
+pub use verify::verify_tx_file;
 pub use withdraw::run_withdraw_tx;
@@ -11,9 +11,11 @@ use tx_builder::ckb::helper::{OmniEth, Tx};
 use tx_builder::ckb::reward::RewardTxBuilder;
 
 use crate::config::parse_type_ids;
+use crate::helper::chain_context;
 use crate::{MAX_TRY, ROCKSDB_PATH, TYPE_IDS_PATH};
 
 pub async fn run_reward_tx(ckb: &CkbRpcClient, user_key: H256, current_epoch: u64) -> Result<()> {
+    let ctx = chain_context();
     let type_ids = parse_type_ids(TYPE_IDS_PATH);
 
     let omni_eth = OmniEth::new(user_key.clone());
@@ -24,6 +26,7 @@ pub async fn run_reward_tx(ckb: &CkbRpcClient, user_key: H256, current_epoch: u6
 
     let tx = RewardTxBuilder::new(
         ckb,
+        &ctx,
         RewardTypeIds {
             selection_type_id:    type_ids.selection_type_id.into_h256().unwrap(),
             metadata_type_id:     type_ids.metadata_type_id.into_h256().unwrap(),
@@ -38,7 +41,7 @@ pub async fn run_reward_tx(ckb: &CkbRpcClient, user_key: H256, current_epoch: u6
         current_epoch,
         1,
     )
-    .await
+    .await?
     .build_tx()
     .await?;
 
@@ -10,6 +10,7 @@ use tx_builder::ckb::helper::{OmniEth, Tx};
 use tx_builder::ckb::stake::StakeTxBuilder;
 
 use crate::config::parse_type_ids;
+use crate::helper::chain_context;
 use crate::helper::signer::{EthSigner, UnlockMode};
 use crate::mock::gen_bls_keypair;
 use crate::{MAX_TRY, TYPE_IDS_PATH};
@@ -93,6 +94,7 @@ async fn stake_tx(
     current_epoch: u64,
     first_stake_info: Option<FirstStakeInfo>,
 ) -> Result<()> {
+    let ctx = chain_context();
     let type_ids = parse_type_ids(TYPE_IDS_PATH);
     let first_stake = first_stake_info.is_some();
     let checkpoint_type_id = type_ids.checkpoint_type_id.into_h256().unwrap();
@@ -103,6 +105,7 @@ async fn stake_tx(
 
     let tx = StakeTxBuilder::new(
         ckb,
+        &ctx,
         StakeTypeIds {
             metadata_type_id,
             checkpoint_type_id,
@@ -0,0 +1,28 @@
+use std::path::Path;
+
+use ckb_jsonrpc_types::Transaction;
+use ckb_types::{core::TransactionView, packed};
+use rpc_client::ckb_client::ckb_rpc_client::CkbRpcClient;
+
+use tx_builder::ckb::helper::Tx;
+use tx_builder::ckb::MAX_TX_CYCLES;
+
+pub async fn verify_tx_file(ckb: &CkbRpcClient, path: impl AsRef<Path>) {
+    let json = std::fs::read_to_string(path).expect("read tx file");
+    let tx: Transaction = serde_json::from_str(&json).expect("parse tx json");
+    let tx: TransactionView = packed::Transaction::from(tx).into_view();
+
+    println!("verify tx: 0x{}", tx.hash());
+    match Tx::new(ckb, tx).verify(MAX_TX_CYCLES).await {
+        Ok(verification) => {
+            for group in verification.groups.iter() {
+                println!(
+                    "{:?} script 0x{}, cycles: {}",
+                    group.group_type, group.script_hash, group.cycles
+                );
+            }
+            println!("total cycles: {}", verification.cycles);
+        }
+        Err(e) => println!("verify failed: {}", e),
+    }
+}
@@ -7,9 +7,11 @@ use tx_builder::ckb::helper::{OmniEth, Tx};
 use tx_builder::ckb::withdraw::WithdrawTxBuilder;
 
 use crate::config::parse_type_ids;
+use crate::helper::chain_context;
 use crate::{MAX_TRY, TYPE_IDS_PATH};
 
 pub async fn run_withdraw_tx(ckb: &CkbRpcClient, user_key: H256, current_epoch: u64) {
+    let ctx = chain_context();
     let type_ids = parse_type_ids(TYPE_IDS_PATH);
     let checkpoint_type_id = type_ids.checkpoint_type_id.into_h256().unwrap();
     let metadata_type_id = type_ids.metadata_type_id.into_h256().unwrap();
@@ -19,6 +21,7 @@ pub async fn run_withdraw_tx(ckb: &CkbRpcClient, user_key: H256, current_epoch:
 
     let tx = WithdrawTxBuilder::new(
         ckb,
+        &ctx,
         StakeTypeIds {
             metadata_type_id,
             checkpoint_type_id,
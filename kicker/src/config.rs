@@ -0,0 +1,46 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use tx_builder::ckb::signer::SignerConfig;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct KickerConfig {
+    pub enable:          bool,
+    // where the kicker progress and the metadata context are persisted
+    pub state_dir:       PathBuf,
+    // seconds between two polls of axon
+    pub interval:        u64,
+    // how many times a step is retried before waiting for the next poll
+    pub max_retry:       u32,
+    // how many times the status of a sent tx is queried, one query per second
+    pub max_wait:        u64,
+    // export an smt snapshot into the state dir after the txs of every epoch
+    pub export_snapshot: bool,
+    // bootstrap the smt database from this snapshot if the database does not exist
+    pub import_snapshot: Option<PathBuf>,
+    // rebuild the smt database from the smt cell history on ckb if the database does not
+    // exist and no snapshot is set
+    pub rebuild_smt:     bool,
+    // log a report of the next tx instead of sending it, the progress is not advanced
+    pub dry_run:         bool,
+    // how the kicker txs are signed, an omni-eth lock of the private key by default
+    pub signer:          SignerConfig,
+}
+
+impl Default for KickerConfig {
+    fn default() -> Self {
+        Self {
+            enable:          false,
+            state_dir:       PathBuf::from("free-space/kicker"),
+            interval:        10,
+            max_retry:       3,
+            max_wait:        300,
+            export_snapshot: false,
+            import_snapshot: None,
+            rebuild_smt:     false,
+            dry_run:         false,
+            signer:          SignerConfig::default(),
+        }
+    }
+}
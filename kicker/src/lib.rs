@@ -1,14 +1,589 @@
-pub fn add(left: usize, right: usize) -> usize {
-    left + right
+mod config;
+mod snapshot;
+mod state;
+#[cfg(test)]
+mod tests;
+
+pub use config::KickerConfig;
+pub use snapshot::{import_snapshot, verify_smt_roots};
+pub use state::{KickerState, Step};
+
+use std::{future::Future, path::PathBuf, sync::Arc, time::Duration};
+
+use anyhow::{anyhow, Result};
+use ckb_types::prelude::Pack;
+use futures::stream::{BoxStream, StreamExt};
+use molecule::prelude::Entity;
+
+use common::traits::{
+    axon_rpc_client::AxonRpc,
+    ckb_rpc_client::CkbRpc,
+    smt::{DelegateSmtStorage, ProposalSmtStorage, RewardSmtStorage, SmtSnapshot, StakeSmtStorage},
+    tx_builder::{
+        ICheckpointTxBuilder, IDelegateSmtTxBuilder, IMetadataTxBuilder, IStakeSmtTxBuilder,
+    },
+};
+use common::types::axon_rpc_client::{Block, Header, LatestCheckPointInfo, Proof as AxonProof};
+use common::types::axon_types::{checkpoint::CheckpointCellData, metadata::MetadataCellData};
+use common::types::ckb_rpc_client::Cell;
+use common::types::tx_builder::{
+    ChainContext, Checkpoint, CheckpointProof, Epoch, Metadata, NonTopDelegators, NonTopStakers,
+    Proof, Proposal, ProposeCount, UnsignedTx,
+};
+use common::types::Status;
+use common::utils::convert::{to_ckb_h160, to_ckb_h256, to_u64};
+use tx_builder::ckb::checkpoint::CheckpointTxBuilder;
+use tx_builder::ckb::delegate_smt::DelegateSmtTxBuilder;
+use tx_builder::ckb::dry_run::DryRun;
+use tx_builder::ckb::helper::{
+    Checkpoint as HCheckpoint, Delegate, Metadata as HMetadata, Stake, Tx, Xudt,
+};
+use tx_builder::ckb::metadata::MetadataSmtTxBuilder;
+use tx_builder::ckb::signer::TxSigner;
+use tx_builder::ckb::stake_smt::StakeSmtTxBuilder;
+use tx_builder::ckb::CkbTxErr;
+
+const TX_POLL_INTERVAL_MS: u64 = 1000;
+pub const SMT_SNAPSHOT_FILE: &str = "smt_snapshot";
+const DRY_RUN_DIR: &str = "dry_run";
+const DRY_RUN_SMT_DIR: &str = "smt";
+const SCRATCH_SMT_DIR: &str = "scratch_smt";
+
+/// Sends a checkpoint tx at the end of every Axon period. When the on-chain
+/// checkpoint enters a new epoch, the stake smt, delegate smt and metadata txs
+/// are sent one by one. The progress is persisted after each of them, so a
+/// restarted kicker goes on from where it stopped.
+pub struct Kicker<C, A, S> {
+    ckb:     C,
+    axon:    A,
+    smt:     S,
+    signer:  Arc<dyn TxSigner>,
+    ctx:     ChainContext,
+    config:  KickerConfig,
+    state:   Option<KickerState>,
+    headers: Option<BoxStream<'static, Header>>,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+impl<C, A, S> Kicker<C, A, S>
+where
+    C: CkbRpc,
+    A: AxonRpc,
+    S: StakeSmtStorage
+        + DelegateSmtStorage
+        + ProposalSmtStorage
+        + RewardSmtStorage
+        + SmtSnapshot
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+{
+    pub fn new(
+        ckb: C,
+        axon: A,
+        smt: S,
+        signer: Arc<dyn TxSigner>,
+        ctx: ChainContext,
+        config: KickerConfig,
+    ) -> Self {
+        let state = KickerState::load_from_dir(&config.state_dir);
+
+        Self {
+            ckb,
+            axon,
+            smt,
+            signer,
+            ctx,
+            config,
+            state,
+            headers: None,
+        }
+    }
+
+    /// Kick on every new Axon header besides the polls, so a checkpoint is sent
+    /// as soon as its period ends.
+    pub fn with_headers(mut self, headers: BoxStream<'static, Header>) -> Self {
+        self.headers = Some(headers);
+        self
+    }
+
+    pub async fn run(mut self) {
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.interval));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            match self.headers.as_mut() {
+                Some(headers) => {
+                    tokio::select! {
+                        _ = interval.tick() => {}
+                        _ = headers.next() => {}
+                    }
+                }
+                None => {
+                    interval.tick().await;
+                }
+            }
+
+            if let Err(e) = self.kick().await {
+                log::error!("[kicker] kick failed: {}", e);
+            }
+        }
+    }
+
+    async fn kick(&mut self) -> Result<()> {
+        if self.config.dry_run {
+            return self.dry_run().await;
+        }
+
+        // The metadata of an epoch must be updated before the checkpoint of the next
+        // epoch is sent.
+        self.send_epoch_txs().await?;
+        self.retry("checkpoint", || self.send_checkpoint()).await?;
+        self.send_epoch_txs().await
+    }
+
+    async fn send_epoch_txs(&mut self) -> Result<()> {
+        let epoch =
+            HCheckpoint::get_epoch(&self.ckb, &self.ctx, &self.ctx.type_ids.checkpoint_type_id)
+                .await?;
+
+        let mut state = match &self.state {
+            Some(state) if state.epoch >= epoch => state.clone(),
+            Some(_) => KickerState {
+                epoch,
+                step: Step::StakeSmt,
+            },
+            None => KickerState {
+                epoch,
+                step: self.chain_step(epoch).await?,
+            },
+        };
+        self.state = Some(state.clone());
+        state.dump_to_dir(&self.config.state_dir)?;
+
+        while state.step != Step::Done {
+            match state.step {
+                Step::StakeSmt => {
+                    self.retry("stake smt", || self.send_stake_smt(epoch))
+                        .await?
+                }
+                Step::DelegateSmt => {
+                    self.retry("delegate smt", || self.send_delegate_smt(epoch))
+                        .await?
+                }
+                Step::Metadata => self.retry("metadata", || self.send_metadata()).await?,
+                Step::Done => unreachable!(),
+            }
+
+            state.step = state.step.next();
+            self.state = Some(state.clone());
+            state.dump_to_dir(&self.config.state_dir)?;
+
+            if state.step == Step::Done && self.config.export_snapshot {
+                self.export_snapshot(epoch);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Without any persisted progress, the step of the epoch is derived from the
+    // chain. The metadata tx of an epoch moves the metadata cell to the epoch,
+    // and no checkpoint is sent before it. So before the metadata tx, an smt
+    // cell committed after the checkpoint cell is the output of the smt tx of
+    // the epoch.
+    async fn chain_step(&self, epoch: Epoch) -> Result<Step> {
+        let metadata_epoch = to_u64(&self.metadata_cell_data().await?.epoch());
+        if metadata_epoch >= epoch {
+            return Ok(Step::Done);
+        }
+
+        let checkpoint_block = self.last_checkpoint_cell().await?.block_number.value();
+        let delegate_smt_cell = Delegate::get_smt_cell(
+            &self.ckb,
+            Delegate::smt_type(&self.ctx, &self.ctx.type_ids.delegate_smt_type_id),
+        )
+        .await?;
+        if delegate_smt_cell.block_number.value() > checkpoint_block {
+            return Ok(Step::Metadata);
+        }
+
+        let stake_smt_cell = Stake::get_smt_cell(
+            &self.ckb,
+            Stake::smt_type(&self.ctx, &self.ctx.type_ids.stake_smt_type_id),
+        )
+        .await?;
+        if stake_smt_cell.block_number.value() > checkpoint_block {
+            return Ok(Step::DelegateSmt);
+        }
+
+        Ok(Step::StakeSmt)
+    }
+
+    // Reports the next tx instead of sending it, the progress is left as it is.
+    // The builders write the SMTs they are given, so the txs are built on a
+    // scratch copy of the local SMTs. The metadata context is kept apart from
+    // the real one too.
+    async fn dry_run(&self) -> Result<()> {
+        let epoch =
+            HCheckpoint::get_epoch(&self.ckb, &self.ctx, &self.ctx.type_ids.checkpoint_type_id)
+                .await?;
+        let step = match &self.state {
+            Some(state) if state.epoch >= epoch => state.step,
+            Some(_) => Step::StakeSmt,
+            None => self.chain_step(epoch).await?,
+        };
+
+        let dry_run_dir = self.config.state_dir.join(DRY_RUN_DIR);
+        let smt = self.smt.scratch_copy(&dry_run_dir.join(DRY_RUN_SMT_DIR))?;
+
+        let (name, report) = match step {
+            Step::StakeSmt => {
+                let (tx, non_top_stakers) = self.build_stake_smt(epoch, smt).await?;
+                let report = DryRun::new(&self.ckb, &self.ctx, &tx.tx).await?;
+                ("stake smt", report.with_non_top_stakers(non_top_stakers))
+            }
+            Step::DelegateSmt => {
+                let (tx, non_top_delegators) = self.build_delegate_smt(epoch, smt).await?;
+                let report = DryRun::new(&self.ckb, &self.ctx, &tx.tx).await?;
+                (
+                    "delegate smt",
+                    report.with_non_top_delegators(non_top_delegators),
+                )
+            }
+            Step::Metadata => {
+                let tx = self.build_metadata(dry_run_dir, smt).await?;
+                ("metadata", DryRun::new(&self.ckb, &self.ctx, &tx.tx).await?)
+            }
+            Step::Done => match self.build_checkpoint().await? {
+                Some(tx) => (
+                    "checkpoint",
+                    DryRun::new(&self.ckb, &self.ctx, &tx.tx).await?,
+                ),
+                None => return Ok(()),
+            },
+        };
+
+        log::info!("[kicker] dry run of the {} tx:\n{}", name, report);
+        Ok(())
+    }
+
+    // A failed export does not block the kicker, the snapshot of the next epoch
+    // will be tried.
+    fn export_snapshot(&self, epoch: Epoch) {
+        let path = self.config.state_dir.join(SMT_SNAPSHOT_FILE);
+        match self.smt.export_snapshot(epoch, &path) {
+            Ok(()) => log::info!("[kicker] smt snapshot of epoch {} exported", epoch),
+            Err(e) => log::error!("[kicker] export smt snapshot failed: {}", e),
+        }
+    }
+
+    async fn send_checkpoint(&self) -> Result<()> {
+        match self.build_checkpoint().await? {
+            Some(tx) => self.send_tx(tx).await,
+            None => Ok(()),
+        }
+    }
+
+    // None if the current period has not ended yet.
+    async fn build_checkpoint(&self) -> Result<Option<UnsignedTx>> {
+        let last_checkpoint = parse_checkpoint(&self.last_checkpoint_cell().await?)?;
+        let metadata = self.metadata().await?;
+
+        let latest_block = self.axon.get_block(None).await?;
+        if latest_block.header.number
+            < last_checkpoint.latest_block_height + metadata.period_len as u64
+        {
+            return Ok(None);
+        }
+
+        let (epoch, period) = if last_checkpoint.period + 1 == metadata.epoch_len {
+            (last_checkpoint.epoch + 1, 0)
+        } else {
+            (last_checkpoint.epoch, last_checkpoint.period + 1)
+        };
+
+        let last_block = self
+            .axon
+            .get_block(Some(latest_block.header.number - 1))
+            .await?;
+        let prev_block = self
+            .axon
+            .get_block(Some(last_block.header.number - 1))
+            .await?;
+        let axon_metadata = self.axon.get_current_metadata().await?;
+        let info =
+            LatestCheckPointInfo::new(&last_block.header, &latest_block.header, &axon_metadata);
+
+        log::info!(
+            "[kicker] checkpoint, epoch: {}, period: {}, block height: {}",
+            epoch,
+            period,
+            info.latest_block_height
+        );
+
+        let checkpoint = Checkpoint {
+            epoch,
+            period,
+            state_root: to_ckb_h256(&info.state_root),
+            latest_block_height: info.latest_block_height,
+            latest_block_hash: to_ckb_h256(&info.latest_block_hash),
+            timestamp: info.timestamp,
+            propose_count: info
+                .propose_count
+                .iter()
+                .map(|c| ProposeCount {
+                    proposer: to_ckb_h160(&c.address),
+                    count:    c.count,
+                })
+                .collect(),
+        };
+        let proof = CheckpointProof {
+            proof:    to_proof(&info.proof),
+            proposal: to_proposal(&last_block, &prev_block),
+        };
+
+        let tx = CheckpointTxBuilder::new(
+            &self.ckb,
+            &self.ctx,
+            self.signer.signer_lock(&self.ctx)?,
+            (&self.ctx.type_ids).into(),
+            metadata.epoch_len as u64,
+            checkpoint,
+            proof,
+        )
+        .await
+        .build_tx()
+        .await?;
+
+        Ok(Some(tx))
+    }
+
+    async fn send_stake_smt(&self, epoch: Epoch) -> Result<()> {
+        let smt = self.scratch_smt()?;
+        let (tx, _) = self.build_stake_smt(epoch, smt.clone()).await?;
+        self.send_tx(tx).await?;
+        self.smt.replace_with(&smt)
+    }
+
+    async fn build_stake_smt(&self, epoch: Epoch, smt: S) -> Result<(UnsignedTx, NonTopStakers)> {
+        let stake_cells = Stake::get_all_cells(
+            &self.ckb,
+            &self.ctx,
+            &self.ctx.type_ids.metadata_type_id,
+            Xudt::type_(&self.ctx, &self.ctx.type_ids.xudt_owner.pack()),
+        )
+        .await?;
+
+        log::info!(
+            "[kicker] stake smt, epoch: {}, stake cells: {}",
+            epoch,
+            stake_cells.len()
+        );
+
+        StakeSmtTxBuilder::new(
+            &self.ckb,
+            &self.ctx,
+            self.signer.signer_lock(&self.ctx)?,
+            epoch,
+            (&self.ctx.type_ids).into(),
+            stake_cells,
+            smt,
+        )
+        .build_tx()
+        .await
+    }
+
+    async fn send_delegate_smt(&self, epoch: Epoch) -> Result<()> {
+        let smt = self.scratch_smt()?;
+        let (tx, _) = self.build_delegate_smt(epoch, smt.clone()).await?;
+        self.send_tx(tx).await?;
+        self.smt.replace_with(&smt)
+    }
+
+    async fn build_delegate_smt(
+        &self,
+        epoch: Epoch,
+        smt: S,
+    ) -> Result<(UnsignedTx, NonTopDelegators)> {
+        let delegate_cells = Delegate::get_all_cells(
+            &self.ckb,
+            &self.ctx,
+            &self.ctx.type_ids.metadata_type_id,
+            Xudt::type_(&self.ctx, &self.ctx.type_ids.xudt_owner.pack()),
+        )
+        .await?;
+
+        log::info!(
+            "[kicker] delegate smt, epoch: {}, delegate cells: {}",
+            epoch,
+            delegate_cells.len()
+        );
+
+        DelegateSmtTxBuilder::new(
+            &self.ckb,
+            &self.ctx,
+            self.signer.signer_lock(&self.ctx)?,
+            epoch,
+            (&self.ctx.type_ids).into(),
+            delegate_cells,
+            smt,
+        )
+        .build_tx()
+        .await
+    }
+
+    async fn send_metadata(&self) -> Result<()> {
+        let smt = self.scratch_smt()?;
+        let tx = self
+            .build_metadata(self.config.state_dir.clone(), smt.clone())
+            .await?;
+        self.send_tx(tx).await?;
+        self.smt.replace_with(&smt)
+    }
+
+    // The builders write the SMTs they are given, so the epoch txs are built on a
+    // scratch copy of the local SMTs. The copy replaces the local SMTs only after
+    // the tx is committed, a failed or retried tx leaves them as they are.
+    fn scratch_smt(&self) -> Result<S> {
+        self.smt
+            .scratch_copy(&self.config.state_dir.join(SCRATCH_SMT_DIR))
+    }
+
+    async fn build_metadata(&self, context_dir: PathBuf, smt: S) -> Result<UnsignedTx> {
+        let checkpoint_cell = self.last_checkpoint_cell().await?;
+
+        log::info!("[kicker] metadata");
+
+        // The metadata context is kept in the state dir so that an interrupted
+        // election can be resumed.
+        MetadataSmtTxBuilder::new(
+            &self.ckb,
+            &self.ctx,
+            self.signer.signer_lock(&self.ctx)?,
+            (&self.ctx.type_ids).into(),
+            checkpoint_cell,
+            smt,
+            context_dir,
+        )
+        .await
+        .build_tx()
+        .await
+    }
+
+    async fn send_tx(&self, tx: UnsignedTx) -> Result<()> {
+        let tx = self.signer.sign_unsigned(tx).await?;
+        let mut tx = Tx::new(&self.ckb, tx);
+        let tx_hash = tx.send().await?;
+        log::info!("[kicker] tx sent: 0x{}", tx_hash);
+
+        tx.wait_until_committed(TX_POLL_INTERVAL_MS, self.config.max_wait)
+            .await?;
+
+        match tx.query_status().await? {
+            Some(tx) if tx.tx_status.status == Status::Committed => {
+                log::info!("[kicker] tx committed: 0x{}", tx_hash);
+                Ok(())
+            }
+            _ => Err(anyhow!("tx 0x{} is not committed", tx_hash)),
+        }
+    }
+
+    async fn last_checkpoint_cell(&self) -> Result<Cell> {
+        HCheckpoint::get_cell(
+            &self.ckb,
+            HCheckpoint::type_(&self.ctx, &self.ctx.type_ids.checkpoint_type_id),
+        )
+        .await
+    }
+
+    async fn metadata(&self) -> Result<Metadata> {
+        let data = self.metadata_cell_data().await?;
+        let metadata = data
+            .metadata()
+            .get(0)
+            .ok_or(CkbTxErr::MalformedCellData("metadata"))?;
+        Ok(metadata.into())
+    }
+
+    async fn metadata_cell_data(&self) -> Result<MetadataCellData> {
+        let cell = HMetadata::get_cell(
+            &self.ckb,
+            HMetadata::type_(&self.ctx, &self.ctx.type_ids.metadata_type_id),
+        )
+        .await?;
+        let data = cell
+            .output_data
+            .ok_or(CkbTxErr::MalformedCellData("metadata"))?
+            .into_bytes();
+        Ok(MetadataCellData::from_compatible_slice(&data)
+            .map_err(|_| CkbTxErr::MalformedCellData("metadata"))?)
+    }
+
+    async fn retry<F, Fut>(&self, name: &str, f: F) -> Result<()>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        let mut try_count = 0;
+
+        loop {
+            match f().await {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    try_count += 1;
+                    log::warn!(
+                        "[kicker] {} tx failed, try count: {}, error: {}",
+                        name,
+                        try_count,
+                        e
+                    );
+
+                    if try_count >= self.config.max_retry {
+                        return Err(e);
+                    }
+                }
+            }
+
+            tokio::time::sleep(Duration::from_secs(self.config.interval)).await;
+        }
+    }
+}
+
+fn parse_checkpoint(cell: &Cell) -> Result<Checkpoint> {
+    let data = cell
+        .output_data
+        .clone()
+        .ok_or(CkbTxErr::MalformedCellData("checkpoint"))?
+        .into_bytes();
+    let data = CheckpointCellData::from_compatible_slice(&data)
+        .map_err(|_| CkbTxErr::MalformedCellData("checkpoint"))?;
+    Ok(data.into())
+}
+
+fn to_proof(proof: &AxonProof) -> Proof {
+    Proof {
+        number:     proof.number,
+        round:      proof.round,
+        block_hash: proof.block_hash,
+        signature:  proof.signature.clone(),
+        bitmap:     proof.bitmap.clone(),
+    }
+}
+
+fn to_proposal(block: &Block, prev_block: &Block) -> Proposal {
+    let header = &block.header;
 
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
+    Proposal {
+        prev_hash:                header.prev_hash,
+        proposer:                 header.proposer,
+        prev_state_root:          prev_block.header.state_root,
+        transactions_root:        header.transactions_root,
+        signed_txs_hash:          header.signed_txs_hash,
+        timestamp:                header.timestamp,
+        number:                   header.number,
+        proof:                    to_proof(&header.proof),
+        call_system_script_count: header.call_system_script_count as u32,
+        tx_hashes:                block.tx_hashes.clone(),
     }
 }
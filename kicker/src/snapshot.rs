@@ -0,0 +1,107 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use ckb_types::bytes::Bytes;
+use molecule::prelude::Entity;
+
+use common::traits::{
+    ckb_rpc_client::CkbRpc,
+    smt::{DelegateSmtStorage, RewardSmtStorage, SmtSnapshot, StakeSmtStorage},
+};
+use common::types::axon_types::{
+    delegate::DelegateSmtCellData, reward::RewardSmtCellData, stake::StakeSmtCellData,
+};
+use common::types::ckb_rpc_client::Cell;
+use common::types::tx_builder::{ChainContext, Epoch};
+use common::utils::convert::{to_eth_h160, to_h160};
+use tx_builder::ckb::helper::{Delegate, Reward, Stake};
+use tx_builder::ckb::CkbTxErr;
+
+/// Imports the SMTs exported by an existing kicker. The imported top roots are
+/// checked against the stake smt, delegate smt and reward smt cells on chain.
+/// On a mismatch an error is returned, and the caller should discard the SMT
+/// database.
+pub async fn import_snapshot<C, S>(
+    ckb: &C,
+    ctx: &ChainContext,
+    smt: &S,
+    path: &Path,
+) -> Result<Epoch>
+where
+    C: CkbRpc,
+    S: StakeSmtStorage + DelegateSmtStorage + RewardSmtStorage + SmtSnapshot,
+{
+    let epoch = smt.import_snapshot(path)?;
+    verify_smt_roots(ckb, ctx, smt).await?;
+
+    log::info!("[kicker] smt snapshot of epoch {} imported", epoch);
+    Ok(epoch)
+}
+
+pub async fn verify_smt_roots<C, S>(ckb: &C, ctx: &ChainContext, smt: &S) -> Result<()>
+where
+    C: CkbRpc,
+    S: StakeSmtStorage + DelegateSmtStorage + RewardSmtStorage,
+{
+    let stake_cell =
+        Stake::get_smt_cell(ckb, Stake::smt_type(ctx, &ctx.type_ids.stake_smt_type_id)).await?;
+    let stake_data = StakeSmtCellData::from_slice(&cell_data(stake_cell, "stake smt")?)
+        .map_err(|_| CkbTxErr::MalformedCellData("stake smt"))?;
+    let stake_root = StakeSmtStorage::get_top_root(smt).await?;
+    if stake_data.smt_root().as_slice() != stake_root.as_slice() {
+        return Err(anyhow!("stake smt root mismatch"));
+    }
+
+    let delegate_cell = Delegate::get_smt_cell(
+        ckb,
+        Delegate::smt_type(ctx, &ctx.type_ids.delegate_smt_type_id),
+    )
+    .await?;
+    let delegate_data = DelegateSmtCellData::from_slice(&cell_data(delegate_cell, "delegate smt")?)
+        .map_err(|_| CkbTxErr::MalformedCellData("delegate smt"))?;
+    let chain_roots = delegate_data
+        .smt_roots()
+        .into_iter()
+        .map(|staker_root| {
+            (
+                to_eth_h160(&to_h160(&staker_root.staker())),
+                staker_root.root(),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+    // a local staker missing in the cell is a mismatch too, unless its smt is
+    // empty
+    let mut stakers = DelegateSmtStorage::get_stakers(smt)
+        .await?
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+    stakers.extend(chain_roots.keys().copied());
+    for staker in stakers {
+        let root = DelegateSmtStorage::get_top_root(smt, staker).await?;
+        let matched = match chain_roots.get(&staker) {
+            Some(chain_root) => chain_root.as_slice() == root.as_slice(),
+            None => root.is_zero(),
+        };
+        if !matched {
+            return Err(anyhow!("delegate smt root mismatch, staker: {}", staker));
+        }
+    }
+
+    let reward_cell = Reward::get_cell(ckb, ctx, &ctx.type_ids.reward_smt_type_id).await?;
+    let reward_data = RewardSmtCellData::from_slice(&cell_data(reward_cell, "reward smt")?)
+        .map_err(|_| CkbTxErr::MalformedCellData("reward smt"))?;
+    let reward_root = RewardSmtStorage::get_root(smt).await?;
+    if reward_data.claim_smt_root().as_slice() != reward_root.as_slice() {
+        return Err(anyhow!("reward smt root mismatch"));
+    }
+
+    Ok(())
+}
+
+fn cell_data(cell: Cell, name: &'static str) -> Result<Bytes> {
+    Ok(cell
+        .output_data
+        .ok_or(CkbTxErr::MalformedCellData(name))?
+        .into_bytes())
+}
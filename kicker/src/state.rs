@@ -0,0 +1,57 @@
+use std::{fs::File, path::Path};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use common::types::tx_builder::Epoch;
+use common::utils::fs::dump_json;
+
+const STATE_FILE: &str = "kicker_state";
+
+/// The txs sent on an epoch change, in the order they must be committed.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Step {
+    StakeSmt,
+    DelegateSmt,
+    Metadata,
+    Done,
+}
+
+impl Step {
+    pub fn next(self) -> Self {
+        match self {
+            Step::StakeSmt => Step::DelegateSmt,
+            Step::DelegateSmt => Step::Metadata,
+            Step::Metadata | Step::Done => Step::Done,
+        }
+    }
+}
+
+/// The progress of the kicker in the epoch it last entered.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct KickerState {
+    pub epoch: Epoch,
+    pub step:  Step,
+}
+
+impl KickerState {
+    pub fn load_from_dir(path: &Path) -> Option<Self> {
+        let state_path = path.join(STATE_FILE);
+
+        match File::open(&state_path) {
+            Ok(f) => serde_json::from_reader(f).ok(),
+            Err(e) => {
+                log::warn!(
+                    "[kicker] failed to open state file: {:?}, error: {:?}",
+                    state_path,
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    pub fn dump_to_dir(&self, path: &Path) -> Result<()> {
+        dump_json(path, STATE_FILE, self)
+    }
+}
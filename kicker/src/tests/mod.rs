@@ -0,0 +1,339 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use ckb_types::bytes::Bytes;
+use ckb_types::core::{Capacity, TransactionBuilder};
+use ckb_types::packed::{CellInput, CellOutput, OutPoint, Script};
+use ckb_types::prelude::{Builder, Entity, Pack, Unpack};
+use ckb_types::{h160, h256, H160};
+
+use common::traits::{
+    async_trait,
+    axon_rpc_client::AxonRpc,
+    smt::{DelegateSmtStorage, StakeSmtStorage},
+};
+use common::types::axon_rpc_client::{Block, BlockNumber, LatestCheckPointInfo, Metadata};
+use common::types::axon_types::{
+    checkpoint::CheckpointCellData,
+    delegate::DelegateSmtCellData,
+    metadata::{Metadata as AMetadata, MetadataCellData, MetadataList},
+    reward::RewardSmtCellData,
+    stake::{StakeAtCellData, StakeAtCellLockData, StakeSmtCellData},
+};
+use common::types::smt::UserAmount;
+use common::types::tx_builder::{
+    ChainContext, FeeParams, NetworkParams, NetworkType, StakeItem, TypeIds,
+};
+use common::utils::convert::{to_eth_h160, to_identity, to_uint16, to_uint64};
+use common::utils::mock::MockCkbRpc;
+use storage::SmtManager;
+use tx_builder::ckb::helper::{
+    token_cell_data, Checkpoint as HCheckpoint, Delegate, Metadata as HMetadata, Reward, Stake,
+    Xudt,
+};
+use tx_builder::ckb::keystore::SignerKey;
+use tx_builder::ckb::registry::builtin_scripts;
+use tx_builder::ckb::signer::{OmniEthSigner, TxSigner};
+use tx_builder::ckb::{CkbTxErr, INAUGURATION};
+
+use crate::{verify_smt_roots, Kicker, KickerConfig, KickerState, Step};
+
+const STAKER: H160 = h160!("0x88fc88b65d9a613a1ad53fb2f41e5cb9c741fb6d");
+const KICKER_KEY: &str = "0x37aa0f893d05914a4def0460c0a984d3611546cfb26924d7a7ca6e0db9950a2d";
+
+const CKB: u64 = 100_000_000;
+// the checkpoint enters epoch 1, whose metadata is not updated yet
+const EPOCH: u64 = 1;
+const CHECKPOINT_BLOCK: u64 = 2;
+
+struct MockAxonRpc;
+
+#[async_trait]
+impl AxonRpc for MockAxonRpc {
+    async fn get_checkpoint_info(&self) -> Result<LatestCheckPointInfo> {
+        Err(anyhow!("axon is not mocked"))
+    }
+
+    async fn get_current_metadata(&self) -> Result<Metadata> {
+        Err(anyhow!("axon is not mocked"))
+    }
+
+    async fn get_block(&self, _number: Option<BlockNumber>) -> Result<Block> {
+        Err(anyhow!("axon is not mocked"))
+    }
+}
+
+type TestKicker = Kicker<MockCkbRpc, MockAxonRpc, SmtManager>;
+
+fn chain_context() -> ChainContext {
+    ChainContext {
+        network:  NetworkType::Testnet,
+        params:   NetworkParams::builtin(&NetworkType::Testnet).unwrap(),
+        scripts:  Arc::new(builtin_scripts(&NetworkType::Testnet).unwrap()),
+        type_ids: TypeIds {
+            metadata_type_id: h256!("0x1"),
+            checkpoint_type_id: h256!("0x2"),
+            stake_smt_type_id: h256!("0x3"),
+            delegate_smt_type_id: h256!("0x4"),
+            xudt_owner: h256!("0x5"),
+            ..Default::default()
+        },
+        fee:      FeeParams::default(),
+    }
+}
+
+fn test_dir(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join("spark-kicker-test").join(name);
+    let _ = std::fs::remove_dir_all(&path);
+    path
+}
+
+fn signer() -> Arc<dyn TxSigner> {
+    Arc::new(OmniEthSigner::new(SignerKey::from_hex(KICKER_KEY).unwrap()))
+}
+
+fn kicker(ckb: &MockCkbRpc, smt: &SmtManager, state_dir: &Path) -> TestKicker {
+    let config = KickerConfig {
+        state_dir: state_dir.to_path_buf(),
+        interval: 0,
+        max_retry: 2,
+        max_wait: 5,
+        ..Default::default()
+    };
+    Kicker::new(
+        ckb.clone(),
+        MockAxonRpc,
+        smt.clone(),
+        signer(),
+        chain_context(),
+        config,
+    )
+}
+
+fn output(lock: Script, type_: Option<Script>) -> CellOutput {
+    CellOutput::new_builder()
+        .lock(lock)
+        .type_(type_.pack())
+        .capacity(Capacity::shannons(1000 * CKB).pack())
+        .build()
+}
+
+fn metadata_data(epoch: u64) -> Bytes {
+    let metadata = AMetadata::new_builder().quorum(to_uint16(1)).build();
+    MetadataCellData::new_builder()
+        .epoch(to_uint64(epoch))
+        .metadata(
+            MetadataList::new_builder()
+                .push(metadata.clone())
+                .push(metadata)
+                .build(),
+        )
+        .build()
+        .as_bytes()
+}
+
+// The smt cells, the metadata cell, a stake AT cell of the staker increasing
+// 100 from the epoch and the capacity cells of the kicker. The checkpoint cell
+// of the epoch is committed later.
+fn commit_cells(ckb: &MockCkbRpc, ctx: &ChainContext) -> OutPoint {
+    let type_ids = &ctx.type_ids;
+    let kicker_lock = signer().signer_lock(ctx).unwrap().lock;
+
+    let stake_data = StakeAtCellData::new_builder()
+        .lock(
+            StakeAtCellLockData::new_builder()
+                .l2_address(to_identity(&STAKER))
+                .delta(
+                    StakeItem {
+                        is_increase:        true,
+                        amount:             100,
+                        inauguration_epoch: EPOCH + INAUGURATION,
+                    }
+                    .into(),
+                )
+                .build(),
+        )
+        .build();
+
+    let mut tx = TransactionBuilder::default()
+        .output(output(
+            Script::default(),
+            Some(Stake::smt_type(ctx, &type_ids.stake_smt_type_id)),
+        ))
+        .output_data(StakeSmtCellData::new_builder().build().as_bytes().pack())
+        .output(output(
+            Script::default(),
+            Some(Delegate::smt_type(ctx, &type_ids.delegate_smt_type_id)),
+        ))
+        .output_data(DelegateSmtCellData::new_builder().build().as_bytes().pack())
+        .output(output(
+            Script::default(),
+            Some(HMetadata::type_(ctx, &type_ids.metadata_type_id)),
+        ))
+        .output_data(metadata_data(EPOCH - 1).pack())
+        .output(output(
+            Stake::lock(ctx, &type_ids.metadata_type_id, &STAKER),
+            Some(Xudt::type_(ctx, &type_ids.xudt_owner.pack())),
+        ))
+        .output_data(token_cell_data(100, stake_data.as_bytes()).pack());
+    for _ in 0..10 {
+        tx = tx
+            .output(output(kicker_lock.clone(), None))
+            .output_data(Bytes::new().pack());
+    }
+    let tx = tx.build();
+    ckb.commit(tx.clone(), 1, 1_000);
+
+    let checkpoint_tx = TransactionBuilder::default()
+        .output(output(
+            Script::default(),
+            Some(HCheckpoint::type_(ctx, &type_ids.checkpoint_type_id)),
+        ))
+        .output_data(
+            CheckpointCellData::new_builder()
+                .epoch(to_uint64(EPOCH))
+                .build()
+                .as_bytes()
+                .pack(),
+        )
+        .build();
+    ckb.commit(checkpoint_tx, CHECKPOINT_BLOCK, CHECKPOINT_BLOCK * 1_000);
+
+    // the metadata cell
+    OutPoint::new(tx.hash(), 2)
+}
+
+// Commits the first tx sent, then the txs sent after it fail.
+async fn commit_first_sent(ckb: MockCkbRpc, block_number: u64) {
+    loop {
+        if let Some(tx) = ckb.sent_transactions().pop() {
+            ckb.commit_sent(&tx.hash().unpack(), block_number, block_number * 1_000);
+            ckb.set_send_error(Some("send failed"));
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+async fn stake_amount(smt: &SmtManager) -> Option<u128> {
+    StakeSmtStorage::get_amount(smt, EPOCH + INAUGURATION, to_eth_h160(&STAKER))
+        .await
+        .unwrap()
+}
+
+fn state(step: Step) -> Option<KickerState> {
+    Some(KickerState { epoch: EPOCH, step })
+}
+
+#[tokio::test]
+async fn send_epoch_txs_through_failure_and_resume() {
+    let ctx = chain_context();
+    let ckb = MockCkbRpc::new();
+    let metadata_out_point = commit_cells(&ckb, &ctx);
+
+    let dir = test_dir("resume");
+    let state_dir = dir.join("state");
+    let smt = SmtManager::new(dir.join("smt"));
+
+    // Without a persisted state, the stake smt tx is the first one, derived from
+    // the chain. A failed tx leaves the local smts as they are.
+    let mut first = kicker(&ckb, &smt, &state_dir);
+    assert_eq!(first.chain_step(EPOCH).await.unwrap(), Step::StakeSmt);
+
+    ckb.set_send_error(Some("send failed"));
+    assert!(first.send_epoch_txs().await.is_err());
+    assert_eq!(
+        KickerState::load_from_dir(&state_dir),
+        state(Step::StakeSmt)
+    );
+    assert_eq!(stake_amount(&smt).await, None);
+
+    // The stake smt tx is committed and applied to the local smts, then the
+    // delegate smt tx fails.
+    ckb.set_send_error(None);
+    let committer = tokio::spawn(commit_first_sent(ckb.clone(), CHECKPOINT_BLOCK + 1));
+    assert!(first.send_epoch_txs().await.is_err());
+    committer.await.unwrap();
+    assert_eq!(
+        KickerState::load_from_dir(&state_dir),
+        state(Step::DelegateSmt)
+    );
+    assert_eq!(stake_amount(&smt).await, Some(100));
+    drop(first);
+
+    // A restarted kicker resumes from the persisted step.
+    let mut restarted = kicker(&ckb, &smt, &state_dir);
+    assert_eq!(restarted.state, state(Step::DelegateSmt));
+    assert!(restarted.send_epoch_txs().await.is_err());
+    assert_eq!(
+        KickerState::load_from_dir(&state_dir),
+        state(Step::DelegateSmt)
+    );
+    assert_eq!(stake_amount(&smt).await, Some(100));
+    drop(restarted);
+
+    // Without the persisted state, the stake smt cell committed after the
+    // checkpoint cell shows the stake smt tx is sent.
+    std::fs::remove_dir_all(&state_dir).unwrap();
+    let fresh = kicker(&ckb, &smt, &state_dir);
+    assert_eq!(fresh.state, None);
+    assert_eq!(fresh.chain_step(EPOCH).await.unwrap(), Step::DelegateSmt);
+
+    // and the metadata of the epoch shows all the txs are sent
+    let metadata_tx = TransactionBuilder::default()
+        .input(CellInput::new(metadata_out_point, 0))
+        .output(output(
+            Script::default(),
+            Some(HMetadata::type_(&ctx, &ctx.type_ids.metadata_type_id)),
+        ))
+        .output_data(metadata_data(EPOCH).pack())
+        .build();
+    ckb.commit(metadata_tx, CHECKPOINT_BLOCK + 2, 4_000);
+    assert_eq!(fresh.chain_step(EPOCH).await.unwrap(), Step::Done);
+}
+
+fn commit_reward_smt_cell(ckb: &MockCkbRpc, ctx: &ChainContext, data: Bytes) {
+    let tx = TransactionBuilder::default()
+        .output(output(
+            Script::default(),
+            Some(Reward::smt_type(ctx, &ctx.type_ids.reward_smt_type_id)),
+        ))
+        .output_data(data.pack())
+        .build();
+    ckb.commit(tx, CHECKPOINT_BLOCK + 1, (CHECKPOINT_BLOCK + 1) * 1_000);
+}
+
+#[tokio::test]
+async fn verify_smt_roots_of_local_stakers() {
+    let ctx = chain_context();
+    let ckb = MockCkbRpc::new();
+    let smt = SmtManager::new(test_dir("verify_smt_roots"));
+    commit_cells(&ckb, &ctx);
+
+    commit_reward_smt_cell(&ckb, &ctx, Bytes::from(vec![1, 2, 3]));
+    let err = verify_smt_roots(&ckb, &ctx, &smt).await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<CkbTxErr>(),
+        Some(CkbTxErr::MalformedCellData("reward smt"))
+    ));
+
+    ckb.fork(CHECKPOINT_BLOCK + 1);
+    commit_reward_smt_cell(
+        &ckb,
+        &ctx,
+        RewardSmtCellData::new_builder().build().as_bytes(),
+    );
+    verify_smt_roots(&ckb, &ctx, &smt).await.unwrap();
+
+    // the delegations of a staker missing in the delegate smt cell
+    DelegateSmtStorage::insert(&smt, EPOCH, to_eth_h160(&STAKER), vec![UserAmount {
+        user:        [1u8; 20].into(),
+        amount:      100,
+        is_increase: true,
+    }])
+    .await
+    .unwrap();
+    assert!(verify_smt_roots(&ckb, &ctx, &smt).await.is_err());
+}
@@ -0,0 +1,26 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct IndexerConfig {
+    pub enable:      bool,
+    // where the last indexed block is persisted
+    pub state_dir:   PathBuf,
+    // seconds between two scans
+    pub interval:    u64,
+    // the block to start from when there is no persisted state
+    pub start_block: u64,
+}
+
+impl Default for IndexerConfig {
+    fn default() -> Self {
+        Self {
+            enable:      false,
+            state_dir:   PathBuf::from("free-space/indexer"),
+            interval:    10,
+            start_block: 0,
+        }
+    }
+}
@@ -1,14 +1,367 @@
-pub fn add(left: usize, right: usize) -> usize {
-    left + right
+mod config;
+mod normalize;
+mod parser;
+mod state;
+mod tests;
+mod tx_parser;
+
+pub use config::IndexerConfig;
+pub use parser::{delegate_deltas, stake_delta, withdraw_infos, Record};
+pub use state::IndexerState;
+pub use tx_parser::TxParser;
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use ckb_types::{prelude::Pack, H256};
+
+use common::traits::{
+    async_trait,
+    ckb_rpc_client::CkbRpc,
+    query::{ChainRollback, TransactionStorage},
+};
+use common::types::api::OperationStatus;
+use common::types::ckb_rpc_client::{
+    CellType, IndexerScriptSearchMode, Order, ScriptType, SearchKey, SearchKeyFilter, Tx,
+    TxWithCell,
+};
+use common::types::tx_builder::ChainContext;
+use tx_builder::ckb::helper::{Delegate, Reward, Stake, Withdraw, Xudt};
+
+use crate::parser::parse_owner;
+use crate::tx_parser::{CellKind, REWARD_AT_OUTPUT};
+
+const SCAN_BLOCK_RANGE: u64 = 1000;
+const PAGE_LIMIT: u32 = 100;
+
+/// Scans the outputs of the committed stake, delegate, withdraw and reward txs
+/// and writes the decoded user operations into the transaction table, and the
+/// stakers, the delegations, the reward claims and the withdraw unlocks into
+/// their tables.
+///
+/// The outputs are found by the `get_transactions` of the ckb indexer, which
+/// includes the spent cells, so an output spent before it is scanned is not
+/// missed. A record is identified by the tx hash and the output, so the blocks
+/// can be scanned again.
+pub struct Indexer<C, T> {
+    ckb:         C,
+    storage:     Arc<T>,
+    ctx:         ChainContext,
+    config:      IndexerConfig,
+    state:       IndexerState,
+    // the first block reverted by a reorg since the last scan
+    rescan_from: Arc<AtomicU64>,
+}
+
+impl<C, T> Indexer<C, T>
+where
+    C: CkbRpc,
+    T: TransactionStorage + Send + Sync,
+{
+    pub fn new(ckb: C, storage: Arc<T>, ctx: ChainContext, config: IndexerConfig) -> Self {
+        let state = IndexerState::load_from_dir(&config.state_dir).unwrap_or(IndexerState {
+            next_block: config.start_block,
+        });
+
+        Self {
+            ckb,
+            storage,
+            ctx,
+            config,
+            state,
+            rescan_from: Arc::new(AtomicU64::new(u64::MAX)),
+        }
+    }
+
+    /// The handle to roll back the indexed rows of the blocks reverted by a
+    /// reorg, the blocks are indexed again by the next scan.
+    pub fn rollback_handle(&self) -> IndexerRollback<T> {
+        IndexerRollback {
+            storage:     Arc::clone(&self.storage),
+            rescan_from: Arc::clone(&self.rescan_from),
+        }
+    }
+
+    pub async fn run(mut self) {
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.interval));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = self.scan().await {
+                log::error!("[indexer] scan failed: {}", e);
+            }
+        }
+    }
+
+    async fn scan(&mut self) -> Result<()> {
+        let tip: u64 = self.ckb.get_indexer_tip().await?.block_number.into();
+        // blocks within the confirmation depth are not indexed in case of a reorg
+        let end = (tip + 1).saturating_sub(self.ctx.params.confirmations);
+
+        let rescan_from = self.rescan_from.swap(u64::MAX, Ordering::SeqCst);
+        if rescan_from < self.state.next_block {
+            log::warn!(
+                "[indexer] roll back from block {} to {}",
+                self.state.next_block,
+                rescan_from
+            );
+            self.state.next_block = rescan_from;
+            self.state.dump_to_dir(&self.config.state_dir)?;
+        }
+
+        while self.state.next_block < end {
+            let from = self.state.next_block;
+            let to = end.min(from + SCAN_BLOCK_RANGE);
+            self.scan_range(from, to).await?;
+
+            self.state.next_block = to;
+            self.state.dump_to_dir(&self.config.state_dir)?;
+        }
+
+        Ok(())
+    }
+
+    async fn scan_range(&self, from: u64, to: u64) -> Result<()> {
+        log::info!("[indexer] scan blocks [{}, {})", from, to);
+
+        let block_range = Some([from.into(), to.into()]);
+        let metadata_type_id = &self.ctx.type_ids.metadata_type_id;
+        let xudt = Xudt::type_(&self.ctx, &self.ctx.type_ids.xudt_owner.pack());
+
+        let mut outputs = vec![];
+        for (kind, lock_prefix) in [
+            (
+                CellKind::Stake,
+                Stake::lock_prefix(&self.ctx, metadata_type_id),
+            ),
+            (
+                CellKind::Delegate,
+                Delegate::lock_prefix(&self.ctx, metadata_type_id),
+            ),
+            (
+                CellKind::Withdraw,
+                Withdraw::lock_prefix(&self.ctx, metadata_type_id),
+            ),
+        ] {
+            let search_key = SearchKey {
+                script:      lock_prefix.into(),
+                script_type: ScriptType::Lock,
+                filter:      Some(SearchKeyFilter {
+                    script: Some(xudt.clone().into()),
+                    block_range,
+                    ..Default::default()
+                }),
+
+                script_search_mode:   Some(IndexerScriptSearchMode::Prefix),
+                with_data:            None,
+                group_by_transaction: None,
+            };
+            for output in self.get_outputs(search_key).await? {
+                outputs.push((kind, output));
+            }
+        }
+
+        let search_key = SearchKey {
+            script:      Reward::smt_type(&self.ctx, &self.ctx.type_ids.reward_smt_type_id).into(),
+            script_type: ScriptType::Type,
+            filter:      Some(SearchKeyFilter {
+                block_range,
+                ..Default::default()
+            }),
+
+            script_search_mode:   None,
+            with_data:            None,
+            group_by_transaction: None,
+        };
+        for output in self.get_outputs(search_key).await? {
+            outputs.push((CellKind::Reward, output));
+        }
+
+        // index the outputs in the order they are committed
+        outputs.sort_by_key(|(_, output)| {
+            (
+                output.block_number.value(),
+                output.tx_index.value(),
+                output.io_index.value(),
+            )
+        });
+
+        let mut timestamps = HashMap::new();
+        for (kind, output) in outputs {
+            let block_number = output.block_number.value();
+            let timestamp = match timestamps.get(&block_number) {
+                Some(timestamp) => *timestamp,
+                None => {
+                    let timestamp = self.block_timestamp(block_number).await?;
+                    timestamps.insert(block_number, timestamp);
+                    timestamp
+                }
+            };
+            self.index_output(kind, output, timestamp).await?;
+        }
+
+        Ok(())
+    }
+
+    // The outputs of the txs matching the search key, the inputs are skipped.
+    async fn get_outputs(&self, search_key: SearchKey) -> Result<Vec<TxWithCell>> {
+        let mut outputs = vec![];
+        let mut after = None;
+
+        loop {
+            let page = self
+                .ckb
+                .get_transactions(search_key.clone(), Order::Asc, PAGE_LIMIT.into(), after)
+                .await?;
+            if page.objects.is_empty() {
+                break;
+            }
+
+            for tx in page.objects {
+                match tx {
+                    Tx::Ungrouped(tx) if matches!(tx.io_type, CellType::Output) => outputs.push(tx),
+                    Tx::Ungrouped(_) => (),
+                    Tx::Grouped(tx) => {
+                        return Err(anyhow!("grouped tx returned: 0x{}", tx.tx_hash))
+                    }
+                }
+            }
+
+            if page.last_cursor.is_empty() {
+                break;
+            }
+            after = Some(page.last_cursor);
+        }
+
+        Ok(outputs)
+    }
+
+    // The block timestamp in seconds.
+    async fn block_timestamp(&self, block_number: u64) -> Result<u32> {
+        let header = self
+            .ckb
+            .get_header_by_number(block_number.into())
+            .await?
+            .ok_or_else(|| anyhow!("block not found: {}", block_number))?;
+        Ok((header.inner.timestamp.value() / 1000) as u32)
+    }
+
+    async fn index_output(&self, kind: CellKind, output: TxWithCell, timestamp: u32) -> Result<()> {
+        let parser = self.parser();
+        let block_number = output.block_number.value();
+        let tx = parser.get_tx(output.tx_hash.clone()).await?;
+
+        if let CellKind::Reward = kind {
+            let records = parser.parse_reward(&tx).await?;
+            for record in records.iter() {
+                self.save_reward_claim(&tx, record, timestamp).await?;
+            }
+            return self
+                .save(
+                    output.tx_hash,
+                    REWARD_AT_OUTPUT,
+                    block_number,
+                    timestamp,
+                    records,
+                )
+                .await;
+        }
+
+        let index = output.io_index.value() as usize;
+        let (cell, data) = match (
+            tx.inner.outputs.get(index),
+            tx.inner.outputs_data.get(index),
+        ) {
+            (Some(cell), Some(data)) => (cell, data.clone().into_bytes()),
+            _ => return Err(anyhow!("output not found: 0x{}, {}", tx.hash, index)),
+        };
+        let owner = match parse_owner(cell.lock.args.as_bytes()) {
+            Some(owner) => owner,
+            None => return Ok(()),
+        };
+        let old_data = parser.find_input_data(&tx, cell).await?;
+
+        let records = parser
+            .parse_output(kind, &tx, owner, &data, old_data.as_ref())
+            .await?;
+        match kind {
+            CellKind::Stake => {
+                if let Some(record) = records.first() {
+                    self.save_staker(&tx, owner, &data, record.epoch, block_number)
+                        .await?;
+                }
+            }
+            CellKind::Delegate => self.save_delegations(owner, &data, block_number).await?,
+            CellKind::Withdraw => {
+                self.save_withdraw_unlocks(&tx, owner, &data, old_data.as_ref(), timestamp)
+                    .await?
+            }
+            CellKind::Reward => unreachable!(),
+        }
+
+        self.save(output.tx_hash, index, block_number, timestamp, records)
+            .await
+    }
+
+    async fn save(
+        &self,
+        tx_hash: H256,
+        output_index: usize,
+        block_number: u64,
+        timestamp: u32,
+        records: Vec<Record>,
+    ) -> Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+
+        let models = records
+            .into_iter()
+            .enumerate()
+            .map(|(item_index, record)| {
+                record.into_model(
+                    &tx_hash,
+                    Some(output_index),
+                    Some(block_number),
+                    item_index,
+                    timestamp,
+                    OperationStatus::Success,
+                )
+            })
+            .collect();
+
+        // The txs relayed by spark are recorded as pending when they are sent,
+        // the records are replaced by the indexed ones.
+        self.storage
+            .save_indexed_records(tx_hash.to_string(), models)
+            .await
+    }
+
+    pub(crate) fn parser(&self) -> TxParser<'_, C> {
+        TxParser::new(&self.ckb, &self.ctx)
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Rolls back the indexed rows of the reverted blocks, and makes the indexer
+/// scan them again.
+pub struct IndexerRollback<T> {
+    storage:     Arc<T>,
+    rescan_from: Arc<AtomicU64>,
+}
 
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
+#[async_trait]
+impl<T> ChainRollback for IndexerRollback<T>
+where
+    T: TransactionStorage + Send + Sync,
+{
+    async fn rollback_blocks(&self, from: u64) -> Result<()> {
+        self.storage.rollback_blocks(from).await?;
+        self.rescan_from.fetch_min(from, Ordering::SeqCst);
+        Ok(())
     }
 }
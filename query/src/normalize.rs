@@ -0,0 +1,216 @@
+use anyhow::Result;
+use ckb_jsonrpc_types::TransactionView;
+use ckb_types::bytes::Bytes;
+
+use common::traits::{ckb_rpc_client::CkbRpc, query::TransactionStorage};
+use common::types::relation_db::transaction::{decode_amount, encode_amount};
+use common::types::relation_db::{
+    delegation, epoch_snapshot, reward_claim, staker, withdraw_unlock,
+};
+use common::types::smt::Address;
+use common::types::tx_builder::Epoch;
+use common::utils::convert::to_eth_h160;
+use storage::relation_db::Set;
+use tx_builder::ckb::helper::Delegate;
+
+use crate::parser::{
+    parse_delegations, parse_requirement, parse_requirement_type_id, parse_withdraw_unlocks,
+};
+use crate::{Indexer, Record};
+
+/// Besides the transaction records, the indexer keeps the stakers, the
+/// delegations, the reward claims and the withdraw unlocks. Every row is
+/// derived from a committed output alone, so scanning the blocks again writes
+/// the same rows.
+impl<C, T> Indexer<C, T>
+where
+    C: CkbRpc,
+    T: TransactionStorage + Send + Sync,
+{
+    // The first stake tx of a staker creates its delegate requirement cell. A
+    // staker who staked before the indexed blocks is read from the live
+    // requirement cell, so that the delegations to it can be saved.
+    pub(crate) async fn save_staker(
+        &self,
+        tx: &TransactionView,
+        staker: Address,
+        data: &Bytes,
+        epoch: Epoch,
+        block_number: u64,
+    ) -> Result<()> {
+        let requirement_type_id = match parse_requirement_type_id(data) {
+            Some(type_id) => type_id,
+            None => return Ok(()),
+        };
+        let requirement_type = Delegate::requirement_type(
+            &self.ctx,
+            &self.ctx.type_ids.metadata_type_id,
+            &requirement_type_id,
+        );
+        let json_type: ckb_jsonrpc_types::Script = requirement_type.clone().into();
+
+        let output_data = tx
+            .inner
+            .outputs
+            .iter()
+            .zip(tx.inner.outputs_data.iter())
+            .find(|(output, _)| output.type_.as_ref() == Some(&json_type))
+            .map(|(_, data)| data.clone().into_bytes());
+        let requirement_data = match output_data {
+            Some(data) => data,
+            None if self.storage.get_staker(staker).await?.is_some() => return Ok(()),
+            None => match Delegate::get_requirement_cell(&self.ckb, requirement_type).await {
+                Ok(cell) => cell.output_data.unwrap_or_default().into_bytes(),
+                Err(e) => {
+                    log::warn!(
+                        "[indexer] requirement of staker {} not found: {}",
+                        staker,
+                        e
+                    );
+                    return Ok(());
+                }
+            },
+        };
+
+        let requirement = match parse_requirement(&requirement_data) {
+            Some(requirement) => requirement,
+            None => {
+                log::warn!("[indexer] invalid requirement of staker {}", staker);
+                return Ok(());
+            }
+        };
+
+        self.storage
+            .upsert_staker(staker::ActiveModel {
+                address:            Set(staker.to_string()),
+                commission_rate:    Set(requirement.commission_rate as u32),
+                maximum_delegators: Set(requirement.maximum_delegators),
+                threshold:          Set(encode_amount(requirement.threshold)),
+                epoch:              Set(epoch),
+                block_number:       Set(Some(block_number)),
+            })
+            .await
+    }
+
+    // A delegation row holds the amount delegated from its epoch on. The delta
+    // in the delegate AT cell accumulates the operations taking effect in the
+    // epoch, so the amount is the one of the previous row with the delta.
+    pub(crate) async fn save_delegations(
+        &self,
+        delegator: Address,
+        data: &Bytes,
+        block_number: u64,
+    ) -> Result<()> {
+        for delegation in parse_delegations(data) {
+            let staker = to_eth_h160(&delegation.staker);
+            // the row refers to the staker
+            if self.storage.get_staker(staker).await?.is_none() {
+                log::warn!(
+                    "[indexer] staker {} of delegator {} not indexed",
+                    staker,
+                    delegator
+                );
+                continue;
+            }
+
+            let epoch = delegation.inauguration_epoch;
+            let amount = self
+                .storage
+                .get_delegation_before(staker, delegator, epoch)
+                .await?
+                .map(|previous| decode_amount(&previous.amount))
+                .transpose()?
+                .unwrap_or(0);
+            let amount = if delegation.is_increase {
+                amount + delegation.amount
+            } else {
+                amount.saturating_sub(delegation.amount)
+            };
+
+            self.storage
+                .upsert_delegation(delegation::ActiveModel {
+                    staker: Set(staker.to_string()),
+                    delegator: Set(delegator.to_string()),
+                    epoch: Set(epoch),
+                    amount: Set(encode_amount(amount)),
+                    block_number: Set(Some(block_number)),
+                    ..Default::default()
+                })
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) async fn save_reward_claim(
+        &self,
+        tx: &TransactionView,
+        record: &Record,
+        timestamp: u32,
+    ) -> Result<()> {
+        self.ensure_epoch_snapshot(record.epoch, timestamp).await?;
+
+        self.storage
+            .upsert_reward_claim(reward_claim::ActiveModel {
+                address: Set(record.address.to_string()),
+                epoch: Set(record.epoch),
+                amount: Set(encode_amount(record.amount)),
+                tx_hash: Set(tx.hash.to_string()),
+                ..Default::default()
+            })
+            .await
+    }
+
+    // The kicker adds the unstaked and undelegated amounts to the withdraw AT
+    // cell, they are unlocked at their unlock epochs.
+    pub(crate) async fn save_withdraw_unlocks(
+        &self,
+        tx: &TransactionView,
+        address: Address,
+        data: &Bytes,
+        old_data: Option<&Bytes>,
+        timestamp: u32,
+    ) -> Result<()> {
+        let unlocks = parse_withdraw_unlocks(data, old_data);
+        if unlocks.is_empty() {
+            return Ok(());
+        }
+
+        let epoch = self.parser().tx_epoch(tx).await?;
+        self.ensure_epoch_snapshot(epoch, timestamp).await?;
+
+        for (unlock_epoch, amount) in unlocks {
+            self.storage
+                .upsert_withdraw_unlock(withdraw_unlock::ActiveModel {
+                    address: Set(address.to_string()),
+                    amount: Set(encode_amount(amount)),
+                    epoch: Set(epoch),
+                    unlock_epoch: Set(unlock_epoch),
+                    tx_hash: Set(tx.hash.to_string()),
+                    ..Default::default()
+                })
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    // The reward claims and the withdraw unlocks refer to the snapshot of their
+    // epoch, which is created when the first of them is indexed. The totals are
+    // not known from the outputs, so they are left zero.
+    async fn ensure_epoch_snapshot(&self, epoch: Epoch, timestamp: u32) -> Result<()> {
+        if self.storage.get_epoch_snapshot(epoch).await?.is_some() {
+            return Ok(());
+        }
+
+        self.storage
+            .insert_epoch_snapshot(epoch_snapshot::ActiveModel {
+                epoch:                 Set(epoch),
+                total_stake_amount:    Set(encode_amount(0)),
+                total_delegate_amount: Set(encode_amount(0)),
+                staker_count:          Set(0),
+                timestamp:             Set(timestamp as u64),
+            })
+            .await
+    }
+}
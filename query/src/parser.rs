@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+
+use ckb_types::{bytes::Bytes, H256};
+use molecule::prelude::Entity;
+
+use common::types::api::{HistoryEvent, OperationType};
+use common::types::axon_types::{
+    delegate::{DelegateAtCellData, DelegateCellData},
+    stake::StakeAtCellData,
+    withdraw::WithdrawAtCellData,
+};
+use common::types::smt::Address;
+use common::types::tx_builder::{Amount, DelegateItem, DelegateRequirement, Epoch, StakeItem};
+use common::utils::convert::{new_u128, to_u128, to_u32, to_u64};
+use tx_builder::ckb::helper::{Delegate, Stake};
+use tx_builder::ckb::{INAUGURATION, TOKEN_BYTES};
+
+// The AT lock args are the metadata type hash followed by the owner address.
+const OWNER_OFFSET: usize = 32;
+const ADDRESS_LEN: usize = 20;
+
+/// A user operation decoded from a committed tx.
+#[derive(Clone, Debug)]
+pub struct Record {
+    pub address:   Address,
+    pub operation: OperationType,
+    pub event:     HistoryEvent,
+    pub amount:    Amount,
+    pub epoch:     Epoch,
+}
+
+pub fn parse_owner(lock_args: &[u8]) -> Option<Address> {
+    lock_args
+        .get(OWNER_OFFSET..OWNER_OFFSET + ADDRESS_LEN)
+        .map(Address::from_slice)
+}
+
+pub fn parse_token_amount(data: &[u8]) -> Option<Amount> {
+    if data.len() < TOKEN_BYTES {
+        None
+    } else {
+        Some(new_u128(data))
+    }
+}
+
+pub fn parse_stake(address: Address, data: &Bytes, old_data: Option<&Bytes>) -> Option<Record> {
+    let new_delta = stake_delta(data)?;
+    let old_delta = old_data.and_then(stake_delta);
+
+    to_record(
+        address,
+        OperationType::Stake,
+        elect_change(
+            &ElectDelta::from(&new_delta),
+            old_delta.as_ref().map(ElectDelta::from),
+        ),
+        new_delta.inauguration_epoch,
+    )
+}
+
+pub fn parse_delegate(address: Address, data: &Bytes, old_data: Option<&Bytes>) -> Vec<Record> {
+    let new_delegates = match delegate_deltas(data) {
+        Some(delegates) => delegates,
+        None => return vec![],
+    };
+    let old_delegates = old_data
+        .and_then(delegate_deltas)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|d| (d.staker.clone(), d))
+        .collect::<HashMap<_, _>>();
+
+    new_delegates
+        .iter()
+        .filter_map(|new_delta| {
+            to_record(
+                address,
+                OperationType::Delegate,
+                elect_change(
+                    &ElectDelta::from(new_delta),
+                    old_delegates.get(&new_delta.staker).map(ElectDelta::from),
+                ),
+                new_delta.inauguration_epoch,
+            )
+        })
+        .collect()
+}
+
+/// Only a decreased withdraw AT cell is a user operation, the kicker increases
+/// it when the redeemed tokens are unlocked. The epoch is the one of the tx.
+pub fn parse_withdraw(
+    address: Address,
+    data: &Bytes,
+    old_data: Option<&Bytes>,
+    epoch: Epoch,
+) -> Option<Record> {
+    let new_amount = parse_token_amount(data)?;
+    let old_amount = parse_token_amount(old_data?)?;
+
+    if new_amount >= old_amount {
+        return None;
+    }
+
+    Some(Record {
+        address,
+        operation: OperationType::Withdraw,
+        event: HistoryEvent::Redeem,
+        amount: old_amount - new_amount,
+        epoch,
+    })
+}
+
+/// The type id of the delegate requirement cell of the staker.
+pub fn parse_requirement_type_id(data: &Bytes) -> Option<H256> {
+    if data.len() < TOKEN_BYTES {
+        return None;
+    }
+    let stake_data = StakeAtCellData::from_slice(&data[TOKEN_BYTES..]).ok()?;
+    let type_id = stake_data
+        .lock()
+        .requirement_info()
+        .requirement()
+        .requirement_type_id();
+    H256::from_slice(&type_id.as_bytes()).ok()
+}
+
+pub fn parse_requirement(data: &[u8]) -> Option<DelegateRequirement> {
+    let requirement = DelegateCellData::from_slice(data)
+        .ok()?
+        .delegate_requirement();
+    Some(DelegateRequirement {
+        commission_rate:    requirement.commission_rate().into(),
+        maximum_delegators: to_u32(&requirement.max_delegator_size()),
+        threshold:          to_u128(&requirement.threshold()),
+    })
+}
+
+/// The deltas of the delegations to all stakers in a delegate AT cell.
+pub fn parse_delegations(data: &Bytes) -> Vec<DelegateItem> {
+    delegate_deltas(data).unwrap_or_default()
+}
+
+/// The amounts added to a withdraw AT cell by the kicker, by their unlock
+/// epochs. The amounts redeemed by the user are not unlocks.
+pub fn parse_withdraw_unlocks(data: &Bytes, old_data: Option<&Bytes>) -> Vec<(Epoch, Amount)> {
+    let new_infos = withdraw_infos(data).unwrap_or_default();
+    let old_infos = old_data
+        .and_then(withdraw_infos)
+        .unwrap_or_default()
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+
+    new_infos
+        .into_iter()
+        .filter_map(|(unlock_epoch, amount)| {
+            let old_amount = old_infos.get(&unlock_epoch).copied().unwrap_or(0);
+            (amount > old_amount).then(|| (unlock_epoch, amount - old_amount))
+        })
+        .collect()
+}
+
+struct ElectDelta {
+    is_increase:        bool,
+    amount:             Amount,
+    inauguration_epoch: Epoch,
+}
+
+impl From<&StakeItem> for ElectDelta {
+    fn from(item: &StakeItem) -> Self {
+        ElectDelta {
+            is_increase:        item.is_increase,
+            amount:             item.amount,
+            inauguration_epoch: item.inauguration_epoch,
+        }
+    }
+}
+
+impl From<&DelegateItem> for ElectDelta {
+    fn from(item: &DelegateItem) -> Self {
+        ElectDelta {
+            is_increase:        item.is_increase,
+            amount:             item.amount,
+            inauguration_epoch: item.inauguration_epoch,
+        }
+    }
+}
+
+impl ElectDelta {
+    fn signed_amount(&self) -> i128 {
+        if self.is_increase {
+            self.amount as i128
+        } else {
+            -(self.amount as i128)
+        }
+    }
+}
+
+// The delta in an AT cell accumulates all the operations of an epoch. A delta
+// of an earlier epoch has been handled by the kicker, so it is not a part of
+// the new one.
+fn elect_change(new_delta: &ElectDelta, old_delta: Option<ElectDelta>) -> i128 {
+    let old_amount = old_delta
+        .filter(|old| old.inauguration_epoch == new_delta.inauguration_epoch)
+        .map(|old| old.signed_amount())
+        .unwrap_or(0);
+    new_delta.signed_amount() - old_amount
+}
+
+fn to_record(
+    address: Address,
+    operation: OperationType,
+    change: i128,
+    inauguration_epoch: Epoch,
+) -> Option<Record> {
+    if change == 0 {
+        return None;
+    }
+
+    Some(Record {
+        address,
+        operation,
+        event: if change > 0 {
+            HistoryEvent::Add
+        } else {
+            HistoryEvent::Redeem
+        },
+        amount: change.unsigned_abs(),
+        epoch: inauguration_epoch.saturating_sub(INAUGURATION),
+    })
+}
+
+/// The stake delta in a stake AT cell, none if the data is malformed.
+pub fn stake_delta(data: &Bytes) -> Option<StakeItem> {
+    if data.len() < TOKEN_BYTES {
+        return None;
+    }
+    let stake_data = StakeAtCellData::from_slice(&data[TOKEN_BYTES..]).ok()?;
+    Some(Stake::item(&stake_data.lock().delta()))
+}
+
+/// The delegate deltas in a delegate AT cell, none if the data is malformed.
+pub fn delegate_deltas(data: &Bytes) -> Option<Vec<DelegateItem>> {
+    if data.len() < TOKEN_BYTES {
+        return None;
+    }
+    let delegate_data = DelegateAtCellData::from_slice(&data[TOKEN_BYTES..]).ok()?;
+    Some(
+        delegate_data
+            .lock()
+            .delegator_infos()
+            .into_iter()
+            .map(|delegate| Delegate::item(&delegate))
+            .collect(),
+    )
+}
+
+/// The withdraw amounts in a withdraw AT cell by their unlock epochs, none if
+/// the data is malformed.
+pub fn withdraw_infos(data: &Bytes) -> Option<Vec<(Epoch, Amount)>> {
+    if data.len() < TOKEN_BYTES {
+        return None;
+    }
+    let withdraw_data = WithdrawAtCellData::from_slice(&data[TOKEN_BYTES..]).ok()?;
+    Some(
+        withdraw_data
+            .lock()
+            .withdraw_infos()
+            .into_iter()
+            .map(|info| (to_u64(&info.unlock_epoch()), to_u128(&info.amount())))
+            .collect(),
+    )
+}
@@ -0,0 +1,36 @@
+use std::{fs::File, path::Path};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use common::utils::fs::dump_json;
+
+const STATE_FILE: &str = "indexer_state";
+
+/// The first block which has not been indexed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct IndexerState {
+    pub next_block: u64,
+}
+
+impl IndexerState {
+    pub fn load_from_dir(path: &Path) -> Option<Self> {
+        let state_path = path.join(STATE_FILE);
+
+        match File::open(&state_path) {
+            Ok(f) => serde_json::from_reader(f).ok(),
+            Err(e) => {
+                log::warn!(
+                    "[indexer] failed to open state file: {:?}, error: {:?}",
+                    state_path,
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    pub fn dump_to_dir(&self, path: &Path) -> Result<()> {
+        dump_json(path, STATE_FILE, self)
+    }
+}
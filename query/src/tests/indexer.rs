@@ -0,0 +1,582 @@
+use std::sync::Arc;
+
+use ckb_types::core::{Capacity, TransactionBuilder, TransactionView};
+use ckb_types::packed::{CellDep, CellInput, CellOutput, OutPoint, Script};
+use ckb_types::prelude::{Builder, Entity, Pack, Unpack};
+use ckb_types::{bytes::Bytes, h160, h256, H160, H256};
+
+use common::traits::query::TransactionStorage;
+use common::types::api::OperationStatus;
+use common::types::axon_types::checkpoint::CheckpointCellData;
+use common::types::axon_types::delegate::{
+    DelegateAtCellData, DelegateAtCellLockData, DelegateCellData, DelegateInfoDeltas,
+};
+use common::types::axon_types::stake::{
+    DelegateRequirementArgs, DelegateRequirementInfo, StakeAtCellData, StakeAtCellLockData,
+};
+use common::types::axon_types::withdraw::{
+    WithdrawAtCellData, WithdrawAtCellLockData, WithdrawInfo, WithdrawInfos,
+};
+use common::types::relation_db::transaction::{self, decode_amount, encode_amount};
+use common::types::tx_builder::{
+    ChainContext, DelegateItem, DelegateRequirement, FeeParams, NetworkParams, NetworkType,
+    StakeItem, TypeIds,
+};
+use common::utils::convert::{to_byte32, to_eth_h160, to_uint128, to_uint64};
+use common::utils::mock::MockCkbRpc;
+use storage::relation_db::{Set, TransactionHistory};
+use tx_builder::ckb::helper::{token_cell_data, Checkpoint, Delegate, Stake, Withdraw, Xudt};
+use tx_builder::ckb::registry::builtin_scripts;
+
+use crate::{Indexer, IndexerConfig};
+
+const STAKER_A: H160 = h160!("0x88fc88b65d9a613a1ad53fb2f41e5cb9c741fb6d");
+const STAKER_B: H160 = h160!("0x7e1c2a1f0ab7d8c7b1e8b1c5d4e3f2a1b0c9d8e7");
+const DELEGATOR: H160 = h160!("0x3c5e1b0a9d8f7e6d5c4b3a29180716253d4c5b6a");
+
+fn chain_context() -> ChainContext {
+    ChainContext {
+        network:  NetworkType::Testnet,
+        params:   NetworkParams::builtin(&NetworkType::Testnet).unwrap(),
+        scripts:  Arc::new(builtin_scripts(&NetworkType::Testnet).unwrap()),
+        type_ids: TypeIds {
+            metadata_type_id: h256!("0x1"),
+            checkpoint_type_id: h256!("0x2"),
+            xudt_owner: h256!("0x3"),
+            ..Default::default()
+        },
+        fee:      FeeParams::default(),
+    }
+}
+
+fn stake_data(total: u128, amount: u128, inauguration_epoch: u64) -> Bytes {
+    let data = StakeAtCellData::new_builder()
+        .lock(
+            StakeAtCellLockData::new_builder()
+                .delta(
+                    StakeItem {
+                        is_increase: true,
+                        amount,
+                        inauguration_epoch,
+                    }
+                    .into(),
+                )
+                .build(),
+        )
+        .build();
+    token_cell_data(total, data.as_bytes())
+}
+
+fn cell(lock: Script, type_: Option<Script>) -> CellOutput {
+    CellOutput::new_builder()
+        .lock(lock)
+        .type_(type_.pack())
+        .capacity(Capacity::shannons(1000).pack())
+        .build()
+}
+
+fn tx(
+    inputs: Vec<OutPoint>,
+    outputs: Vec<(CellOutput, Bytes)>,
+    cell_deps: Vec<OutPoint>,
+) -> TransactionView {
+    TransactionBuilder::default()
+        .inputs(
+            inputs
+                .into_iter()
+                .map(|out_point| CellInput::new(out_point, 0)),
+        )
+        .outputs(outputs.iter().map(|(output, _)| output.clone()))
+        .outputs_data(outputs.iter().map(|(_, data)| data.pack()))
+        .cell_deps(
+            cell_deps
+                .into_iter()
+                .map(|out_point| CellDep::new_builder().out_point(out_point).build()),
+        )
+        .build()
+}
+
+// The tx hash as it is stored, without the 0x prefix.
+fn hash_of(tx: &TransactionView) -> String {
+    let tx_hash: H256 = tx.hash().unpack();
+    tx_hash.to_string()
+}
+
+async fn indexer(ckb: MockCkbRpc) -> Indexer<MockCkbRpc, TransactionHistory> {
+    let storage = Arc::new(TransactionHistory::new("sqlite::memory:").await);
+    let config = IndexerConfig {
+        state_dir: std::env::temp_dir().join("spark-indexer-test-none"),
+        ..Default::default()
+    };
+    Indexer::new(ckb, storage, chain_context(), config)
+}
+
+// A tx with an ordinary cell, which is spent by the first stake tx.
+fn genesis(ckb: &MockCkbRpc) -> OutPoint {
+    let genesis = tx(
+        vec![],
+        vec![(cell(Script::default(), None), Bytes::new())],
+        vec![],
+    );
+    ckb.commit(genesis.clone(), 1, 1_000);
+    OutPoint::new(genesis.hash(), 0)
+}
+
+fn stake_cell(ctx: &ChainContext, staker: &H160) -> CellOutput {
+    let xudt = Xudt::type_(ctx, &ctx.type_ids.xudt_owner.pack());
+    cell(
+        Stake::lock(ctx, &ctx.type_ids.metadata_type_id, staker),
+        Some(xudt),
+    )
+}
+
+#[tokio::test]
+async fn index_multi_cell_tx() {
+    let ctx = chain_context();
+    let ckb = MockCkbRpc::new();
+    let input = genesis(&ckb);
+
+    let stake_tx = tx(
+        vec![input],
+        vec![
+            (stake_cell(&ctx, &STAKER_A), stake_data(100, 100, 3)),
+            (stake_cell(&ctx, &STAKER_B), stake_data(50, 50, 3)),
+        ],
+        vec![],
+    );
+    let tx_hash = hash_of(&stake_tx);
+    ckb.commit(stake_tx, 2, 2_000_000);
+
+    let indexer = indexer(ckb).await;
+    // the tx sent by spark is recorded as pending before it is committed
+    indexer
+        .storage
+        .insert(transaction::ActiveModel {
+            address: Set(STAKER_A.to_string()),
+            timestamp: Set(1),
+            operation: Set(0),
+            event: Set(0),
+            tx_hash: Set(tx_hash.clone()),
+            total_amount: Set(encode_amount(100)),
+            stake_amount: Set(encode_amount(100)),
+            delegate_amount: Set(encode_amount(0)),
+            withdrawable_amount: Set(encode_amount(0)),
+            stake_rate: Set(String::new()),
+            delegate_rate: Set(String::new()),
+            epoch: Set(1),
+            status: Set(OperationStatus::Pending as u32),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    indexer.scan_range(0, 10).await.unwrap();
+    // scanning the blocks again changes nothing
+    indexer.scan_range(0, 10).await.unwrap();
+
+    let mut records = indexer
+        .storage
+        .get_records_by_tx_hash(tx_hash)
+        .await
+        .unwrap();
+    records.sort_by_key(|record| record.output_index);
+    assert_eq!(records.len(), 2);
+
+    let amounts = records
+        .iter()
+        .map(|record| {
+            (
+                record.output_index,
+                decode_amount(&record.total_amount).unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(amounts, vec![(Some(0), 100), (Some(1), 50)]);
+    for record in records {
+        assert_eq!(record.status, OperationStatus::Success as u32);
+        assert_eq!(record.timestamp, 2_000);
+        assert_eq!(record.epoch, 1);
+    }
+}
+
+#[tokio::test]
+async fn index_spent_cell() {
+    let ctx = chain_context();
+    let ckb = MockCkbRpc::new();
+    let input = genesis(&ckb);
+
+    let first_tx = tx(
+        vec![input],
+        vec![(stake_cell(&ctx, &STAKER_A), stake_data(100, 100, 3))],
+        vec![],
+    );
+    ckb.commit(first_tx.clone(), 2, 2_000_000);
+
+    // the first stake cell is spent before the blocks are scanned
+    let second_tx = tx(
+        vec![OutPoint::new(first_tx.hash(), 0)],
+        vec![(stake_cell(&ctx, &STAKER_A), stake_data(130, 130, 3))],
+        vec![],
+    );
+    ckb.commit(second_tx.clone(), 3, 3_000_000);
+
+    let indexer = indexer(ckb).await;
+    indexer.scan_range(0, 10).await.unwrap();
+
+    let first = indexer
+        .storage
+        .get_records_by_tx_hash(hash_of(&first_tx))
+        .await
+        .unwrap();
+    assert_eq!(first.len(), 1);
+    assert_eq!(decode_amount(&first[0].total_amount).unwrap(), 100);
+    assert_eq!(first[0].timestamp, 2_000);
+
+    let second = indexer
+        .storage
+        .get_records_by_tx_hash(hash_of(&second_tx))
+        .await
+        .unwrap();
+    assert_eq!(second.len(), 1);
+    assert_eq!(decode_amount(&second[0].total_amount).unwrap(), 30);
+    assert_eq!(second[0].timestamp, 3_000);
+}
+
+#[tokio::test]
+async fn index_withdraw_with_tx_epoch() {
+    let ctx = chain_context();
+    let ckb = MockCkbRpc::new();
+    let xudt = Xudt::type_(&ctx, &ctx.type_ids.xudt_owner.pack());
+    let withdraw_lock = Withdraw::lock(&ctx, &ctx.type_ids.metadata_type_id, &STAKER_A);
+    let checkpoint = |epoch| {
+        CheckpointCellData::new_builder()
+            .epoch(to_uint64(epoch))
+            .build()
+            .as_bytes()
+    };
+
+    let init_tx = tx(
+        vec![],
+        vec![
+            (
+                cell(
+                    Script::default(),
+                    Some(Checkpoint::type_(&ctx, &ctx.type_ids.checkpoint_type_id)),
+                ),
+                checkpoint(5),
+            ),
+            (
+                cell(withdraw_lock.clone(), Some(xudt.clone())),
+                token_cell_data(100, Bytes::new()),
+            ),
+        ],
+        vec![],
+    );
+    ckb.commit(init_tx.clone(), 1, 1_000_000);
+
+    let withdraw_tx = tx(
+        vec![OutPoint::new(init_tx.hash(), 1)],
+        vec![(
+            cell(withdraw_lock, Some(xudt)),
+            token_cell_data(40, Bytes::new()),
+        )],
+        vec![OutPoint::new(init_tx.hash(), 0)],
+    );
+    ckb.commit(withdraw_tx.clone(), 2, 2_000_000);
+
+    // the checkpoint moves to the next epoch before the blocks are scanned
+    let checkpoint_tx = tx(
+        vec![OutPoint::new(init_tx.hash(), 0)],
+        vec![(
+            cell(
+                Script::default(),
+                Some(Checkpoint::type_(&ctx, &ctx.type_ids.checkpoint_type_id)),
+            ),
+            checkpoint(6),
+        )],
+        vec![],
+    );
+    ckb.commit(checkpoint_tx, 3, 3_000_000);
+
+    let indexer = indexer(ckb).await;
+    indexer.scan_range(0, 10).await.unwrap();
+
+    let records = indexer
+        .storage
+        .get_records_by_tx_hash(hash_of(&withdraw_tx))
+        .await
+        .unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(decode_amount(&records[0].withdrawable_amount).unwrap(), 60);
+    assert_eq!(records[0].epoch, 5);
+    assert_eq!(records[0].timestamp, 2_000);
+}
+
+fn checkpoint_data(epoch: u64) -> Bytes {
+    CheckpointCellData::new_builder()
+        .epoch(to_uint64(epoch))
+        .build()
+        .as_bytes()
+}
+
+fn checkpoint_cell(ctx: &ChainContext) -> CellOutput {
+    cell(
+        Script::default(),
+        Some(Checkpoint::type_(ctx, &ctx.type_ids.checkpoint_type_id)),
+    )
+}
+
+// The stake AT cell data of the first stake, which refers to the delegate
+// requirement cell.
+fn first_stake_data(amount: u128, inauguration_epoch: u64, requirement_type_id: &H256) -> Bytes {
+    let requirement_info = DelegateRequirementInfo::new_builder()
+        .requirement(
+            DelegateRequirementArgs::new_builder()
+                .requirement_type_id(to_byte32(requirement_type_id))
+                .build(),
+        )
+        .build();
+    let data = StakeAtCellData::new_builder()
+        .lock(
+            StakeAtCellLockData::new_builder()
+                .delta(
+                    StakeItem {
+                        is_increase: true,
+                        amount,
+                        inauguration_epoch,
+                    }
+                    .into(),
+                )
+                .requirement_info(requirement_info)
+                .build(),
+        )
+        .build();
+    token_cell_data(amount, data.as_bytes())
+}
+
+fn delegate_data(total: u128, delegations: Vec<DelegateItem>) -> Bytes {
+    let data = DelegateAtCellData::new_builder()
+        .lock(
+            DelegateAtCellLockData::new_builder()
+                .delegator_infos(
+                    DelegateInfoDeltas::new_builder()
+                        .extend(delegations.into_iter().map(Into::into))
+                        .build(),
+                )
+                .build(),
+        )
+        .build();
+    token_cell_data(total, data.as_bytes())
+}
+
+fn withdraw_data(unlocks: &[(u64, u128)]) -> Bytes {
+    let infos = WithdrawInfos::new_builder()
+        .extend(unlocks.iter().map(|(unlock_epoch, amount)| {
+            WithdrawInfo::new_builder()
+                .amount(to_uint128(*amount))
+                .unlock_epoch(to_uint64(*unlock_epoch))
+                .build()
+        }))
+        .build();
+    let data = WithdrawAtCellData::new_builder()
+        .lock(
+            WithdrawAtCellLockData::new_builder()
+                .withdraw_infos(infos)
+                .build(),
+        )
+        .build();
+    let total = unlocks.iter().map(|(_, amount)| amount).sum();
+    token_cell_data(total, data.as_bytes())
+}
+
+#[tokio::test]
+async fn index_stakers_and_delegations() {
+    let ctx = chain_context();
+    let ckb = MockCkbRpc::new();
+    let input = genesis(&ckb);
+    let xudt = Xudt::type_(&ctx, &ctx.type_ids.xudt_owner.pack());
+    let delegate_lock = Delegate::lock(&ctx, &ctx.type_ids.metadata_type_id, &DELEGATOR);
+    let requirement_type_id = h256!("0x4");
+
+    // the first stake tx creates the delegate requirement cell of the staker
+    let requirement = DelegateCellData::new_builder()
+        .delegate_requirement(
+            DelegateRequirement {
+                commission_rate:    20,
+                maximum_delegators: 10,
+                threshold:          50,
+            }
+            .into(),
+        )
+        .build()
+        .as_bytes();
+    let stake_tx = tx(
+        vec![input],
+        vec![
+            (
+                stake_cell(&ctx, &STAKER_A),
+                first_stake_data(100, 3, &requirement_type_id),
+            ),
+            (
+                cell(
+                    Script::default(),
+                    Some(Delegate::requirement_type(
+                        &ctx,
+                        &ctx.type_ids.metadata_type_id,
+                        &requirement_type_id,
+                    )),
+                ),
+                requirement,
+            ),
+        ],
+        vec![],
+    );
+    ckb.commit(stake_tx, 2, 2_000_000);
+
+    // the delegation to B is not saved, B is not a staker
+    let first_tx = tx(
+        vec![],
+        vec![(
+            cell(delegate_lock.clone(), Some(xudt.clone())),
+            delegate_data(120, vec![
+                DelegateItem::new(STAKER_A, true, 100, 3),
+                DelegateItem::new(STAKER_B, true, 20, 3),
+            ]),
+        )],
+        vec![],
+    );
+    ckb.commit(first_tx.clone(), 3, 3_000_000);
+
+    let second_tx = tx(
+        vec![OutPoint::new(first_tx.hash(), 0)],
+        vec![(
+            cell(delegate_lock, Some(xudt)),
+            delegate_data(120, vec![DelegateItem::new(STAKER_A, false, 30, 4)]),
+        )],
+        vec![],
+    );
+    ckb.commit(second_tx, 4, 4_000_000);
+
+    let indexer = indexer(ckb).await;
+    indexer.scan_range(0, 10).await.unwrap();
+    // scanning the blocks again changes nothing
+    indexer.scan_range(0, 10).await.unwrap();
+
+    let staker = to_eth_h160(&STAKER_A);
+    let record = indexer.storage.get_staker(staker).await.unwrap().unwrap();
+    assert_eq!(record.commission_rate, 20);
+    assert_eq!(record.maximum_delegators, 10);
+    assert_eq!(record.threshold, encode_amount(50));
+    assert_eq!(record.epoch, 1);
+    assert!(indexer
+        .storage
+        .get_staker(to_eth_h160(&STAKER_B))
+        .await
+        .unwrap()
+        .is_none());
+
+    let delegator = to_eth_h160(&DELEGATOR);
+    for (epoch, amount) in [(3, 100), (4, 70)] {
+        let delegations = indexer
+            .storage
+            .get_delegations_by_delegator(delegator, epoch)
+            .await
+            .unwrap();
+        assert_eq!(delegations.len(), 1);
+        assert_eq!(delegations[0].staker, staker.to_string());
+        assert_eq!(decode_amount(&delegations[0].amount).unwrap(), amount);
+    }
+}
+
+#[tokio::test]
+async fn index_withdraw_unlocks() {
+    let ctx = chain_context();
+    let ckb = MockCkbRpc::new();
+    let xudt = Xudt::type_(&ctx, &ctx.type_ids.xudt_owner.pack());
+    let withdraw_lock = Withdraw::lock(&ctx, &ctx.type_ids.metadata_type_id, &STAKER_A);
+
+    let init_tx = tx(
+        vec![],
+        vec![
+            (checkpoint_cell(&ctx), checkpoint_data(5)),
+            (
+                cell(withdraw_lock.clone(), Some(xudt.clone())),
+                token_cell_data(0, Bytes::new()),
+            ),
+        ],
+        vec![],
+    );
+    ckb.commit(init_tx.clone(), 1, 1_000_000);
+
+    // the kicker adds the unstaked amounts to the withdraw AT cell
+    let first_tx = tx(
+        vec![OutPoint::new(init_tx.hash(), 1)],
+        vec![(
+            cell(withdraw_lock.clone(), Some(xudt.clone())),
+            withdraw_data(&[(7, 40)]),
+        )],
+        vec![OutPoint::new(init_tx.hash(), 0)],
+    );
+    ckb.commit(first_tx.clone(), 2, 2_000_000);
+
+    let second_tx = tx(
+        vec![OutPoint::new(first_tx.hash(), 0)],
+        vec![(
+            cell(withdraw_lock, Some(xudt)),
+            withdraw_data(&[(7, 100), (8, 10)]),
+        )],
+        vec![OutPoint::new(init_tx.hash(), 0)],
+    );
+    ckb.commit(second_tx.clone(), 3, 3_000_000);
+
+    let indexer = indexer(ckb).await;
+    indexer.scan_range(0, 10).await.unwrap();
+    // scanning the blocks again changes nothing
+    indexer.scan_range(0, 10).await.unwrap();
+
+    // the unlocks refer to the snapshot of the epoch
+    let snapshot = indexer
+        .storage
+        .get_epoch_snapshot(5)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(snapshot.timestamp, 2_000);
+
+    let address = to_eth_h160(&STAKER_A);
+    assert!(indexer
+        .storage
+        .get_unlocked_withdraws(address, 6)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let unlocks = indexer
+        .storage
+        .get_unlocked_withdraws(address, 8)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|unlock| {
+            (
+                unlock.tx_hash,
+                unlock.unlock_epoch,
+                decode_amount(&unlock.amount).unwrap(),
+                unlock.epoch,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(unlocks, vec![
+        (hash_of(&first_tx), 7, 40, 5),
+        (hash_of(&second_tx), 7, 60, 5),
+        (hash_of(&second_tx), 8, 10, 5),
+    ]);
+
+    // the transaction table records the user operations only
+    assert!(indexer
+        .storage
+        .get_records_by_tx_hash(hash_of(&first_tx))
+        .await
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,4 @@
+#[cfg(test)]
+mod indexer;
+#[cfg(test)]
+mod parser;
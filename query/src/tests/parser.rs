@@ -0,0 +1,108 @@
+use ckb_types::bytes::Bytes;
+use molecule::prelude::{Builder, Entity};
+
+use common::types::api::{HistoryEvent, OperationType};
+use common::types::axon_types::stake::{StakeAtCellData, StakeAtCellLockData};
+use common::types::smt::Address;
+use common::types::tx_builder::StakeItem;
+use tx_builder::ckb::helper::token_cell_data;
+
+use crate::parser::*;
+
+fn stake_data(total: u128, is_increase: bool, amount: u128, inauguration_epoch: u64) -> Bytes {
+    let data = StakeAtCellData::new_builder()
+        .lock(
+            StakeAtCellLockData::new_builder()
+                .delta(
+                    StakeItem {
+                        is_increase,
+                        amount,
+                        inauguration_epoch,
+                    }
+                    .into(),
+                )
+                .build(),
+        )
+        .build();
+    token_cell_data(total, data.as_bytes())
+}
+
+#[test]
+fn first_stake() {
+    let record = parse_stake(Address::default(), &stake_data(100, true, 100, 2), None).unwrap();
+
+    assert!(matches!(record.operation, OperationType::Stake));
+    assert!(matches!(record.event, HistoryEvent::Add));
+    assert_eq!(record.amount, 100);
+    assert_eq!(record.epoch, 0);
+}
+
+#[test]
+fn stake_in_same_epoch() {
+    let old = stake_data(100, true, 100, 3);
+
+    let record = parse_stake(
+        Address::default(),
+        &stake_data(100, true, 40, 3),
+        Some(&old),
+    )
+    .unwrap();
+    assert!(matches!(record.event, HistoryEvent::Redeem));
+    assert_eq!(record.amount, 60);
+    assert_eq!(record.epoch, 1);
+
+    let record = parse_stake(
+        Address::default(),
+        &stake_data(100, false, 10, 3),
+        Some(&old),
+    )
+    .unwrap();
+    assert!(matches!(record.event, HistoryEvent::Redeem));
+    assert_eq!(record.amount, 110);
+}
+
+#[test]
+fn stake_in_new_epoch() {
+    let old = stake_data(100, true, 100, 2);
+    let record = parse_stake(
+        Address::default(),
+        &stake_data(100, false, 30, 3),
+        Some(&old),
+    )
+    .unwrap();
+
+    assert!(matches!(record.event, HistoryEvent::Redeem));
+    assert_eq!(record.amount, 30);
+    assert_eq!(record.epoch, 1);
+}
+
+#[test]
+fn stake_smt_reset() {
+    let old = stake_data(100, true, 100, 2);
+    let new = stake_data(100, false, 0, 0);
+
+    assert!(parse_stake(Address::default(), &new, Some(&old)).is_none());
+}
+
+#[test]
+fn withdraw() {
+    let old = token_cell_data(100, Bytes::new());
+    let new = token_cell_data(30, Bytes::new());
+
+    let record = parse_withdraw(Address::default(), &new, Some(&old), 5).unwrap();
+    assert!(matches!(record.operation, OperationType::Withdraw));
+    assert_eq!(record.amount, 70);
+    assert_eq!(record.epoch, 5);
+
+    // unlocked by the kicker
+    assert!(parse_withdraw(Address::default(), &old, Some(&new), 5).is_none());
+}
+
+#[test]
+fn owner() {
+    let mut args = vec![0u8; 32];
+    args.extend_from_slice(&[1u8; 20]);
+
+    assert_eq!(parse_owner(&args), Some(Address::repeat_byte(1)));
+    assert_eq!(parse_owner(&args[..40]), None);
+}
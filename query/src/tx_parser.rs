@@ -0,0 +1,292 @@
+use anyhow::{anyhow, Result};
+use ckb_jsonrpc_types::{CellOutput, Either, OutPoint, Script, TransactionView};
+use ckb_types::{bytes::Bytes, prelude::Pack, H256};
+use molecule::prelude::Entity;
+
+use common::traits::ckb_rpc_client::CkbRpc;
+use common::types::api::{HistoryEvent, OperationStatus, OperationType};
+use common::types::axon_types::checkpoint::CheckpointCellData;
+use common::types::relation_db::transaction::{self, encode_amount};
+use common::types::smt::Address;
+use common::types::tx_builder::{ChainContext, Epoch};
+use common::utils::convert::to_u64;
+use storage::relation_db::Set;
+use tx_builder::ckb::helper::{Checkpoint, Delegate, Reward, Stake, Withdraw, Xudt};
+
+use crate::parser::{parse_delegate, parse_owner, parse_stake, parse_token_amount, parse_withdraw};
+use crate::Record;
+
+// The outputs of a reward tx are the reward smt cell, the selection cell and
+// the AT cell of the user.
+pub(crate) const REWARD_AT_OUTPUT: usize = 2;
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum CellKind {
+    Stake,
+    Delegate,
+    Withdraw,
+    Reward,
+}
+
+/// Decodes the user operations of a tx from its outputs and the cells it
+/// spends. The tx does not need to be committed, so the operations of a tx
+/// are known before it is sent.
+pub struct TxParser<'a, C> {
+    ckb: &'a C,
+    ctx: &'a ChainContext,
+}
+
+impl<'a, C: CkbRpc> TxParser<'a, C> {
+    pub fn new(ckb: &'a C, ctx: &'a ChainContext) -> Self {
+        Self { ckb, ctx }
+    }
+
+    /// The records of the tx by the indexes of the outputs they are decoded
+    /// from.
+    pub async fn parse(&self, tx: &TransactionView) -> Result<Vec<(usize, Vec<Record>)>> {
+        let reward_smt: Script =
+            Reward::smt_type(self.ctx, &self.ctx.type_ids.reward_smt_type_id).into();
+        if tx
+            .inner
+            .outputs
+            .iter()
+            .any(|output| output.type_.as_ref() == Some(&reward_smt))
+        {
+            return Ok(vec![(REWARD_AT_OUTPUT, self.parse_reward(tx).await?)]);
+        }
+
+        let mut records = vec![];
+        for (index, (output, data)) in tx
+            .inner
+            .outputs
+            .iter()
+            .zip(tx.inner.outputs_data.iter())
+            .enumerate()
+        {
+            let kind = match self.kind_of(output) {
+                Some(kind) => kind,
+                None => continue,
+            };
+            let owner = match parse_owner(output.lock.args.as_bytes()) {
+                Some(owner) => owner,
+                None => continue,
+            };
+            let old_data = self.find_input_data(tx, output).await?;
+            let output_records = self
+                .parse_output(
+                    kind,
+                    tx,
+                    owner,
+                    &data.clone().into_bytes(),
+                    old_data.as_ref(),
+                )
+                .await?;
+            if !output_records.is_empty() {
+                records.push((index, output_records));
+            }
+        }
+
+        Ok(records)
+    }
+
+    pub(crate) async fn parse_output(
+        &self,
+        kind: CellKind,
+        tx: &TransactionView,
+        owner: Address,
+        data: &Bytes,
+        old_data: Option<&Bytes>,
+    ) -> Result<Vec<Record>> {
+        match kind {
+            CellKind::Stake => Ok(parse_stake(owner, data, old_data).into_iter().collect()),
+            CellKind::Delegate => Ok(parse_delegate(owner, data, old_data)),
+            // The epoch is looked up only for a redeem, the withdraw cells
+            // created or unlocked by the kicker are not user operations.
+            CellKind::Withdraw => match parse_withdraw(owner, data, old_data, Epoch::default()) {
+                Some(record) => Ok(vec![Record {
+                    epoch: self.tx_epoch(tx).await?,
+                    ..record
+                }]),
+                None => Ok(vec![]),
+            },
+            CellKind::Reward => self.parse_reward(tx).await,
+        }
+    }
+
+    // The AT cells of the stake, delegate and withdraw locks.
+    fn kind_of(&self, output: &CellOutput) -> Option<CellKind> {
+        let xudt: Script = Xudt::type_(self.ctx, &self.ctx.type_ids.xudt_owner.pack()).into();
+        if output.type_.as_ref() != Some(&xudt) {
+            return None;
+        }
+
+        let metadata_type_id = &self.ctx.type_ids.metadata_type_id;
+        [
+            (
+                CellKind::Stake,
+                Stake::lock_prefix(self.ctx, metadata_type_id),
+            ),
+            (
+                CellKind::Delegate,
+                Delegate::lock_prefix(self.ctx, metadata_type_id),
+            ),
+            (
+                CellKind::Withdraw,
+                Withdraw::lock_prefix(self.ctx, metadata_type_id),
+            ),
+        ]
+        .into_iter()
+        .find(|(_, prefix)| {
+            let prefix: Script = prefix.clone().into();
+            output.lock.code_hash == prefix.code_hash
+                && output.lock.hash_type == prefix.hash_type
+                && output
+                    .lock
+                    .args
+                    .as_bytes()
+                    .starts_with(prefix.args.as_bytes())
+        })
+        .map(|(kind, _)| kind)
+    }
+
+    pub(crate) async fn parse_reward(&self, tx: &TransactionView) -> Result<Vec<Record>> {
+        let xudt = Xudt::type_(self.ctx, &self.ctx.type_ids.xudt_owner.pack());
+        let (output, data) = match (
+            tx.inner.outputs.get(REWARD_AT_OUTPUT),
+            tx.inner.outputs_data.get(REWARD_AT_OUTPUT),
+        ) {
+            (Some(output), Some(data)) if output.type_ == Some(xudt.into()) => (output, data),
+            _ => return Ok(vec![]),
+        };
+        let new_amount = parse_token_amount(data.as_bytes()).unwrap_or(0);
+
+        let mut old_amount = 0;
+        for (input, input_data) in self.get_inputs(tx).await? {
+            if input.type_ == output.type_ {
+                old_amount += parse_token_amount(&input_data).unwrap_or(0);
+            }
+        }
+
+        let args = output.lock.args.as_bytes();
+        if new_amount <= old_amount || args.len() < 20 {
+            return Ok(vec![]);
+        }
+
+        Ok(vec![Record {
+            address:   Address::from_slice(&args[..20]),
+            operation: OperationType::Reward,
+            event:     HistoryEvent::Add,
+            amount:    new_amount - old_amount,
+            epoch:     self.tx_epoch(tx).await?,
+        }])
+    }
+
+    // The withdraw and reward txs depend on the checkpoint cell, whose epoch is
+    // the epoch the tx is committed in.
+    pub(crate) async fn tx_epoch(&self, tx: &TransactionView) -> Result<Epoch> {
+        let checkpoint: Script =
+            Checkpoint::type_(self.ctx, &self.ctx.type_ids.checkpoint_type_id).into();
+
+        for cell_dep in tx.inner.cell_deps.iter() {
+            let (output, data) = self.get_output(&cell_dep.out_point).await?;
+            if output.type_.as_ref() == Some(&checkpoint) {
+                let data = CheckpointCellData::from_slice(&data)
+                    .map_err(|e| anyhow!("invalid checkpoint data: {}", e))?;
+                return Ok(to_u64(&data.epoch()));
+            }
+        }
+
+        Err(anyhow!("checkpoint cell dep not found: 0x{}", tx.hash))
+    }
+
+    pub(crate) async fn find_input_data(
+        &self,
+        tx: &TransactionView,
+        output: &CellOutput,
+    ) -> Result<Option<Bytes>> {
+        Ok(self
+            .get_inputs(tx)
+            .await?
+            .into_iter()
+            .find(|(input, _)| input.lock == output.lock && input.type_ == output.type_)
+            .map(|(_, data)| data))
+    }
+
+    async fn get_inputs(&self, tx: &TransactionView) -> Result<Vec<(CellOutput, Bytes)>> {
+        let mut inputs = Vec::with_capacity(tx.inner.inputs.len());
+        for input in tx.inner.inputs.iter() {
+            inputs.push(self.get_output(&input.previous_output).await?);
+        }
+        Ok(inputs)
+    }
+
+    async fn get_output(&self, out_point: &OutPoint) -> Result<(CellOutput, Bytes)> {
+        let index = out_point.index.value() as usize;
+        let tx = self.get_tx(out_point.tx_hash.clone()).await?;
+
+        match (
+            tx.inner.outputs.get(index),
+            tx.inner.outputs_data.get(index),
+        ) {
+            (Some(output), Some(data)) => Ok((output.clone(), data.clone().into_bytes())),
+            _ => Err(anyhow!("cell not found: {:?}", out_point)),
+        }
+    }
+
+    pub(crate) async fn get_tx(&self, tx_hash: H256) -> Result<TransactionView> {
+        let tx = self
+            .ckb
+            .get_transaction(tx_hash.clone())
+            .await?
+            .and_then(|tx| tx.transaction)
+            .ok_or_else(|| anyhow!("tx not found: 0x{}", tx_hash))?;
+
+        match tx.inner {
+            Either::Left(tx) => Ok(tx),
+            Either::Right(_) => Err(anyhow!("tx in bytes: 0x{}", tx_hash)),
+        }
+    }
+}
+
+impl Record {
+    /// The transaction row of the record. The records of a tx which is not
+    /// indexed yet have no output index and block, they are replaced once it
+    /// is.
+    pub fn into_model(
+        self,
+        tx_hash: &H256,
+        output_index: Option<usize>,
+        block_number: Option<u64>,
+        item_index: usize,
+        timestamp: u32,
+        status: OperationStatus,
+    ) -> transaction::ActiveModel {
+        let amount = self.amount;
+        let (stake_amount, delegate_amount, withdrawable_amount) = match self.operation {
+            OperationType::Stake => (amount, 0, 0),
+            OperationType::Delegate => (0, amount, 0),
+            OperationType::Withdraw => (0, 0, amount),
+            OperationType::Reward => (0, 0, 0),
+        };
+
+        transaction::ActiveModel {
+            address: Set(self.address.to_string()),
+            timestamp: Set(timestamp),
+            operation: Set(self.operation as u32),
+            event: Set(self.event as u32),
+            tx_hash: Set(tx_hash.to_string()),
+            total_amount: Set(encode_amount(amount)),
+            stake_amount: Set(encode_amount(stake_amount)),
+            delegate_amount: Set(encode_amount(delegate_amount)),
+            withdrawable_amount: Set(encode_amount(withdrawable_amount)),
+            stake_rate: Set(String::new()),
+            delegate_rate: Set(String::new()),
+            epoch: Set(self.epoch as u32),
+            status: Set(status as u32),
+            output_index: Set(output_index.map(|index| index as u32)),
+            item_index: Set(item_index as u32),
+            block_number: Set(block_number),
+            ..Default::default()
+        }
+    }
+}
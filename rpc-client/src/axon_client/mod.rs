@@ -1,22 +1,35 @@
+use std::collections::VecDeque;
 use std::sync::{atomic::AtomicU64, Arc};
+use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use common::{
-    traits::axon_rpc_client::{AxonRpc, AxonWsRpc, SubmitProcess},
+    traits::{
+        axon_rpc_client::{AxonRpc, AxonWsRpc, SubmitProcess},
+        query::ChainRollback,
+    },
     types::{
-        api::ChainState,
-        axon_rpc_client::{Block, Header, LatestCheckPointInfo, Metadata},
+        axon_rpc_client::{Block, BlockNumber, Header, LatestCheckPointInfo, Metadata, NewHead},
         ckb_rpc_client::Cell,
     },
 };
+use futures::stream::{self, BoxStream, StreamExt};
 use jsonrpsee::{
-    core::client::ClientT,
+    core::client::{ClientT, Subscription, SubscriptionClientT},
     http_client::{HttpClient, HttpClientBuilder},
     rpc_params,
     ws_client::WsClientBuilder,
 };
 use reqwest::Url;
+use tokio::sync::mpsc;
+
+const HEADER_CHANNEL_SIZE: usize = 16;
+const MIN_RECONNECT_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(60);
+// The cell process keeps 100 scanned ranges with a notified cell at most, the
+// older cells can not be rolled back.
+const MAX_NOTIFIED_CELLS: usize = 100;
 
 macro_rules! request {
     ($client:expr, $method:expr $(, $param:expr)*) => {
@@ -29,7 +42,27 @@ macro_rules! request {
     };
 }
 
-pub struct RpcSubmit;
+/// Keeps the latest cells notified to axon, a cell reverted by a reorg is
+/// removed, and so are the rows indexed from the reverted blocks. Rolling back
+/// a cell which is not notified is an error, and the cell process is stopped.
+#[derive(Default)]
+pub struct RpcSubmit {
+    notified: VecDeque<Cell>,
+    chain:    Option<Arc<dyn ChainRollback>>,
+}
+
+impl RpcSubmit {
+    pub fn new(chain: Option<Arc<dyn ChainRollback>>) -> Self {
+        Self {
+            notified: VecDeque::new(),
+            chain,
+        }
+    }
+
+    pub fn notified_cells(&self) -> impl Iterator<Item = &Cell> {
+        self.notified.iter()
+    }
+}
 
 #[async_trait]
 impl SubmitProcess for RpcSubmit {
@@ -38,61 +71,339 @@ impl SubmitProcess for RpcSubmit {
     }
 
     async fn notify_axon(&mut self, cell: &Cell) -> bool {
-        println!("cell: {:?}", cell);
+        log::info!("[axon] notify cell: {:?}", cell.out_point);
+        self.notified.push_back(cell.clone());
+        while self.notified.len() > MAX_NOTIFIED_CELLS {
+            self.notified.pop_front();
+        }
+        true
+    }
+
+    async fn rollback(&mut self, from: u64, cells: &[Cell]) -> bool {
+        for cell in cells {
+            match self
+                .notified
+                .iter()
+                .rposition(|notified| notified.out_point == cell.out_point)
+            {
+                Some(index) => {
+                    self.notified.remove(index);
+                    log::warn!("[axon] roll back cell: {:?}", cell.out_point);
+                }
+                None => {
+                    log::error!("[axon] roll back a cell not notified: {:?}", cell.out_point);
+                    return false;
+                }
+            }
+        }
+
+        if let Some(chain) = self.chain.as_ref() {
+            if let Err(e) = chain.rollback_blocks(from).await {
+                log::error!("[axon] roll back blocks from {} failed: {}", from, e);
+                return false;
+            }
+        }
         true
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct AxonRpcClient {
     http_client: HttpClient,
+    ws_url:      String,
     _id:         Arc<AtomicU64>,
 }
 
 impl AxonRpcClient {
-    pub async fn new(axon_http_url: &str, _axon_ws_url: &str) -> Self {
+    pub async fn new(axon_http_url: &str, axon_ws_url: &str) -> Self {
         let axon_http_url =
             Url::parse(axon_http_url).expect("axon http url, e.g. \"http://localhost:8000\"");
-        // todo: modify axon ws server
-        // let ws = WsClientBuilder::default().build(axon_ws_url).await.unwrap();
         let http = HttpClientBuilder::default().build(axon_http_url).unwrap();
         AxonRpcClient {
             http_client: http,
+            ws_url:      axon_ws_url.to_string(),
             _id:         Arc::new(AtomicU64::new(0)),
         }
     }
+
+    // Forwards the new headers until the receiver is dropped. A lost connection
+    // is renewed with an exponential backoff, which is reset once a header is
+    // forwarded.
+    async fn forward_headers(self, sender: mpsc::Sender<Header>) {
+        let mut backoff = MIN_RECONNECT_BACKOFF;
+
+        while !sender.is_closed() {
+            match self.subscribe_headers(&sender, &mut backoff).await {
+                Ok(()) => return,
+                Err(e) => log::warn!(
+                    "[axon] header subscription lost: {}, renew in {:?}",
+                    e,
+                    backoff
+                ),
+            }
+
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+        }
+    }
+
+    // Returns ok if the receiver is dropped.
+    async fn subscribe_headers(
+        &self,
+        sender: &mpsc::Sender<Header>,
+        backoff: &mut Duration,
+    ) -> Result<()> {
+        let ws = WsClientBuilder::default().build(&self.ws_url).await?;
+        let mut sub: Subscription<NewHead> = ws
+            .subscribe("eth_subscribe", rpc_params!["newHeads"], "eth_unsubscribe")
+            .await?;
+
+        while let Some(head) = sub.next().await {
+            // the axon header is not a part of the web3 style notification
+            let block = self.get_block(Some(head?.number)).await?;
+            *backoff = MIN_RECONNECT_BACKOFF;
+
+            if sender.send(block.header).await.is_err() {
+                return Ok(());
+            }
+        }
+
+        Err(anyhow!("subscription closed by axon"))
+    }
 }
 
-#[async_trait]
 impl AxonWsRpc for AxonRpcClient {
-    async fn sub_axon_header(&self) -> Result<ChainState> {
-        // todo: create rpc ws connection to axon
-        let ws = WsClientBuilder::default()
-            .build("ws://localhost:8000/socket")
-            .await
-            .unwrap();
-        let _response: Header = ws.request("eth_subscription", rpc_params![]).await?;
-        Ok(ChainState::default())
+    fn sub_axon_header(&self) -> BoxStream<'static, Header> {
+        // without the ws url there is nothing to subscribe or renew
+        if self.ws_url.is_empty() {
+            log::warn!("[axon] no ws url, the new headers are not subscribed");
+            return stream::pending().boxed();
+        }
+
+        let (sender, receiver) = mpsc::channel(HEADER_CHANNEL_SIZE);
+        tokio::spawn(self.clone().forward_headers(sender));
+
+        stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|header| (header, receiver))
+        })
+        .boxed()
     }
 }
 
 #[async_trait]
 impl AxonRpc for AxonRpcClient {
     async fn get_checkpoint_info(&self) -> Result<LatestCheckPointInfo> {
-        let metadata: Metadata = request!(self.http_client, "axon_getCurrentMetadata");
-        let block: Block = request!(self.http_client, "axon_getBlockById", "latest");
-        let last_block_number = format!("0x{:X}", block.header.number - 1);
-        let last_block: Block = request!(self.http_client, "axon_getBlockById", last_block_number);
+        let metadata = self.get_current_metadata().await?;
+        let block = self.get_block(None).await?;
+        let last_block = self.get_block(Some(block.header.number - 1)).await?;
         Ok(LatestCheckPointInfo::new(
             &last_block.header,
             &block.header,
             &metadata,
         ))
     }
+
+    async fn get_current_metadata(&self) -> Result<Metadata> {
+        let metadata = self
+            .http_client
+            .request("axon_getCurrentMetadata", rpc_params![])
+            .await?;
+        Ok(metadata)
+    }
+
+    async fn get_block(&self, number: Option<BlockNumber>) -> Result<Block> {
+        let id = match number {
+            Some(number) => format!("0x{:X}", number),
+            None => "latest".to_string(),
+        };
+        let block = self
+            .http_client
+            .request("axon_getBlockById", rpc_params![id])
+            .await?;
+        Ok(block)
+    }
 }
 
+#[cfg(test)]
 mod tests {
 
+    #[tokio::test]
+    async fn test_rollback() {
+        use super::*;
+        use ckb_types::{packed, prelude::*};
+
+        let cell = |index: u32| Cell {
+            output:       packed::CellOutput::default().into(),
+            output_data:  None,
+            out_point:    packed::OutPoint::new(packed::Byte32::default(), index).into(),
+            block_number: u64::from(index).into(),
+            tx_index:     0.into(),
+        };
+        let notified = |submit: &RpcSubmit| {
+            submit
+                .notified_cells()
+                .map(|cell| cell.out_point.index.value())
+                .collect::<Vec<_>>()
+        };
+
+        let mut submit = RpcSubmit::default();
+        for index in 0..3 {
+            assert!(submit.notify_axon(&cell(index)).await);
+        }
+
+        assert!(submit.rollback(1, &[cell(1), cell(2)]).await);
+        assert_eq!(notified(&submit), vec![0]);
+
+        // the reverted cells are not notified any more
+        assert!(!submit.rollback(1, &[cell(2)]).await);
+        assert_eq!(notified(&submit), vec![0]);
+    }
+
+    #[tokio::test]
+    async fn test_rollback_blocks() {
+        use super::*;
+        use std::sync::atomic::Ordering;
+
+        #[derive(Default)]
+        struct Blocks(AtomicU64);
+
+        #[async_trait]
+        impl ChainRollback for Blocks {
+            async fn rollback_blocks(&self, from: u64) -> Result<()> {
+                if from == 0 {
+                    return Err(anyhow!("rollback failed"));
+                }
+                self.0.store(from, Ordering::SeqCst);
+                Ok(())
+            }
+        }
+
+        let blocks = Arc::new(Blocks::default());
+        let mut submit = RpcSubmit::new(Some(blocks.clone()));
+        assert!(submit.rollback(5, &[]).await);
+        assert_eq!(blocks.0.load(Ordering::SeqCst), 5);
+
+        // the cell process is stopped if the rows are not rolled back
+        assert!(!submit.rollback(0, &[]).await);
+    }
+
+    // An axon node whose subscription sends the number of the connection as a
+    // new head, then breaks with a malformed head. The http and ws requests are
+    // served on the same address.
+    async fn start_axon_node() -> (String, jsonrpsee::server::ServerHandle) {
+        use super::*;
+        use common::types::axon_rpc_client::mock_header;
+        use jsonrpsee::core::SubscriptionResult;
+        use jsonrpsee::server::{
+            PendingSubscriptionSink, RpcModule, ServerBuilder, SubscriptionMessage,
+        };
+        use serde_json::json;
+        use std::sync::atomic::Ordering;
+
+        async fn notify_head(pending: PendingSubscriptionSink, number: u64) -> SubscriptionResult {
+            let sink = pending.accept().await?;
+            let head = json!({
+                "number": format!("{:#x}", number),
+                "hash": format!("{:#066x}", number),
+            });
+            sink.send(SubscriptionMessage::from_json(&head)?).await?;
+            sink.send(SubscriptionMessage::from_json(&json!("malformed"))?)
+                .await?;
+            Ok(())
+        }
+
+        let server = ServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let mut module = RpcModule::new(());
+        module
+            .register_method("axon_getBlockById", |params, _| {
+                let id: String = params.one()?;
+                let number = u64::from_str_radix(id.trim_start_matches("0x"), 16).unwrap();
+
+                // the uints of the axon header are hex strings
+                let mut header = serde_json::to_value(mock_header()).unwrap();
+                for field in ["timestamp", "call_system_script_count", "chain_id"] {
+                    header[field] = json!(format!("{:#x}", header[field].as_u64().unwrap()));
+                }
+                for field in ["number", "round"] {
+                    header["proof"][field] = json!("0x0");
+                }
+                header["number"] = json!(format!("{:#x}", number));
+                Ok::<_, jsonrpsee::types::ErrorObjectOwned>(json!({
+                    "header": header,
+                    "tx_hashes": [],
+                }))
+            })
+            .unwrap();
+
+        let connections = Arc::new(AtomicU64::new(0));
+        module
+            .register_subscription(
+                "eth_subscribe",
+                "eth_subscription",
+                "eth_unsubscribe",
+                move |_, pending, _| {
+                    let number = connections.fetch_add(1, Ordering::SeqCst) + 1;
+                    notify_head(pending, number)
+                },
+            )
+            .unwrap();
+
+        (addr.to_string(), server.start(module).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_renew_header_subscription() {
+        use super::*;
+        use std::time::Instant;
+
+        let (addr, handle) = start_axon_node().await;
+        let client =
+            AxonRpcClient::new(&format!("http://{}", addr), &format!("ws://{}", addr)).await;
+        let mut headers = client.sub_axon_header();
+
+        let mut received = Vec::new();
+        for _ in 0..3 {
+            let header = tokio::time::timeout(Duration::from_secs(10), headers.next())
+                .await
+                .expect("no header after the reconnection")
+                .unwrap();
+            received.push((header.number, Instant::now()));
+        }
+        assert_eq!(
+            received
+                .iter()
+                .map(|(number, _)| *number)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+
+        // the backoff is reset by every header, so every connection is renewed
+        // after the minimum backoff
+        for pair in received.windows(2) {
+            let elapsed = pair[1].1 - pair[0].1;
+            assert!(elapsed >= MIN_RECONNECT_BACKOFF, "{:?}", elapsed);
+            assert!(elapsed < MIN_RECONNECT_BACKOFF * 2, "{:?}", elapsed);
+        }
+
+        drop(headers);
+        handle.stop().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_no_header_subscription() {
+        use super::*;
+
+        let client = AxonRpcClient::new("http://127.0.0.1:8000", "").await;
+        let mut headers = client.sub_axon_header();
+        assert!(
+            tokio::time::timeout(Duration::from_millis(100), headers.next())
+                .await
+                .is_err()
+        );
+    }
+
     // #[tokio::test]
     async fn _test_http_client() {
         use super::*;
@@ -1,14 +1,19 @@
 use common::{
     traits::{axon_rpc_client::SubmitProcess, ckb_rpc_client::CkbRpc},
-    types::ckb_rpc_client::{Order, RpcSearchKey, TipState},
+    types::ckb_rpc_client::{Order, RpcSearchKey, ScannedRange, TipState},
 };
 
+// The number of latest scanned ranges kept to detect a fork.
+const MAX_SCANNED_RANGES: usize = 100;
+
 pub struct CellProcess<T, S, R> {
-    key:      RpcSearchKey,
-    scan_tip: T,
-    rpc:      R,
-    process:  S,
-    stop:     bool,
+    key:           RpcSearchKey,
+    scan_tip:      T,
+    rpc:           R,
+    process:       S,
+    // the confirmations of the network, unless the key sets its own
+    confirmations: u64,
+    stop:          bool,
 }
 
 impl<T, S, R> CellProcess<T, S, R>
@@ -17,12 +22,13 @@ where
     S: SubmitProcess,
     R: CkbRpc,
 {
-    pub fn new(key: RpcSearchKey, tip: T, rpc: R, process: S) -> Self {
+    pub fn new(key: RpcSearchKey, tip: T, rpc: R, process: S, confirmations: u64) -> Self {
         Self {
             key,
             scan_tip: tip,
             rpc,
             process,
+            confirmations,
             stop: false,
         }
     }
@@ -30,6 +36,10 @@ where
     pub async fn run(&mut self) {
         let mut interval = tokio::time::interval(std::time::Duration::from_secs(8));
         interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        // the chain may have been reorganized while the process is down
+        self.check_fork().await;
+
         loop {
             if self.stop || self.process.is_closed() {
                 break;
@@ -40,11 +50,22 @@ where
 
     async fn scan(&mut self, interval: &mut tokio::time::Interval) {
         let indexer_tip = rpc_get!(self.rpc.get_indexer_tip());
-        let old_tip = *self.scan_tip.load();
+        let mut tip = self.scan_tip.load().clone();
+        let old_tip = tip.number;
+        let confirmations = self.key.confirmations.unwrap_or(self.confirmations);
 
-        if indexer_tip.block_number.value().saturating_sub(24) > old_tip.value() {
-            // use tip - 24 as new tip
-            let new_tip = indexer_tip.block_number.value().saturating_sub(24).into();
+        if indexer_tip
+            .block_number
+            .value()
+            .saturating_sub(confirmations)
+            > old_tip.value()
+        {
+            // use tip - confirmations as new tip
+            let new_tip = indexer_tip
+                .block_number
+                .value()
+                .saturating_sub(confirmations)
+                .into();
 
             let search_key = self.key.clone().into_key(Some([old_tip, new_tip]));
 
@@ -52,13 +73,179 @@ where
                 .rpc
                 .get_cells(search_key.clone(), Order::Asc, 1.into(), None));
 
+            let mut cells = Vec::new();
             if !txs.objects.is_empty() {
                 let cell = txs.objects.first().unwrap();
                 self.process.notify_axon(cell).await;
+                cells.push(cell.clone());
             }
-            self.scan_tip.update(new_tip);
+
+            // the block range is [old_tip, new_tip)
+            let hash = match rpc_get!(self.rpc.get_block_hash((new_tip.value() - 1).into())) {
+                Some(hash) => hash,
+                // the block is reverted just now, check it in the next round
+                None => return,
+            };
+            tip.ranges.push_back(ScannedRange {
+                from: old_tip,
+                to: new_tip,
+                hash,
+                cells,
+            });
+            while tip.ranges.len() > MAX_SCANNED_RANGES {
+                tip.ranges.pop_front();
+            }
+            tip.number = new_tip;
+            self.scan_tip.update(tip);
         } else {
+            self.check_fork().await;
             interval.tick().await;
         }
     }
+
+    // Rolls the scan tip back to the last scanned range that is still on the
+    // chain, and the notified cells after it are rolled back too.
+    async fn check_fork(&mut self) {
+        let mut tip = self.scan_tip.load().clone();
+        let mut reverted = Vec::new();
+
+        while let Some(range) = tip.ranges.back() {
+            let hash = rpc_get!(self.rpc.get_block_hash((range.to.value() - 1).into()));
+            if hash.as_ref() == Some(&range.hash) {
+                break;
+            }
+
+            let range = tip.ranges.pop_back().unwrap();
+            tip.number = range.from;
+            reverted.push(range.cells);
+        }
+
+        if reverted.is_empty() {
+            return;
+        }
+
+        log::warn!(
+            "[cell process] fork detected, roll back scan tip from {} to {}",
+            self.scan_tip.load().number.value(),
+            tip.number.value()
+        );
+        if tip.ranges.is_empty() {
+            log::warn!("[cell process] the fork may be deeper than the scanned ranges");
+        }
+
+        // roll back the cells from the oldest one
+        let cells = reverted.into_iter().rev().flatten().collect::<Vec<_>>();
+        if !self.process.rollback(tip.number.value(), &cells).await {
+            self.stop = true;
+        }
+        self.scan_tip.update(tip);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use ckb_types::{core::TransactionBuilder, packed, prelude::*};
+    use common::{
+        types::ckb_rpc_client::{Cell, ScanTipInfo, ScriptType},
+        utils::mock::MockCkbRpc,
+    };
+
+    use super::*;
+
+    struct Tip(ScanTipInfo);
+
+    impl TipState for Tip {
+        fn load(&self) -> &ScanTipInfo {
+            &self.0
+        }
+
+        fn update(&mut self, current: ScanTipInfo) {
+            self.0 = current;
+        }
+    }
+
+    // The first reverted blocks and the cells of every rollback.
+    #[derive(Default)]
+    struct Rollbacks(Vec<(u64, Vec<u64>)>);
+
+    #[async_trait]
+    impl SubmitProcess for Rollbacks {
+        fn is_closed(&self) -> bool {
+            false
+        }
+
+        async fn notify_axon(&mut self, _cell: &Cell) -> bool {
+            true
+        }
+
+        async fn rollback(&mut self, from: u64, cells: &[Cell]) -> bool {
+            let cells = cells.iter().map(|cell| cell.block_number.value()).collect();
+            self.0.push((from, cells));
+            true
+        }
+    }
+
+    fn cell(block_number: u64) -> Cell {
+        Cell {
+            output:       packed::CellOutput::default().into(),
+            output_data:  None,
+            out_point:    packed::OutPoint::default().into(),
+            block_number: block_number.into(),
+            tx_index:     0.into(),
+        }
+    }
+
+    fn commit_blocks(ckb: &MockCkbRpc, blocks: std::ops::Range<u64>) {
+        for number in blocks {
+            let tx = TransactionBuilder::default()
+                .version((number as u32).pack())
+                .build();
+            ckb.commit(tx, number, number * 1000);
+        }
+    }
+
+    async fn range(ckb: &MockCkbRpc, from: u64, to: u64, cells: Vec<Cell>) -> ScannedRange {
+        ScannedRange {
+            from: from.into(),
+            to: to.into(),
+            hash: ckb.get_block_hash((to - 1).into()).await.unwrap().unwrap(),
+            cells,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_fork() {
+        let ckb = MockCkbRpc::new();
+        commit_blocks(&ckb, 1..11);
+
+        let mut tip = ScanTipInfo::new(8.into());
+        tip.ranges.push_back(range(&ckb, 0, 4, vec![cell(2)]).await);
+        tip.ranges.push_back(range(&ckb, 4, 8, vec![cell(5)]).await);
+        let key = RpcSearchKey {
+            script:             packed::Script::default().into(),
+            script_type:        ScriptType::Lock,
+            script_search_mode: None,
+            filter:             None,
+            confirmations:      None,
+        };
+        let mut process = CellProcess::new(key, Tip(tip), ckb.clone(), Rollbacks::default(), 0);
+
+        // nothing is rolled back on the same chain
+        process.check_fork().await;
+        assert!(process.process.0.is_empty());
+        assert_eq!(process.scan_tip.load().number.value(), 8);
+
+        // the blocks from 6 are replaced, the range [4, 8) is scanned again
+        ckb.fork(6);
+        commit_blocks(&ckb, 6..12);
+        process.check_fork().await;
+        assert_eq!(process.process.0, vec![(4, vec![5])]);
+        assert_eq!(process.scan_tip.load().number.value(), 4);
+        assert_eq!(process.scan_tip.load().ranges.len(), 1);
+        assert!(!process.stop);
+
+        process.check_fork().await;
+        assert_eq!(process.process.0.len(), 1);
+    }
 }
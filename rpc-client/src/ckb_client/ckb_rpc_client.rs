@@ -1,13 +1,15 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use ckb_jsonrpc_types::{
-    CellWithStatus, JsonBytes, OutPoint, OutputsValidator, Transaction,
-    TransactionWithStatusResponse, Uint32,
+    BlockNumber, CellWithStatus, HeaderView, JsonBytes, OutPoint, OutputsValidator, Transaction,
+    TransactionWithStatusResponse, Uint32, Uint64,
 };
 use ckb_types::H256;
 use common::{
     traits::ckb_rpc_client::CkbRpc,
-    types::ckb_rpc_client::{Cell, IndexerTip, Order, Pagination, SearchKey},
+    types::ckb_rpc_client::{
+        Cell, FeeRateStatistics, IndexerTip, Order, Pagination, SearchKey, Tx,
+    },
 };
 use reqwest::{Client, Url};
 
@@ -114,6 +116,24 @@ impl CkbRpcClient {
         jsonrpc!("get_indexer_tip", self, IndexerTip)
     }
 
+    pub fn get_transactions(
+        &self,
+        search_key: SearchKey,
+        order: Order,
+        limit: Uint32,
+        after: Option<JsonBytes>,
+    ) -> impl Future<Output = Result<Pagination<Tx>>> {
+        jsonrpc!(
+            "get_transactions",
+            self,
+            Pagination<Tx>,
+            search_key,
+            order,
+            limit,
+            after,
+        )
+    }
+
     pub fn send_transaction(
         &self,
         tx: &Transaction,
@@ -122,6 +142,18 @@ impl CkbRpcClient {
         jsonrpc!("send_transaction", self, H256, tx, outputs_validator)
     }
 
+    pub fn get_fee_rate_statistics(
+        &self,
+        target: Option<Uint64>,
+    ) -> impl Future<Output = Result<Option<FeeRateStatistics>>> {
+        jsonrpc!(
+            "get_fee_rate_statistics",
+            self,
+            Option<FeeRateStatistics>,
+            target
+        )
+    }
+
     pub fn get_transaction(
         &self,
         hash: H256,
@@ -133,6 +165,20 @@ impl CkbRpcClient {
             hash
         )
     }
+
+    pub fn get_block_hash(
+        &self,
+        number: BlockNumber,
+    ) -> impl Future<Output = Result<Option<H256>>> {
+        jsonrpc!("get_block_hash", self, Option<H256>, number)
+    }
+
+    pub fn get_header_by_number(
+        &self,
+        number: BlockNumber,
+    ) -> impl Future<Output = Result<Option<HeaderView>>> {
+        jsonrpc!("get_header_by_number", self, Option<HeaderView>, number)
+    }
 }
 
 #[async_trait]
@@ -155,6 +201,16 @@ impl CkbRpc for CkbRpcClient {
         self.get_indexer_tip().await
     }
 
+    async fn get_transactions(
+        &self,
+        search_key: SearchKey,
+        order: Order,
+        limit: Uint32,
+        after: Option<JsonBytes>,
+    ) -> Result<Pagination<Tx>> {
+        self.get_transactions(search_key, order, limit, after).await
+    }
+
     async fn send_transaction(
         &self,
         tx: &Transaction,
@@ -163,7 +219,22 @@ impl CkbRpc for CkbRpcClient {
         self.send_transaction(tx, outputs_validator).await
     }
 
+    async fn get_fee_rate_statistics(
+        &self,
+        target: Option<Uint64>,
+    ) -> Result<Option<FeeRateStatistics>> {
+        self.get_fee_rate_statistics(target).await
+    }
+
     async fn get_transaction(&self, hash: H256) -> Result<Option<TransactionWithStatusResponse>> {
         self.get_transaction(hash).await
     }
+
+    async fn get_block_hash(&self, number: BlockNumber) -> Result<Option<H256>> {
+        self.get_block_hash(number).await
+    }
+
+    async fn get_header_by_number(&self, number: BlockNumber) -> Result<Option<HeaderView>> {
+        self.get_header_by_number(number).await
+    }
 }
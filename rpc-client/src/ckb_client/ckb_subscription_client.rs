@@ -1,11 +1,11 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use common::traits::ckb_rpc_client::CkbSubscriptionRpc;
+use common::traits::{ckb_rpc_client::CkbSubscriptionRpc, query::ChainRollback};
 use std::path::PathBuf;
 use std::sync::{atomic::AtomicPtr, Arc};
 
 use ckb_jsonrpc_types::BlockNumber;
-use common::types::ckb_rpc_client::RpcSearchKey;
+use common::types::ckb_rpc_client::{RpcSearchKey, ScanTipInfo};
 
 use crate::axon_client::RpcSubmit;
 use crate::ckb_client::{
@@ -16,24 +16,35 @@ use crate::ckb_client::{
 };
 
 pub struct CkbSubscriptionClient {
-    cell_handles: Arc<dashmap::DashMap<RpcSearchKey, tokio::task::JoinHandle<()>>>,
-    state:        State,
-    client:       CkbRpcClient,
+    cell_handles:  Arc<dashmap::DashMap<RpcSearchKey, tokio::task::JoinHandle<()>>>,
+    state:         State,
+    client:        CkbRpcClient,
+    // the confirmations of the network, used by the keys without their own
+    confirmations: u64,
+    // rolls back the rows indexed from the blocks reverted by a reorg
+    rollback:      Option<Arc<dyn ChainRollback>>,
 }
 
 impl CkbSubscriptionClient {
-    pub fn new(ckb_uri: &str, path: PathBuf) -> Self {
+    pub fn new(
+        ckb_uri: &str,
+        path: PathBuf,
+        confirmations: u64,
+        rollback: Option<Arc<dyn ChainRollback>>,
+    ) -> Self {
         let client = CkbRpcClient::new(ckb_uri);
         let mut global = GlobalState::new(path);
         let state = global.state.clone();
 
-        let cell_handles = global.spawn_cells(client.clone());
+        let cell_handles = global.spawn_cells(client.clone(), confirmations, rollback.clone());
         let _global_handle = tokio::spawn(async move { global.run().await });
 
         Self {
             cell_handles,
             state,
             client,
+            confirmations,
+            rollback,
         }
     }
 
@@ -45,15 +56,20 @@ impl CkbSubscriptionClient {
 
         if indexer_tip.block_number > start {
             let scan_tip = ScanTip(Arc::new(ScanTipInner(AtomicPtr::new(Box::into_raw(
-                Box::new(start),
+                Box::new(ScanTipInfo::new(start)),
             )))));
 
             self.state
                 .cell_states
                 .insert(search_key.clone(), scan_tip.clone());
 
-            let mut cell_process =
-                CellProcess::new(search_key.clone(), scan_tip, self.client.clone(), RpcSubmit);
+            let mut cell_process = CellProcess::new(
+                search_key.clone(),
+                scan_tip,
+                self.client.clone(),
+                RpcSubmit::new(self.rollback.clone()),
+                self.confirmations,
+            );
 
             let handle = tokio::spawn(async move {
                 cell_process.run().await;
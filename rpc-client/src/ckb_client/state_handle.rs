@@ -1,11 +1,12 @@
 use std::{
-    fs::{copy, create_dir_all, remove_file, rename, File, OpenOptions},
-    io::Write,
+    fs::File,
     path::{Path, PathBuf},
     sync::Arc,
 };
 
+use common::traits::query::ChainRollback;
 use common::types::ckb_rpc_client::RpcSearchKey;
+use common::utils::fs::dump_json;
 
 use crate::ckb_client::{cell_process::CellProcess, ckb_rpc_client::CkbRpcClient, types::State};
 
@@ -62,6 +63,8 @@ impl GlobalState {
     pub fn spawn_cells(
         &self,
         client: CkbRpcClient,
+        confirmations: u64,
+        rollback: Option<Arc<dyn ChainRollback>>,
     ) -> Arc<dashmap::DashMap<RpcSearchKey, tokio::task::JoinHandle<()>>> {
         if !self.state.cell_states.is_empty() {
             for kv in self.state.cell_states.iter() {
@@ -69,7 +72,8 @@ impl GlobalState {
                     kv.key().clone(),
                     kv.value().clone(),
                     client.clone(),
-                    RpcSubmit,
+                    RpcSubmit::new(rollback.clone()),
+                    confirmations,
                 );
 
                 let handle = tokio::spawn(async move {
@@ -107,32 +111,6 @@ impl GlobalState {
     }
 
     fn dump_to_dir<P: AsRef<Path>>(&self, path: P) {
-        // create dir
-        create_dir_all(&path).unwrap();
-        // dump file to a temporary sub-directory
-        let tmp_dir = path.as_ref().join("tmp");
-        create_dir_all(&tmp_dir).unwrap();
-        let tmp_scan_state = tmp_dir.join("scan_state");
-        let mut file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .append(false)
-            .open(&tmp_scan_state)
-            .unwrap();
-        // empty file and dump the json string to it
-        file.set_len(0)
-            .and_then(|_| serde_json::to_string(&self.state).map_err(Into::into))
-            .and_then(|json_string| file.write_all(json_string.as_bytes()))
-            .and_then(|_| file.sync_all())
-            .unwrap();
-        move_file(tmp_scan_state, path.as_ref().join("scan_state")).unwrap();
+        dump_json(path.as_ref(), "scan_state", &self.state).unwrap();
     }
 }
-
-fn move_file<P: AsRef<Path>>(src: P, dst: P) -> Result<(), std::io::Error> {
-    if rename(&src, &dst).is_err() {
-        copy(&src, &dst)?;
-        remove_file(&src)?;
-    }
-    Ok(())
-}
@@ -6,7 +6,7 @@ use std::sync::{
 use ckb_jsonrpc_types::BlockNumber;
 use serde::{ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
 
-use common::types::ckb_rpc_client::{RpcSearchKey, TipState};
+use common::types::ckb_rpc_client::{RpcSearchKey, ScanTipInfo, TipState};
 
 #[derive(Clone)]
 pub struct State {
@@ -48,7 +48,7 @@ impl<'a> Deserialize<'a> for State {
     }
 }
 
-pub struct ScanTipInner(pub AtomicPtr<BlockNumber>);
+pub struct ScanTipInner(pub AtomicPtr<ScanTipInfo>);
 
 pub struct ScanTip(pub Arc<ScanTipInner>);
 
@@ -61,17 +61,17 @@ impl Drop for ScanTipInner {
 impl Clone for ScanTip {
     fn clone(&self) -> Self {
         ScanTip(Arc::new(ScanTipInner(AtomicPtr::new(Box::into_raw(
-            Box::new(*self.load()),
+            Box::new(self.load().clone()),
         )))))
     }
 }
 
 impl TipState for ScanTip {
-    fn load(&self) -> &BlockNumber {
+    fn load(&self) -> &ScanTipInfo {
         unsafe { &*self.0 .0.load(Ordering::Acquire) }
     }
 
-    fn update(&mut self, current: BlockNumber) {
+    fn update(&mut self, current: ScanTipInfo) {
         let raw = self
             .0
              .0
@@ -99,7 +99,18 @@ impl<'a> Deserialize<'a> for ScanTip {
     where
         D: Deserializer<'a>,
     {
-        let inner = BlockNumber::deserialize(deserializer)?;
+        // the state dumped by an old version only has the block number
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum ScanTipVisitor {
+            Info(ScanTipInfo),
+            Number(BlockNumber),
+        }
+
+        let inner = match ScanTipVisitor::deserialize(deserializer)? {
+            ScanTipVisitor::Info(info) => info,
+            ScanTipVisitor::Number(number) => ScanTipInfo::new(number),
+        };
 
         Ok(ScanTip(Arc::new(ScanTipInner(AtomicPtr::new(
             Box::into_raw(Box::new(inner)),
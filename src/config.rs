@@ -1,22 +1,126 @@
+use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::{fs, io};
 
-use common::types::tx_builder::NetworkType;
+use anyhow::{anyhow, Result};
+use ckb_types::H256;
+use common::types::tx_builder::{FeeParams, FeeStrategy, NetworkParams, NetworkType, TypeIds};
+use kicker::KickerConfig;
+use query::IndexerConfig;
 use serde::{de, Deserialize};
+use tx_builder::ckb::keystore::{KeystoreConfig, SignerKey};
+use zeroize::Zeroizing;
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct SparkConfig {
-    pub private_key:        String,
+    // plaintext, the keystore is preferred
+    #[serde(default)]
+    pub private_key:        Option<SignerKey>,
+    #[serde(default)]
+    pub keystore:           Option<KeystoreConfig>,
     pub rpc_listen_address: SocketAddr,
     pub rdb_url:            String,
     pub kvdb_path:          PathBuf,
-    pub network_type:       NetworkType,
+    // mainnet, testnet, devnet, custom:<name> or the name of a custom network
+    #[serde(rename = "network_type")]
+    pub network_name:       String,
+    // the custom networks by name
+    #[serde(default)]
+    pub networks:           HashMap<String, NetworkConfig>,
+    // the deployed scripts override the builtin ones of the network
+    #[serde(default)]
+    pub deployment:         Option<PathBuf>,
+    pub ckb_node_url:       String,
+    pub axon_node_url:      String,
+    // subscribe to the new axon headers if set
+    #[serde(default)]
+    pub axon_ws_url:        Option<String>,
+    // a fixed fee rate of 1000 shannons per KB if not set
+    #[serde(default)]
+    pub fee_strategy:       Option<FeeStrategy>,
+    // how many times a tx is balanced again if its change can not pay the fee
+    #[serde(default)]
+    pub balance_retry:      u32,
+    // the other type ids are read from the metadata cell if set
+    #[serde(default)]
+    pub metadata_type_id:   Option<H256>,
+    #[serde(default)]
+    pub type_ids:           Option<TypeIds>,
+    #[serde(default)]
+    pub kicker:             KickerConfig,
+    #[serde(default)]
+    pub indexer:            IndexerConfig,
+}
+
+/// A custom network, selected by `custom:<name>` or its name in `network_type`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct NetworkConfig {
+    #[serde(flatten)]
+    pub params:     NetworkParams,
+    // the manifest of all the scripts of the network
+    pub deployment: PathBuf,
+}
+
+impl SparkConfig {
+    /// The key of the kicker, decrypted from the keystore if it is set.
+    pub fn signer_key(&self) -> Result<SignerKey> {
+        match (&self.keystore, &self.private_key) {
+            (Some(keystore), _) => keystore.load(),
+            (None, Some(private_key)) => Ok(private_key.clone()),
+            (None, None) => Err(anyhow!("Neither keystore nor private_key is set")),
+        }
+    }
+
+    /// How the fee of the txs is paid.
+    pub fn fee(&self) -> FeeParams {
+        let default = FeeParams::default();
+        FeeParams {
+            strategy:      self.fee_strategy.clone().unwrap_or(default.strategy),
+            balance_retry: self.balance_retry,
+        }
+    }
+
+    /// The network selected by `network_type`. A name which is not a builtin
+    /// network selects the custom network of the same name in `networks`.
+    pub fn network_type(&self) -> Result<NetworkType, String> {
+        match self.network_name.parse() {
+            Ok(network) => Ok(network),
+            Err(_) if self.networks.contains_key(&self.network_name) => {
+                Ok(NetworkType::Custom(self.network_name.clone()))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The network with its parameters and deployment manifest.
+    pub fn network(&self) -> Result<(NetworkType, NetworkParams, Option<&Path>), String> {
+        let network_type = self.network_type()?;
+        match &network_type {
+            NetworkType::Custom(name) => {
+                let network = self
+                    .networks
+                    .get(name)
+                    .ok_or_else(|| format!("The network `{}` is not in the config", name))?;
+                Ok((
+                    network_type.clone(),
+                    network.params.clone(),
+                    Some(network.deployment.as_path()),
+                ))
+            }
+            network => Ok((
+                network.clone(),
+                NetworkParams::builtin(network).expect("builtin network"),
+                self.deployment.as_deref(),
+            )),
+        }
+    }
 }
 
 /// Parse a config from reader.
 pub fn parse_reader<R: io::Read, T: de::DeserializeOwned>(r: &mut R) -> Result<T, ParseError> {
-    let mut buf = String::new();
+    // the config may hold the private key
+    let mut buf = Zeroizing::new(String::new());
     r.read_to_string(&mut buf)?;
     Ok(toml::from_str(&buf)?)
 }
@@ -46,3 +150,49 @@ impl From<toml::de::Error> for ParseError {
         ParseError::Deserialize(error)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_config(network_type: &str) -> SparkConfig {
+        let config = format!(
+            r#"
+            rpc_listen_address = "127.0.0.1:8000"
+            rdb_url = ""
+            kvdb_path = "db"
+            network_type = "{}"
+            ckb_node_url = "http://127.0.0.1:8114"
+            axon_node_url = "http://127.0.0.1:8000"
+
+            [networks.my-net]
+            address_prefix = "ckt"
+            deployment = "my-net.toml"
+            confirmations = 6
+            "#,
+            network_type
+        );
+        toml::from_str(&config).unwrap()
+    }
+
+    #[test]
+    fn test_network() {
+        let (network, params, deployment) = parse_config("testnet").network().unwrap();
+        assert_eq!(network, NetworkType::Testnet);
+        assert_eq!(
+            params,
+            NetworkParams::builtin(&NetworkType::Testnet).unwrap()
+        );
+        assert_eq!(deployment, None);
+
+        for name in ["custom:my-net", "my-net"] {
+            let (network, params, deployment) = parse_config(name).network().unwrap();
+            assert_eq!(network, NetworkType::Custom("my-net".to_owned()));
+            assert_eq!(params.confirmations, 6);
+            assert_eq!(deployment, Some(Path::new("my-net.toml")));
+        }
+
+        assert!(parse_config("other-net").network().is_err());
+        assert!(parse_config("custom:other-net").network().is_err());
+    }
+}
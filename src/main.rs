@@ -1,24 +1,135 @@
 mod config;
 
-use std::{env, sync::Arc};
+use std::{env, fs, future::pending, sync::Arc};
 
 use api::{run_server, DefaultAPIAdapter};
+use common::traits::axon_rpc_client::AxonWsRpc;
+use common::types::tx_builder::ChainContext;
 use config::SparkConfig;
+use kicker::{import_snapshot, Kicker};
+use query::Indexer;
+use rpc_client::{axon_client::AxonRpcClient, ckb_client::ckb_rpc_client::CkbRpcClient};
 use storage::{SmtManager, TransactionHistory};
-use tx_builder::set_network_type;
+use tx_builder::ckb::{
+    registry::{network_scripts, TypeIdRegistry},
+    smt_rebuild::SmtRebuilder,
+};
 
 #[tokio::main]
 async fn main() {
     let args = env::args().nth(1).expect("Missing env variable");
     let config: SparkConfig = config::parse_file(args).expect("Failed to parse config file");
-    set_network_type(config.network_type);
+    let (network, params, deployment) = config.network().expect("Invalid network");
+    let scripts =
+        network_scripts(&network, deployment).expect("Failed to load the scripts of the network");
+    let ckb_rpc = CkbRpcClient::new(&config.ckb_node_url);
+    let type_ids = match (&config.metadata_type_id, &config.type_ids) {
+        (Some(metadata_type_id), _) => {
+            TypeIdRegistry::discover(&ckb_rpc, &scripts, metadata_type_id)
+                .await
+                .expect("Failed to discover the type ids from the metadata cell")
+                .into_type_ids()
+        }
+        (None, Some(type_ids)) => type_ids.clone(),
+        (None, None) => panic!("Either metadata_type_id or type_ids should be configured"),
+    };
+    let ctx = ChainContext {
+        network,
+        params,
+        scripts: Arc::new(scripts),
+        type_ids,
+        fee: config.fee(),
+    };
 
     let rdb = Arc::new(TransactionHistory::new(&config.rdb_url).await);
-    let kvdb = Arc::new(SmtManager::new(&config.kvdb_path));
-    let api_adapter = Arc::new(DefaultAPIAdapter::new(rdb, kvdb));
-    let _handle = run_server(api_adapter, config.rpc_listen_address)
+    let axon_rpc = AxonRpcClient::new(
+        &config.axon_node_url,
+        config.axon_ws_url.as_deref().unwrap_or_default(),
+    )
+    .await;
+    let kvdb = Arc::new(load_smt(&config, &ctx, &ckb_rpc).await);
+    let api_adapter = Arc::new(
+        DefaultAPIAdapter::new(
+            Arc::clone(&rdb),
+            Arc::clone(&kvdb),
+            ckb_rpc.clone(),
+            axon_rpc.clone(),
+            ctx.clone(),
+        )
+        .with_scratch_dir(config.kvdb_path.with_extension("scratch")),
+    );
+    if config.axon_ws_url.is_some() {
+        api_adapter.follow_headers(axon_rpc.sub_axon_header());
+    }
+    let server = run_server(api_adapter, config.rpc_listen_address)
         .await
-        .unwrap();
+        .expect("Failed to start the rpc server");
 
-    println!("Hello, world!");
+    let indexer = config.indexer.enable.then(|| {
+        let indexer = Indexer::new(ckb_rpc.clone(), rdb, ctx.clone(), config.indexer.clone());
+        tokio::spawn(indexer.run())
+    });
+
+    let kicker = async {
+        if !config.kicker.enable {
+            return pending().await;
+        }
+
+        let kicker_key = config.signer_key().expect("Failed to load the kicker key");
+        let headers = config
+            .axon_ws_url
+            .as_ref()
+            .map(|_| axon_rpc.sub_axon_header());
+        let signer = config
+            .kicker
+            .signer
+            .build(kicker_key)
+            .expect("Failed to build the kicker signer");
+        let mut kicker = Kicker::new(
+            ckb_rpc,
+            axon_rpc,
+            (*kvdb).clone(),
+            signer,
+            ctx,
+            config.kicker.clone(),
+        );
+        if let Some(headers) = headers {
+            kicker = kicker.with_headers(headers);
+        }
+        kicker.run().await
+    };
+
+    // the services run until one of them stops
+    tokio::select! {
+        _ = server.stopped() => panic!("The rpc server stopped"),
+        res = async {
+            match indexer {
+                Some(handle) => handle.await,
+                None => pending().await,
+            }
+        } => panic!("The indexer stopped: {:?}", res),
+        _ = kicker => panic!("The kicker stopped"),
+    }
+}
+
+async fn load_smt(config: &SparkConfig, ctx: &ChainContext, ckb_rpc: &CkbRpcClient) -> SmtManager {
+    if config.kvdb_path.exists() {
+        return SmtManager::new(&config.kvdb_path);
+    }
+
+    let smt = SmtManager::new(&config.kvdb_path);
+    let res = match &config.kicker.import_snapshot {
+        Some(snapshot) => import_snapshot(ckb_rpc, ctx, &smt, snapshot)
+            .await
+            .map(|_| ()),
+        None if config.kicker.rebuild_smt => SmtRebuilder::new(ckb_rpc, ctx, &smt).rebuild().await,
+        None => return smt,
+    };
+
+    if let Err(e) = res {
+        drop(smt);
+        fs::remove_dir_all(&config.kvdb_path).expect("Failed to remove the smt database");
+        panic!("Failed to recover the smt database: {}", e);
+    }
+    smt
 }
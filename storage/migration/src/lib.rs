@@ -1,12 +1,24 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20220101_000001_create_table;
+mod m20230701_000002_decimal_amount;
+mod m20230702_000003_normalized_tables;
+mod m20230703_000004_output_index;
+mod m20230704_000005_address_amount;
+mod m20230705_000006_block_number;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20220101_000001_create_table::Migration)]
+        vec![
+            Box::new(m20220101_000001_create_table::Migration),
+            Box::new(m20230701_000002_decimal_amount::Migration),
+            Box::new(m20230702_000003_normalized_tables::Migration),
+            Box::new(m20230703_000004_output_index::Migration),
+            Box::new(m20230704_000005_address_amount::Migration),
+            Box::new(m20230705_000006_block_number::Migration),
+        ]
     }
 }
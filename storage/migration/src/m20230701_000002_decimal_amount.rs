@@ -0,0 +1,146 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::ConnectionTrait;
+
+// u128::MAX has 39 decimal digits
+const AMOUNT_LEN: u32 = 39;
+
+/// The amounts are u128 tokens, which overflow the integer columns. They are
+/// migrated to zero padded decimal strings. SQLite can not alter the type of a
+/// column, so the table is rebuilt.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(transaction_table(Transaction::TableV2, |col| {
+                col.string_len(AMOUNT_LEN).not_null()
+            }))
+            .await?;
+
+        let amount = |col: &str| format!(r#"printf('%0{}d', "{}")"#, AMOUNT_LEN, col);
+        copy_rows(manager, &amount).await?;
+
+        replace_table(manager).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(transaction_table(Transaction::TableV2, |col| {
+                col.integer().not_null()
+            }))
+            .await?;
+
+        let amount = |col: &str| format!(r#"CAST("{}" AS INTEGER)"#, col);
+        copy_rows(manager, &amount).await?;
+
+        replace_table(manager).await
+    }
+}
+
+fn transaction_table(
+    table: Transaction,
+    amount_col: impl Fn(&mut ColumnDef) -> &mut ColumnDef,
+) -> TableCreateStatement {
+    Table::create()
+        .table(table)
+        .col(
+            ColumnDef::new(Transaction::Id)
+                .integer()
+                .not_null()
+                .auto_increment()
+                .primary_key(),
+        )
+        .col(
+            ColumnDef::new(Transaction::Address)
+                .string_len(42)
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(Transaction::Timestamp)
+                .big_integer()
+                .not_null(),
+        )
+        .col(ColumnDef::new(Transaction::Operation).integer().not_null())
+        .col(ColumnDef::new(Transaction::Event).integer().not_null())
+        .col(
+            ColumnDef::new(Transaction::TxHash)
+                .string_len(66)
+                .not_null(),
+        )
+        .col(amount_col(&mut ColumnDef::new(Transaction::TotalAmount)))
+        .col(amount_col(&mut ColumnDef::new(Transaction::StakeAmount)))
+        .col(amount_col(&mut ColumnDef::new(Transaction::DelegateAmount)))
+        .col(amount_col(&mut ColumnDef::new(
+            Transaction::WithdrawableAmount,
+        )))
+        .col(
+            ColumnDef::new(Transaction::StakeRate)
+                .string_len(10)
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(Transaction::DelegateRate)
+                .string_len(10)
+                .not_null(),
+        )
+        .col(ColumnDef::new(Transaction::Epoch).integer().not_null())
+        .col(ColumnDef::new(Transaction::Status).integer().not_null())
+        .to_owned()
+}
+
+// Copies the rows into the new table, the amount columns are converted by the
+// sql expression.
+async fn copy_rows(
+    manager: &SchemaManager<'_>,
+    amount: &dyn Fn(&str) -> String,
+) -> Result<(), DbErr> {
+    let columns = "\"id\", \"address\", \"timestamp\", \"operation\", \"event\", \"tx_hash\"";
+    let rest = "\"stake_rate\", \"delegate_rate\", \"epoch\", \"status\"";
+    let sql = format!(
+        "INSERT INTO \"transaction_v2\" SELECT {}, {}, {}, {}, {}, {} FROM \"transaction\"",
+        columns,
+        amount("total_amount"),
+        amount("stake_amount"),
+        amount("delegate_amount"),
+        amount("withdrawable_amount"),
+        rest,
+    );
+    manager.get_connection().execute_unprepared(&sql).await?;
+    Ok(())
+}
+
+async fn replace_table(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .drop_table(Table::drop().table(Transaction::Table).to_owned())
+        .await?;
+    manager
+        .rename_table(
+            Table::rename()
+                .table(Transaction::TableV2, Transaction::Table)
+                .to_owned(),
+        )
+        .await
+}
+
+#[derive(Iden)]
+enum Transaction {
+    Table,
+    #[iden = "transaction_v2"]
+    TableV2,
+    Id,
+    Address,
+    Timestamp,
+    Operation,
+    Event,
+    TxHash,
+    TotalAmount,
+    StakeAmount,
+    DelegateAmount,
+    WithdrawableAmount,
+    StakeRate,
+    DelegateRate,
+    Epoch,
+    Status,
+}
@@ -0,0 +1,335 @@
+use sea_orm_migration::prelude::*;
+
+const ADDRESS_LEN: u32 = 42;
+const AMOUNT_LEN: u32 = 39;
+const TX_HASH_LEN: u32 = 66;
+
+/// The stakers, the delegations of every epoch, the epoch snapshots, the
+/// reward claims and the withdraw unlocks. The amounts are zero padded decimal
+/// strings, the same as the transaction table.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Staker::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Staker::Address)
+                            .string_len(ADDRESS_LEN)
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Staker::CommissionRate).integer().not_null())
+                    .col(
+                        ColumnDef::new(Staker::MaximumDelegators)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Staker::Threshold)
+                            .string_len(AMOUNT_LEN)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Staker::Epoch).big_integer().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Delegation::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Delegation::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Delegation::Staker)
+                            .string_len(ADDRESS_LEN)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Delegation::Delegator)
+                            .string_len(ADDRESS_LEN)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Delegation::Epoch).big_integer().not_null())
+                    .col(
+                        ColumnDef::new(Delegation::Amount)
+                            .string_len(AMOUNT_LEN)
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-delegation-staker")
+                            .from(Delegation::Table, Delegation::Staker)
+                            .to(Staker::Table, Staker::Address)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-delegation-staker-delegator-epoch")
+                    .table(Delegation::Table)
+                    .col(Delegation::Staker)
+                    .col(Delegation::Delegator)
+                    .col(Delegation::Epoch)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-delegation-delegator-epoch")
+                    .table(Delegation::Table)
+                    .col(Delegation::Delegator)
+                    .col(Delegation::Epoch)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(EpochSnapshot::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(EpochSnapshot::Epoch)
+                            .big_integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(EpochSnapshot::TotalStakeAmount)
+                            .string_len(AMOUNT_LEN)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(EpochSnapshot::TotalDelegateAmount)
+                            .string_len(AMOUNT_LEN)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(EpochSnapshot::StakerCount)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(EpochSnapshot::Timestamp)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(RewardClaim::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RewardClaim::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(RewardClaim::Address)
+                            .string_len(ADDRESS_LEN)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(RewardClaim::Epoch).big_integer().not_null())
+                    .col(
+                        ColumnDef::new(RewardClaim::Amount)
+                            .string_len(AMOUNT_LEN)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(RewardClaim::TxHash)
+                            .string_len(TX_HASH_LEN)
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-reward_claim-epoch")
+                            .from(RewardClaim::Table, RewardClaim::Epoch)
+                            .to(EpochSnapshot::Table, EpochSnapshot::Epoch),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-reward_claim-address-epoch")
+                    .table(RewardClaim::Table)
+                    .col(RewardClaim::Address)
+                    .col(RewardClaim::Epoch)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-reward_claim-tx_hash")
+                    .table(RewardClaim::Table)
+                    .col(RewardClaim::TxHash)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(WithdrawUnlock::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(WithdrawUnlock::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(WithdrawUnlock::Address)
+                            .string_len(ADDRESS_LEN)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WithdrawUnlock::Amount)
+                            .string_len(AMOUNT_LEN)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WithdrawUnlock::Epoch)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WithdrawUnlock::UnlockEpoch)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WithdrawUnlock::TxHash)
+                            .string_len(TX_HASH_LEN)
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-withdraw_unlock-epoch")
+                            .from(WithdrawUnlock::Table, WithdrawUnlock::Epoch)
+                            .to(EpochSnapshot::Table, EpochSnapshot::Epoch),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-withdraw_unlock-address-unlock_epoch")
+                    .table(WithdrawUnlock::Table)
+                    .col(WithdrawUnlock::Address)
+                    .col(WithdrawUnlock::UnlockEpoch)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-withdraw_unlock-tx_hash-unlock_epoch")
+                    .table(WithdrawUnlock::Table)
+                    .col(WithdrawUnlock::TxHash)
+                    .col(WithdrawUnlock::UnlockEpoch)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // the referencing tables are dropped first
+        manager
+            .drop_table(Table::drop().table(WithdrawUnlock::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(RewardClaim::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(EpochSnapshot::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Delegation::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Staker::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Staker {
+    Table,
+    Address,
+    CommissionRate,
+    MaximumDelegators,
+    Threshold,
+    Epoch,
+}
+
+#[derive(Iden)]
+enum Delegation {
+    Table,
+    Id,
+    Staker,
+    Delegator,
+    Epoch,
+    Amount,
+}
+
+#[derive(Iden)]
+enum EpochSnapshot {
+    Table,
+    Epoch,
+    TotalStakeAmount,
+    TotalDelegateAmount,
+    StakerCount,
+    Timestamp,
+}
+
+#[derive(Iden)]
+enum RewardClaim {
+    Table,
+    Id,
+    Address,
+    Epoch,
+    Amount,
+    TxHash,
+}
+
+#[derive(Iden)]
+enum WithdrawUnlock {
+    Table,
+    Id,
+    Address,
+    Amount,
+    Epoch,
+    UnlockEpoch,
+    TxHash,
+}
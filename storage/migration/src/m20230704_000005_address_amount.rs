@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, Statement};
+
+const ADDRESS_LEN: u32 = 42;
+const AMOUNT_LEN: u32 = 39;
+// the records of the failed txs are not counted
+const FAILED_STATUS: u32 = 2;
+const REDEEM_EVENT: u32 = 1;
+
+/// The amounts added and redeemed by every address in every operation, kept
+/// along the transaction records. The net amount is a zero padded decimal
+/// string too, so the top addresses are ordered and paged by the database.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AddressAmount::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AddressAmount::Address)
+                            .string_len(ADDRESS_LEN)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AddressAmount::Operation)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AddressAmount::Added)
+                            .string_len(AMOUNT_LEN)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AddressAmount::Redeemed)
+                            .string_len(AMOUNT_LEN)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AddressAmount::Amount)
+                            .string_len(AMOUNT_LEN)
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(AddressAmount::Address)
+                            .col(AddressAmount::Operation),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-address-amount-operation-amount")
+                    .table(AddressAmount::Table)
+                    .col(AddressAmount::Operation)
+                    .col(AddressAmount::Amount)
+                    .col(AddressAmount::Address)
+                    .to_owned(),
+            )
+            .await?;
+
+        fill_amounts(manager).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AddressAmount::Table).to_owned())
+            .await
+    }
+}
+
+// The amounts of the existing records are summed once here, SQL can not sum
+// the amount strings without losing the precision of u128.
+async fn fill_amounts(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+    let backend = manager.get_database_backend();
+
+    let sql = format!(
+        "SELECT \"address\", \"operation\", \"event\", \"total_amount\" FROM \"transaction\" \
+         WHERE \"status\" <> {}",
+        FAILED_STATUS
+    );
+    let rows = db.query_all(Statement::from_string(backend, sql)).await?;
+
+    let mut sums: HashMap<(String, u32), (u128, u128)> = HashMap::new();
+    for row in rows {
+        let address: String = row.try_get("", "address")?;
+        let operation: u32 = row.try_get("", "operation")?;
+        let event: u32 = row.try_get("", "event")?;
+        let amount: String = row.try_get("", "total_amount")?;
+        let amount: u128 = amount
+            .parse()
+            .map_err(|_| DbErr::Custom(format!("invalid amount {:?}", amount)))?;
+
+        let (added, redeemed) = sums.entry((address, operation)).or_default();
+        if event == REDEEM_EVENT {
+            *redeemed += amount;
+        } else {
+            *added += amount;
+        }
+    }
+
+    for ((address, operation), (added, redeemed)) in sums {
+        let insert = Query::insert()
+            .into_table(AddressAmount::Table)
+            .columns([
+                AddressAmount::Address,
+                AddressAmount::Operation,
+                AddressAmount::Added,
+                AddressAmount::Redeemed,
+                AddressAmount::Amount,
+            ])
+            .values_panic([
+                address.into(),
+                operation.into(),
+                encode(added).into(),
+                encode(redeemed).into(),
+                encode(added.saturating_sub(redeemed)).into(),
+            ])
+            .to_owned();
+        db.execute(backend.build(&insert)).await?;
+    }
+
+    Ok(())
+}
+
+fn encode(amount: u128) -> String {
+    format!("{:0width$}", amount, width = AMOUNT_LEN as usize)
+}
+
+#[derive(Iden)]
+enum AddressAmount {
+    Table,
+    Address,
+    Operation,
+    Added,
+    Redeemed,
+    Amount,
+}
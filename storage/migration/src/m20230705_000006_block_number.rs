@@ -0,0 +1,66 @@
+use sea_orm_migration::prelude::*;
+
+/// The indexed rows keep the block of the output they are derived from, so
+/// that the rows of the blocks reverted by a reorg are rolled back. The rows
+/// written before are left without a block, the same as the records of the txs
+/// sent by spark until they are indexed.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite only adds a column per alter statement
+        for table in tables() {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .add_column(ColumnDef::new(BlockNumber).big_integer().null())
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in tables() {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .drop_column(BlockNumber)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+fn tables() -> [DynIden; 3] {
+    [
+        Transaction::Table.into_iden(),
+        Staker::Table.into_iden(),
+        Delegation::Table.into_iden(),
+    ]
+}
+
+#[derive(Iden)]
+enum Transaction {
+    Table,
+}
+
+#[derive(Iden)]
+enum Staker {
+    Table,
+}
+
+#[derive(Iden)]
+enum Delegation {
+    Table,
+}
+
+#[derive(Iden)]
+struct BlockNumber;
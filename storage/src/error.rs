@@ -10,4 +10,13 @@ pub enum StorageError {
 
     #[error("Sql cursor error {0}")]
     SqlCursorError(DbErr),
+
+    #[error("Invalid SMT snapshot: {0}")]
+    InvalidSnapshot(String),
+
+    #[error("Invalid page token: {0}")]
+    InvalidPageToken(String),
+
+    #[error("SMT database is not empty")]
+    NonEmptySmtDatabase,
 }
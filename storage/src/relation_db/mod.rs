@@ -1,15 +1,26 @@
+use std::fmt;
+
 use crate::error::StorageError;
 use anyhow::Result;
 use async_trait::async_trait;
 use common::traits::query::TransactionStorage;
 use common::types::{
-    relation_db::transaction::{self, Model},
-    smt::Address,
+    api::{AddressAmount, HistoryEvent, OperationStatus, Page},
+    relation_db::{
+        address_amount, delegation, epoch_snapshot, reward_claim, staker,
+        transaction::{self, decode_amount, encode_amount, Model},
+        withdraw_unlock,
+    },
+    smt::{Address, Epoch},
 };
+use common::utils::codec::{hex_decode, hex_encode};
 use migration::{Migrator, MigratorTrait};
 pub use sea_orm::Set;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, CursorTrait, Database, DbConn, EntityTrait, QueryFilter,
+    sea_query::{Expr, OnConflict},
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, CursorTrait, Database, DbConn,
+    EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Select, TransactionTrait,
+    Value,
 };
 
 pub async fn establish_connection(database_url: &str) -> Result<DbConn> {
@@ -28,12 +39,161 @@ impl TransactionHistory {
         let db = establish_connection(database_url).await.unwrap();
         Self { db }
     }
+
+    // Pages the records from the largest sort key, the id breaks the ties. The
+    // next page starts after the last record of this page, so it is stable
+    // while new records are inserted.
+    async fn paginate(
+        &self,
+        query: Select<transaction::Entity>,
+        sort: SortKey,
+        page_token: Option<String>,
+        limit: u64,
+    ) -> Result<Page<Model>> {
+        let total = query.clone().count(&self.db).await?;
+
+        let query = match page_token {
+            Some(token) => {
+                let (key, id) = decode_page_token(&token)?;
+                let key = sort.parse(key)?;
+                query.filter(
+                    Condition::any().add(sort.column().lt(key.clone())).add(
+                        Condition::all()
+                            .add(sort.column().eq(key))
+                            .add(transaction::Column::Id.lt(id)),
+                    ),
+                )
+            }
+            None => query,
+        };
+
+        // one more record tells whether there is a next page
+        let mut items = query
+            .order_by_desc(sort.column())
+            .order_by_desc(transaction::Column::Id)
+            .limit(limit + 1)
+            .all(&self.db)
+            .await?;
+        let next_page_token = if items.len() as u64 > limit {
+            items.truncate(limit as usize);
+            items
+                .last()
+                .map(|model| encode_page_token(&sort.key_of(model), model.id))
+        } else {
+            None
+        };
+
+        Ok(Page {
+            items,
+            total,
+            next_page_token,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum SortKey {
+    Timestamp,
+}
+
+impl SortKey {
+    fn column(self) -> transaction::Column {
+        match self {
+            SortKey::Timestamp => transaction::Column::Timestamp,
+        }
+    }
+
+    fn key_of(self, model: &Model) -> String {
+        match self {
+            SortKey::Timestamp => model.timestamp.to_string(),
+        }
+    }
+
+    fn parse(self, key: String) -> Result<Value> {
+        match self {
+            SortKey::Timestamp => key
+                .parse::<u32>()
+                .map(Value::from)
+                .map_err(|_| StorageError::InvalidPageToken(key).into()),
+        }
+    }
+}
+
+// The page token is the hex of the sort key and the id of the last record, or
+// of the amount and the address of the last top address.
+fn encode_page_token(key: &str, id: impl fmt::Display) -> String {
+    hex_encode(format!("{}:{}", key, id))
+}
+
+fn decode_page_token(token: &str) -> Result<(String, u32)> {
+    let invalid = || StorageError::InvalidPageToken(token.to_string());
+    let raw = hex_decode(token).map_err(|_| invalid())?;
+    let raw = String::from_utf8(raw).map_err(|_| invalid())?;
+    let (key, id) = raw.rsplit_once(':').ok_or_else(invalid)?;
+    let id = id.parse().map_err(|_| invalid())?;
+    Ok((key.to_string(), id))
+}
+
+fn decode_amount_page_token(token: &str) -> Result<(u128, String)> {
+    let invalid = || StorageError::InvalidPageToken(token.to_string());
+    let raw = hex_decode(token).map_err(|_| invalid())?;
+    let raw = String::from_utf8(raw).map_err(|_| invalid())?;
+    let (amount, address) = raw.split_once(':').ok_or_else(invalid)?;
+    let amount = amount.parse().map_err(|_| invalid())?;
+    Ok((amount, address.to_string()))
+}
+
+// The amounts of the addresses are kept along the records, so that the top
+// addresses are paged by the database. The records of the failed txs are not
+// counted, a record is added when it is written or when its tx is no longer
+// failed, and it is removed the other way round.
+async fn count_record<C: ConnectionTrait>(conn: &C, record: &Model, add: bool) -> Result<()> {
+    if record.status == OperationStatus::Failed as u32 {
+        return Ok(());
+    }
+
+    let row = address_amount::Entity::find_by_id((record.address.clone(), record.operation))
+        .one(conn)
+        .await?;
+    let (mut added, mut redeemed) = match row.as_ref() {
+        Some(row) => (decode_amount(&row.added)?, decode_amount(&row.redeemed)?),
+        None => (0, 0),
+    };
+
+    let amount = decode_amount(&record.total_amount)?;
+    let sum = if record.event == HistoryEvent::Redeem as u32 {
+        &mut redeemed
+    } else {
+        &mut added
+    };
+    *sum = if add {
+        *sum + amount
+    } else {
+        sum.saturating_sub(amount)
+    };
+
+    let model = address_amount::ActiveModel {
+        address:   Set(record.address.clone()),
+        operation: Set(record.operation),
+        added:     Set(encode_amount(added)),
+        redeemed:  Set(encode_amount(redeemed)),
+        amount:    Set(encode_amount(added.saturating_sub(redeemed))),
+    };
+    if row.is_some() {
+        model.update(conn).await?;
+    } else {
+        model.insert(conn).await?;
+    }
+    Ok(())
 }
 
 #[async_trait]
 impl TransactionStorage for TransactionHistory {
-    async fn insert(&mut self, tx_record: transaction::ActiveModel) -> Result<()> {
-        let tx_record = tx_record.insert(&self.db).await?;
+    async fn insert(&self, tx_record: transaction::ActiveModel) -> Result<()> {
+        let txn = self.db.begin().await?;
+        let tx_record = tx_record.insert(&txn).await?;
+        count_record(&txn, &tx_record, true).await?;
+        txn.commit().await?;
         log::info!(
             "Transaction created with address: {}, timestamp: {}, tx_hash: {}",
             tx_record.address,
@@ -43,82 +203,449 @@ impl TransactionStorage for TransactionHistory {
         Ok(())
     }
 
+    async fn update_status(&self, tx_hash: String, status: u32) -> Result<()> {
+        let txn = self.db.begin().await?;
+
+        // the amounts change when the tx fails or no longer fails
+        let failed = OperationStatus::Failed as u32;
+        let records = transaction::Entity::find()
+            .filter(transaction::Column::TxHash.eq(tx_hash.clone()))
+            .all(&txn)
+            .await?;
+        for record in records {
+            if (record.status == failed) != (status == failed) {
+                count_record(&txn, &record, false).await?;
+                count_record(&txn, &Model { status, ..record }, true).await?;
+            }
+        }
+
+        let res = transaction::Entity::update_many()
+            .col_expr(transaction::Column::Status, Expr::value(status))
+            .filter(transaction::Column::TxHash.eq(tx_hash.clone()))
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+        log::info!(
+            "Transaction status updated with tx_hash: {}, status: {}, rows: {}",
+            tx_hash,
+            status,
+            res.rows_affected
+        );
+        Ok(())
+    }
+
+    async fn save_indexed_records(
+        &self,
+        tx_hash: String,
+        records: Vec<transaction::ActiveModel>,
+    ) -> Result<()> {
+        let txn = self.db.begin().await?;
+
+        // the pending records are replaced by the indexed ones
+        let pending = transaction::Entity::find()
+            .filter(transaction::Column::TxHash.eq(tx_hash.clone()))
+            .filter(transaction::Column::OutputIndex.is_null())
+            .all(&txn)
+            .await?;
+        for record in pending.iter() {
+            count_record(&txn, record, false).await?;
+        }
+        transaction::Entity::delete_many()
+            .filter(transaction::Column::TxHash.eq(tx_hash.clone()))
+            .filter(transaction::Column::OutputIndex.is_null())
+            .exec(&txn)
+            .await?;
+
+        let count = records.len();
+        for record in records {
+            let find_indexed = transaction::Entity::find()
+                .filter(transaction::Column::TxHash.eq(tx_hash.clone()))
+                .filter(
+                    transaction::Column::OutputIndex.eq(record
+                        .output_index
+                        .clone()
+                        .take()
+                        .flatten()),
+                )
+                .filter(transaction::Column::ItemIndex.eq(record.item_index.clone().take()));
+
+            // a record indexed again is counted once
+            if let Some(indexed) = find_indexed.clone().one(&txn).await? {
+                count_record(&txn, &indexed, false).await?;
+            }
+
+            transaction::Entity::insert(record)
+                .on_conflict(
+                    OnConflict::columns([
+                        transaction::Column::TxHash,
+                        transaction::Column::OutputIndex,
+                        transaction::Column::ItemIndex,
+                    ])
+                    .update_columns([
+                        transaction::Column::Timestamp,
+                        transaction::Column::Epoch,
+                        transaction::Column::Status,
+                        transaction::Column::BlockNumber,
+                    ])
+                    .to_owned(),
+                )
+                .exec(&txn)
+                .await?;
+
+            if let Some(indexed) = find_indexed.one(&txn).await? {
+                count_record(&txn, &indexed, true).await?;
+            }
+        }
+
+        txn.commit().await?;
+        log::info!(
+            "Transaction indexed with tx_hash: {}, records: {}",
+            tx_hash,
+            count
+        );
+        Ok(())
+    }
+
+    async fn get_records_by_tx_hash(&self, tx_hash: String) -> Result<Vec<Model>> {
+        let records = transaction::Entity::find()
+            .filter(transaction::Column::TxHash.eq(tx_hash))
+            .all(&self.db)
+            .await?;
+        Ok(records)
+    }
+
     async fn get_records_by_address(
         &self,
         addr: Address,
-        offset: u64,
+        page_token: Option<String>,
         limit: u64,
-    ) -> Result<Vec<Model>> {
-        let mut cursor = transaction::Entity::find()
-            .filter(transaction::Column::Address.eq(addr.to_string()))
-            .cursor_by(transaction::Column::Id);
-        cursor.after(offset).before(offset + limit);
-        match cursor.all(&self.db).await {
-            Ok(records) => Ok(records),
-            Err(e) => Err(StorageError::SqlCursorError(e).into()),
-        }
+    ) -> Result<Page<Model>> {
+        let query =
+            transaction::Entity::find().filter(transaction::Column::Address.eq(addr.to_string()));
+        self.paginate(query, SortKey::Timestamp, page_token, limit)
+            .await
     }
 
     async fn get_operation_history(
         &self,
         addr: Address,
         operation: u32,
-        offset: u64,
+        event: Option<u32>,
+        page_token: Option<String>,
         limit: u64,
-    ) -> Result<Vec<Model>> {
-        let mut cursor = transaction::Entity::find()
+    ) -> Result<Page<Model>> {
+        let mut query = transaction::Entity::find()
             .filter(transaction::Column::Address.eq(addr.to_string()))
-            .filter(transaction::Column::Operation.eq(operation))
-            .cursor_by(transaction::Column::Id);
-        cursor.after(offset).before(offset + limit);
-        match cursor.all(&self.db).await {
-            Ok(records) => Ok(records),
-            Err(e) => Err(StorageError::SqlCursorError(e).into()),
+            .filter(transaction::Column::Operation.eq(operation));
+        if let Some(event) = event {
+            query = query.filter(transaction::Column::Event.eq(event));
         }
+        self.paginate(query, SortKey::Timestamp, page_token, limit)
+            .await
     }
 
     async fn get_stake_amount_by_epoch(
         &self,
         operation: u32,
-        offset: u64,
+        page_token: Option<String>,
         limit: u64,
-    ) -> Result<Vec<Model>> {
+    ) -> Result<Page<Model>> {
+        let query =
+            transaction::Entity::find().filter(transaction::Column::Operation.eq(operation));
+        self.paginate(query, SortKey::Timestamp, page_token, limit)
+            .await
+    }
+
+    async fn get_top_stake_address(
+        &self,
+        operation: u32,
+        page_token: Option<String>,
+        limit: u64,
+    ) -> Result<Page<AddressAmount>> {
+        let query =
+            address_amount::Entity::find().filter(address_amount::Column::Operation.eq(operation));
+        let total = query.clone().count(&self.db).await?;
+
+        // from the largest amount, the addresses break the ties
+        let query = match page_token {
+            Some(token) => {
+                let (amount, address) = decode_amount_page_token(&token)?;
+                let amount = encode_amount(amount);
+                query.filter(
+                    Condition::any()
+                        .add(address_amount::Column::Amount.lt(amount.clone()))
+                        .add(
+                            Condition::all()
+                                .add(address_amount::Column::Amount.eq(amount))
+                                .add(address_amount::Column::Address.lt(address)),
+                        ),
+                )
+            }
+            None => query,
+        };
+
+        // one more address tells whether there is a next page
+        let mut items = query
+            .order_by_desc(address_amount::Column::Amount)
+            .order_by_desc(address_amount::Column::Address)
+            .limit(limit + 1)
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|row| {
+                Ok(AddressAmount {
+                    address: row.address,
+                    amount:  decode_amount(&row.amount)?.to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let next_page_token = if items.len() as u64 > limit {
+            items.truncate(limit as usize);
+            items
+                .last()
+                .map(|item| encode_page_token(&item.amount, &item.address))
+        } else {
+            None
+        };
+
+        Ok(Page {
+            items,
+            total,
+            next_page_token,
+        })
+    }
+
+    async fn get_address_state(&self, addr: Address) -> Result<Vec<Model>> {
         let mut cursor = transaction::Entity::find()
-            .filter(transaction::Column::Operation.eq(operation))
+            .filter(transaction::Column::Address.eq(addr.to_string()))
             .cursor_by(transaction::Column::Id);
-        cursor.after(offset).before(offset + limit);
         match cursor.all(&self.db).await {
             Ok(records) => Ok(records),
             Err(e) => Err(StorageError::SqlCursorError(e).into()),
         }
     }
 
-    async fn get_top_stake_address(&self, operation: u32) -> Result<Vec<Model>> {
-        let mut cursor = transaction::Entity::find()
-            .filter(transaction::Column::Operation.eq(operation))
-            .cursor_by(transaction::Column::TotalAmount);
+    async fn get_latest_stake_transactions(
+        &self,
+        page_token: Option<String>,
+        limit: u64,
+    ) -> Result<Page<Model>> {
+        self.paginate(
+            transaction::Entity::find(),
+            SortKey::Timestamp,
+            page_token,
+            limit,
+        )
+        .await
+    }
+
+    async fn upsert_staker(&self, staker: staker::ActiveModel) -> Result<()> {
+        staker::Entity::insert(staker)
+            .on_conflict(
+                OnConflict::column(staker::Column::Address)
+                    .update_columns([
+                        staker::Column::CommissionRate,
+                        staker::Column::MaximumDelegators,
+                        staker::Column::Threshold,
+                        staker::Column::Epoch,
+                        staker::Column::BlockNumber,
+                    ])
+                    .to_owned(),
+            )
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_staker(&self, addr: Address) -> Result<Option<staker::Model>> {
+        let staker = staker::Entity::find_by_id(addr.to_string())
+            .one(&self.db)
+            .await?;
+        Ok(staker)
+    }
+
+    async fn upsert_delegation(&self, delegation: delegation::ActiveModel) -> Result<()> {
+        delegation::Entity::insert(delegation)
+            .on_conflict(
+                OnConflict::columns([
+                    delegation::Column::Staker,
+                    delegation::Column::Delegator,
+                    delegation::Column::Epoch,
+                ])
+                .update_columns([delegation::Column::Amount, delegation::Column::BlockNumber])
+                .to_owned(),
+            )
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_delegations_by_staker(
+        &self,
+        staker: Address,
+        epoch: Epoch,
+    ) -> Result<Vec<delegation::Model>> {
+        let records = delegation::Entity::find()
+            .filter(delegation::Column::Staker.eq(staker.to_string()))
+            .filter(delegation::Column::Epoch.eq(epoch))
+            .all(&self.db)
+            .await?;
+        Ok(records)
+    }
+
+    async fn get_delegations_by_delegator(
+        &self,
+        delegator: Address,
+        epoch: Epoch,
+    ) -> Result<Vec<delegation::Model>> {
+        let records = delegation::Entity::find()
+            .filter(delegation::Column::Delegator.eq(delegator.to_string()))
+            .filter(delegation::Column::Epoch.eq(epoch))
+            .all(&self.db)
+            .await?;
+        Ok(records)
+    }
+
+    async fn get_delegation_before(
+        &self,
+        staker: Address,
+        delegator: Address,
+        epoch: Epoch,
+    ) -> Result<Option<delegation::Model>> {
+        let record = delegation::Entity::find()
+            .filter(delegation::Column::Staker.eq(staker.to_string()))
+            .filter(delegation::Column::Delegator.eq(delegator.to_string()))
+            .filter(delegation::Column::Epoch.lt(epoch))
+            .order_by_desc(delegation::Column::Epoch)
+            .one(&self.db)
+            .await?;
+        Ok(record)
+    }
+
+    async fn insert_epoch_snapshot(&self, snapshot: epoch_snapshot::ActiveModel) -> Result<()> {
+        let snapshot = snapshot.insert(&self.db).await?;
+        log::info!(
+            "Epoch snapshot created with epoch: {}, stakers: {}",
+            snapshot.epoch,
+            snapshot.staker_count
+        );
+        Ok(())
+    }
+
+    async fn get_epoch_snapshot(&self, epoch: Epoch) -> Result<Option<epoch_snapshot::Model>> {
+        let snapshot = epoch_snapshot::Entity::find_by_id(epoch)
+            .one(&self.db)
+            .await?;
+        Ok(snapshot)
+    }
+
+    async fn upsert_reward_claim(&self, claim: reward_claim::ActiveModel) -> Result<()> {
+        reward_claim::Entity::insert(claim)
+            .on_conflict(
+                OnConflict::column(reward_claim::Column::TxHash)
+                    .update_columns([
+                        reward_claim::Column::Address,
+                        reward_claim::Column::Epoch,
+                        reward_claim::Column::Amount,
+                    ])
+                    .to_owned(),
+            )
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_reward_claims(&self, addr: Address) -> Result<Vec<reward_claim::Model>> {
+        let mut cursor = reward_claim::Entity::find()
+            .filter(reward_claim::Column::Address.eq(addr.to_string()))
+            .cursor_by(reward_claim::Column::Id);
         match cursor.all(&self.db).await {
             Ok(records) => Ok(records),
             Err(e) => Err(StorageError::SqlCursorError(e).into()),
         }
     }
 
-    async fn get_address_state(&self, addr: Address) -> Result<Vec<Model>> {
-        let mut cursor = transaction::Entity::find()
-            .filter(transaction::Column::Address.eq(addr.to_string()))
-            .cursor_by(transaction::Column::Id);
+    async fn upsert_withdraw_unlock(&self, unlock: withdraw_unlock::ActiveModel) -> Result<()> {
+        withdraw_unlock::Entity::insert(unlock)
+            .on_conflict(
+                OnConflict::columns([
+                    withdraw_unlock::Column::TxHash,
+                    withdraw_unlock::Column::UnlockEpoch,
+                ])
+                .update_columns([
+                    withdraw_unlock::Column::Address,
+                    withdraw_unlock::Column::Amount,
+                    withdraw_unlock::Column::Epoch,
+                ])
+                .to_owned(),
+            )
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_unlocked_withdraws(
+        &self,
+        addr: Address,
+        epoch: Epoch,
+    ) -> Result<Vec<withdraw_unlock::Model>> {
+        let mut cursor = withdraw_unlock::Entity::find()
+            .filter(withdraw_unlock::Column::Address.eq(addr.to_string()))
+            .filter(withdraw_unlock::Column::UnlockEpoch.lte(epoch))
+            .cursor_by(withdraw_unlock::Column::Id);
         match cursor.all(&self.db).await {
             Ok(records) => Ok(records),
             Err(e) => Err(StorageError::SqlCursorError(e).into()),
         }
     }
 
-    async fn get_latest_stake_transactions(&self, offset: u64, limit: u64) -> Result<Vec<Model>> {
-        let mut cursor = transaction::Entity::find().cursor_by(transaction::Column::Id);
-        cursor.after(offset).before(offset + limit);
-        match cursor.all(&self.db).await {
-            Ok(records) => Ok(records),
-            Err(e) => Err(StorageError::SqlCursorError(e).into()),
+    async fn rollback_blocks(&self, from: u64) -> Result<()> {
+        let txn = self.db.begin().await?;
+
+        let records = transaction::Entity::find()
+            .filter(transaction::Column::BlockNumber.gte(from))
+            .all(&txn)
+            .await?;
+        for record in records.iter() {
+            count_record(&txn, record, false).await?;
+        }
+        transaction::Entity::delete_many()
+            .filter(transaction::Column::BlockNumber.gte(from))
+            .exec(&txn)
+            .await?;
+
+        delegation::Entity::delete_many()
+            .filter(delegation::Column::BlockNumber.gte(from))
+            .exec(&txn)
+            .await?;
+
+        // the delegations of a staker are deleted with it
+        let stakers = staker::Entity::find()
+            .filter(staker::Column::BlockNumber.gte(from))
+            .all(&txn)
+            .await?;
+        let mut kept = 0;
+        for staker in stakers {
+            let delegated = delegation::Entity::find()
+                .filter(delegation::Column::Staker.eq(staker.address.clone()))
+                .count(&txn)
+                .await?;
+            if delegated > 0 {
+                kept += 1;
+                continue;
+            }
+            staker::Entity::delete_by_id(staker.address)
+                .exec(&txn)
+                .await?;
         }
+
+        txn.commit().await?;
+        log::warn!(
+            "Rolled back blocks from {}, records: {}, stakers kept: {}",
+            from,
+            records.len(),
+            kept
+        );
+        Ok(())
     }
 }
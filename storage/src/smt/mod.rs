@@ -1,3 +1,4 @@
+mod snapshot;
 mod utils;
 
 use std::{collections::HashMap, fs, path::Path, sync::Arc, vec};
@@ -29,6 +30,7 @@ pub type ColumnFamilyStoreSMT<'a, T, W> =
 pub type ColumnFamilyStoreMultiSMT<'a, T, W> =
     SparseMerkleTree<Blake2bHasher, LeafValue, ColumnFamilyStoreMultiTree<'a, T, W>>;
 
+#[derive(Clone)]
 pub struct SmtManager {
     db: Arc<OptimisticTransactionDB>,
 }
@@ -424,6 +426,34 @@ impl DelegateSmtStorage for SmtManager {
         Ok(hash_map)
     }
 
+    async fn get_stakers(&self) -> Result<Vec<Staker>> {
+        // the leaves of the top smts are keyed by the top prefix, the staker
+        // and the epoch
+        let prefix = SmtPrefixType::Top.as_prefix();
+        let key_len = prefix.len() + 20 + 32;
+        let mode = IteratorMode::From(&prefix, Direction::Forward);
+        let read_opt = ReadOptions::default();
+        let cf = self
+            .db
+            .cf_handle(&format!("{}_{}", *DELEGATOR_TABLE, CFSuffixType::Leaf))
+            .unwrap();
+
+        let mut stakers: Vec<Staker> = Vec::new();
+        for (k, _) in self.db.get_iter_cf(cf, &read_opt, mode)? {
+            if !k.starts_with(&prefix) {
+                break;
+            }
+            if k.len() != key_len {
+                continue;
+            }
+            let staker = Address::from_slice(&k[prefix.len()..prefix.len() + 20]);
+            if stakers.last() != Some(&staker) {
+                stakers.push(staker);
+            }
+        }
+        Ok(stakers)
+    }
+
     async fn generate_sub_proof(
         &self,
         staker: Staker,
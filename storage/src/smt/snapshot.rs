@@ -0,0 +1,219 @@
+use std::{fs, path::Path};
+
+use anyhow::Result;
+use blake2b_rs::Blake2bBuilder;
+use rocksdb::{prelude::*, IteratorMode, OptimisticTransactionOptions, WriteOptions};
+
+use common::{
+    traits::smt::SmtSnapshot,
+    types::smt::{
+        CFSuffixType, Epoch, DELEGATOR_TABLE, PROPOSAL_TABLE, REWARD_TABLE, STAKER_TABLE,
+    },
+};
+
+use crate::error::StorageError;
+
+use super::SmtManager;
+
+const SNAPSHOT_MAGIC: &[u8; 8] = b"SPARKSMT";
+const SNAPSHOT_VERSION: u32 = 1;
+const CHECKSUM_LEN: usize = 32;
+
+/// SMT snapshot
+/// All the key-values of the staker, delegator, reward and proposal column
+/// families. Integers are encoded in little endian.
+///
+/// Header
+///     magic(b"SPARKSMT") + version(u32) + epoch(u64)
+///
+/// Entry
+///     column family index(u8) + key length(u32) + key + value length(u32) +
+///     value
+///
+/// Checksum
+///     blake2b hash of the header and all the entries
+impl SmtSnapshot for SmtManager {
+    fn export_snapshot(&self, epoch: Epoch, path: &Path) -> Result<()> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(SNAPSHOT_MAGIC);
+        buf.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+        buf.extend_from_slice(&epoch.to_le_bytes());
+
+        // The entries are read from a snapshot of the database, so that the
+        // smts updated meanwhile do not mix into the exported ones.
+        let mut txn_opts = OptimisticTransactionOptions::new();
+        txn_opts.set_snapshot(true);
+        let txn = self.db.transaction(&WriteOptions::default(), &txn_opts);
+        let snapshot = txn.snapshot();
+        let mut read_opt = ReadOptions::default();
+        read_opt.set_snapshot(&snapshot);
+
+        for (index, cf_name) in column_families().iter().enumerate() {
+            let cf = self.db.cf_handle(cf_name).unwrap();
+            for (k, v) in self.db.get_iter_cf(cf, &read_opt, IteratorMode::Start)? {
+                buf.push(index as u8);
+                write_bytes(&mut buf, &k);
+                write_bytes(&mut buf, &v);
+            }
+        }
+
+        let checksum = checksum(&buf);
+        buf.extend_from_slice(&checksum);
+
+        // an existing snapshot is replaced only after the new one is complete
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, buf)?;
+        fs::rename(tmp_path, path)?;
+        Ok(())
+    }
+
+    fn import_snapshot(&self, path: &Path) -> Result<Epoch> {
+        let data = fs::read(path)?;
+        let (epoch, entries) = decode(&data)?;
+
+        let cf_names = column_families();
+        let read_opt = ReadOptions::default();
+        for cf_name in cf_names.iter() {
+            let cf = self.db.cf_handle(cf_name).unwrap();
+            let mut iter = self.db.get_iter_cf(cf, &read_opt, IteratorMode::Start)?;
+            if iter.next().is_some() {
+                return Err(StorageError::NonEmptySmtDatabase.into());
+            }
+        }
+
+        let inner = self.db.transaction_default();
+        for (index, key, value) in entries {
+            let cf = self.db.cf_handle(&cf_names[index]).unwrap();
+            inner.put_cf(cf, key, value)?;
+        }
+        inner.commit()?;
+
+        Ok(epoch)
+    }
+
+    fn scratch_copy(&self, path: &Path) -> Result<Self> {
+        if path.exists() {
+            fs::remove_dir_all(path)?;
+        }
+
+        let snapshot = path.with_extension("snapshot");
+        self.export_snapshot(Epoch::default(), &snapshot)?;
+        let copy = SmtManager::new(path);
+        copy.import_snapshot(&snapshot)?;
+        fs::remove_file(snapshot)?;
+
+        Ok(copy)
+    }
+
+    fn replace_with(&self, copy: &Self) -> Result<()> {
+        let read_opt = ReadOptions::default();
+        let inner = self.db.transaction_default();
+        for cf_name in column_families().iter() {
+            let cf = self.db.cf_handle(cf_name).unwrap();
+            for (key, _) in self.db.get_iter_cf(cf, &read_opt, IteratorMode::Start)? {
+                inner.delete_cf(cf, key)?;
+            }
+
+            let copy_cf = copy.db.cf_handle(cf_name).unwrap();
+            for (key, value) in copy
+                .db
+                .get_iter_cf(copy_cf, &read_opt, IteratorMode::Start)?
+            {
+                inner.put_cf(cf, key, value)?;
+            }
+        }
+        inner.commit()?;
+
+        Ok(())
+    }
+}
+
+fn column_families() -> Vec<String> {
+    [
+        *STAKER_TABLE,
+        *DELEGATOR_TABLE,
+        *REWARD_TABLE,
+        *PROPOSAL_TABLE,
+    ]
+    .iter()
+    .flat_map(|table| {
+        [
+            format!("{}_{}", table, CFSuffixType::Branch),
+            format!("{}_{}", table, CFSuffixType::Leaf),
+        ]
+    })
+    .collect()
+}
+
+fn write_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+fn checksum(data: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let mut hasher = Blake2bBuilder::new(CHECKSUM_LEN).build();
+    hasher.update(data);
+    let mut hash = [0u8; CHECKSUM_LEN];
+    hasher.finalize(&mut hash);
+    hash
+}
+
+type Entry<'a> = (usize, &'a [u8], &'a [u8]);
+
+fn decode(data: &[u8]) -> Result<(Epoch, Vec<Entry>)> {
+    if data.len() < CHECKSUM_LEN {
+        return Err(invalid("too short"));
+    }
+    let (content, expected) = data.split_at(data.len() - CHECKSUM_LEN);
+    if checksum(content) != expected {
+        return Err(invalid("checksum mismatch"));
+    }
+
+    let mut reader = Reader { data: content };
+    if reader.take(SNAPSHOT_MAGIC.len())? != SNAPSHOT_MAGIC {
+        return Err(invalid("unknown format"));
+    }
+    let version = u32::from_le_bytes(reader.take(4)?.try_into()?);
+    if version != SNAPSHOT_VERSION {
+        return Err(invalid(&format!("unsupported version {}", version)));
+    }
+    let epoch = u64::from_le_bytes(reader.take(8)?.try_into()?);
+
+    let cf_count = column_families().len();
+    let mut entries = vec![];
+    while !reader.data.is_empty() {
+        let index = reader.take(1)?[0] as usize;
+        if index >= cf_count {
+            return Err(invalid(&format!("unknown column family {}", index)));
+        }
+        let key = reader.take_bytes()?;
+        let value = reader.take_bytes()?;
+        entries.push((index, key, value));
+    }
+
+    Ok((epoch, entries))
+}
+
+fn invalid(reason: &str) -> anyhow::Error {
+    StorageError::InvalidSnapshot(reason.to_string()).into()
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.data.len() < len {
+            return Err(invalid("unexpected end"));
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn take_bytes(&mut self) -> Result<&'a [u8]> {
+        let len = u32::from_le_bytes(self.take(4)?.try_into()?);
+        self.take(len as usize)
+    }
+}
@@ -3,11 +3,23 @@ use std::{fs, path::PathBuf, vec};
 use ckb_types::h160;
 
 use common::{
-    traits::smt::{DelegateSmtStorage, ProposalSmtStorage, RewardSmtStorage, StakeSmtStorage},
-    types::smt::{Staker, UserAmount},
+    traits::{
+        query::TransactionStorage,
+        smt::{
+            DelegateSmtStorage, ProposalSmtStorage, RewardSmtStorage, SmtSnapshot, StakeSmtStorage,
+        },
+    },
+    types::{
+        api::{HistoryEvent, OperationStatus},
+        relation_db::{
+            delegation, staker,
+            transaction::{self, decode_amount, encode_amount},
+        },
+        smt::{Address, Staker, UserAmount},
+    },
 };
 
-use super::smt::SmtManager;
+use super::{relation_db::Set, smt::SmtManager, TransactionHistory};
 
 static ROCKSDB_PATH: &str = "./free-space/smt";
 
@@ -194,6 +206,10 @@ async fn test_delegate_functions() {
         .unwrap()
         .unwrap();
     assert_eq!(result, amount);
+    assert_eq!(
+        DelegateSmtStorage::get_stakers(&smt_manager).await.unwrap(),
+        vec![staker]
+    );
 
     // update
     DelegateSmtStorage::insert(&smt_manager, epoch, staker, delegators)
@@ -275,3 +291,409 @@ async fn test_proposal_functions() {
         .unwrap();
     assert_eq!(result, proposal_count);
 }
+
+#[tokio::test]
+async fn test_snapshot() {
+    let path = PathBuf::from(ROCKSDB_PATH).join("snapshot");
+    if path.exists() {
+        fs::remove_dir_all(path.clone()).unwrap();
+    }
+    fs::create_dir_all(path.clone()).unwrap();
+
+    let smt_manager = SmtManager::new(path.join("source"));
+    let user = [5u8; 20].into();
+    let epoch = 1;
+
+    StakeSmtStorage::insert(&smt_manager, epoch, vec![UserAmount {
+        user,
+        amount: 100,
+        is_increase: true,
+    }])
+    .await
+    .unwrap();
+    RewardSmtStorage::insert(&smt_manager, epoch, user)
+        .await
+        .unwrap();
+
+    // export & import
+    let snapshot = path.join("smt_snapshot");
+    smt_manager.export_snapshot(epoch, &snapshot).unwrap();
+
+    let imported = SmtManager::new(path.join("imported"));
+    assert_eq!(imported.import_snapshot(&snapshot).unwrap(), epoch);
+    assert_eq!(
+        StakeSmtStorage::get_top_root(&imported).await.unwrap(),
+        StakeSmtStorage::get_top_root(&smt_manager).await.unwrap()
+    );
+    assert_eq!(
+        RewardSmtStorage::get_root(&imported).await.unwrap(),
+        RewardSmtStorage::get_root(&smt_manager).await.unwrap()
+    );
+    assert_eq!(
+        StakeSmtStorage::get_amount(&imported, epoch, user)
+            .await
+            .unwrap(),
+        Some(100)
+    );
+
+    // only an empty database can be imported into
+    assert!(imported.import_snapshot(&snapshot).is_err());
+
+    // a broken snapshot is rejected
+    let mut data = fs::read(&snapshot).unwrap();
+    data[20] ^= 1;
+    fs::write(&snapshot, data).unwrap();
+    let empty = SmtManager::new(path.join("empty"));
+    assert!(empty.import_snapshot(&snapshot).is_err());
+}
+
+#[tokio::test]
+async fn test_replace_with_scratch_copy() {
+    let path = PathBuf::from(ROCKSDB_PATH).join("scratch");
+    if path.exists() {
+        fs::remove_dir_all(path.clone()).unwrap();
+    }
+    fs::create_dir_all(path.clone()).unwrap();
+
+    let smt_manager = SmtManager::new(path.join("source"));
+    let user = [6u8; 20].into();
+    let other = [7u8; 20].into();
+
+    StakeSmtStorage::insert(&smt_manager, 1, vec![UserAmount {
+        user,
+        amount: 100,
+        is_increase: true,
+    }])
+    .await
+    .unwrap();
+
+    // the copy is written without touching the original
+    let copy = smt_manager.scratch_copy(&path.join("copy")).unwrap();
+    StakeSmtStorage::insert(&copy, 2, vec![UserAmount {
+        user:        other,
+        amount:      50,
+        is_increase: true,
+    }])
+    .await
+    .unwrap();
+    StakeSmtStorage::remove(&copy, 1, vec![user]).await.unwrap();
+    assert_eq!(
+        StakeSmtStorage::get_amount(&smt_manager, 2, other)
+            .await
+            .unwrap(),
+        None
+    );
+
+    smt_manager.replace_with(&copy).unwrap();
+    assert_eq!(
+        StakeSmtStorage::get_top_root(&smt_manager).await.unwrap(),
+        StakeSmtStorage::get_top_root(&copy).await.unwrap()
+    );
+    assert_eq!(
+        StakeSmtStorage::get_amount(&smt_manager, 2, other)
+            .await
+            .unwrap(),
+        Some(50)
+    );
+    assert_eq!(
+        StakeSmtStorage::get_amount(&smt_manager, 1, user)
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+#[test]
+fn test_decode_amount() {
+    assert_eq!(decode_amount(&encode_amount(u128::MAX)).unwrap(), u128::MAX);
+    assert_eq!(decode_amount(&encode_amount(0)).unwrap(), 0);
+
+    // a corrupt amount is not taken as zero
+    assert!(decode_amount("").is_err());
+    assert!(decode_amount("12a").is_err());
+}
+
+#[tokio::test]
+async fn test_delegation_tables() {
+    let rdb = TransactionHistory::new("sqlite::memory:").await;
+    let staker: Staker = [1u8; 20].into();
+    let delegator = [2u8; 20].into();
+
+    let delegation = |amount| delegation::ActiveModel {
+        staker: Set(staker.to_string()),
+        delegator: Set(delegator.to_string()),
+        epoch: Set(2),
+        amount: Set(encode_amount(amount)),
+        ..Default::default()
+    };
+
+    // the staker must exist before its delegations
+    assert!(rdb.upsert_delegation(delegation(100)).await.is_err());
+
+    rdb.upsert_staker(staker::ActiveModel {
+        address:            Set(staker.to_string()),
+        commission_rate:    Set(20),
+        maximum_delegators: Set(10),
+        threshold:          Set(encode_amount(50)),
+        epoch:              Set(1),
+        block_number:       Set(None),
+    })
+    .await
+    .unwrap();
+    rdb.upsert_delegation(delegation(100)).await.unwrap();
+    rdb.upsert_delegation(delegation(300)).await.unwrap();
+
+    let delegations = rdb.get_delegations_by_staker(staker, 2).await.unwrap();
+    assert_eq!(delegations.len(), 1);
+    assert_eq!(delegations[0].amount, encode_amount(300));
+    assert_eq!(
+        rdb.get_delegations_by_delegator(delegator, 2)
+            .await
+            .unwrap(),
+        delegations
+    );
+    assert!(rdb
+        .get_delegations_by_delegator(delegator, 3)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_transaction_pagination() {
+    let rdb = TransactionHistory::new("sqlite::memory:").await;
+    let addr: Address = [1u8; 20].into();
+    let other: Address = [2u8; 20].into();
+
+    for (i, address) in [addr, other, addr, addr, other, addr, addr]
+        .iter()
+        .enumerate()
+    {
+        rdb.insert(transaction::ActiveModel {
+            address: Set(address.to_string()),
+            timestamp: Set(i as u32 / 2),
+            operation: Set(0),
+            event: Set(0),
+            tx_hash: Set(format!("{:#066x}", i)),
+            total_amount: Set(encode_amount(i as u128 * 10)),
+            stake_amount: Set(encode_amount(0)),
+            delegate_amount: Set(encode_amount(0)),
+            withdrawable_amount: Set(encode_amount(0)),
+            stake_rate: Set("".to_string()),
+            delegate_rate: Set("".to_string()),
+            epoch: Set(1),
+            status: Set(0),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    }
+
+    // the records of the address from the newest, the ids break the ties
+    let mut ids = Vec::new();
+    let mut page_token = None;
+    loop {
+        let page = rdb
+            .get_records_by_address(addr, page_token, 2)
+            .await
+            .unwrap();
+        assert_eq!(page.total, 5);
+        ids.extend(page.items.iter().map(|m| m.id));
+        page_token = page.next_page_token;
+        if page_token.is_none() {
+            break;
+        }
+    }
+    assert_eq!(ids, vec![7, 6, 4, 3, 1]);
+
+    // the other address redeems 20
+    rdb.insert(transaction::ActiveModel {
+        address: Set(other.to_string()),
+        timestamp: Set(4),
+        operation: Set(0),
+        event: Set(HistoryEvent::Redeem as u32),
+        tx_hash: Set(format!("{:#066x}", 7)),
+        total_amount: Set(encode_amount(20)),
+        stake_amount: Set(encode_amount(0)),
+        delegate_amount: Set(encode_amount(0)),
+        withdrawable_amount: Set(encode_amount(0)),
+        stake_rate: Set("".to_string()),
+        delegate_rate: Set("".to_string()),
+        epoch: Set(1),
+        status: Set(0),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+
+    // the amounts of an address are summed, 0 + 20 + 30 + 50 + 60 and 10 + 40 - 20
+    let mut top = Vec::new();
+    let mut page_token = None;
+    loop {
+        let page = rdb.get_top_stake_address(0, page_token, 1).await.unwrap();
+        assert_eq!(page.total, 2);
+        top.extend(
+            page.items
+                .into_iter()
+                .map(|item| (item.address, item.amount)),
+        );
+        page_token = page.next_page_token;
+        if page_token.is_none() {
+            break;
+        }
+    }
+    assert_eq!(top, vec![
+        (addr.to_string(), "160".to_string()),
+        (other.to_string(), "30".to_string()),
+    ]);
+
+    assert!(rdb
+        .get_records_by_address(addr, Some("invalid".to_string()), 2)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_top_address_amounts() {
+    let rdb = TransactionHistory::new("sqlite::memory:").await;
+    let addr: Address = [1u8; 20].into();
+    let tx_hash = format!("{:#066x}", 1);
+
+    let record = |status: OperationStatus, output_index| transaction::ActiveModel {
+        address: Set(addr.to_string()),
+        timestamp: Set(1),
+        operation: Set(0),
+        event: Set(HistoryEvent::Add as u32),
+        tx_hash: Set(tx_hash.clone()),
+        total_amount: Set(encode_amount(100)),
+        stake_amount: Set(encode_amount(100)),
+        delegate_amount: Set(encode_amount(0)),
+        withdrawable_amount: Set(encode_amount(0)),
+        stake_rate: Set("".to_string()),
+        delegate_rate: Set("".to_string()),
+        epoch: Set(1),
+        status: Set(status as u32),
+        output_index: Set(output_index),
+        ..Default::default()
+    };
+    let top_amount = || async {
+        let page = rdb.get_top_stake_address(0, None, 10).await.unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.items[0].address, addr.to_string());
+        page.items[0].amount.clone()
+    };
+
+    // a pending record is counted until its tx fails
+    rdb.insert(record(OperationStatus::Pending, None))
+        .await
+        .unwrap();
+    assert_eq!(top_amount().await, "100");
+    rdb.update_status(tx_hash.clone(), OperationStatus::Failed as u32)
+        .await
+        .unwrap();
+    assert_eq!(top_amount().await, "0");
+    rdb.update_status(tx_hash.clone(), OperationStatus::Success as u32)
+        .await
+        .unwrap();
+    assert_eq!(top_amount().await, "100");
+
+    // the pending record is replaced by the indexed one, which is counted once
+    // however many times it is indexed
+    for _ in 0..2 {
+        rdb.save_indexed_records(tx_hash.clone(), vec![record(
+            OperationStatus::Success,
+            Some(0),
+        )])
+        .await
+        .unwrap();
+        assert_eq!(top_amount().await, "100");
+    }
+    assert_eq!(
+        rdb.get_records_by_tx_hash(tx_hash.clone())
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_rollback_blocks() {
+    let rdb = TransactionHistory::new("sqlite::memory:").await;
+    let staker_a: Staker = [1u8; 20].into();
+    let staker_b: Staker = [2u8; 20].into();
+    let delegator: Address = [3u8; 20].into();
+
+    let staker = |address: Staker, block_number| staker::ActiveModel {
+        address:            Set(address.to_string()),
+        commission_rate:    Set(20),
+        maximum_delegators: Set(10),
+        threshold:          Set(encode_amount(50)),
+        epoch:              Set(1),
+        block_number:       Set(Some(block_number)),
+    };
+    let record = |i: u64, block_number| transaction::ActiveModel {
+        address: Set(delegator.to_string()),
+        timestamp: Set(i as u32),
+        operation: Set(0),
+        event: Set(HistoryEvent::Add as u32),
+        tx_hash: Set(format!("{:#066x}", i)),
+        total_amount: Set(encode_amount(100)),
+        stake_amount: Set(encode_amount(100)),
+        delegate_amount: Set(encode_amount(0)),
+        withdrawable_amount: Set(encode_amount(0)),
+        stake_rate: Set("".to_string()),
+        delegate_rate: Set("".to_string()),
+        epoch: Set(1),
+        status: Set(OperationStatus::Success as u32),
+        output_index: Set(Some(0)),
+        block_number: Set(Some(block_number)),
+        ..Default::default()
+    };
+
+    rdb.upsert_staker(staker(staker_a, 2)).await.unwrap();
+    rdb.upsert_staker(staker(staker_b, 6)).await.unwrap();
+    // staker a is updated after the fork, but its older delegation stays
+    rdb.upsert_staker(staker(staker_a, 7)).await.unwrap();
+    for (epoch, block_number) in [(2, 3), (3, 8)] {
+        rdb.upsert_delegation(delegation::ActiveModel {
+            staker: Set(staker_a.to_string()),
+            delegator: Set(delegator.to_string()),
+            epoch: Set(epoch),
+            amount: Set(encode_amount(100)),
+            block_number: Set(Some(block_number)),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    }
+    for (i, block_number) in [(1, 3), (2, 5), (3, 8)] {
+        rdb.insert(record(i, block_number)).await.unwrap();
+    }
+
+    rdb.rollback_blocks(5).await.unwrap();
+
+    let records = rdb
+        .get_records_by_address(delegator, None, 10)
+        .await
+        .unwrap();
+    assert_eq!(records.total, 1);
+    assert_eq!(records.items[0].block_number, Some(3));
+    let top = rdb.get_top_stake_address(0, None, 10).await.unwrap();
+    assert_eq!(top.items[0].amount, "100");
+
+    assert!(rdb.get_staker(staker_a).await.unwrap().is_some());
+    assert!(rdb.get_staker(staker_b).await.unwrap().is_none());
+    assert!(rdb
+        .get_delegations_by_delegator(delegator, 3)
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        rdb.get_delegations_by_delegator(delegator, 2)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
@@ -0,0 +1,381 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use ckb_types::{
+    bytes::Bytes,
+    core::{Capacity, TransactionBuilder, TransactionView},
+    packed::{CellInput, CellOutput, Script},
+    prelude::{Entity, Pack},
+};
+
+use common::traits::ckb_rpc_client::CkbRpc;
+use common::traits::smt::DelegateSmtStorage;
+use common::traits::tx_builder::{
+    IBatchTxBuilder, IDelegateTxBuilder, IStakeTxBuilder, IWithdrawTxBuilder,
+};
+use common::types::axon_types::withdraw::WithdrawAtCellData;
+use common::types::tx_builder::{
+    Amount, BatchItems, ChainContext, Epoch, EthAddress, StakeTypeIds,
+};
+use common::utils::convert::new_u128;
+
+use crate::ckb::define::constants::{INAUGURATION, TOKEN_BYTES};
+use crate::ckb::define::error::CkbTxErr;
+use crate::ckb::delegate::DelegateTxBuilder;
+use crate::ckb::helper::{
+    token_cell_data, Checkpoint, Delegate, Metadata, OmniEth, Secp256k1, Stake, Tx, Withdraw, Xudt,
+};
+use crate::ckb::stake::StakeTxBuilder;
+use crate::ckb::withdraw::WithdrawTxBuilder;
+
+/// Sends the stake delta, the delegate deltas and the withdraw of a user in one
+/// tx. The AT cells of the wallet are collected once and passed through the
+/// withdraw, the stake and the delegates in turn, then the tx is balanced once.
+///
+/// The first stake also creates the delegate requirement cell, whose type id
+/// depends on the balanced inputs, so it can not be batched.
+pub struct BatchTxBuilder<'a, C: CkbRpc, D: DelegateSmtStorage> {
+    ckb:                  &'a C,
+    ctx:                  &'a ChainContext,
+    type_ids:             StakeTypeIds,
+    delegate_smt_storage: D,
+    current_epoch:        Epoch,
+    user:                 EthAddress,
+    batch:                BatchItems,
+    stake_lock:           Script,
+    delegate_lock:        Script,
+    withdraw_lock:        Script,
+    token_lock:           Script,
+    xudt:                 Script,
+}
+
+#[async_trait]
+impl<'a, C: CkbRpc, D: DelegateSmtStorage + Clone> IBatchTxBuilder<'a, C, D>
+    for BatchTxBuilder<'a, C, D>
+{
+    fn new(
+        ckb: &'a C,
+        ctx: &'a ChainContext,
+        type_ids: StakeTypeIds,
+        user: EthAddress,
+        current_epoch: Epoch,
+        batch: BatchItems,
+        delegate_smt_storage: D,
+    ) -> Self {
+        let stake_lock = Stake::lock(ctx, &type_ids.metadata_type_id, &user);
+        let delegate_lock = Delegate::lock(ctx, &type_ids.metadata_type_id, &user);
+        let withdraw_lock = Withdraw::lock(ctx, &type_ids.metadata_type_id, &user);
+        let token_lock = OmniEth::lock(ctx, &user);
+        let xudt = Xudt::type_(ctx, &type_ids.xudt_owner.pack());
+
+        Self {
+            ckb,
+            ctx,
+            type_ids,
+            delegate_smt_storage,
+            current_epoch,
+            user,
+            batch,
+            stake_lock,
+            delegate_lock,
+            withdraw_lock,
+            token_lock,
+            xudt,
+        }
+    }
+
+    async fn build_tx(self) -> Result<TransactionView> {
+        self.check_batch()?;
+
+        let withdraw_cell =
+            Withdraw::get_cell(self.ckb, self.withdraw_lock.clone(), self.xudt.clone()).await?;
+        if self.batch.withdraw && withdraw_cell.is_none() {
+            return Err(CkbTxErr::CellNotFound("Withdraw".to_owned()).into());
+        }
+
+        let stake_cell = match self.batch.stake {
+            Some(_) => Some(
+                Stake::get_cell(self.ckb, self.stake_lock.clone(), self.xudt.clone())
+                    .await?
+                    .ok_or(CkbTxErr::BatchFirstStake)?,
+            ),
+            None => None,
+        };
+
+        let delegate_cell = if self.batch.delegates.is_empty() {
+            None
+        } else {
+            Delegate::get_cell(self.ckb, self.delegate_lock.clone(), self.xudt.clone()).await?
+        };
+
+        let mut inputs = vec![];
+        let mut witnesses = vec![];
+        let mut cell_deps = vec![
+            OmniEth::lock_dep(self.ctx),
+            Secp256k1::lock_dep(self.ctx),
+            Xudt::type_dep(self.ctx),
+        ];
+
+        // withdraw AT cell
+        let withdraw_data = match &withdraw_cell {
+            Some(cell) if self.batch.withdraw => {
+                inputs.push(
+                    CellInput::new_builder()
+                        .previous_output(cell.out_point.clone().into())
+                        .build(),
+                );
+                witnesses.push(OmniEth::witness_placeholder().as_bytes());
+                cell_deps.push(Withdraw::lock_dep(self.ctx));
+                cell.output_data.clone().map(|d| d.into_bytes())
+            }
+            _ => None,
+        };
+
+        // stake AT cell
+        let stake_data = stake_cell.map(|cell| {
+            inputs.push(
+                CellInput::new_builder()
+                    .previous_output(cell.out_point.into())
+                    .build(),
+            );
+            witnesses.push(Stake::witness(0u8).as_bytes());
+            cell_deps.push(Stake::lock_dep(self.ctx));
+            cell.output_data.unwrap_or_default().into_bytes()
+        });
+
+        let first_delegate = !self.batch.delegates.is_empty() && delegate_cell.is_none();
+
+        // delegate AT cell
+        let delegate_data = delegate_cell.map(|cell| {
+            inputs.push(
+                CellInput::new_builder()
+                    .previous_output(cell.out_point.into())
+                    .build(),
+            );
+            witnesses.push(Delegate::witness(0u8).as_bytes());
+            cell_deps.push(Delegate::lock_dep(self.ctx));
+            cell.output_data.unwrap_or_default().into_bytes()
+        });
+
+        if withdraw_data.is_some() || stake_data.is_some() || delegate_data.is_some() {
+            cell_deps.push(
+                Checkpoint::cell_dep(self.ckb, self.ctx, &self.type_ids.checkpoint_type_id).await?,
+            );
+            cell_deps.push(
+                Metadata::cell_dep(self.ckb, self.ctx, &self.type_ids.metadata_type_id).await?,
+            );
+        }
+
+        // AT cells, the first of the lock group carries its witness
+        let mut wallet_amount = self.add_token_to_inputs(&mut inputs).await?;
+        witnesses.push(OmniEth::witness_placeholder().as_bytes()); // AT cell lock
+        witnesses.resize(inputs.len(), Bytes::new());
+
+        let mut outputs = vec![];
+        let mut outputs_data = vec![];
+
+        // The unlocked tokens are withdrawn first, so they can be staked or delegated
+        // in the same tx.
+        if let Some(withdraw_data) = withdraw_data {
+            let data = self
+                .withdraw_builder()
+                .build_data(wallet_amount, withdraw_data)
+                .await?;
+            wallet_amount = parse_wallet_amount(&data[1]);
+            self.push_output(
+                &mut outputs,
+                &mut outputs_data,
+                &self.withdraw_lock,
+                &data[0],
+            )?;
+        }
+
+        if let Some(stake_data) = stake_data {
+            let data = self
+                .stake_builder()
+                .update_stake_data(wallet_amount, stake_data)?;
+            wallet_amount = parse_wallet_amount(&data[1]);
+            self.push_output(&mut outputs, &mut outputs_data, &self.stake_lock, &data[0])?;
+        }
+
+        if !self.batch.delegates.is_empty() {
+            let delegate_builder = self.delegate_builder();
+            let (delegate, wallet) = match delegate_data {
+                Some(delegate_data) => {
+                    let data = delegate_builder
+                        .update_delegate_data(wallet_amount, delegate_data)
+                        .await?;
+                    (data[0].clone(), data[1].clone())
+                }
+                None => {
+                    let data = delegate_builder.first_delegate_data(wallet_amount)?;
+                    (data[1].clone(), data[0].clone())
+                }
+            };
+            wallet_amount = parse_wallet_amount(&wallet);
+            self.push_output(
+                &mut outputs,
+                &mut outputs_data,
+                &self.delegate_lock,
+                &delegate,
+            )?;
+        }
+
+        // AT cell
+        self.push_output(
+            &mut outputs,
+            &mut outputs_data,
+            &self.token_lock,
+            &wallet_amount.pack().as_bytes(),
+        )?;
+
+        // the withdraw AT cell is created by the first delegate the same as the
+        // delegate tx
+        if first_delegate && withdraw_cell.is_none() {
+            self.push_output(
+                &mut outputs,
+                &mut outputs_data,
+                &self.withdraw_lock,
+                &token_cell_data(0, WithdrawAtCellData::default().as_bytes()),
+            )?;
+        }
+
+        witnesses.push(OmniEth::witness_placeholder().as_bytes()); // capacity provider lock
+
+        let tx = TransactionBuilder::default()
+            .inputs(inputs)
+            .outputs(outputs)
+            .outputs_data(outputs_data.pack())
+            .cell_deps(cell_deps)
+            .witnesses(witnesses.pack())
+            .build();
+
+        let mut tx = Tx::new(self.ckb, tx);
+        tx.balance(self.ctx, self.token_lock.clone()).await?;
+
+        Ok(tx.inner())
+    }
+}
+
+impl<'a, C: CkbRpc, D: DelegateSmtStorage + Clone> BatchTxBuilder<'a, C, D> {
+    fn check_batch(&self) -> Result<()> {
+        if self.batch.stake.is_none() && self.batch.delegates.is_empty() && !self.batch.withdraw {
+            return Err(CkbTxErr::EmptyBatch.into());
+        }
+
+        let stake_epochs = self.batch.stake.iter().map(|s| s.inauguration_epoch);
+        let delegate_epochs = self.batch.delegates.iter().map(|d| d.inauguration_epoch);
+        for epoch in stake_epochs.chain(delegate_epochs) {
+            if epoch > self.current_epoch + INAUGURATION {
+                return Err(CkbTxErr::InaugurationEpoch {
+                    expected: self.current_epoch + INAUGURATION,
+                    found:    epoch,
+                }
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn withdraw_builder(&self) -> WithdrawTxBuilder<'a, C> {
+        WithdrawTxBuilder::new(
+            self.ckb,
+            self.ctx,
+            self.type_ids.clone(),
+            self.user.clone(),
+            self.current_epoch,
+        )
+    }
+
+    fn stake_builder(&self) -> StakeTxBuilder<'a, C> {
+        StakeTxBuilder::new(
+            self.ckb,
+            self.ctx,
+            self.type_ids.clone(),
+            self.user.clone(),
+            self.current_epoch,
+            self.batch.stake.clone().unwrap_or_default(),
+            None,
+        )
+    }
+
+    fn delegate_builder(&self) -> DelegateTxBuilder<'a, C, D> {
+        DelegateTxBuilder::new(
+            self.ckb,
+            self.ctx,
+            self.type_ids.clone(),
+            self.user.clone(),
+            self.current_epoch,
+            self.batch.delegates.clone(),
+            self.delegate_smt_storage.clone(),
+        )
+    }
+
+    // The tokens of the wallet cover the stake and delegate increases, the
+    // decreases of the delegates are deducted from them.
+    async fn add_token_to_inputs(&self, inputs: &mut Vec<CellInput>) -> Result<Amount> {
+        let mut total_increase = 0;
+        let mut total_decrease = 0;
+
+        for stake in self.batch.stake.iter().filter(|s| s.is_increase) {
+            total_increase += stake.amount;
+        }
+        for delegate in self.batch.delegates.iter() {
+            if delegate.is_increase {
+                total_increase += delegate.amount;
+            } else {
+                total_decrease += delegate.amount;
+            }
+        }
+
+        let expected_amount = if total_increase <= total_decrease {
+            1
+        } else {
+            total_increase - total_decrease
+        };
+
+        let (token_cells, amount) = Xudt::collect(
+            self.ckb,
+            self.token_lock.clone(),
+            self.xudt.clone(),
+            expected_amount,
+        )
+        .await?;
+
+        if token_cells.is_empty() {
+            return Err(CkbTxErr::CellNotFound("AT".to_owned()).into());
+        }
+
+        // AT cells
+        for token_cell in token_cells.into_iter() {
+            inputs.push(
+                CellInput::new_builder()
+                    .previous_output(token_cell.out_point.into())
+                    .build(),
+            );
+        }
+
+        Ok(amount)
+    }
+
+    fn push_output(
+        &self,
+        outputs: &mut Vec<CellOutput>,
+        outputs_data: &mut Vec<Bytes>,
+        lock: &Script,
+        data: &Bytes,
+    ) -> Result<()> {
+        outputs.push(
+            CellOutput::new_builder()
+                .lock(lock.clone())
+                .type_(Some(self.xudt.clone()).pack())
+                .build_exact_capacity(Capacity::bytes(data.len())?)?,
+        );
+        outputs_data.push(data.clone());
+        Ok(())
+    }
+}
+
+fn parse_wallet_amount(data: &Bytes) -> Amount {
+    new_u128(&data[..TOKEN_BYTES])
+}
@@ -1,8 +1,7 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use ckb_sdk::{ScriptGroup, ScriptGroupType};
 use ckb_types::{
-    core::{Capacity, TransactionBuilder, TransactionView},
+    core::{Capacity, TransactionBuilder},
     packed::{CellInput, CellOutput, WitnessArgs},
     prelude::{Entity, Pack},
 };
@@ -10,16 +9,15 @@ use common::utils::convert::{to_bytes, to_u32, to_u64};
 use common::{
     traits::{ckb_rpc_client::CkbRpc, tx_builder::ICheckpointTxBuilder},
     types::axon_types::checkpoint::{CheckpointCellData, CheckpointWitness},
-    types::tx_builder::{Checkpoint, CheckpointProof, CheckpointTypeIds, PrivateKey},
+    types::tx_builder::{
+        ChainContext, Checkpoint, CheckpointProof, CheckpointTypeIds, SignerLock, UnsignedTx,
+    },
 };
 use molecule::prelude::Builder;
 
 use crate::ckb::{
     define::error::CkbTxErr,
-    helper::{
-        AlwaysSuccess, Checkpoint as HCheckpoint, Metadata as HMetadata, OmniEth, Secp256k1, Tx,
-        Xudt,
-    },
+    helper::{AlwaysSuccess, Checkpoint as HCheckpoint, Metadata as HMetadata, Tx, Xudt},
 };
 
 pub struct CheckpointTxBuilder<'a, C>
@@ -27,7 +25,8 @@ where
     C: CkbRpc,
 {
     ckb:            &'a C,
-    kicker_key:     PrivateKey,
+    ctx:            &'a ChainContext,
+    kicker:         SignerLock,
     type_ids:       CheckpointTypeIds,
     epoch_len:      u64,
     new_checkpoint: Checkpoint,
@@ -41,15 +40,17 @@ where
 {
     async fn new(
         ckb: &'a C,
-        kicker_key: PrivateKey,
+        ctx: &'a ChainContext,
+        kicker: SignerLock,
         type_ids: CheckpointTypeIds,
         epoch_len: u64,
         new_checkpoint: Checkpoint,
         proof: CheckpointProof,
     ) -> Self {
         Self {
-            kicker_key,
+            kicker,
             ckb,
+            ctx,
             type_ids,
             epoch_len,
             new_checkpoint,
@@ -57,8 +58,8 @@ where
         }
     }
 
-    async fn build_tx(self) -> Result<TransactionView> {
-        let checkpoint_type = HCheckpoint::type_(&self.type_ids.checkpoint_type_id);
+    async fn build_tx(self) -> Result<UnsignedTx> {
+        let checkpoint_type = HCheckpoint::type_(self.ctx, &self.type_ids.checkpoint_type_id);
 
         let last_checkpoint_cell = HCheckpoint::get_cell(self.ckb, checkpoint_type.clone()).await?;
 
@@ -84,22 +85,22 @@ where
             .as_bytes()];
 
         let outputs = vec![CellOutput::new_builder()
-            .lock(AlwaysSuccess::lock())
+            .lock(AlwaysSuccess::lock(self.ctx))
             .type_(Some(checkpoint_type).pack())
             .build_exact_capacity(Capacity::bytes(outputs_data[0].len())?)?];
 
-        let cell_deps = vec![
-            OmniEth::lock_dep(),
-            Secp256k1::lock_dep(),
-            Xudt::type_dep(),
-            AlwaysSuccess::lock_dep(),
-            HCheckpoint::type_dep(),
+        let mut cell_deps = vec![
+            Xudt::type_dep(self.ctx),
+            AlwaysSuccess::lock_dep(self.ctx),
+            HCheckpoint::type_dep(self.ctx),
             HMetadata::cell_dep(
                 self.ckb,
+                self.ctx,
                 &self.type_ids.metadata_type_id, // metadata type script args
             )
             .await?,
         ];
+        self.kicker.add_cell_deps(&mut cell_deps);
 
         let witnesses = vec![
             WitnessArgs::new_builder()
@@ -115,7 +116,7 @@ where
                 )
                 .build()
                 .as_bytes(),
-            OmniEth::witness_placeholder().as_bytes(),
+            self.kicker.witness_placeholder.as_bytes(),
         ];
 
         let tx = TransactionBuilder::default()
@@ -126,20 +127,14 @@ where
             .witnesses(witnesses.pack())
             .build();
 
-        let omni_eth = OmniEth::new(self.kicker_key.clone());
-        let kick_lock = OmniEth::lock(&omni_eth.address()?);
-
         let mut tx = Tx::new(self.ckb, tx);
-        tx.balance(kick_lock.clone()).await?;
-
-        tx.sign(&omni_eth.signer()?, &ScriptGroup {
-            script:         kick_lock,
-            group_type:     ScriptGroupType::Lock,
-            input_indices:  vec![1],
-            output_indices: vec![],
-        })?;
+        tx.balance(self.ctx, self.kicker.lock.clone()).await?;
 
-        Ok(tx.inner())
+        let script_groups = vec![tx.lock_script_group(&self.kicker.lock).await?];
+        Ok(UnsignedTx {
+            tx: tx.inner(),
+            script_groups,
+        })
     }
 }
 
@@ -1,7 +1,14 @@
+use common::types::ckb_rpc_client::MIN_FEE_RATE;
+
 pub const INAUGURATION: u64 = 2;
 
 pub const TOKEN_BYTES: usize = 16;
 
 pub const START_EPOCH: u64 = 0;
 
-pub const FEE_RATE: u64 = 1000;
+// the default fee rate and the minimum one accepted by the nodes, shannons per
+// KB
+pub const FEE_RATE: u64 = MIN_FEE_RATE;
+
+// the max cycles of a block on ckb
+pub const MAX_TX_CYCLES: u64 = 3_500_000_000;
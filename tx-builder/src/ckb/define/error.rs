@@ -59,6 +59,9 @@ pub enum CkbTxErr {
     #[error("Cell not found: {0}")]
     CellNotFound(String),
 
+    #[error("Malformed data of the {0} cell")]
+    MalformedCellData(&'static str),
+
     #[error("Deserialize bls pub key error")]
     Deserialize,
 
@@ -77,6 +80,12 @@ pub enum CkbTxErr {
     #[error("Stake amount not found in stack SMT. epoch: {0}, staker: {1}")]
     StakeAmountNotFound(u64, H160),
 
+    #[error("Commission rate of the staker `{0}` not found")]
+    CommissionRateNotFound(H160),
+
+    #[error("The commission rate `{0}` is more than 100")]
+    CommissionRate(u8),
+
     #[error(
         "Not right checkpoint occassion, latest epoch {current_epoch:?} and period {current_period:?}, recorded epoch {recorded_epoch:?} and period {recorded_period:?} is not meet the condition"
     )]
@@ -100,4 +109,27 @@ pub enum CkbTxErr {
 
     #[error("Do not delegate to yourself!")]
     DelegateYourself,
+
+    #[error("There is no stake, delegate or withdraw in the batch")]
+    EmptyBatch,
+
+    #[error("The first stake can not be batched, it should be sent alone")]
+    BatchFirstStake,
+
+    #[error("The `{0}` script is not in the deployment manifest")]
+    ScriptNotDeployed(&'static str),
+
+    #[error("The metadata cell records type id {found:?}, expected: {expected:?}")]
+    MetadataTypeIdMismatch {
+        expected: ckb_types::H256,
+        found:    ckb_types::H256,
+    },
+
+    #[error("{group_type} script 0x{script_hash} failed, exit code: {exit_code:?}, {reason}")]
+    ScriptVerification {
+        group_type:  String,
+        script_hash: ckb_types::H256,
+        exit_code:   Option<i8>,
+        reason:      String,
+    },
 }
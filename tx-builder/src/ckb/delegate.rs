@@ -15,7 +15,9 @@ use common::traits::tx_builder::IDelegateTxBuilder;
 use common::types::axon_types::delegate::*;
 use common::types::axon_types::withdraw::WithdrawAtCellData;
 use common::types::ckb_rpc_client::Cell;
-use common::types::tx_builder::{Amount, DelegateItem, Epoch, EthAddress, StakeTypeIds};
+use common::types::tx_builder::{
+    Amount, ChainContext, DelegateItem, Epoch, EthAddress, StakeTypeIds,
+};
 use common::utils::convert::*;
 
 use crate::ckb::define::constants::{INAUGURATION, TOKEN_BYTES};
@@ -30,6 +32,7 @@ use crate::ckb::helper::{
 
 pub struct DelegateTxBuilder<'a, C: CkbRpc, D: DelegateSmtStorage> {
     ckb:                  &'a C,
+    ctx:                  &'a ChainContext,
     type_ids:             StakeTypeIds,
     delegate_smt_storage: D,
     current_epoch:        Epoch,
@@ -47,19 +50,21 @@ impl<'a, C: CkbRpc, D: DelegateSmtStorage> IDelegateTxBuilder<'a, C, D>
 {
     fn new(
         ckb: &'a C,
+        ctx: &'a ChainContext,
         type_ids: StakeTypeIds,
         delegator: EthAddress,
         current_epoch: Epoch,
         delegates: Vec<DelegateItem>,
         delegate_smt_storage: D,
     ) -> Self {
-        let delegate_lock = Delegate::lock(&type_ids.metadata_type_id, &delegator);
-        let withdraw_lock = Withdraw::lock(&type_ids.metadata_type_id, &delegator);
-        let token_lock = OmniEth::lock(&delegator);
-        let xudt = Xudt::type_(&type_ids.xudt_owner.pack());
+        let delegate_lock = Delegate::lock(ctx, &type_ids.metadata_type_id, &delegator);
+        let withdraw_lock = Withdraw::lock(ctx, &type_ids.metadata_type_id, &delegator);
+        let token_lock = OmniEth::lock(ctx, &delegator);
+        let xudt = Xudt::type_(ctx, &type_ids.xudt_owner.pack());
 
         Self {
             ckb,
+            ctx,
             type_ids,
             delegate_smt_storage,
             current_epoch,
@@ -120,7 +125,11 @@ impl<'a, C: CkbRpc, D: DelegateSmtStorage> DelegateTxBuilder<'a, C, D> {
         self.add_withdraw_to_outputs(&mut outputs, &mut outputs_data)
             .await?;
 
-        let cell_deps = vec![OmniEth::lock_dep(), Secp256k1::lock_dep(), Xudt::type_dep()];
+        let cell_deps = vec![
+            OmniEth::lock_dep(self.ctx),
+            Secp256k1::lock_dep(self.ctx),
+            Xudt::type_dep(self.ctx),
+        ];
 
         let witnesses = vec![
             OmniEth::witness_placeholder().as_bytes(), // AT cell lock
@@ -136,7 +145,7 @@ impl<'a, C: CkbRpc, D: DelegateSmtStorage> DelegateTxBuilder<'a, C, D> {
             .build();
 
         let mut tx = Tx::new(self.ckb, tx);
-        tx.balance(self.token_lock.clone()).await?;
+        tx.balance(self.ctx, self.token_lock.clone()).await?;
 
         Ok(tx.inner())
     }
@@ -168,12 +177,12 @@ impl<'a, C: CkbRpc, D: DelegateSmtStorage> DelegateTxBuilder<'a, C, D> {
         ];
 
         let cell_deps = vec![
-            OmniEth::lock_dep(),
-            Secp256k1::lock_dep(),
-            Xudt::type_dep(),
-            Delegate::lock_dep(),
-            Checkpoint::cell_dep(self.ckb, &self.type_ids.checkpoint_type_id).await?,
-            Metadata::cell_dep(self.ckb, &self.type_ids.metadata_type_id).await?,
+            OmniEth::lock_dep(self.ctx),
+            Secp256k1::lock_dep(self.ctx),
+            Xudt::type_dep(self.ctx),
+            Delegate::lock_dep(self.ctx),
+            Checkpoint::cell_dep(self.ckb, self.ctx, &self.type_ids.checkpoint_type_id).await?,
+            Metadata::cell_dep(self.ckb, self.ctx, &self.type_ids.metadata_type_id).await?,
         ];
 
         let witnesses = vec![
@@ -191,7 +200,7 @@ impl<'a, C: CkbRpc, D: DelegateSmtStorage> DelegateTxBuilder<'a, C, D> {
             .build();
 
         let mut tx = Tx::new(self.ckb, tx);
-        tx.balance(self.token_lock.clone()).await?;
+        tx.balance(self.ctx, self.token_lock.clone()).await?;
 
         Ok(tx.inner())
     }
@@ -261,7 +270,7 @@ impl<'a, C: CkbRpc, D: DelegateSmtStorage> DelegateTxBuilder<'a, C, D> {
         Ok(())
     }
 
-    fn first_delegate_data(&self, mut wallet_amount: Amount) -> CkbTxResult<Vec<Bytes>> {
+    pub(crate) fn first_delegate_data(&self, mut wallet_amount: Amount) -> CkbTxResult<Vec<Bytes>> {
         log::info!(
             "[first delegate] delegator: {}, old wallet amount: {}",
             self.delegator.to_string(),
@@ -322,7 +331,7 @@ impl<'a, C: CkbRpc, D: DelegateSmtStorage> DelegateTxBuilder<'a, C, D> {
         ])
     }
 
-    async fn update_delegate_data(
+    pub(crate) async fn update_delegate_data(
         &self,
         mut wallet_amount: Amount,
         delegate_data: Bytes,
@@ -2,10 +2,9 @@ use std::collections::HashMap;
 
 use anyhow::Result;
 use async_trait::async_trait;
-use ckb_sdk::{ScriptGroup, ScriptGroupType};
 use ckb_types::{
     bytes::Bytes,
-    core::{Capacity, TransactionBuilder, TransactionView},
+    core::{Capacity, TransactionBuilder},
     packed::{CellDep, CellInput, CellOutput, WitnessArgs},
     prelude::{Entity, Pack},
     H160,
@@ -22,8 +21,8 @@ use common::types::axon_types::delegate::{
 use common::types::ckb_rpc_client::Cell;
 use common::types::smt::{Delegator as SmtDelegator, UserAmount};
 use common::types::tx_builder::{
-    Amount, DelegateItem, DelegateSmtTypeIds, Delegator, Epoch, InDelegateSmt, InStakeSmt,
-    NonTopDelegators, PrivateKey, Staker,
+    Amount, ChainContext, DelegateItem, DelegateSmtTypeIds, Delegator, Epoch, InDelegateSmt,
+    InStakeSmt, NonTopDelegators, SignerLock, Staker, UnsignedTx,
 };
 use common::utils::convert::{new_u128, to_ckb_h160, to_eth_h160, to_h160, to_usize};
 
@@ -40,7 +39,8 @@ use crate::ckb::helper::{
 
 pub struct DelegateSmtTxBuilder<'a, C: CkbRpc, D: DelegateSmtStorage> {
     ckb:                   &'a C,
-    kicker:                PrivateKey,
+    ctx:                   &'a ChainContext,
+    kicker:                SignerLock,
     current_epoch:         Epoch,
     type_ids:              DelegateSmtTypeIds,
     delegate_cells:        Vec<Cell>,
@@ -57,7 +57,8 @@ impl<'a, C: CkbRpc, D: DelegateSmtStorage> IDelegateSmtTxBuilder<'a, C, D>
 {
     fn new(
         ckb: &'a C,
-        kicker: PrivateKey,
+        ctx: &'a ChainContext,
+        kicker: SignerLock,
         current_epoch: Epoch,
         type_ids: DelegateSmtTypeIds,
         delegate_cells: Vec<Cell>,
@@ -65,6 +66,7 @@ impl<'a, C: CkbRpc, D: DelegateSmtStorage> IDelegateSmtTxBuilder<'a, C, D>
     ) -> Self {
         Self {
             ckb,
+            ctx,
             kicker,
             current_epoch,
             type_ids,
@@ -77,8 +79,8 @@ impl<'a, C: CkbRpc, D: DelegateSmtStorage> IDelegateSmtTxBuilder<'a, C, D>
         }
     }
 
-    async fn build_tx(mut self) -> Result<(TransactionView, NonTopDelegators)> {
-        let delegate_smt_type = Delegate::smt_type(&self.type_ids.delegate_smt_type_id);
+    async fn build_tx(mut self) -> Result<(UnsignedTx, NonTopDelegators)> {
+        let delegate_smt_type = Delegate::smt_type(self.ctx, &self.type_ids.delegate_smt_type_id);
         let delegate_smt_cell = Delegate::get_smt_cell(self.ckb, delegate_smt_type.clone()).await?;
 
         let mut inputs = vec![
@@ -95,7 +97,7 @@ impl<'a, C: CkbRpc, D: DelegateSmtStorage> IDelegateSmtTxBuilder<'a, C, D>
         let mut outputs = vec![
             // delegate smt cell
             CellOutput::new_builder()
-                .lock(AlwaysSuccess::lock())
+                .lock(AlwaysSuccess::lock(self.ctx))
                 .type_(Some(delegate_smt_type).pack())
                 .build_exact_capacity(Capacity::bytes(smt_data.len())?)?,
         ];
@@ -113,20 +115,21 @@ impl<'a, C: CkbRpc, D: DelegateSmtStorage> IDelegateSmtTxBuilder<'a, C, D>
         .await?;
 
         let mut cell_deps = vec![
-            Secp256k1::lock_dep(),
-            OmniEth::lock_dep(),
-            AlwaysSuccess::lock_dep(),
-            Xudt::type_dep(),
-            Delegate::lock_dep(),
-            Delegate::smt_type_dep(),
-            Checkpoint::cell_dep(self.ckb, &self.type_ids.checkpoint_type_id).await?,
-            Metadata::cell_dep(self.ckb, &self.type_ids.metadata_type_id).await?,
-            Withdraw::lock_dep(),
+            Secp256k1::lock_dep(self.ctx),
+            OmniEth::lock_dep(self.ctx),
+            AlwaysSuccess::lock_dep(self.ctx),
+            Xudt::type_dep(self.ctx),
+            Delegate::lock_dep(self.ctx),
+            Delegate::smt_type_dep(self.ctx),
+            Checkpoint::cell_dep(self.ckb, self.ctx, &self.type_ids.checkpoint_type_id).await?,
+            Metadata::cell_dep(self.ckb, self.ctx, &self.type_ids.metadata_type_id).await?,
+            Withdraw::lock_dep(self.ctx),
         ];
         cell_deps.extend(self.stake_cell_deps);
         cell_deps.extend(self.requirement_cell_deps);
+        self.kicker.add_cell_deps(&mut cell_deps);
 
-        witnesses.push(OmniEth::witness_placeholder().as_bytes()); // capacity provider lock
+        witnesses.push(self.kicker.witness_placeholder.as_bytes()); // capacity provider lock
 
         let tx = TransactionBuilder::default()
             .inputs(inputs)
@@ -136,20 +139,15 @@ impl<'a, C: CkbRpc, D: DelegateSmtStorage> IDelegateSmtTxBuilder<'a, C, D>
             .witnesses(witnesses.pack())
             .build();
 
-        let omni_eth = OmniEth::new(self.kicker.clone());
-        let kicker_lock = OmniEth::lock(&omni_eth.address()?);
-
         let mut tx = Tx::new(self.ckb, tx);
-        tx.balance(kicker_lock.clone()).await?;
+        tx.balance(self.ctx, self.kicker.lock.clone()).await?;
 
-        tx.sign(&omni_eth.signer()?, &ScriptGroup {
-            script:         kicker_lock,
-            group_type:     ScriptGroupType::Lock,
-            input_indices:  vec![tx.inner_ref().witnesses().len() - 1],
-            output_indices: vec![],
-        })?;
+        let unsigned_tx = UnsignedTx {
+            script_groups: vec![tx.lock_script_group(&self.kicker.lock).await?],
+            tx:            tx.inner(),
+        };
 
-        Ok((tx.inner(), statistics.non_top_delegators))
+        Ok((unsigned_tx, statistics.non_top_delegators))
     }
 }
 
@@ -167,7 +165,7 @@ impl<'a, C: CkbRpc, D: DelegateSmtStorage> DelegateSmtTxBuilder<'a, C, D> {
         outputs_data: &mut Vec<Bytes>,
         witnesses: &mut Vec<Bytes>,
     ) -> Result<()> {
-        let xudt = Xudt::type_(&self.type_ids.xudt_owner.pack());
+        let xudt = Xudt::type_(self.ctx, &self.type_ids.xudt_owner.pack());
 
         for (delegator, delegate_cell) in self.inputs_delegate_cells.iter() {
             // inputs: delegate AT cell
@@ -187,7 +185,8 @@ impl<'a, C: CkbRpc, D: DelegateSmtStorage> DelegateSmtTxBuilder<'a, C, D> {
                 old_total_delegate_amount,
             );
 
-            let withdraw_lock = Withdraw::lock(&self.type_ids.metadata_type_id, delegator);
+            let withdraw_lock =
+                Withdraw::lock(self.ctx, &self.type_ids.metadata_type_id, delegator);
             let mut total_withdraw_amount = 0;
 
             if statistics.withdraw_amounts.contains_key(delegator) {
@@ -259,7 +258,11 @@ impl<'a, C: CkbRpc, D: DelegateSmtStorage> DelegateSmtTxBuilder<'a, C, D> {
             // outputs: delegate AT cell
             outputs.push(
                 CellOutput::new_builder()
-                    .lock(Delegate::lock(&self.type_ids.metadata_type_id, delegator))
+                    .lock(Delegate::lock(
+                        self.ctx,
+                        &self.type_ids.metadata_type_id,
+                        delegator,
+                    ))
                     .type_(Some(xudt.clone()).pack())
                     .build_exact_capacity(Capacity::bytes(new_delegate_data.len())?)?,
             );
@@ -478,7 +481,7 @@ impl<'a, C: CkbRpc, D: DelegateSmtStorage> DelegateSmtTxBuilder<'a, C, D> {
 
         let delete_count = all_delegates.len() - maximum_delegators;
         let deleted_delegators = &all_delegates[..delete_count];
-        let xudt = Xudt::type_(&self.type_ids.xudt_owner.pack());
+        let xudt = Xudt::type_(self.ctx, &self.type_ids.xudt_owner.pack());
 
         for (delegator, amount) in deleted_delegators {
             log::info!(
@@ -510,7 +513,7 @@ impl<'a, C: CkbRpc, D: DelegateSmtStorage> DelegateSmtTxBuilder<'a, C, D> {
                 if !self.inputs_delegate_cells.contains_key(&tx_delegator) {
                     let cell = Delegate::get_cell(
                         self.ckb,
-                        Delegate::lock(&self.type_ids.metadata_type_id, &tx_delegator),
+                        Delegate::lock(self.ctx, &self.type_ids.metadata_type_id, &tx_delegator),
                         xudt.clone(),
                     )
                     .await?
@@ -571,6 +574,7 @@ impl<'a, C: CkbRpc, D: DelegateSmtStorage> DelegateSmtTxBuilder<'a, C, D> {
 
         let (requirement_type_id, stake_cell_outpoint) = Stake::get_delegate_requirement_type_id(
             self.ckb,
+            self.ctx,
             &self.type_ids.metadata_type_id,
             staker,
             &self.type_ids.xudt_owner,
@@ -585,7 +589,11 @@ impl<'a, C: CkbRpc, D: DelegateSmtStorage> DelegateSmtTxBuilder<'a, C, D> {
 
         let delegate_requirement_cell = Delegate::get_requirement_cell(
             self.ckb,
-            Delegate::requirement_type(&self.type_ids.metadata_type_id, &requirement_type_id),
+            Delegate::requirement_type(
+                self.ctx,
+                &self.type_ids.metadata_type_id,
+                &requirement_type_id,
+            ),
         )
         .await?;
 
@@ -642,7 +650,8 @@ impl<'a, C: CkbRpc, D: DelegateSmtStorage> DelegateSmtTxBuilder<'a, C, D> {
         }
 
         ADelegateSmtCellData::from(DelegateSmtCellData {
-            metadata_type_hash: Metadata::type_(&self.type_ids.metadata_type_id).calc_script_hash(),
+            metadata_type_hash: Metadata::type_(self.ctx, &self.type_ids.metadata_type_id)
+                .calc_script_hash(),
             smt_roots:          new_smt_roots.values().cloned().collect(),
         })
         .as_bytes()
@@ -0,0 +1,330 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+
+use anyhow::{anyhow, Result};
+use ckb_types::{
+    bytes::Bytes,
+    core::TransactionView,
+    packed::{CellOutput, OutPoint, Script},
+    prelude::*,
+    H160, H256,
+};
+
+use common::traits::ckb_rpc_client::CkbRpc;
+use common::types::axon_types::{
+    delegate::DelegateSmtCellData, reward::RewardSmtCellData, stake::StakeSmtCellData,
+};
+use common::types::tx_builder::{Amount, ChainContext, NonTopDelegators, NonTopStakers};
+use common::utils::convert::{new_u128, to_ckb_byte32, to_h160, to_h256};
+
+use crate::ckb::define::constants::TOKEN_BYTES;
+use crate::ckb::helper::ckb::cell_collector::get_live_cell;
+use crate::ckb::helper::{Delegate, Reward, Stake, Xudt};
+
+/// What a built transaction would do if it were sent. Nothing is sent, the
+/// inputs are resolved from the live cells.
+///
+/// The smt tx builders update the SMTs they are given while building, so the tx
+/// of a dry run is to be built on a scratch copy of the SMTs.
+#[derive(Clone, Debug, Default)]
+pub struct DryRun {
+    pub tx_hash:            H256,
+    pub consumed_cells:     Vec<CellSummary>,
+    pub created_cells:      Vec<CellSummary>,
+    pub token_deltas:       Vec<TokenDelta>,
+    // shannons
+    pub fee:                u64,
+    pub smt_roots:          Vec<SmtRootChange>,
+    pub non_top_stakers:    NonTopStakers,
+    pub non_top_delegators: NonTopDelegators,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CellSummary {
+    // none for the created cells
+    pub out_point:    Option<OutPoint>,
+    pub lock_hash:    H256,
+    pub type_hash:    Option<H256>,
+    pub capacity:     u64,
+    pub token_amount: Option<Amount>,
+}
+
+/// The tokens consumed from and created to a lock.
+#[derive(Clone, Debug, Default)]
+pub struct TokenDelta {
+    pub lock_hash: H256,
+    pub consumed:  Amount,
+    pub created:   Amount,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SmtRootChange {
+    pub smt:    &'static str,
+    // the delegate smt has a root per staker
+    pub staker: Option<H160>,
+    pub before: Option<H256>,
+    pub after:  Option<H256>,
+}
+
+impl DryRun {
+    pub async fn new(ckb: &impl CkbRpc, ctx: &ChainContext, tx: &TransactionView) -> Result<Self> {
+        let xudt = Xudt::type_(ctx, &ctx.type_ids.xudt_owner.pack());
+
+        let mut inputs = Vec::with_capacity(tx.inputs().len());
+        for input in tx.inputs().into_iter() {
+            let out_point = input.previous_output();
+            let cell = get_live_cell(ckb, out_point.clone(), true).await?;
+            let output: CellOutput = cell.output.into();
+            let data = cell
+                .data
+                .map(|d| d.content.into_bytes())
+                .unwrap_or_default();
+            inputs.push((Some(out_point), output, data));
+        }
+        let outputs = tx
+            .outputs_with_data_iter()
+            .map(|(output, data)| (None, output, data))
+            .collect::<Vec<_>>();
+
+        let consumed_cells = summarize(&inputs, &xudt);
+        let created_cells = summarize(&outputs, &xudt);
+
+        let inputs_capacity = consumed_cells.iter().map(|c| c.capacity).sum::<u64>();
+        let outputs_capacity = created_cells.iter().map(|c| c.capacity).sum::<u64>();
+        let fee = inputs_capacity
+            .checked_sub(outputs_capacity)
+            .ok_or_else(|| anyhow!("outputs capacity exceeds inputs capacity"))?;
+
+        Ok(Self {
+            tx_hash: tx.hash().unpack(),
+            token_deltas: token_deltas(&consumed_cells, &created_cells),
+            fee,
+            smt_roots: smt_roots(ctx, &inputs, &outputs),
+            consumed_cells,
+            created_cells,
+            ..Default::default()
+        })
+    }
+
+    pub fn with_non_top_stakers(mut self, stakers: NonTopStakers) -> Self {
+        self.non_top_stakers = stakers;
+        self
+    }
+
+    pub fn with_non_top_delegators(mut self, delegators: NonTopDelegators) -> Self {
+        self.non_top_delegators = delegators;
+        self
+    }
+}
+
+pub(crate) type ResolvedCell = (Option<OutPoint>, CellOutput, Bytes);
+
+pub(crate) fn summarize(cells: &[ResolvedCell], xudt: &Script) -> Vec<CellSummary> {
+    cells
+        .iter()
+        .map(|(out_point, output, data)| {
+            let type_ = output.type_().to_opt();
+            let is_token = type_.as_ref() == Some(xudt) && data.len() >= TOKEN_BYTES;
+
+            CellSummary {
+                out_point:    out_point.clone(),
+                lock_hash:    output.calc_lock_hash().unpack(),
+                type_hash:    type_.map(|t| t.calc_script_hash().unpack()),
+                capacity:     output.capacity().unpack(),
+                token_amount: is_token.then(|| new_u128(&data[..TOKEN_BYTES])),
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn token_deltas(consumed: &[CellSummary], created: &[CellSummary]) -> Vec<TokenDelta> {
+    let mut deltas: BTreeMap<H256, TokenDelta> = BTreeMap::new();
+
+    for cell in consumed.iter() {
+        if let Some(amount) = cell.token_amount {
+            token_delta(&mut deltas, &cell.lock_hash).consumed += amount;
+        }
+    }
+    for cell in created.iter() {
+        if let Some(amount) = cell.token_amount {
+            token_delta(&mut deltas, &cell.lock_hash).created += amount;
+        }
+    }
+
+    deltas.into_values().collect()
+}
+
+fn token_delta<'a>(
+    deltas: &'a mut BTreeMap<H256, TokenDelta>,
+    lock_hash: &H256,
+) -> &'a mut TokenDelta {
+    deltas
+        .entry(lock_hash.clone())
+        .or_insert_with(|| TokenDelta {
+            lock_hash: lock_hash.clone(),
+            ..Default::default()
+        })
+}
+
+pub(crate) fn smt_roots(
+    ctx: &ChainContext,
+    inputs: &[ResolvedCell],
+    outputs: &[ResolvedCell],
+) -> Vec<SmtRootChange> {
+    let type_ids = &ctx.type_ids;
+    let cell_data = |cells: &[ResolvedCell], type_: &Script| {
+        cells
+            .iter()
+            .find(|(_, output, _)| output.type_().to_opt().as_ref() == Some(type_))
+            .map(|(_, _, data)| data.clone())
+    };
+
+    let mut changes = vec![];
+
+    let stake_smt = Stake::smt_type(ctx, &type_ids.stake_smt_type_id);
+    let stake_root = |data: Bytes| {
+        to_h256(&to_ckb_byte32(
+            &StakeSmtCellData::new_unchecked(data).smt_root(),
+        ))
+    };
+    let before = cell_data(inputs, &stake_smt).map(stake_root);
+    let after = cell_data(outputs, &stake_smt).map(stake_root);
+    if before.is_some() || after.is_some() {
+        changes.push(SmtRootChange {
+            smt: "stake",
+            staker: None,
+            before,
+            after,
+        });
+    }
+
+    let reward_smt = Reward::smt_type(ctx, &type_ids.reward_smt_type_id);
+    let reward_root = |data: Bytes| {
+        to_h256(&to_ckb_byte32(
+            &RewardSmtCellData::new_unchecked(data).claim_smt_root(),
+        ))
+    };
+    let before = cell_data(inputs, &reward_smt).map(reward_root);
+    let after = cell_data(outputs, &reward_smt).map(reward_root);
+    if before.is_some() || after.is_some() {
+        changes.push(SmtRootChange {
+            smt: "reward",
+            staker: None,
+            before,
+            after,
+        });
+    }
+
+    let delegate_smt = Delegate::smt_type(ctx, &type_ids.delegate_smt_type_id);
+    let delegate_roots = |data: Bytes| {
+        DelegateSmtCellData::new_unchecked(data)
+            .smt_roots()
+            .into_iter()
+            .map(|r| (to_h160(&r.staker()), to_h256(&to_ckb_byte32(&r.root()))))
+            .collect::<BTreeMap<_, _>>()
+    };
+    let before = cell_data(inputs, &delegate_smt)
+        .map(delegate_roots)
+        .unwrap_or_default();
+    let mut after = cell_data(outputs, &delegate_smt)
+        .map(delegate_roots)
+        .unwrap_or_default();
+    for (staker, root) in before.into_iter() {
+        changes.push(SmtRootChange {
+            smt:    "delegate",
+            after:  after.remove(&staker),
+            staker: Some(staker),
+            before: Some(root),
+        });
+    }
+    for (staker, root) in after.into_iter() {
+        changes.push(SmtRootChange {
+            smt:    "delegate",
+            staker: Some(staker),
+            before: None,
+            after:  Some(root),
+        });
+    }
+
+    changes
+}
+
+impl Display for DryRun {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "tx: 0x{}, fee: {} shannons", self.tx_hash, self.fee)?;
+
+        writeln!(f, "consumed cells:")?;
+        for cell in self.consumed_cells.iter() {
+            writeln!(f, "  {}", cell)?;
+        }
+        writeln!(f, "created cells:")?;
+        for cell in self.created_cells.iter() {
+            writeln!(f, "  {}", cell)?;
+        }
+
+        writeln!(f, "token deltas:")?;
+        for delta in self.token_deltas.iter() {
+            writeln!(
+                f,
+                "  lock: 0x{}, consumed: {}, created: {}",
+                delta.lock_hash, delta.consumed, delta.created
+            )?;
+        }
+
+        writeln!(f, "smt roots:")?;
+        for root in self.smt_roots.iter() {
+            write!(f, "  {}", root.smt)?;
+            if let Some(staker) = &root.staker {
+                write!(f, " of 0x{}", staker)?;
+            }
+            writeln!(
+                f,
+                ": {} -> {}",
+                fmt_root(&root.before),
+                fmt_root(&root.after)
+            )?;
+        }
+
+        writeln!(f, "non top stakers:")?;
+        for (staker, in_smt) in self.non_top_stakers.iter() {
+            writeln!(f, "  0x{}, in stake smt: {}", staker, in_smt)?;
+        }
+        writeln!(f, "non top delegators:")?;
+        for (delegator, stakers) in self.non_top_delegators.iter() {
+            for (staker, in_smt) in stakers.iter() {
+                writeln!(
+                    f,
+                    "  0x{} of staker 0x{}, in delegate smt: {}",
+                    delegator, staker, in_smt
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for CellSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(out_point) = &self.out_point {
+            let tx_hash: H256 = out_point.tx_hash().unpack();
+            let index: u32 = out_point.index().unpack();
+            write!(f, "0x{}:{}, ", tx_hash, index)?;
+        }
+        write!(f, "lock: 0x{}", self.lock_hash)?;
+        if let Some(type_hash) = &self.type_hash {
+            write!(f, ", type: 0x{}", type_hash)?;
+        }
+        write!(f, ", capacity: {}", self.capacity)?;
+        if let Some(amount) = self.token_amount {
+            write!(f, ", token: {}", amount)?;
+        }
+        Ok(())
+    }
+}
+
+fn fmt_root(root: &Option<H256>) -> String {
+    root.as_ref()
+        .map(|r| format!("0x{}", r))
+        .unwrap_or_else(|| "none".to_string())
+}
@@ -1,5 +1,4 @@
 use anyhow::Result;
-use ckb_sdk::{ScriptGroup, ScriptGroupType};
 use ckb_types::{
     bytes::Bytes,
     core::{Capacity, TransactionBuilder, TransactionView},
@@ -11,20 +10,29 @@ use common::traits::ckb_rpc_client::CkbRpc;
 use common::types::tx_builder::*;
 
 use crate::ckb::helper::{OmniEth, Secp256k1, Tx};
+use crate::ckb::keystore::SignerKey;
+use crate::ckb::signer::{SighashSigner, TxSigner};
 
 use super::helper::sighash::Sighash;
 
 pub struct FaucetTxBuilder<'a, C: CkbRpc> {
-    ckb:        &'a C,
-    seeder_key: PrivateKey,
-    users:      Vec<(EthAddress, Amount)>,
+    ckb:    &'a C,
+    ctx:    &'a ChainContext,
+    seeder: SighashSigner,
+    users:  Vec<(EthAddress, Amount)>,
 }
 
 impl<'a, C: CkbRpc> FaucetTxBuilder<'a, C> {
-    pub fn new(ckb: &'a C, seeder_key: PrivateKey, stakers: Vec<(StakerEthAddr, Amount)>) -> Self {
+    pub fn new(
+        ckb: &'a C,
+        ctx: &'a ChainContext,
+        seeder_key: SignerKey,
+        stakers: Vec<(StakerEthAddr, Amount)>,
+    ) -> Self {
         Self {
             ckb,
-            seeder_key,
+            ctx,
+            seeder: SighashSigner::new(seeder_key),
             users: stakers,
         }
     }
@@ -37,13 +45,13 @@ impl<'a, C: CkbRpc> FaucetTxBuilder<'a, C> {
         for (user, ckb_bytes) in self.users.into_iter() {
             outputs.push(
                 CellOutput::new_builder()
-                    .lock(OmniEth::lock(&user))
+                    .lock(OmniEth::lock(self.ctx, &user))
                     .build_exact_capacity(Capacity::bytes(ckb_bytes as usize)?)?,
             );
             outputs_data.push(Bytes::default());
         }
 
-        let cell_deps = vec![Secp256k1::lock_dep()];
+        let cell_deps = vec![Secp256k1::lock_dep(self.ctx)];
 
         let witnesses = vec![
             Sighash::witness_placeholder().as_bytes(), // capacity provider lock
@@ -57,19 +65,12 @@ impl<'a, C: CkbRpc> FaucetTxBuilder<'a, C> {
             .witnesses(witnesses.pack())
             .build();
 
-        let sig_hash = Sighash::new(self.seeder_key.clone());
-        let sig_lock = sig_hash.lock()?;
+        let sig_lock = self.seeder.signer_lock(self.ctx)?.lock;
 
         let mut tx = Tx::new(self.ckb, tx);
-        tx.balance(sig_lock.clone()).await?;
+        tx.balance(self.ctx, sig_lock.clone()).await?;
 
-        tx.sign(&sig_hash.signer()?, &ScriptGroup {
-            script:         sig_lock.clone(),
-            group_type:     ScriptGroupType::Lock,
-            input_indices:  vec![0],
-            output_indices: vec![],
-        })?;
-
-        Ok(tx.inner())
+        let script_groups = vec![tx.lock_script_group(&sig_lock).await?];
+        self.seeder.sign(tx.inner(), &script_groups).await
     }
 }
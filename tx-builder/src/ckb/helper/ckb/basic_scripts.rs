@@ -2,101 +2,37 @@ use bytes::Bytes;
 use ckb_hash::new_blake2b;
 use ckb_sdk::constants::TYPE_ID_CODE_HASH;
 use ckb_types::core::ScriptHashType;
-use ckb_types::packed::{CellDep, CellInput, OutPoint, Script};
+use ckb_types::packed::{CellDep, CellInput, Script};
 use ckb_types::prelude::{Builder, Entity, Pack};
 use ckb_types::H256;
 
-use common::types::tx_builder::NetworkType;
+use common::types::tx_builder::ChainContext;
 
-use crate::ckb::define::scripts::*;
-use crate::ckb::NETWORK_TYPE;
-use crate::{cell_dep, out_point, script};
+use crate::script;
 
 pub struct AlwaysSuccess;
 pub struct Secp256k1;
 pub struct TypeId;
 
 impl AlwaysSuccess {
-    pub fn lock() -> Script {
-        match **NETWORK_TYPE.load() {
-            NetworkType::Mainnet => script!(
-                &ALWAYS_SUCCESS_LOCK_MAINNET.code_hash,
-                ALWAYS_SUCCESS_LOCK_MAINNET.hash_type,
-                bytes::Bytes::default()
-            ),
-            NetworkType::Testnet => script!(
-                &ALWAYS_SUCCESS_LOCK_TESTNET.code_hash,
-                ALWAYS_SUCCESS_LOCK_TESTNET.hash_type,
-                bytes::Bytes::default()
-            ),
-            NetworkType::Devnet => script!(
-                &ALWAYS_SUCCESS_LOCK_DEVNET.code_hash,
-                ALWAYS_SUCCESS_LOCK_DEVNET.hash_type,
-                bytes::Bytes::default()
-            ),
-        }
+    pub fn lock(ctx: &ChainContext) -> Script {
+        ctx.scripts
+            .always_success_lock
+            .script(bytes::Bytes::default())
     }
 
-    pub fn lock_dep() -> CellDep {
-        match **NETWORK_TYPE.load() {
-            NetworkType::Mainnet => cell_dep!(
-                &ALWAYS_SUCCESS_LOCK_MAINNET.tx_hash,
-                ALWAYS_SUCCESS_LOCK_MAINNET.index,
-                ALWAYS_SUCCESS_LOCK_MAINNET.dep_type
-            ),
-            NetworkType::Testnet => cell_dep!(
-                &ALWAYS_SUCCESS_LOCK_TESTNET.tx_hash,
-                ALWAYS_SUCCESS_LOCK_TESTNET.index,
-                ALWAYS_SUCCESS_LOCK_TESTNET.dep_type
-            ),
-            NetworkType::Devnet => cell_dep!(
-                &ALWAYS_SUCCESS_LOCK_DEVNET.tx_hash,
-                ALWAYS_SUCCESS_LOCK_DEVNET.index,
-                ALWAYS_SUCCESS_LOCK_DEVNET.dep_type
-            ),
-        }
+    pub fn lock_dep(ctx: &ChainContext) -> CellDep {
+        ctx.scripts.always_success_lock.cell_dep()
     }
 }
 
 impl Secp256k1 {
-    pub fn lock_dep() -> CellDep {
-        match **NETWORK_TYPE.load() {
-            NetworkType::Mainnet => cell_dep!(
-                &SECP2561_BLAKE160_MAINNET.tx_hash,
-                SECP2561_BLAKE160_MAINNET.index,
-                SECP2561_BLAKE160_MAINNET.dep_type
-            ),
-            NetworkType::Testnet => cell_dep!(
-                &SECP2561_BLAKE160_TESTNET.tx_hash,
-                SECP2561_BLAKE160_TESTNET.index,
-                SECP2561_BLAKE160_TESTNET.dep_type
-            ),
-            NetworkType::Devnet => cell_dep!(
-                &SECP2561_BLAKE160_DEVNET.tx_hash,
-                SECP2561_BLAKE160_DEVNET.index,
-                SECP2561_BLAKE160_DEVNET.dep_type
-            ),
-        }
+    pub fn lock_dep(ctx: &ChainContext) -> CellDep {
+        ctx.scripts.secp256k1_blake160.cell_dep()
     }
 
-    pub fn lock(args: Bytes) -> Script {
-        match **NETWORK_TYPE.load() {
-            NetworkType::Mainnet => script!(
-                &SECP2561_BLAKE160_MAINNET.code_hash,
-                SECP2561_BLAKE160_MAINNET.hash_type,
-                args
-            ),
-            NetworkType::Testnet => script!(
-                &SECP2561_BLAKE160_TESTNET.code_hash,
-                SECP2561_BLAKE160_TESTNET.hash_type,
-                args
-            ),
-            NetworkType::Devnet => script!(
-                &SECP2561_BLAKE160_DEVNET.code_hash,
-                SECP2561_BLAKE160_DEVNET.hash_type,
-                args
-            ),
-        }
+    pub fn lock(ctx: &ChainContext, args: Bytes) -> Script {
+        ctx.scripts.secp256k1_blake160.script(args)
     }
 }
 
@@ -6,7 +6,9 @@ use ckb_types::{
 };
 
 use common::traits::ckb_rpc_client::CkbRpc;
-use common::types::ckb_rpc_client::{Cell, Order, ScriptType, SearchKey, SearchKeyFilter};
+use common::types::ckb_rpc_client::{
+    Cell, IndexerScriptSearchMode, Order, ScriptType, SearchKey, SearchKeyFilter,
+};
 
 use crate::ckb::define::error::*;
 
@@ -71,6 +73,49 @@ pub async fn get_cell_by_scripts(
     }
 }
 
+pub async fn get_cells_by_lock_prefix(
+    ckb_rpc: &impl CkbRpc,
+    lock_prefix: Script,
+    type_: Script,
+) -> Result<Vec<Cell>> {
+    let search_key = SearchKey {
+        script:      lock_prefix.into(),
+        script_type: ScriptType::Lock,
+        filter:      Some(SearchKeyFilter {
+            script: Some(type_.into()),
+            ..Default::default()
+        }),
+
+        script_search_mode:   Some(IndexerScriptSearchMode::Prefix),
+        with_data:            Some(true),
+        group_by_transaction: None,
+    };
+
+    get_all_cells(ckb_rpc, search_key).await
+}
+
+pub async fn get_all_cells(ckb_rpc: &impl CkbRpc, search_key: SearchKey) -> Result<Vec<Cell>> {
+    let mut cells = vec![];
+    let mut after = None;
+    let limit = Uint32::from(100);
+
+    loop {
+        let result = ckb_rpc
+            .get_cells(search_key.clone(), Order::Asc, limit, after)
+            .await?;
+        if result.objects.is_empty() {
+            break;
+        }
+        cells.extend(result.objects);
+        if result.last_cursor.is_empty() {
+            break;
+        }
+        after = Some(result.last_cursor);
+    }
+
+    Ok(cells)
+}
+
 pub async fn get_live_cells(
     ckb_rpc: &impl CkbRpc,
     search_key: SearchKey,
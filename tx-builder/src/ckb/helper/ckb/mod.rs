@@ -3,10 +3,12 @@ pub mod cell_collector;
 pub mod omni;
 pub mod sighash;
 pub mod tx;
+pub mod verifier;
 pub mod xudt;
 
 pub use basic_scripts::{AlwaysSuccess, Secp256k1, TypeId};
 pub use omni::OmniEth;
 pub use sighash::Sighash;
 pub use tx::Tx;
+pub use verifier::Verification;
 pub use xudt::Xudt;
@@ -1,91 +1,62 @@
 use anyhow::Result;
 use ckb_hash::blake2b_256;
-use ckb_sdk::traits::SecpCkbRawKeySigner;
 use ckb_sdk::unlock::SecpSighashScriptSigner;
-use ckb_sdk::{Address, SECP256K1};
+use ckb_sdk::Address;
 use ckb_types::core::ScriptHashType;
-use ckb_types::packed::{CellDep, OutPoint, Script, WitnessArgs};
+use ckb_types::packed::{CellDep, Script, WitnessArgs};
 use ckb_types::prelude::{Builder, Pack};
-use ckb_types::H256;
+use ckb_types::{H160, H256};
 use molecule::prelude::Entity;
+use zeroize::Zeroize;
 
-use common::types::tx_builder::NetworkType;
+use common::types::tx_builder::ChainContext;
 
-use crate::ckb::define::scripts::{
-    SECP2561_BLAKE160_DEVNET, SECP2561_BLAKE160_MAINNET, SECP2561_BLAKE160_TESTNET,
-};
-use crate::ckb::NETWORK_TYPE;
-use crate::{cell_dep, out_point};
+use crate::ckb::address_network;
+use crate::ckb::keystore::{KeySigner, SignerKey};
 
 pub struct Sighash {
     pub private_key: H256,
 }
 
+impl Drop for Sighash {
+    fn drop(&mut self) {
+        self.private_key.0.zeroize();
+    }
+}
+
 impl Sighash {
     pub fn new(private_key: H256) -> Self {
         Self { private_key }
     }
 
-    pub fn address(&self) -> Result<Address> {
-        let sender_key = secp256k1::SecretKey::from_slice(self.private_key.as_bytes())?;
-        let pubkey = secp256k1::PublicKey::from_secret_key(&SECP256K1, &sender_key);
+    pub fn address(&self, ctx: &ChainContext) -> Result<Address> {
+        let pubkey = SignerKey::from(self.private_key.clone()).pubkey()?;
         let hash160 = blake2b_256(&pubkey.serialize()[..])[0..20].to_vec();
 
-        Ok(match **NETWORK_TYPE.load() {
-            NetworkType::Mainnet => {
-                let address_payload = ckb_sdk::AddressPayload::new_full(
-                    ScriptHashType::Type,
-                    SECP2561_BLAKE160_MAINNET.code_hash.clone().pack(),
-                    hash160.into(),
-                );
-                Address::new(ckb_sdk::NetworkType::Mainnet, address_payload, true)
-            }
-            NetworkType::Testnet => {
-                let address_payload = ckb_sdk::AddressPayload::new_full(
-                    ScriptHashType::Type,
-                    SECP2561_BLAKE160_TESTNET.code_hash.clone().pack(),
-                    hash160.into(),
-                );
-                Address::new(ckb_sdk::NetworkType::Testnet, address_payload, true)
-            }
-            NetworkType::Devnet => {
-                let address_payload = ckb_sdk::AddressPayload::new_full(
-                    ScriptHashType::Type,
-                    SECP2561_BLAKE160_DEVNET.code_hash.clone().pack(),
-                    hash160.into(),
-                );
-                Address::new(ckb_sdk::NetworkType::Dev, address_payload, true)
-            }
-        })
+        let address_payload = ckb_sdk::AddressPayload::new_full(
+            ScriptHashType::Type,
+            ctx.scripts.secp256k1_blake160.code_hash.pack(),
+            hash160.into(),
+        );
+        Ok(Address::new(address_network(ctx), address_payload, true))
+    }
+
+    pub fn lock(&self, ctx: &ChainContext) -> Result<Script> {
+        Ok(Script::from(&self.address(ctx)?))
     }
 
-    pub fn lock(&self) -> Result<Script> {
-        Ok(Script::from(&self.address()?))
+    pub fn pubkey_hash_lock(ctx: &ChainContext, pubkey_hash: &H160) -> Script {
+        ctx.scripts
+            .secp256k1_blake160
+            .script(bytes::Bytes::copy_from_slice(pubkey_hash.as_bytes()))
     }
 
-    pub fn lock_dep() -> CellDep {
-        match **NETWORK_TYPE.load() {
-            NetworkType::Mainnet => cell_dep!(
-                &SECP2561_BLAKE160_MAINNET.tx_hash,
-                SECP2561_BLAKE160_MAINNET.index,
-                SECP2561_BLAKE160_MAINNET.dep_type
-            ),
-            NetworkType::Testnet => cell_dep!(
-                &SECP2561_BLAKE160_TESTNET.tx_hash,
-                SECP2561_BLAKE160_TESTNET.index,
-                SECP2561_BLAKE160_TESTNET.dep_type
-            ),
-            NetworkType::Devnet => cell_dep!(
-                &SECP2561_BLAKE160_DEVNET.tx_hash,
-                SECP2561_BLAKE160_DEVNET.index,
-                SECP2561_BLAKE160_DEVNET.dep_type
-            ),
-        }
+    pub fn lock_dep(ctx: &ChainContext) -> CellDep {
+        ctx.scripts.secp256k1_blake160.cell_dep()
     }
 
     pub fn signer(&self) -> Result<SecpSighashScriptSigner> {
-        let key = secp256k1::SecretKey::from_slice(self.private_key.as_bytes())?;
-        let signer = SecpCkbRawKeySigner::new_with_secret_keys(vec![key]);
+        let signer = KeySigner::new(vec![SignerKey::from(self.private_key.clone())])?;
         Ok(SecpSighashScriptSigner::new(Box::new(signer)))
     }
 
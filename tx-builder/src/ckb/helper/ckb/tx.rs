@@ -4,7 +4,7 @@ use ckb_sdk::types::ScriptGroup;
 use ckb_sdk::unlock::ScriptSigner;
 use ckb_types::{
     core::{Capacity, TransactionView},
-    packed::{Byte32, Bytes, CellInput, CellOutput, Script},
+    packed::{Byte32, Bytes, CellInput, CellOutput, Script, WitnessArgs},
     prelude::*,
     H256,
 };
@@ -12,11 +12,14 @@ use linked_hash_map::LinkedHashMap;
 
 use common::traits::ckb_rpc_client::CkbRpc;
 use common::types::ckb_rpc_client::{ScriptType, SearchKey};
+use common::types::tx_builder::{ChainContext, EthAddress, FeeStrategy};
 use common::types::TransactionWithStatusResponse;
 
 use crate::ckb::define::constants::FEE_RATE;
-use crate::ckb::define::error::CkbTxErr;
+use crate::ckb::define::error::{CkbTxErr, CkbTxResult};
 use crate::ckb::helper::ckb::cell_collector::{get_live_cell, get_live_cells};
+use crate::ckb::helper::ckb::verifier::{verify_tx, Verification};
+use crate::ckb::helper::ckb::{OmniEth, Secp256k1, Sighash};
 
 const KB: u64 = 1000;
 
@@ -59,16 +62,49 @@ impl<'a, C: CkbRpc> Tx<'a, C> {
     /// There is no pure CKB cell in the input and output of the transaction.
     /// Collect CKB cells and add them to the input of the transaction.
     /// Add a CKB change cell to the output of the transaction.
-    pub async fn balance(&mut self, capacity_provider: Script) -> Result<()> {
+    ///
+    /// The fee is paid for the size of the signed transaction, so an empty
+    /// witness of the capacity provider is filled with a placeholder. If the
+    /// change can not pay the fee, the CKB cells are collected again for at
+    /// most `ctx.fee.balance_retry` times.
+    pub async fn balance(&mut self, ctx: &ChainContext, capacity_provider: Script) -> Result<()> {
+        let fee_rate = self.fee_rate(&ctx.fee.strategy).await?;
+        let inputs = self.tx.inputs().into_iter().collect::<Vec<_>>();
+
         let outputs_capacity = self.add_ckb_to_outputs(capacity_provider.clone())?;
+        self.fill_witness_placeholder(ctx, &capacity_provider, inputs.len());
 
-        let inputs_capacity = self
-            .add_ckb_to_intputs(capacity_provider.clone(), outputs_capacity)
-            .await?;
+        let mut required_capacity = outputs_capacity;
+        let mut try_count = 0;
 
-        self.change_ckb(inputs_capacity, outputs_capacity)?;
+        loop {
+            let inputs_capacity = self
+                .add_ckb_to_intputs(capacity_provider.clone(), required_capacity)
+                .await?;
 
-        Ok(())
+            match self.change_ckb(inputs_capacity, outputs_capacity, fee_rate) {
+                Err(CkbTxErr::InsufficientCapacity {
+                    outputs_capacity: needed_capacity,
+                    ..
+                }) if try_count < ctx.fee.balance_retry => {
+                    try_count += 1;
+                    log::warn!(
+                        "[tx] change can not pay the fee, needed capacity: {}, try count: {}",
+                        needed_capacity,
+                        try_count
+                    );
+
+                    // The new inputs enlarge the tx, the fee is counted twice to cover them.
+                    required_capacity = needed_capacity + (needed_capacity - outputs_capacity);
+                    self.tx = self
+                        .tx
+                        .as_advanced_builder()
+                        .set_inputs(inputs.clone())
+                        .build();
+                }
+                res => return res.map_err(Into::into),
+            }
+        }
     }
 
     pub fn sign(&mut self, signer: &impl ScriptSigner, script_group: &ScriptGroup) -> Result<()> {
@@ -76,6 +112,12 @@ impl<'a, C: CkbRpc> Tx<'a, C> {
         Ok(())
     }
 
+    /// Runs the scripts locally before the transaction is sent, the failed
+    /// script group is reported with its exit code.
+    pub async fn verify(&self, max_cycles: u64) -> Result<Verification> {
+        verify_tx(self.rpc, &self.tx, max_cycles).await
+    }
+
     pub async fn send(&mut self) -> Result<String> {
         let outputs_validator = Some(OutputsValidator::Passthrough);
         self.tx_hash = self
@@ -120,7 +162,11 @@ impl<'a, C: CkbRpc> Tx<'a, C> {
                 .rpc
                 .get_live_cell(input.previous_output().into(), false)
                 .await?;
-            let output: CellOutput = output.cell.unwrap().output.into();
+            let output: CellOutput = output
+                .cell
+                .ok_or_else(|| CkbTxErr::CellNotFound(input.previous_output().to_string()))?
+                .output
+                .into();
 
             let lock_group_entry = lock_groups
                 .entry(output.calc_lock_hash())
@@ -150,6 +196,16 @@ impl<'a, C: CkbRpc> Tx<'a, C> {
         })
     }
 
+    /// The lock group of `lock` with all of its inputs, including the ones
+    /// added by the balancing.
+    pub async fn lock_script_group(&self, lock: &Script) -> Result<ScriptGroup> {
+        let mut script_groups = self.gen_script_group().await?;
+        Ok(script_groups
+            .lock_groups
+            .remove(&lock.calc_script_hash())
+            .ok_or_else(|| CkbTxErr::CellNotFound(lock.to_string()))?)
+    }
+
     fn add_ckb_to_outputs(&mut self, capacity_provider: Script) -> Result<u64> {
         let mut outputs = self.tx.outputs().into_iter().collect::<Vec<_>>();
         let mut outputs_data = self.tx.outputs_data().into_iter().collect::<Vec<_>>();
@@ -203,16 +259,20 @@ impl<'a, C: CkbRpc> Tx<'a, C> {
         Ok(inputs_capacity)
     }
 
-    fn change_ckb(&mut self, inputs_capacity: u64, outputs_capacity: u64) -> Result<()> {
+    fn change_ckb(
+        &mut self,
+        inputs_capacity: u64,
+        outputs_capacity: u64,
+        fee_rate: u64,
+    ) -> CkbTxResult<()> {
         let tx_size = self.tx.data().as_reader().serialized_size_in_block();
-        let needed_capacity = outputs_capacity + Self::fee(tx_size).as_u64();
+        let needed_capacity = outputs_capacity + Self::fee(tx_size, fee_rate).as_u64();
 
         if inputs_capacity < needed_capacity {
             return Err(CkbTxErr::InsufficientCapacity {
                 inputs_capacity,
                 outputs_capacity: needed_capacity,
-            }
-            .into());
+            });
         }
 
         let change = inputs_capacity - needed_capacity;
@@ -235,11 +295,63 @@ impl<'a, C: CkbRpc> Tx<'a, C> {
         Ok(())
     }
 
-    fn fee(tx_size: usize) -> Capacity {
-        let fee = FEE_RATE.saturating_mul(tx_size as u64) / KB;
+    fn fee(tx_size: usize, fee_rate: u64) -> Capacity {
+        let fee = fee_rate.saturating_mul(tx_size as u64) / KB;
         Capacity::shannons(fee)
     }
 
+    pub(crate) async fn fee_rate(&self, fee_strategy: &FeeStrategy) -> Result<u64> {
+        Ok(match fee_strategy {
+            FeeStrategy::Fixed(fee_rate) => *fee_rate,
+            FeeStrategy::Estimated => self.estimate_fee_rate().await?,
+            // a cap lower than the minimum fee rate would make the tx rejected
+            FeeStrategy::Capped(max_fee_rate) => self
+                .estimate_fee_rate()
+                .await?
+                .min((*max_fee_rate).max(FEE_RATE)),
+        })
+    }
+
+    // The median fee rate of the recent blocks, which is never lower than the
+    // minimum fee rate of the nodes.
+    async fn estimate_fee_rate(&self) -> Result<u64> {
+        let statistics = self.rpc.get_fee_rate_statistics(None).await?;
+        Ok(statistics
+            .map(|s| s.median.value())
+            .unwrap_or(FEE_RATE)
+            .max(FEE_RATE))
+    }
+
+    // The first collected CKB cell is the first input of the capacity provider
+    // lock group unless the builder has put one before it, whose witness is
+    // filled already.
+    fn fill_witness_placeholder(
+        &mut self,
+        ctx: &ChainContext,
+        capacity_provider: &Script,
+        index: usize,
+    ) {
+        let placeholder = match witness_placeholder(ctx, capacity_provider) {
+            Some(placeholder) => placeholder,
+            None => return,
+        };
+
+        let mut witnesses = self.tx.witnesses().into_iter().collect::<Vec<_>>();
+        if witnesses.len() <= index {
+            witnesses.resize(index + 1, Bytes::default());
+        }
+        if !witnesses[index].is_empty() {
+            return;
+        }
+        witnesses[index] = placeholder.as_bytes().pack();
+
+        self.tx = self
+            .tx
+            .as_advanced_builder()
+            .set_witnesses(witnesses)
+            .build();
+    }
+
     async fn calc_inputs_capacity(&self, inputs: &[CellInput]) -> Result<u64> {
         let mut inputs_capacity: u64 = 0;
         for input in inputs.iter() {
@@ -260,3 +372,13 @@ impl<'a, C: CkbRpc> Tx<'a, C> {
             .sum::<u64>()
     }
 }
+
+fn witness_placeholder(ctx: &ChainContext, lock: &Script) -> Option<WitnessArgs> {
+    if lock.code_hash() == OmniEth::lock(ctx, &EthAddress::default()).code_hash() {
+        Some(OmniEth::witness_placeholder())
+    } else if lock.code_hash() == Secp256k1::lock(ctx, Default::default()).code_hash() {
+        Some(Sighash::witness_placeholder())
+    } else {
+        None
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use ckb_chain_spec::consensus::ConsensusBuilder;
+use ckb_script::{ScriptError, ScriptGroupType, TransactionScriptsVerifier, TxVerifyEnv};
+use ckb_traits::{CellDataProvider, HeaderProvider};
+use ckb_types::{
+    bytes::Bytes,
+    core::{
+        cell::{CellMeta, CellMetaBuilder, ResolvedTransaction},
+        hardfork::HardForkSwitch,
+        Cycle, DepType, HeaderBuilder, HeaderView, TransactionView,
+    },
+    packed::{Byte32, CellOutput, OutPoint, OutPointVec},
+    prelude::*,
+    H256,
+};
+
+use common::traits::ckb_rpc_client::CkbRpc;
+
+use crate::ckb::define::error::CkbTxErr;
+use crate::ckb::helper::ckb::cell_collector::get_live_cell;
+
+/// The cycles of a script group which passed the verification.
+#[derive(Clone, Debug)]
+pub struct ScriptGroupCycles {
+    pub group_type:  ScriptGroupType,
+    pub script_hash: H256,
+    pub cycles:      Cycle,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Verification {
+    pub cycles: Cycle,
+    pub groups: Vec<ScriptGroupCycles>,
+}
+
+/// Runs the scripts of the transaction locally, the inputs and the cell deps
+/// are resolved from the live cells. Every script group is verified on its
+/// own, the first failed one is returned as
+/// [`CkbTxErr::ScriptVerification`].
+pub async fn verify_tx(
+    ckb: &impl CkbRpc,
+    tx: &TransactionView,
+    max_cycles: Cycle,
+) -> Result<Verification> {
+    if !tx.header_deps().is_empty() {
+        return Err(anyhow!(
+            "header deps are not supported by the local verifier"
+        ));
+    }
+
+    let mut loader = LiveCells::default();
+
+    let mut resolved_inputs = Vec::with_capacity(tx.inputs().len());
+    for input in tx.inputs().into_iter() {
+        resolved_inputs.push(loader.resolve(ckb, input.previous_output()).await?);
+    }
+
+    let mut resolved_cell_deps = vec![];
+    let mut resolved_dep_groups = vec![];
+    for cell_dep in tx.cell_deps().into_iter() {
+        let cell = loader.resolve(ckb, cell_dep.out_point()).await?;
+
+        if cell_dep.dep_type() == DepType::DepGroup.into() {
+            let out_points =
+                OutPointVec::from_slice(&cell.mem_cell_data.clone().unwrap_or_default())
+                    .map_err(|e| anyhow!("invalid dep group {}: {}", cell_dep.out_point(), e))?;
+            for out_point in out_points.into_iter() {
+                resolved_cell_deps.push(loader.resolve(ckb, out_point).await?);
+            }
+            resolved_dep_groups.push(cell);
+        } else {
+            resolved_cell_deps.push(cell);
+        }
+    }
+
+    let rtx = ResolvedTransaction {
+        transaction: tx.clone(),
+        resolved_cell_deps,
+        resolved_inputs,
+        resolved_dep_groups,
+    };
+
+    // The chain is regarded as having activated all the hardforks.
+    let hardfork_switch = HardForkSwitch::new_without_any_enabled()
+        .as_builder()
+        .rfc_0028(0)
+        .rfc_0029(0)
+        .rfc_0030(0)
+        .rfc_0031(0)
+        .rfc_0032(0)
+        .rfc_0036(0)
+        .rfc_0038(0)
+        .build()
+        .map_err(|e| anyhow!("invalid hardfork switch: {}", e))?;
+    let consensus = ConsensusBuilder::default()
+        .hardfork_switch(hardfork_switch)
+        .build();
+    let tip = HeaderBuilder::default().number(0.pack()).build();
+    let tx_env = TxVerifyEnv::new_submit(&tip);
+
+    let verifier = TransactionScriptsVerifier::new(&rtx, &consensus, &loader, &tx_env);
+
+    let mut verification = Verification::default();
+    for (script_hash, group) in verifier.groups() {
+        let remaining_cycles = max_cycles.saturating_sub(verification.cycles);
+        let cycles = verifier
+            .verify_single(group.group_type, script_hash, remaining_cycles)
+            .map_err(|e| script_err(group.group_type, script_hash, e))?;
+
+        verification.cycles += cycles;
+        verification.groups.push(ScriptGroupCycles {
+            group_type: group.group_type,
+            script_hash: script_hash.unpack(),
+            cycles,
+        });
+    }
+
+    Ok(verification)
+}
+
+fn script_err(group_type: ScriptGroupType, script_hash: &Byte32, e: ScriptError) -> CkbTxErr {
+    let exit_code = match &e {
+        ScriptError::ValidationFailure(_, code) => Some(*code),
+        _ => None,
+    };
+
+    CkbTxErr::ScriptVerification {
+        group_type: format!("{:?}", group_type),
+        script_hash: script_hash.unpack(),
+        exit_code,
+        reason: e.to_string(),
+    }
+}
+
+// The cells read by the verifier, they are all fetched before the verification.
+#[allow(clippy::mutable_key_type)]
+#[derive(Clone, Default)]
+struct LiveCells {
+    cells: HashMap<OutPoint, (CellOutput, Bytes)>,
+}
+
+impl LiveCells {
+    async fn resolve(&mut self, ckb: &impl CkbRpc, out_point: OutPoint) -> Result<CellMeta> {
+        if !self.cells.contains_key(&out_point) {
+            let cell = get_live_cell(ckb, out_point.clone(), true).await?;
+            let data = cell
+                .data
+                .map(|d| d.content.into_bytes())
+                .unwrap_or_default();
+            self.cells
+                .insert(out_point.clone(), (cell.output.into(), data));
+        }
+
+        let (output, data) = self.cells[&out_point].clone();
+        Ok(CellMetaBuilder::from_cell_output(output, data)
+            .out_point(out_point)
+            .build())
+    }
+}
+
+impl CellDataProvider for LiveCells {
+    fn get_cell_data(&self, out_point: &OutPoint) -> Option<Bytes> {
+        self.cells.get(out_point).map(|(_, data)| data.clone())
+    }
+
+    fn get_cell_data_hash(&self, out_point: &OutPoint) -> Option<Byte32> {
+        self.cells
+            .get(out_point)
+            .map(|(_, data)| CellOutput::calc_data_hash(data))
+    }
+}
+
+impl HeaderProvider for LiveCells {
+    fn get_header(&self, _hash: &Byte32) -> Option<HeaderView> {
+        None
+    }
+}
@@ -1,59 +1,24 @@
 use anyhow::Result;
 use ckb_jsonrpc_types::Uint32;
-use ckb_types::packed::{Byte32, CellDep, OutPoint, Script};
-use ckb_types::prelude::{Builder, Entity, Pack};
+use ckb_types::packed::{Byte32, CellDep, Script};
+use ckb_types::prelude::Entity;
 
 use common::traits::ckb_rpc_client::CkbRpc;
 use common::types::ckb_rpc_client::{Cell, Order, ScriptType, SearchKey, SearchKeyFilter};
-use common::types::tx_builder::{Amount, NetworkType};
+use common::types::tx_builder::{Amount, ChainContext};
 use common::utils::convert::*;
 
 use crate::ckb::define::constants::TOKEN_BYTES;
-use crate::ckb::define::scripts::*;
-use crate::ckb::NETWORK_TYPE;
-use crate::{cell_dep, out_point, script};
 
 pub struct Xudt;
 
 impl Xudt {
-    pub fn type_(owner_lock_hash: &Byte32) -> Script {
-        match **NETWORK_TYPE.load() {
-            NetworkType::Mainnet => script!(
-                &XUDT_TYPE_MAINNET.code_hash,
-                XUDT_TYPE_MAINNET.hash_type,
-                owner_lock_hash.as_bytes()
-            ),
-            NetworkType::Testnet => script!(
-                &XUDT_TYPE_TESTNET.code_hash,
-                XUDT_TYPE_TESTNET.hash_type,
-                owner_lock_hash.as_bytes()
-            ),
-            NetworkType::Devnet => script!(
-                &XUDT_TYPE_DEVNET.code_hash,
-                XUDT_TYPE_DEVNET.hash_type,
-                owner_lock_hash.as_bytes()
-            ),
-        }
+    pub fn type_(ctx: &ChainContext, owner_lock_hash: &Byte32) -> Script {
+        ctx.scripts.xudt_type.script(owner_lock_hash.as_bytes())
     }
 
-    pub fn type_dep() -> CellDep {
-        match **NETWORK_TYPE.load() {
-            NetworkType::Mainnet => cell_dep!(
-                &XUDT_TYPE_MAINNET.tx_hash,
-                XUDT_TYPE_MAINNET.index,
-                XUDT_TYPE_MAINNET.dep_type
-            ),
-            NetworkType::Testnet => cell_dep!(
-                &XUDT_TYPE_TESTNET.tx_hash,
-                XUDT_TYPE_TESTNET.index,
-                XUDT_TYPE_TESTNET.dep_type
-            ),
-            NetworkType::Devnet => cell_dep!(
-                &XUDT_TYPE_DEVNET.tx_hash,
-                XUDT_TYPE_DEVNET.index,
-                XUDT_TYPE_DEVNET.dep_type
-            ),
-        }
+    pub fn type_dep(ctx: &ChainContext) -> CellDep {
+        ctx.scripts.xudt_type.cell_dep()
     }
 
     pub async fn collect(
@@ -1,6 +1,6 @@
 use anyhow::Result;
 use bytes::Bytes;
-use ckb_types::packed::{CellDep, OutPoint, Script, WitnessArgs};
+use ckb_types::packed::{CellDep, Script, WitnessArgs};
 use ckb_types::prelude::{Builder, Entity, Pack};
 use ckb_types::{H160, H256};
 
@@ -10,71 +10,42 @@ use common::types::axon_types::delegate::{
     DelegateSmtWitness as ADelegateSmtWitness,
 };
 use common::types::ckb_rpc_client::Cell;
-use common::types::tx_builder::{DelegateItem, NetworkType};
+use common::types::tx_builder::{ChainContext, DelegateItem};
 use common::utils::convert::*;
 
-use crate::ckb::define::scripts::*;
 use crate::ckb::define::types::{DelegateSmtUpdateInfo, DelegateSmtWitness, StakeGroupInfo};
-use crate::ckb::helper::ckb::cell_collector::{get_cell_by_scripts, get_cell_by_type};
+use crate::ckb::helper::ckb::cell_collector::{
+    get_cell_by_scripts, get_cell_by_type, get_cells_by_lock_prefix,
+};
 use crate::ckb::helper::metadata::Metadata;
 use crate::ckb::helper::unique_cell_dep;
-use crate::ckb::NETWORK_TYPE;
-use crate::{cell_dep, out_point, script};
 
 pub struct Delegate;
 
 impl Delegate {
-    pub fn lock(metadata_type_id: &H256, delegate_addr: &H160) -> Script {
-        let metadata_type_hash = Metadata::type_(metadata_type_id).calc_script_hash();
+    pub fn lock(ctx: &ChainContext, metadata_type_id: &H256, delegate_addr: &H160) -> Script {
+        let metadata_type_hash = Metadata::type_(ctx, metadata_type_id).calc_script_hash();
         let args = DelegateArgs::new_builder()
             .metadata_type_id(to_axon_byte32(&metadata_type_hash))
             .delegator_addr(to_identity(delegate_addr))
             .build()
             .as_bytes();
 
-        match **NETWORK_TYPE.load() {
-            NetworkType::Mainnet => script!(
-                &DELEGATE_LOCK_MAINNET.code_hash,
-                DELEGATE_LOCK_MAINNET.hash_type,
-                args
-            ),
-            NetworkType::Testnet => script!(
-                &DELEGATE_LOCK_TESTNET.code_hash,
-                DELEGATE_LOCK_TESTNET.hash_type,
-                args
-            ),
-            NetworkType::Devnet => script!(
-                &DELEGATE_LOCK_DEVNET.code_hash,
-                DELEGATE_LOCK_DEVNET.hash_type,
-                args
-            ),
-        }
+        ctx.scripts.delegate_lock.script(args)
     }
 
-    pub fn smt_type(delegate_smt_type_id: &H256) -> Script {
+    pub fn smt_type(ctx: &ChainContext, delegate_smt_type_id: &H256) -> Script {
         let args = Bytes::from(delegate_smt_type_id.as_bytes().to_vec());
 
-        match **NETWORK_TYPE.load() {
-            NetworkType::Mainnet => script!(
-                &DELEGATE_SMT_TYPE_MAINNET.code_hash,
-                DELEGATE_SMT_TYPE_MAINNET.hash_type,
-                args
-            ),
-            NetworkType::Testnet => script!(
-                &DELEGATE_SMT_TYPE_TESTNET.code_hash,
-                DELEGATE_SMT_TYPE_TESTNET.hash_type,
-                args
-            ),
-            NetworkType::Devnet => script!(
-                &DELEGATE_SMT_TYPE_DEVNET.code_hash,
-                DELEGATE_SMT_TYPE_DEVNET.hash_type,
-                args
-            ),
-        }
+        ctx.scripts.delegate_smt_type.script(args)
     }
 
-    pub fn requirement_type(metadata_type_id: &H256, requirement_type_id: &H256) -> Script {
-        let metadata_type_hash = Metadata::type_(metadata_type_id).calc_script_hash();
+    pub fn requirement_type(
+        ctx: &ChainContext,
+        metadata_type_id: &H256,
+        requirement_type_id: &H256,
+    ) -> Script {
+        let metadata_type_hash = Metadata::type_(ctx, metadata_type_id).calc_script_hash();
 
         let args = DelegateRequirementArgs::new_builder()
             .metadata_type_id(to_axon_byte32(&metadata_type_hash))
@@ -82,87 +53,27 @@ impl Delegate {
             .build()
             .as_bytes();
 
-        match **NETWORK_TYPE.load() {
-            NetworkType::Mainnet => script!(
-                &DELEGATE_REQUIREMENT_TYPE_MAINNET.code_hash,
-                DELEGATE_REQUIREMENT_TYPE_MAINNET.hash_type,
-                args
-            ),
-            NetworkType::Testnet => script!(
-                &DELEGATE_REQUIREMENT_TYPE_TESTNET.code_hash,
-                DELEGATE_REQUIREMENT_TYPE_TESTNET.hash_type,
-                args
-            ),
-            NetworkType::Devnet => script!(
-                &DELEGATE_REQUIREMENT_TYPE_DEVNET.code_hash,
-                DELEGATE_REQUIREMENT_TYPE_DEVNET.hash_type,
-                args
-            ),
-        }
+        ctx.scripts.delegate_requirement_type.script(args)
     }
 
-    pub fn lock_dep() -> CellDep {
-        match **NETWORK_TYPE.load() {
-            NetworkType::Mainnet => cell_dep!(
-                &DELEGATE_LOCK_MAINNET.tx_hash,
-                DELEGATE_LOCK_MAINNET.index,
-                DELEGATE_LOCK_MAINNET.dep_type
-            ),
-            NetworkType::Testnet => cell_dep!(
-                &DELEGATE_LOCK_TESTNET.tx_hash,
-                DELEGATE_LOCK_TESTNET.index,
-                DELEGATE_LOCK_TESTNET.dep_type
-            ),
-            NetworkType::Devnet => cell_dep!(
-                &DELEGATE_LOCK_DEVNET.tx_hash,
-                DELEGATE_LOCK_DEVNET.index,
-                DELEGATE_LOCK_DEVNET.dep_type
-            ),
-        }
+    pub fn lock_dep(ctx: &ChainContext) -> CellDep {
+        ctx.scripts.delegate_lock.cell_dep()
     }
 
-    pub fn smt_type_dep() -> CellDep {
-        match **NETWORK_TYPE.load() {
-            NetworkType::Mainnet => cell_dep!(
-                &DELEGATE_SMT_TYPE_MAINNET.tx_hash,
-                DELEGATE_SMT_TYPE_MAINNET.index,
-                DELEGATE_SMT_TYPE_MAINNET.dep_type
-            ),
-            NetworkType::Testnet => cell_dep!(
-                &DELEGATE_SMT_TYPE_TESTNET.tx_hash,
-                DELEGATE_SMT_TYPE_TESTNET.index,
-                DELEGATE_SMT_TYPE_TESTNET.dep_type
-            ),
-            NetworkType::Devnet => cell_dep!(
-                &DELEGATE_SMT_TYPE_DEVNET.tx_hash,
-                DELEGATE_SMT_TYPE_DEVNET.index,
-                DELEGATE_SMT_TYPE_DEVNET.dep_type
-            ),
-        }
+    pub fn smt_type_dep(ctx: &ChainContext) -> CellDep {
+        ctx.scripts.delegate_smt_type.cell_dep()
     }
 
-    pub async fn smt_cell_dep(ckb_rpc: &impl CkbRpc, type_id: &H256) -> Result<CellDep> {
-        unique_cell_dep(ckb_rpc, Self::smt_type(type_id)).await
+    pub async fn smt_cell_dep(
+        ckb_rpc: &impl CkbRpc,
+        ctx: &ChainContext,
+        type_id: &H256,
+    ) -> Result<CellDep> {
+        unique_cell_dep(ckb_rpc, Self::smt_type(ctx, type_id)).await
     }
 
-    pub fn requriement_type_dep() -> CellDep {
-        match **NETWORK_TYPE.load() {
-            NetworkType::Mainnet => cell_dep!(
-                &DELEGATE_REQUIREMENT_TYPE_MAINNET.tx_hash,
-                DELEGATE_REQUIREMENT_TYPE_MAINNET.index,
-                DELEGATE_REQUIREMENT_TYPE_MAINNET.dep_type
-            ),
-            NetworkType::Testnet => cell_dep!(
-                &DELEGATE_REQUIREMENT_TYPE_TESTNET.tx_hash,
-                DELEGATE_REQUIREMENT_TYPE_TESTNET.index,
-                DELEGATE_REQUIREMENT_TYPE_TESTNET.dep_type
-            ),
-            NetworkType::Devnet => cell_dep!(
-                &DELEGATE_REQUIREMENT_TYPE_DEVNET.tx_hash,
-                DELEGATE_REQUIREMENT_TYPE_DEVNET.index,
-                DELEGATE_REQUIREMENT_TYPE_DEVNET.dep_type
-            ),
-        }
+    pub fn requriement_type_dep(ctx: &ChainContext) -> CellDep {
+        ctx.scripts.delegate_requirement_type.cell_dep()
     }
 
     pub fn item(delegate: &DelegateInfoDelta) -> DelegateItem {
@@ -189,6 +100,23 @@ impl Delegate {
         get_cell_by_type(ckb_rpc, delegate_requirement_type).await
     }
 
+    /// The lock args start with the metadata type hash, so searching by this
+    /// prefix matches the delegate AT cells of all delegators.
+    pub fn lock_prefix(ctx: &ChainContext, metadata_type_id: &H256) -> Script {
+        let lock = Self::lock(ctx, metadata_type_id, &H160::default());
+        let prefix = lock.args().raw_data().slice(0..32);
+        lock.as_builder().args(prefix.pack()).build()
+    }
+
+    pub async fn get_all_cells(
+        ckb_rpc: &impl CkbRpc,
+        ctx: &ChainContext,
+        metadata_type_id: &H256,
+        xudt: Script,
+    ) -> Result<Vec<Cell>> {
+        get_cells_by_lock_prefix(ckb_rpc, Self::lock_prefix(ctx, metadata_type_id), xudt).await
+    }
+
     pub async fn get_smt_cell(ckb_rpc: &impl CkbRpc, delegate_smt_type: Script) -> Result<Cell> {
         get_cell_by_type(ckb_rpc, delegate_smt_type).await
     }
@@ -1,67 +1,35 @@
 use anyhow::Result;
 use bytes::Bytes;
-use ckb_types::packed::{CellDep, OutPoint, Script};
-use ckb_types::prelude::{Builder, Entity, Pack};
+use ckb_types::packed::{CellDep, Script};
 use ckb_types::H256;
 
 use common::traits::ckb_rpc_client::CkbRpc;
 use common::types::axon_types::metadata::MetadataCellData;
 use common::types::ckb_rpc_client::Cell;
-use common::types::tx_builder::{NetworkType, RewardMeta};
+use common::types::tx_builder::{ChainContext, RewardMeta};
 use common::utils::convert::{to_u128, to_u16, to_u32, to_u64};
 
-use crate::ckb::define::scripts::*;
 use crate::ckb::helper::ckb::cell_collector::get_cell_by_type;
 use crate::ckb::helper::unique_cell_dep;
-use crate::ckb::NETWORK_TYPE;
-use crate::{cell_dep, out_point, script};
 
 pub struct Metadata;
 
 impl Metadata {
-    pub fn type_(args: &H256) -> Script {
+    pub fn type_(ctx: &ChainContext, args: &H256) -> Script {
         let args = Bytes::from(args.as_bytes().to_vec());
-        match **NETWORK_TYPE.load() {
-            NetworkType::Mainnet => script!(
-                &METADATA_TYPE_MAINNET.code_hash,
-                METADATA_TYPE_MAINNET.hash_type,
-                args
-            ),
-            NetworkType::Testnet => script!(
-                &METADATA_TYPE_TESTNET.code_hash,
-                METADATA_TYPE_TESTNET.hash_type,
-                args
-            ),
-            NetworkType::Devnet => script!(
-                &METADATA_TYPE_DEVNET.code_hash,
-                METADATA_TYPE_DEVNET.hash_type,
-                args
-            ),
-        }
+        ctx.scripts.metadata_type.script(args)
     }
 
-    pub fn type_dep() -> CellDep {
-        match **NETWORK_TYPE.load() {
-            NetworkType::Mainnet => cell_dep!(
-                &METADATA_TYPE_MAINNET.tx_hash,
-                METADATA_TYPE_MAINNET.index,
-                METADATA_TYPE_MAINNET.dep_type
-            ),
-            NetworkType::Testnet => cell_dep!(
-                &METADATA_TYPE_TESTNET.tx_hash,
-                METADATA_TYPE_TESTNET.index,
-                METADATA_TYPE_TESTNET.dep_type
-            ),
-            NetworkType::Devnet => cell_dep!(
-                &METADATA_TYPE_DEVNET.tx_hash,
-                METADATA_TYPE_DEVNET.index,
-                METADATA_TYPE_DEVNET.dep_type
-            ),
-        }
+    pub fn type_dep(ctx: &ChainContext) -> CellDep {
+        ctx.scripts.metadata_type.cell_dep()
     }
 
-    pub async fn cell_dep(ckb_rpc: &impl CkbRpc, type_id: &H256) -> Result<CellDep> {
-        unique_cell_dep(ckb_rpc, Self::type_(type_id)).await
+    pub async fn cell_dep(
+        ckb_rpc: &impl CkbRpc,
+        ctx: &ChainContext,
+        type_id: &H256,
+    ) -> Result<CellDep> {
+        unique_cell_dep(ckb_rpc, Self::type_(ctx, type_id)).await
     }
 
     pub async fn get_cell(ckb_rpc: &impl CkbRpc, metadata_type: Script) -> Result<Cell> {
@@ -4,6 +4,7 @@ pub mod ckb;
 pub mod delegate;
 pub mod metadata;
 pub mod rest;
+pub mod reward_calculator;
 pub mod stake;
 pub mod withdraw;
 
@@ -22,6 +23,7 @@ pub use ckb::*;
 pub use delegate::Delegate;
 pub use metadata::Metadata;
 pub use rest::{Issue, Reward, Selection};
+pub use reward_calculator::RewardCalculator;
 pub use stake::Stake;
 pub use withdraw::Withdraw;
 
@@ -0,0 +1,260 @@
+use std::collections::{BTreeSet, HashMap};
+use std::ops::RangeInclusive;
+
+use anyhow::Result;
+use ckb_types::prelude::Entity;
+use ckb_types::H256;
+
+use common::traits::ckb_rpc_client::CkbRpc;
+use common::traits::smt::{DelegateSmtStorage, ProposalSmtStorage, StakeSmtStorage};
+use common::types::axon_types::{delegate::DelegateCellData, metadata::MetadataCellData};
+use common::types::smt::Address;
+use common::types::tx_builder::{Amount, ChainContext, Epoch, RewardMeta};
+use common::utils::convert::to_ckb_h160;
+
+use crate::ckb::define::constants::{INAUGURATION, START_EPOCH};
+use crate::ckb::define::error::CkbTxErr;
+use crate::ckb::helper::{Delegate, Metadata, Stake};
+
+/// The reward of a validator and its delegators at an epoch, and the share of
+/// the user in it.
+#[derive(Clone, Debug, Default)]
+pub struct ValidatorReward {
+    pub validator:             Address,
+    pub propose_count:         u64,
+    pub commission_rate:       u8,
+    pub stake_amount:          Amount,
+    pub total_delegate_amount: Amount,
+    pub total_reward:          Amount,
+    pub reward:                Amount,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct EpochReward {
+    pub epoch:      Epoch,
+    pub amount:     Amount,
+    pub validators: Vec<ValidatorReward>,
+}
+
+/// Calculates the rewards from the SMTs only, nothing is written. The halving
+/// follows the current epoch, the same as the reward transaction.
+pub struct RewardCalculator<'a, S> {
+    smt:                   &'a S,
+    reward_meta:           RewardMeta,
+    minimum_propose_count: u64,
+    current_epoch:         Epoch,
+    commission_rates:      HashMap<Address, u8>,
+}
+
+impl<'a, S> RewardCalculator<'a, S>
+where
+    S: StakeSmtStorage + DelegateSmtStorage + ProposalSmtStorage,
+{
+    pub fn new(
+        smt: &'a S,
+        reward_meta: RewardMeta,
+        minimum_propose_count: u64,
+        current_epoch: Epoch,
+        commission_rates: HashMap<Address, u8>,
+    ) -> Self {
+        Self {
+            smt,
+            reward_meta,
+            minimum_propose_count,
+            current_epoch,
+            commission_rates,
+        }
+    }
+
+    pub fn from_metadata(
+        smt: &'a S,
+        metadata: &MetadataCellData,
+        current_epoch: Epoch,
+        commission_rates: HashMap<Address, u8>,
+    ) -> Self {
+        Self::new(
+            smt,
+            Metadata::parse_reward_meta(metadata),
+            Metadata::calc_minimum_propose_count(metadata),
+            current_epoch,
+            commission_rates,
+        )
+    }
+
+    pub async fn calc_rewards(
+        &self,
+        user: Address,
+        epochs: RangeInclusive<Epoch>,
+    ) -> Result<Vec<EpochReward>> {
+        let mut rewards = Vec::new();
+        for epoch in epochs {
+            rewards.push(self.calc_epoch_reward(user, epoch).await?);
+        }
+        Ok(rewards)
+    }
+
+    pub async fn calc_epoch_reward(&self, user: Address, epoch: Epoch) -> Result<EpochReward> {
+        let propose_counts = ProposalSmtStorage::get_sub_leaves(self.smt, epoch).await?;
+        let mut epoch_reward = EpochReward {
+            epoch,
+            ..Default::default()
+        };
+
+        for (validator, propose_count) in propose_counts.into_iter() {
+            let reward = self
+                .calc_validator_reward(user, epoch, validator, propose_count)
+                .await?;
+            epoch_reward.amount += reward.reward;
+            epoch_reward.validators.push(reward);
+        }
+
+        Ok(epoch_reward)
+    }
+
+    // The base reward is halved every half reward cycle, and is halved to zero
+    // after enough cycles.
+    fn halved_base_reward(&self) -> Result<Amount> {
+        let halvings = self
+            .current_epoch
+            .checked_div(self.reward_meta.half_reward_cycle)
+            .ok_or(CkbTxErr::MalformedCellData("metadata"))?;
+        Ok(u32::try_from(halvings)
+            .ok()
+            .and_then(|halvings| self.reward_meta.base_reward.checked_shr(halvings))
+            .unwrap_or(0))
+    }
+
+    async fn calc_validator_reward(
+        &self,
+        user: Address,
+        epoch: Epoch,
+        validator: Address,
+        propose_count: u64,
+    ) -> Result<ValidatorReward> {
+        let commission_rate = *self
+            .commission_rates
+            .get(&validator)
+            .ok_or(CkbTxErr::CommissionRateNotFound(validator))?;
+
+        let mut total_reward = self.halved_base_reward()?;
+
+        if propose_count < self.minimum_propose_count {
+            total_reward = total_reward * self.reward_meta.propose_discount_rate as u128 / 100;
+        }
+
+        let stake_amount = StakeSmtStorage::get_amount(self.smt, epoch, validator)
+            .await?
+            .ok_or(CkbTxErr::StakeAmountNotFound(epoch, validator))?;
+
+        let all_delegates = DelegateSmtStorage::get_sub_leaves(self.smt, epoch, validator).await?;
+        let total_delegate_amount = all_delegates.values().sum::<Amount>();
+
+        let total_amount = stake_amount + total_delegate_amount;
+        let staker_reward = total_reward * stake_amount / total_amount;
+        let delegators_reward = total_reward - staker_reward;
+
+        let reward = if user == validator {
+            staker_reward + delegators_reward * commission_rate as u128 / 100
+        } else {
+            match DelegateSmtStorage::get_amount(self.smt, epoch + INAUGURATION, validator, user)
+                .await?
+            {
+                Some(delegate_amount) => {
+                    delegators_reward * delegate_amount / total_delegate_amount
+                        * (100 - commission_rate as u128)
+                        / 100
+                }
+                None => 0,
+            }
+        };
+
+        Ok(ValidatorReward {
+            validator,
+            propose_count,
+            commission_rate,
+            stake_amount,
+            total_delegate_amount,
+            total_reward,
+            reward,
+        })
+    }
+}
+
+/// The epochs whose rewards can be claimed now, from the first epoch not
+/// claimed yet which is recorded in the reward SMT.
+pub fn claimable_epochs(
+    current_epoch: Epoch,
+    not_claimed_epoch: Option<Epoch>,
+) -> Option<RangeInclusive<Epoch>> {
+    let start_epoch = not_claimed_epoch.unwrap_or(START_EPOCH + INAUGURATION);
+    let end_epoch = current_epoch.checked_sub(INAUGURATION)?;
+    (start_epoch <= end_epoch).then_some(start_epoch..=end_epoch)
+}
+
+/// The validators which proposed blocks in the epochs, the calculator needs
+/// their commission rates.
+pub async fn proposed_validators(
+    smt: &impl ProposalSmtStorage,
+    epochs: RangeInclusive<Epoch>,
+) -> Result<BTreeSet<Address>> {
+    let mut validators = BTreeSet::new();
+    for epoch in epochs {
+        validators.extend(
+            ProposalSmtStorage::get_sub_leaves(smt, epoch)
+                .await?
+                .into_keys(),
+        );
+    }
+    Ok(validators)
+}
+
+/// Reads the commission rates of the stakers from their delegate requirement
+/// cells.
+pub async fn fetch_commission_rates(
+    ckb: &impl CkbRpc,
+    ctx: &ChainContext,
+    metadata_type_id: &H256,
+    xudt_owner: &H256,
+    stakers: impl IntoIterator<Item = Address>,
+) -> Result<HashMap<Address, u8>> {
+    let mut rates = HashMap::new();
+    for staker in stakers {
+        let (requirement_type_id, _) = Stake::get_delegate_requirement_type_id(
+            ckb,
+            ctx,
+            metadata_type_id,
+            &to_ckb_h160(&staker),
+            xudt_owner,
+        )
+        .await?;
+        let requirement_cell = Delegate::get_requirement_cell(
+            ckb,
+            Delegate::requirement_type(ctx, metadata_type_id, &requirement_type_id),
+        )
+        .await?;
+        let data = DelegateCellData::from_slice(
+            &requirement_cell
+                .output_data
+                .unwrap_or_default()
+                .into_bytes(),
+        )
+        .map_err(|_| CkbTxErr::MalformedCellData("delegate requirement"))?;
+        rates.insert(staker, data.delegate_requirement().commission_rate().into());
+    }
+    Ok(rates)
+}
+
+pub async fn fetch_metadata(
+    ckb: &impl CkbRpc,
+    ctx: &ChainContext,
+    metadata_type_id: &H256,
+) -> Result<MetadataCellData> {
+    let cell = Metadata::get_cell(ckb, Metadata::type_(ctx, metadata_type_id)).await?;
+    let data = cell
+        .output_data
+        .ok_or(CkbTxErr::MalformedCellData("metadata"))?
+        .into_bytes();
+    let data = MetadataCellData::from_compatible_slice(&data)
+        .map_err(|_| CkbTxErr::MalformedCellData("metadata"))?;
+    Ok(data)
+}
@@ -1,6 +1,6 @@
 use anyhow::Result;
 use bytes::Bytes;
-use ckb_types::packed::{CellDep, OutPoint, Script, WitnessArgs};
+use ckb_types::packed::{CellDep, Script, WitnessArgs};
 use ckb_types::prelude::{Builder, Entity, Pack};
 use ckb_types::{H160, H256};
 
@@ -9,113 +9,52 @@ use common::types::axon_types::stake::{
     StakeArgs, StakeAtCellData, StakeAtWitness, StakeInfoDelta, StakeSmtWitness as AStakeSmtWitness,
 };
 use common::types::ckb_rpc_client::Cell;
-use common::types::tx_builder::{NetworkType, StakeItem};
+use common::types::tx_builder::{ChainContext, StakeItem};
 use common::utils::convert::*;
 
 use crate::ckb::define::constants::TOKEN_BYTES;
 use crate::ckb::define::error::CkbTxErr;
-use crate::ckb::define::scripts::*;
 use crate::ckb::define::types::{StakeInfo, StakeSmtUpdateInfo, StakeSmtWitness};
-use crate::ckb::helper::ckb::cell_collector::{get_cell_by_scripts, get_cell_by_type};
+use crate::ckb::helper::ckb::cell_collector::{
+    get_cell_by_scripts, get_cell_by_type, get_cells_by_lock_prefix,
+};
 use crate::ckb::helper::metadata::Metadata;
 use crate::ckb::helper::unique_cell_dep;
 use crate::ckb::helper::xudt::Xudt;
-use crate::ckb::NETWORK_TYPE;
-use crate::{cell_dep, out_point, script};
 
 pub struct Stake;
 
 impl Stake {
-    pub fn lock(metadata_type_id: &H256, staker_addr: &H160) -> Script {
-        let metadata_type_hash = Metadata::type_(metadata_type_id).calc_script_hash();
+    pub fn lock(ctx: &ChainContext, metadata_type_id: &H256, staker_addr: &H160) -> Script {
+        let metadata_type_hash = Metadata::type_(ctx, metadata_type_id).calc_script_hash();
         let args = StakeArgs::new_builder()
             .metadata_type_id(to_axon_byte32(&metadata_type_hash))
             .stake_addr(to_identity(staker_addr))
             .build()
             .as_bytes();
 
-        match **NETWORK_TYPE.load() {
-            NetworkType::Mainnet => script!(
-                &STAKE_LOCK_MAINNET.code_hash,
-                STAKE_LOCK_MAINNET.hash_type,
-                args
-            ),
-            NetworkType::Testnet => script!(
-                &STAKE_LOCK_TESTNET.code_hash,
-                STAKE_LOCK_TESTNET.hash_type,
-                args
-            ),
-            NetworkType::Devnet => script!(
-                &STAKE_LOCK_DEVNET.code_hash,
-                STAKE_LOCK_DEVNET.hash_type,
-                args
-            ),
-        }
+        ctx.scripts.stake_lock.script(args)
     }
 
-    pub fn smt_type(stake_smt_type_id: &H256) -> Script {
+    pub fn smt_type(ctx: &ChainContext, stake_smt_type_id: &H256) -> Script {
         let args = Bytes::from(stake_smt_type_id.as_bytes().to_vec());
-        match **NETWORK_TYPE.load() {
-            NetworkType::Mainnet => script!(
-                &STAKE_SMT_TYPE_MAINNET.code_hash,
-                STAKE_SMT_TYPE_MAINNET.hash_type,
-                args
-            ),
-            NetworkType::Testnet => script!(
-                &STAKE_SMT_TYPE_TESTNET.code_hash,
-                STAKE_SMT_TYPE_TESTNET.hash_type,
-                args
-            ),
-            NetworkType::Devnet => script!(
-                &STAKE_SMT_TYPE_DEVNET.code_hash,
-                STAKE_SMT_TYPE_DEVNET.hash_type,
-                args
-            ),
-        }
+        ctx.scripts.stake_smt_type.script(args)
     }
 
-    pub fn lock_dep() -> CellDep {
-        match **NETWORK_TYPE.load() {
-            NetworkType::Mainnet => cell_dep!(
-                &STAKE_LOCK_MAINNET.tx_hash,
-                STAKE_LOCK_MAINNET.index,
-                STAKE_LOCK_MAINNET.dep_type
-            ),
-            NetworkType::Testnet => cell_dep!(
-                &STAKE_LOCK_TESTNET.tx_hash,
-                STAKE_LOCK_TESTNET.index,
-                STAKE_LOCK_TESTNET.dep_type
-            ),
-            NetworkType::Devnet => cell_dep!(
-                &STAKE_LOCK_DEVNET.tx_hash,
-                STAKE_LOCK_DEVNET.index,
-                STAKE_LOCK_DEVNET.dep_type
-            ),
-        }
+    pub fn lock_dep(ctx: &ChainContext) -> CellDep {
+        ctx.scripts.stake_lock.cell_dep()
     }
 
-    pub fn smt_type_dep() -> CellDep {
-        match **NETWORK_TYPE.load() {
-            NetworkType::Mainnet => cell_dep!(
-                &STAKE_SMT_TYPE_MAINNET.tx_hash,
-                STAKE_SMT_TYPE_MAINNET.index,
-                STAKE_SMT_TYPE_MAINNET.dep_type
-            ),
-            NetworkType::Testnet => cell_dep!(
-                &STAKE_SMT_TYPE_TESTNET.tx_hash,
-                STAKE_SMT_TYPE_TESTNET.index,
-                STAKE_SMT_TYPE_TESTNET.dep_type
-            ),
-            NetworkType::Devnet => cell_dep!(
-                &STAKE_SMT_TYPE_DEVNET.tx_hash,
-                STAKE_SMT_TYPE_DEVNET.index,
-                STAKE_SMT_TYPE_DEVNET.dep_type
-            ),
-        }
+    pub fn smt_type_dep(ctx: &ChainContext) -> CellDep {
+        ctx.scripts.stake_smt_type.cell_dep()
     }
 
-    pub async fn smt_cell_dep(ckb_rpc: &impl CkbRpc, type_id: &H256) -> Result<CellDep> {
-        unique_cell_dep(ckb_rpc, Self::smt_type(type_id)).await
+    pub async fn smt_cell_dep(
+        ckb_rpc: &impl CkbRpc,
+        ctx: &ChainContext,
+        type_id: &H256,
+    ) -> Result<CellDep> {
+        unique_cell_dep(ckb_rpc, Self::smt_type(ctx, type_id)).await
     }
 
     pub fn item(stake: &StakeInfoDelta) -> StakeItem {
@@ -134,6 +73,23 @@ impl Stake {
         get_cell_by_scripts(ckb_rpc, stake_lock, xudt).await
     }
 
+    /// The lock args start with the metadata type hash, so searching by this
+    /// prefix matches the stake AT cells of all stakers.
+    pub fn lock_prefix(ctx: &ChainContext, metadata_type_id: &H256) -> Script {
+        let lock = Self::lock(ctx, metadata_type_id, &H160::default());
+        let prefix = lock.args().raw_data().slice(0..32);
+        lock.as_builder().args(prefix.pack()).build()
+    }
+
+    pub async fn get_all_cells(
+        ckb_rpc: &impl CkbRpc,
+        ctx: &ChainContext,
+        metadata_type_id: &H256,
+        xudt: Script,
+    ) -> Result<Vec<Cell>> {
+        get_cells_by_lock_prefix(ckb_rpc, Self::lock_prefix(ctx, metadata_type_id), xudt).await
+    }
+
     pub async fn get_smt_cell(ckb_rpc: &impl CkbRpc, delegate_smt_type: Script) -> Result<Cell> {
         get_cell_by_type(ckb_rpc, delegate_smt_type).await
     }
@@ -167,14 +123,15 @@ impl Stake {
 
     pub async fn get_delegate_requirement_type_id(
         ckb_rpc: &impl CkbRpc,
+        ctx: &ChainContext,
         metadata_type_id: &H256,
         staker: &H160,
         xudt_owner: &H256,
     ) -> Result<(H256, ckb_jsonrpc_types::OutPoint)> {
         let stake_cell = Stake::get_cell(
             ckb_rpc,
-            Self::lock(metadata_type_id, staker),
-            Xudt::type_(&xudt_owner.pack()),
+            Self::lock(ctx, metadata_type_id, staker),
+            Xudt::type_(ctx, &xudt_owner.pack()),
         )
         .await?;
 
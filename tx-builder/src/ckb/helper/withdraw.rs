@@ -1,78 +1,50 @@
 use anyhow::Result;
-use ckb_types::packed::{CellDep, OutPoint, Script, WitnessArgs};
+use ckb_types::packed::{CellDep, Script, WitnessArgs};
 use ckb_types::prelude::{Builder, Entity, Pack};
 use ckb_types::{H160, H256};
 
 use common::traits::ckb_rpc_client::CkbRpc;
 use common::types::axon_types::withdraw::{WithdrawArgs, WithdrawWitness};
 use common::types::ckb_rpc_client::Cell;
-use common::types::tx_builder::NetworkType;
 use common::types::{
     axon_types::withdraw::{
         WithdrawAtCellData as AWithdrawAtCellData, WithdrawInfo as AWithdrawInfo,
         WithdrawInfos as AWithdrawInfos,
     },
-    tx_builder::Epoch,
+    tx_builder::{ChainContext, Epoch},
 };
 use common::utils::convert::*;
 
 use crate::ckb::define::constants::TOKEN_BYTES;
-use crate::ckb::define::scripts::*;
 use crate::ckb::define::types::WithdrawInfo;
 use crate::ckb::helper::ckb::cell_collector::get_cell_by_scripts;
 use crate::ckb::helper::metadata::Metadata;
 use crate::ckb::helper::token_cell_data;
-use crate::ckb::NETWORK_TYPE;
-use crate::{cell_dep, out_point, script};
 
 pub struct Withdraw;
 
 impl Withdraw {
-    pub fn lock(metadata_type_id: &H256, addr: &H160) -> Script {
-        let metadata_type_hash = Metadata::type_(metadata_type_id).calc_script_hash();
+    pub fn lock(ctx: &ChainContext, metadata_type_id: &H256, addr: &H160) -> Script {
+        let metadata_type_hash = Metadata::type_(ctx, metadata_type_id).calc_script_hash();
         let args = WithdrawArgs::new_builder()
             .metadata_type_id(to_axon_byte32(&metadata_type_hash))
             .addr(to_identity(addr))
             .build()
             .as_bytes();
 
-        match **NETWORK_TYPE.load() {
-            NetworkType::Mainnet => script!(
-                &WITHDRAW_LOCK_MAINNET.code_hash,
-                WITHDRAW_LOCK_MAINNET.hash_type,
-                args
-            ),
-            NetworkType::Testnet => script!(
-                &WITHDRAW_LOCK_TESTNET.code_hash,
-                WITHDRAW_LOCK_TESTNET.hash_type,
-                args
-            ),
-            NetworkType::Devnet => script!(
-                &WITHDRAW_LOCK_DEVNET.code_hash,
-                WITHDRAW_LOCK_DEVNET.hash_type,
-                args
-            ),
-        }
+        ctx.scripts.withdraw_lock.script(args)
     }
 
-    pub fn lock_dep() -> CellDep {
-        match **NETWORK_TYPE.load() {
-            NetworkType::Mainnet => cell_dep!(
-                &WITHDRAW_LOCK_MAINNET.tx_hash,
-                WITHDRAW_LOCK_MAINNET.index,
-                WITHDRAW_LOCK_MAINNET.dep_type
-            ),
-            NetworkType::Testnet => cell_dep!(
-                &WITHDRAW_LOCK_TESTNET.tx_hash,
-                WITHDRAW_LOCK_TESTNET.index,
-                WITHDRAW_LOCK_TESTNET.dep_type
-            ),
-            NetworkType::Devnet => cell_dep!(
-                &WITHDRAW_LOCK_DEVNET.tx_hash,
-                WITHDRAW_LOCK_DEVNET.index,
-                WITHDRAW_LOCK_DEVNET.dep_type
-            ),
-        }
+    /// The lock args start with the metadata type hash, so searching by this
+    /// prefix matches the withdraw AT cells of all users.
+    pub fn lock_prefix(ctx: &ChainContext, metadata_type_id: &H256) -> Script {
+        let lock = Self::lock(ctx, metadata_type_id, &H160::default());
+        let prefix = lock.args().raw_data().slice(0..32);
+        lock.as_builder().args(prefix.pack()).build()
+    }
+
+    pub fn lock_dep(ctx: &ChainContext) -> CellDep {
+        ctx.scripts.withdraw_lock.cell_dep()
     }
 
     pub async fn get_cell(
@@ -2,7 +2,6 @@ use std::collections::HashSet;
 
 use anyhow::Result;
 use ckb_sdk::unlock::InfoCellData;
-use ckb_sdk::{ScriptGroup, ScriptGroupType};
 use ckb_types::H160;
 use ckb_types::{
     bytes::Bytes,
@@ -24,7 +23,6 @@ use common::types::tx_builder::*;
 use common::utils::convert::{to_axon_byte32, to_h256};
 
 use crate::ckb::define::constants::START_EPOCH;
-use crate::ckb::define::scripts::*;
 use crate::ckb::define::types::{
     DelegateSmtCellData, MetadataCellData, RewardSmtCellData, StakeSmtCellData, StakerSmtRoot,
 };
@@ -32,11 +30,13 @@ use crate::ckb::helper::{
     AlwaysSuccess, Checkpoint as HCheckpoint, Delegate, Metadata as HMetadata, OmniEth, Reward,
     Secp256k1, Selection, Stake, Tx, TypeId, Xudt,
 };
-use crate::ckb::NETWORK_TYPE;
+use crate::ckb::keystore::SignerKey;
+use crate::ckb::signer::{OmniEthSigner, TxSigner};
 
 pub struct InitTxBuilder<'a, C: CkbRpc> {
     ckb:        &'a C,
-    seeder_key: PrivateKey,
+    ctx:        &'a ChainContext,
+    seeder:     OmniEthSigner,
     max_supply: Amount,
     checkpoint: Checkpoint,
     metadata:   MetadataInfo,
@@ -46,7 +46,8 @@ pub struct InitTxBuilder<'a, C: CkbRpc> {
 impl<'a, C: CkbRpc> InitTxBuilder<'a, C> {
     pub fn new(
         ckb: &'a C,
-        seeder_key: PrivateKey,
+        ctx: &'a ChainContext,
+        seeder_key: SignerKey,
         max_supply: Amount,
         checkpoint: Checkpoint,
         metadata: MetadataInfo,
@@ -54,7 +55,8 @@ impl<'a, C: CkbRpc> InitTxBuilder<'a, C> {
     ) -> Self {
         Self {
             ckb,
-            seeder_key,
+            ctx,
+            seeder: OmniEthSigner::new(seeder_key),
             max_supply,
             checkpoint,
             metadata,
@@ -63,57 +65,65 @@ impl<'a, C: CkbRpc> InitTxBuilder<'a, C> {
     }
 
     pub async fn build_tx(mut self) -> Result<(TransactionView, TypeIds)> {
-        let omni_eth = OmniEth::new(self.seeder_key.clone());
-        let seeder_lock = OmniEth::lock(&omni_eth.address()?);
+        let seeder_address = self.seeder.address()?;
+        let seeder_lock = OmniEth::lock(self.ctx, &seeder_address);
 
         let outputs_data = self.build_data();
 
         let outputs = vec![
             // issue cell
             CellOutput::new_builder()
-                .lock(OmniEth::supply_lock(H160::default(), Byte32::default())?)
+                .lock(OmniEth::supply_lock(
+                    self.ctx,
+                    H160::default(),
+                    Byte32::default(),
+                )?)
                 .type_(Some(TypeId::mock()).pack())
                 .build_exact_capacity(Capacity::bytes(outputs_data[0].len())?)?,
             // selection cell
             CellOutput::new_builder()
-                .lock(Selection::lock(&Byte32::default(), &Byte32::default()))
+                .lock(Selection::lock(
+                    self.ctx,
+                    &Byte32::default(),
+                    &Byte32::default(),
+                ))
                 .type_(Some(TypeId::mock()).pack())
                 .build_exact_capacity(Capacity::bytes(outputs_data[1].len())?)?,
             // checkpoint cell
             CellOutput::new_builder()
-                .lock(AlwaysSuccess::lock())
-                .type_(Some(HCheckpoint::type_(&H256::default())).pack())
+                .lock(AlwaysSuccess::lock(self.ctx))
+                .type_(Some(HCheckpoint::type_(self.ctx, &H256::default())).pack())
                 .build_exact_capacity(Capacity::bytes(outputs_data[2].len())?)?,
             // metadata cell
             CellOutput::new_builder()
-                .lock(AlwaysSuccess::lock())
-                .type_(Some(HMetadata::type_(&H256::default())).pack())
+                .lock(AlwaysSuccess::lock(self.ctx))
+                .type_(Some(HMetadata::type_(self.ctx, &H256::default())).pack())
                 .build_exact_capacity(Capacity::bytes(outputs_data[3].len())?)?,
             // stake smt cell
             CellOutput::new_builder()
-                .lock(AlwaysSuccess::lock())
-                .type_(Some(Stake::smt_type(&H256::default())).pack())
+                .lock(AlwaysSuccess::lock(self.ctx))
+                .type_(Some(Stake::smt_type(self.ctx, &H256::default())).pack())
                 .build_exact_capacity(Capacity::bytes(outputs_data[4].len())?)?,
             // delegate smt cell
             CellOutput::new_builder()
-                .lock(AlwaysSuccess::lock())
-                .type_(Some(Delegate::smt_type(&H256::default())).pack())
+                .lock(AlwaysSuccess::lock(self.ctx))
+                .type_(Some(Delegate::smt_type(self.ctx, &H256::default())).pack())
                 .build_exact_capacity(Capacity::bytes(outputs_data[5].len())?)?,
             // reward smt cell
             CellOutput::new_builder()
-                .lock(AlwaysSuccess::lock())
+                .lock(AlwaysSuccess::lock(self.ctx))
                 .type_(Some(TypeId::mock()).pack())
                 .build_exact_capacity(Capacity::bytes(outputs_data[6].len())?)?,
         ];
 
         let cell_deps = vec![
-            OmniEth::lock_dep(),
-            Secp256k1::lock_dep(),
-            HCheckpoint::type_dep(),
-            HMetadata::type_dep(),
-            Stake::smt_type_dep(),
-            Delegate::smt_type_dep(),
-            Reward::smt_type_dep(),
+            OmniEth::lock_dep(self.ctx),
+            Secp256k1::lock_dep(self.ctx),
+            HCheckpoint::type_dep(self.ctx),
+            HMetadata::type_dep(self.ctx),
+            Stake::smt_type_dep(self.ctx),
+            Delegate::smt_type_dep(self.ctx),
+            Reward::smt_type_dep(self.ctx),
         ];
 
         let witnesses = vec![
@@ -129,19 +139,15 @@ impl<'a, C: CkbRpc> InitTxBuilder<'a, C> {
             .build();
 
         let mut tx = Tx::new(self.ckb, tx);
-        tx.balance(seeder_lock.clone()).await?;
+        tx.balance(self.ctx, seeder_lock.clone()).await?;
 
-        let (tx_view, type_id_args) = self.modify_outputs(tx.inner_ref(), omni_eth.address()?)?;
+        let (tx_view, type_id_args) = self.modify_outputs(tx.inner_ref(), seeder_address)?;
         tx.set_tx(tx_view);
 
-        tx.sign(&omni_eth.signer()?, &ScriptGroup {
-            script:         seeder_lock,
-            group_type:     ScriptGroupType::Lock,
-            input_indices:  vec![0],
-            output_indices: vec![],
-        })?;
+        let script_groups = vec![tx.lock_script_group(&seeder_lock).await?];
+        let tx = self.seeder.sign(tx.inner(), &script_groups).await?;
 
-        Ok((tx.inner(), type_id_args))
+        Ok((tx, type_id_args))
     }
 
     fn build_data(&mut self) -> Vec<Bytes> {
@@ -168,7 +174,7 @@ impl<'a, C: CkbRpc> InitTxBuilder<'a, C> {
             AStakeSmtCellData::default().as_bytes(),
             // delegate smt cell data
             ADelegateSmtCellData::from(DelegateSmtCellData {
-                metadata_type_hash: HMetadata::type_(&H256::default()).calc_script_hash(),
+                metadata_type_hash: HMetadata::type_(self.ctx, &H256::default()).calc_script_hash(),
                 smt_roots:          {
                     let mut smt_roots = vec![];
                     for staker in self.stakers.clone() {
@@ -199,7 +205,8 @@ impl<'a, C: CkbRpc> InitTxBuilder<'a, C> {
         // issue cell
         let issue_type_id = TypeId::calc(&first_input, 0);
         let issue_type = TypeId::script(&issue_type_id);
-        let issue_lock = OmniEth::supply_lock(seeder_addr, issue_type.calc_script_hash())?;
+        let issue_lock =
+            OmniEth::supply_lock(self.ctx, seeder_addr, issue_type.calc_script_hash())?;
         let issue_lock_hash = issue_lock.calc_script_hash();
         outputs[0] = tx
             .output(0)
@@ -215,12 +222,12 @@ impl<'a, C: CkbRpc> InitTxBuilder<'a, C> {
             .output(2)
             .unwrap()
             .as_builder()
-            .type_(Some(HCheckpoint::type_(&checkpoint_type_id)).pack())
+            .type_(Some(HCheckpoint::type_(self.ctx, &checkpoint_type_id)).pack())
             .build();
 
         // metadata cell
         let metadata_type_id = TypeId::calc(&first_input, 3);
-        let metadata_type = HMetadata::type_(&metadata_type_id);
+        let metadata_type = HMetadata::type_(self.ctx, &metadata_type_id);
         let metadata_type_hash = metadata_type.calc_script_hash();
         outputs[3] = tx
             .output(3)
@@ -235,7 +242,7 @@ impl<'a, C: CkbRpc> InitTxBuilder<'a, C> {
             .output(4)
             .unwrap()
             .as_builder()
-            .type_(Some(Stake::smt_type(&stake_smt_type_id)).pack())
+            .type_(Some(Stake::smt_type(self.ctx, &stake_smt_type_id)).pack())
             .build();
 
         // delegate smt cell
@@ -244,12 +251,12 @@ impl<'a, C: CkbRpc> InitTxBuilder<'a, C> {
             .output(5)
             .unwrap()
             .as_builder()
-            .type_(Some(Delegate::smt_type(&delegate_smt_type_id)).pack())
+            .type_(Some(Delegate::smt_type(self.ctx, &delegate_smt_type_id)).pack())
             .build();
 
         // reward smt cell
         let reward_smt_type_id = TypeId::calc(&first_input, 6);
-        let reward_type = Reward::smt_type(&reward_smt_type_id);
+        let reward_type = Reward::smt_type(self.ctx, &reward_smt_type_id);
         let reward_type_hash = reward_type.calc_script_hash();
         outputs[6] = tx
             .output(6)
@@ -261,7 +268,7 @@ impl<'a, C: CkbRpc> InitTxBuilder<'a, C> {
         // selection cell
         let selection_type_id = TypeId::calc(&first_input, 1);
         let selection_type = TypeId::script(&selection_type_id);
-        let selection_lock = Selection::lock(&issue_lock_hash, &reward_type_hash);
+        let selection_lock = Selection::lock(self.ctx, &issue_lock_hash, &reward_type_hash);
         let selection_lock_hash = selection_lock.calc_script_hash();
         outputs[1] = tx
             .output(1)
@@ -275,7 +282,7 @@ impl<'a, C: CkbRpc> InitTxBuilder<'a, C> {
         outputs_data[0] = InfoCellData::new_simple(
             0,
             self.max_supply,
-            to_h256(&Xudt::type_(&selection_lock_hash).calc_script_hash()),
+            to_h256(&Xudt::type_(self.ctx, &selection_lock_hash).calc_script_hash()),
         )
         .pack()
         .pack();
@@ -285,13 +292,13 @@ impl<'a, C: CkbRpc> InitTxBuilder<'a, C> {
         outputs_data[2] = checkpoint
             .as_builder()
             .metadata_type_id(to_axon_byte32(
-                &HMetadata::type_(&metadata_type_id).calc_script_hash(),
+                &HMetadata::type_(self.ctx, &metadata_type_id).calc_script_hash(),
             ))
             .build()
             .as_bytes()
             .pack();
 
-        let network_type = NETWORK_TYPE.load();
+        let scripts = &self.ctx.scripts;
 
         let type_ids = TypeIds {
             issue_type_id,
@@ -302,63 +309,17 @@ impl<'a, C: CkbRpc> InitTxBuilder<'a, C> {
             stake_smt_type_id,
             delegate_smt_type_id,
             xudt_owner: to_h256(&selection_lock_hash),
-            checkpoint_code_hash: if **network_type == NetworkType::Mainnet {
-                CHECKPOINT_TYPE_MAINNET.code_hash.clone()
-            } else if **network_type == NetworkType::Testnet {
-                CHECKPOINT_TYPE_TESTNET.code_hash.clone()
-            } else {
-                CHECKPOINT_TYPE_DEVNET.code_hash.clone()
-            },
-            metadata_code_hash: if **network_type == NetworkType::Mainnet {
-                METADATA_TYPE_MAINNET.code_hash.clone()
-            } else if **network_type == NetworkType::Testnet {
-                METADATA_TYPE_TESTNET.code_hash.clone()
-            } else {
-                METADATA_TYPE_DEVNET.code_hash.clone()
-            },
-            reward_code_hash: if **network_type == NetworkType::Mainnet {
-                REWARD_SMT_TYPE_MAINNET.code_hash.clone()
-            } else if **network_type == NetworkType::Testnet {
-                REWARD_SMT_TYPE_TESTNET.code_hash.clone()
-            } else {
-                REWARD_SMT_TYPE_DEVNET.code_hash.clone()
-            },
-            stake_smt_code_hash: if **network_type == NetworkType::Mainnet {
-                STAKE_SMT_TYPE_MAINNET.code_hash.clone()
-            } else if **network_type == NetworkType::Testnet {
-                STAKE_SMT_TYPE_TESTNET.code_hash.clone()
-            } else {
-                STAKE_SMT_TYPE_DEVNET.code_hash.clone()
-            },
-            delegate_smt_code_hash: if **network_type == NetworkType::Mainnet {
-                DELEGATE_SMT_TYPE_MAINNET.code_hash.clone()
-            } else if **network_type == NetworkType::Testnet {
-                DELEGATE_SMT_TYPE_TESTNET.code_hash.clone()
-            } else {
-                DELEGATE_SMT_TYPE_DEVNET.code_hash.clone()
-            },
-            withdraw_code_hash: if **network_type == NetworkType::Mainnet {
-                WITHDRAW_LOCK_MAINNET.code_hash.clone()
-            } else if **network_type == NetworkType::Testnet {
-                WITHDRAW_LOCK_TESTNET.code_hash.clone()
-            } else {
-                WITHDRAW_LOCK_DEVNET.code_hash.clone()
-            },
-            stake_code_hash: if **network_type == NetworkType::Mainnet {
-                STAKE_LOCK_MAINNET.code_hash.clone()
-            } else if **network_type == NetworkType::Testnet {
-                STAKE_LOCK_TESTNET.code_hash.clone()
-            } else {
-                STAKE_LOCK_DEVNET.code_hash.clone()
-            },
-            delegate_code_hash: if **network_type == NetworkType::Mainnet {
-                DELEGATE_LOCK_MAINNET.code_hash.clone()
-            } else if **network_type == NetworkType::Testnet {
-                DELEGATE_LOCK_TESTNET.code_hash.clone()
-            } else {
-                DELEGATE_LOCK_DEVNET.code_hash.clone()
-            },
-            xudt_type_hash: to_h256(&Xudt::type_(&selection_lock_hash).calc_script_hash()),
+            checkpoint_code_hash: scripts.checkpoint_type.code_hash.clone(),
+            metadata_code_hash: scripts.metadata_type.code_hash.clone(),
+            reward_code_hash: scripts.reward_smt_type.code_hash.clone(),
+            stake_smt_code_hash: scripts.stake_smt_type.code_hash.clone(),
+            delegate_smt_code_hash: scripts.delegate_smt_type.code_hash.clone(),
+            withdraw_code_hash: scripts.withdraw_lock.code_hash.clone(),
+            stake_code_hash: scripts.stake_lock.code_hash.clone(),
+            delegate_code_hash: scripts.delegate_lock.code_hash.clone(),
+            xudt_type_hash: to_h256(
+                &Xudt::type_(self.ctx, &selection_lock_hash).calc_script_hash(),
+            ),
         };
 
         // metadata cell data
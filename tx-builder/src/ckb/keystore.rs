@@ -0,0 +1,187 @@
+use std::fmt::{self, Debug, Formatter};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use ckb_crypto::secp::Pubkey;
+use ckb_hash::blake2b_256;
+use ckb_sdk::traits::{Signer, SignerError};
+use ckb_sdk::util::keccak160;
+use ckb_sdk::SECP256K1;
+use ckb_types::{bytes::Bytes, core::TransactionView, H160, H256};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use zeroize::{Zeroize, Zeroizing};
+
+use common::types::tx_builder::PrivateKey;
+
+const DEFAULT_PASSWORD_ENV: &str = "SPARK_KEYSTORE_PASSWORD";
+
+/// A private key which is zeroized when dropped. It is lent to the signers for
+/// every signature, and the copy is zeroized after the signature.
+#[derive(Clone)]
+pub struct SignerKey(Zeroizing<[u8; 32]>);
+
+impl SignerKey {
+    pub fn from_hex(hex: &str) -> Result<Self> {
+        let hex = Zeroizing::new(hex.trim().trim_start_matches("0x").to_owned());
+        if hex.len() != 64 {
+            return Err(anyhow!("invalid private key length: {}", hex.len() / 2));
+        }
+        let mut key = Zeroizing::new([0u8; 32]);
+        faster_hex::hex_decode(hex.as_bytes(), key.as_mut())
+            .map_err(|_| anyhow!("invalid private key"))?;
+        Ok(SignerKey(key))
+    }
+
+    pub fn from_slice(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != 32 {
+            return Err(anyhow!("invalid private key length: {}", bytes.len()));
+        }
+        let mut key = Zeroizing::new([0u8; 32]);
+        key.copy_from_slice(bytes);
+        Ok(SignerKey(key))
+    }
+
+    /// Decrypts an Ethereum keystore v3 file, of either scrypt or pbkdf2.
+    pub fn from_keystore(path: &Path, password: &str) -> Result<Self> {
+        let key = Zeroizing::new(eth_keystore::decrypt_key(path, password)?);
+        Self::from_slice(&key)
+    }
+
+    pub fn with_key<T>(&self, f: impl FnOnce(&PrivateKey) -> T) -> T {
+        let mut key: PrivateKey = H256(*self.0);
+        let ret = f(&key);
+        key.0.zeroize();
+        ret
+    }
+
+    /// The secp256k1 secret key only lives for the call, it is overwritten
+    /// afterwards since it can not be zeroized.
+    pub fn with_secret_key<T>(&self, f: impl FnOnce(&secp256k1::SecretKey) -> T) -> Result<T> {
+        let mut key = secp256k1::SecretKey::from_slice(self.0.as_ref())?;
+        let ret = f(&key);
+        // SAFETY: the key is overwritten by another valid key
+        unsafe { std::ptr::write_volatile(&mut key, secp256k1::ONE_KEY) };
+        Ok(ret)
+    }
+
+    pub fn pubkey(&self) -> Result<secp256k1::PublicKey> {
+        self.with_secret_key(|key| secp256k1::PublicKey::from_secret_key(&SECP256K1, key))
+    }
+}
+
+impl<'de> Deserialize<'de> for SignerKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = Zeroizing::new(String::deserialize(deserializer)?);
+        SignerKey::from_hex(&hex).map_err(de::Error::custom)
+    }
+}
+
+impl From<PrivateKey> for SignerKey {
+    fn from(mut key: PrivateKey) -> Self {
+        let signer_key = SignerKey(Zeroizing::new(key.0));
+        key.0.zeroize();
+        signer_key
+    }
+}
+
+impl Debug for SignerKey {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str("SignerKey(..)")
+    }
+}
+
+/// Signs like `SecpCkbRawKeySigner`, but the keys are kept zeroized and a
+/// secp256k1 secret key is only made for a signature.
+pub struct KeySigner {
+    keys: Vec<(H160, SignerKey)>,
+}
+
+impl KeySigner {
+    /// The keys of the sighash locks, found by the blake160 of the pubkey.
+    pub fn new(keys: Vec<SignerKey>) -> Result<Self> {
+        let keys = keys
+            .into_iter()
+            .map(|key| {
+                let pubkey = key.pubkey()?;
+                let id = H160::from_slice(&blake2b_256(&pubkey.serialize()[..])[0..20])?;
+                Ok((id, key))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { keys })
+    }
+
+    /// The keys of the Ethereum addresses.
+    pub fn new_ethereum(keys: Vec<SignerKey>) -> Result<Self> {
+        let keys = keys
+            .into_iter()
+            .map(|key| Ok((keccak160(Pubkey::from(key.pubkey()?).as_ref()), key)))
+            .collect::<Result<_>>()?;
+        Ok(Self { keys })
+    }
+
+    fn key(&self, id: &[u8]) -> Option<&SignerKey> {
+        self.keys
+            .iter()
+            .find(|(key_id, _)| key_id.as_bytes() == id)
+            .map(|(_, key)| key)
+    }
+}
+
+impl Signer for KeySigner {
+    fn match_id(&self, id: &[u8]) -> bool {
+        self.key(id).is_some()
+    }
+
+    fn sign(
+        &self,
+        id: &[u8],
+        message: &[u8],
+        recoverable: bool,
+        _tx: &TransactionView,
+    ) -> Result<Bytes, SignerError> {
+        let key = self.key(id).ok_or(SignerError::IdNotFound)?;
+        let message = secp256k1::Message::from_slice(message)
+            .map_err(|e| SignerError::InvalidMessage(e.to_string()))?;
+
+        key.with_secret_key(|key| {
+            if recoverable {
+                let (rec_id, data) = SECP256K1
+                    .sign_ecdsa_recoverable(&message, key)
+                    .serialize_compact();
+                let mut signature = data.to_vec();
+                signature.push(rec_id.to_i32() as u8);
+                Bytes::from(signature)
+            } else {
+                let signature = SECP256K1.sign_ecdsa(&message, key).serialize_compact();
+                Bytes::from(signature.to_vec())
+            }
+        })
+        .map_err(SignerError::Other)
+    }
+}
+
+/// An Ethereum keystore v3 file. The password is read from the env var, or is
+/// prompted if the env var is not set.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct KeystoreConfig {
+    pub path:         PathBuf,
+    #[serde(default = "default_password_env")]
+    pub password_env: String,
+}
+
+fn default_password_env() -> String {
+    DEFAULT_PASSWORD_ENV.to_owned()
+}
+
+impl KeystoreConfig {
+    pub fn load(&self) -> Result<SignerKey> {
+        let password = match std::env::var(&self.password_env) {
+            Ok(password) => Zeroizing::new(password),
+            Err(_) => Zeroizing::new(rpassword::prompt_password(format!(
+                "Password of the keystore {}: ",
+                self.path.display()
+            ))?),
+        };
+        SignerKey::from_keystore(&self.path, &password)
+    }
+}
@@ -1,15 +1,13 @@
 use std::{
     collections::HashMap,
-    fs::{copy, create_dir_all, remove_file, rename, File},
-    io::Write,
+    fs::{create_dir_all, File},
     path::PathBuf,
 };
 
 use anyhow::Result;
 use async_trait::async_trait;
-use ckb_sdk::{ScriptGroup, ScriptGroupType};
 use ckb_types::{
-    core::{Capacity, TransactionBuilder, TransactionView},
+    core::{Capacity, TransactionBuilder},
     packed::{CellDep, CellInput, CellOutput, WitnessArgs},
     prelude::{Entity, Pack, Reader},
 };
@@ -34,6 +32,7 @@ use common::types::axon_types::{
 use common::types::ckb_rpc_client::Cell;
 use common::types::tx_builder::*;
 use common::utils::convert::{to_byte32, to_u64, to_uint64};
+use common::utils::fs::dump_json;
 use molecule::prelude::Builder;
 
 use crate::ckb::define::constants::*;
@@ -47,7 +46,8 @@ const DEFAULT_CONTEXT_PATH: &str = "metadata_context";
 
 pub struct MetadataSmtTxBuilder<'a, C: CkbRpc, PSmt> {
     ckb:                     &'a C,
-    kicker:                  PrivateKey,
+    ctx:                     &'a ChainContext,
+    kicker:                  SignerLock,
     type_ids:                MetadataTypeIds,
     last_checkpoint:         Cell,
     smt:                     PSmt,
@@ -251,7 +251,7 @@ where
 
         let stake_smt_cell_data = StakeSmtCellData {
             smt_root:           Into::<[u8; 32]>::into(new_stake_root).into(),
-            metadata_type_hash: HMetadata::type_(&self.type_ids.metadata_type_id)
+            metadata_type_hash: HMetadata::type_(self.ctx, &self.type_ids.metadata_type_id)
                 .calc_script_hash(),
         };
 
@@ -327,7 +327,7 @@ where
         }
         let delegate_smt_cell_data = DelegateSmtCellData {
             smt_roots:          delegator_staker_smt_roots,
-            metadata_type_hash: HMetadata::type_(&self.type_ids.metadata_type_id)
+            metadata_type_hash: HMetadata::type_(self.ctx, &self.type_ids.metadata_type_id)
                 .calc_script_hash(),
         };
 
@@ -340,7 +340,7 @@ where
         new_stake_smt_proof: Vec<u8>,
         new_delegator_proofs: Vec<DelegateProof>,
     ) -> Result<(AMetadataCellData, WitnessArgs)> {
-        let xudt = Xudt::type_(&self.type_ids.xudt_owner.pack());
+        let xudt = Xudt::type_(self.ctx, &self.type_ids.xudt_owner.pack());
         ProposalSmtStorage::insert(
             &self.smt,
             self.last_checkpoint_data.epoch,
@@ -369,7 +369,11 @@ where
             let mut new_validators = Vec::new();
 
             for v in context.validators.iter() {
-                let stake_lock = HStake::lock(&self.type_ids.metadata_type_id, &v.staker.0.into());
+                let stake_lock = HStake::lock(
+                    self.ctx,
+                    &self.type_ids.metadata_type_id,
+                    &v.staker.0.into(),
+                );
                 let stake_cell = HStake::get_cell(self.ckb, stake_lock, xudt.clone())
                     .await?
                     .expect("Must have stake AT cell");
@@ -472,7 +476,7 @@ where
         Vec<CellOutput>,
         Vec<bytes::Bytes>,
     )> {
-        let xudt = Xudt::type_(&self.type_ids.xudt_owner.pack());
+        let xudt = Xudt::type_(self.ctx, &self.type_ids.xudt_owner.pack());
         let mut witnesses = Vec::new();
         let mut withdraw_set: HashMap<H160, u128> = HashMap::default();
         // remove no top staker
@@ -485,7 +489,8 @@ where
         for (addr, amount) in context.no_top_stakers.iter() {
             *withdraw_set.entry(*addr).or_default() += amount;
 
-            let stake_lock = HStake::lock(&self.type_ids.metadata_type_id, &addr.0.into());
+            let stake_lock =
+                HStake::lock(self.ctx, &self.type_ids.metadata_type_id, &addr.0.into());
             let stake_cell = HStake::get_cell(self.ckb, stake_lock.clone(), xudt.clone())
                 .await?
                 .expect("Must have stake AT cell");
@@ -532,8 +537,11 @@ where
                     match delegator_at_cell_datas.entry(*addr) {
                         std::collections::hash_map::Entry::Occupied(v) => v.into_mut(),
                         std::collections::hash_map::Entry::Vacant(v) => {
-                            let delegate_lock =
-                                HDelegate::lock(&self.type_ids.metadata_type_id, &addr.0.into());
+                            let delegate_lock = HDelegate::lock(
+                                self.ctx,
+                                &self.type_ids.metadata_type_id,
+                                &addr.0.into(),
+                            );
                             let delegate_cell =
                                 HDelegate::get_cell(self.ckb, delegate_lock.clone(), xudt.clone())
                                     .await?
@@ -589,7 +597,8 @@ where
         let mut withdraw_outputs: Vec<CellOutput> = Vec::with_capacity(withdraw_set.len());
         let mut withdraw_output_datas: Vec<bytes::Bytes> = Vec::with_capacity(withdraw_set.len());
         for (addr, amount) in withdraw_set {
-            let withdraw_lock = Withdraw::lock(&self.type_ids.metadata_type_id, &addr.0.into());
+            let withdraw_lock =
+                Withdraw::lock(self.ctx, &self.type_ids.metadata_type_id, &addr.0.into());
             let withdraw_cell = Withdraw::get_cell(self.ckb, withdraw_lock.clone(), xudt.clone())
                 .await?
                 .expect("Must have withdraw cell");
@@ -638,7 +647,7 @@ where
         Ok((witnesses, inputs, outputs, output_datas))
     }
 
-    async fn build_tx(self) -> Result<TransactionView> {
+    async fn build_tx(self) -> Result<UnsignedTx> {
         let context = self.generate_context().await?;
         let (new_stake_smt_proof, stake_smt_cell_data) = self.generate_staker(&context).await?;
         let (new_delegator_proofs, delegate_smt_cell_data) =
@@ -649,13 +658,13 @@ where
         let (no_top_witnesses, no_top_inputs, no_top_outputs, no_top_output_datas) =
             self.no_top_process(&context).await?;
 
-        let metadata_type = HMetadata::type_(&self.type_ids.metadata_type_id);
+        let metadata_type = HMetadata::type_(self.ctx, &self.type_ids.metadata_type_id);
 
-        let stake_smt = HStake::smt_type(&self.type_ids.stake_smt_type_id);
+        let stake_smt = HStake::smt_type(self.ctx, &self.type_ids.stake_smt_type_id);
 
         let last_stake_smt_cell = HStake::get_smt_cell(self.ckb, stake_smt.clone()).await?;
 
-        let delegate_smt = HDelegate::smt_type(&self.type_ids.delegate_smt_type_id);
+        let delegate_smt = HDelegate::smt_type(self.ctx, &self.type_ids.delegate_smt_type_id);
 
         let last_delegate_smt_cell =
             HDelegate::get_smt_cell(self.ckb, delegate_smt.clone()).await?;
@@ -693,17 +702,17 @@ where
         let mut outputs = vec![
             // metadata cell
             CellOutput::new_builder()
-                .lock(AlwaysSuccess::lock())
+                .lock(AlwaysSuccess::lock(self.ctx))
                 .type_(Some(metadata_type).pack())
                 .build_exact_capacity(Capacity::bytes(outputs_data[0].len())?)?,
             // stake smt cell
             CellOutput::new_builder()
-                .lock(AlwaysSuccess::lock())
+                .lock(AlwaysSuccess::lock(self.ctx))
                 .type_(Some(stake_smt).pack())
                 .build_exact_capacity(Capacity::bytes(outputs_data[1].len())?)?,
             // delegate smt cell
             CellOutput::new_builder()
-                .lock(AlwaysSuccess::lock())
+                .lock(AlwaysSuccess::lock(self.ctx))
                 .type_(Some(delegate_smt).pack())
                 .build_exact_capacity(Capacity::bytes(outputs_data[2].len())?)?,
         ];
@@ -713,17 +722,17 @@ where
         outputs.extend(no_top_outputs);
         outputs_data.extend(no_top_output_datas);
 
-        let cell_deps = vec![
-            Secp256k1::lock_dep(),
-            OmniEth::lock_dep(),
-            AlwaysSuccess::lock_dep(),
-            Xudt::type_dep(),
-            HDelegate::lock_dep(),
-            HDelegate::smt_type_dep(),
-            HStake::lock_dep(),
-            HStake::smt_type_dep(),
-            Withdraw::lock_dep(),
-            HMetadata::type_dep(),
+        let mut cell_deps = vec![
+            Secp256k1::lock_dep(self.ctx),
+            OmniEth::lock_dep(self.ctx),
+            AlwaysSuccess::lock_dep(self.ctx),
+            Xudt::type_dep(self.ctx),
+            HDelegate::lock_dep(self.ctx),
+            HDelegate::smt_type_dep(self.ctx),
+            HStake::lock_dep(self.ctx),
+            HStake::smt_type_dep(self.ctx),
+            Withdraw::lock_dep(self.ctx),
+            HMetadata::type_dep(self.ctx),
             // checkpoint cell dep
             CellDep::new_builder()
                 .out_point(self.last_checkpoint.out_point.into())
@@ -735,8 +744,9 @@ where
                 .out_point(self.last_metadata_cell.out_point.into())
                 .build(),
         ];
+        self.kicker.add_cell_deps(&mut cell_deps);
 
-        witnesses.push(OmniEth::witness_placeholder().as_bytes()); // capacity provider lock
+        witnesses.push(self.kicker.witness_placeholder.as_bytes()); // capacity provider lock
 
         let tx = TransactionBuilder::default()
             .inputs(inputs)
@@ -746,20 +756,15 @@ where
             .witnesses(witnesses.pack())
             .build();
 
-        let omni_eth = OmniEth::new(self.kicker.clone());
-        let kicker_lock = OmniEth::lock(&omni_eth.address()?);
-
         let mut tx = Tx::new(self.ckb, tx);
-        tx.balance(kicker_lock.clone()).await?;
+        tx.balance(self.ctx, self.kicker.lock.clone()).await?;
 
-        tx.sign(&omni_eth.signer()?, &ScriptGroup {
-            script:         kicker_lock,
-            group_type:     ScriptGroupType::Lock,
-            input_indices:  vec![tx.inner_ref().witnesses().len() - 1],
-            output_indices: vec![],
-        })?;
+        let unsigned_tx = UnsignedTx {
+            script_groups: vec![tx.lock_script_group(&self.kicker.lock).await?],
+            tx:            tx.inner(),
+        };
 
-        Ok(tx.inner())
+        Ok(unsigned_tx)
     }
 }
 
@@ -770,7 +775,8 @@ where
 {
     async fn new(
         ckb: &'a C,
-        kicker: PrivateKey,
+        ctx: &'a ChainContext,
+        kicker: SignerLock,
         type_ids: MetadataTypeIds,
         last_checkpoint: Cell,
         smt: PSmt,
@@ -780,7 +786,7 @@ where
         let checkpoint_data = CheckpointCellData::new_unchecked(checkpoint_data);
         let last_checkpoint_data: Checkpoint = checkpoint_data.into();
 
-        let metadata_type = HMetadata::type_(&type_ids.metadata_type_id);
+        let metadata_type = HMetadata::type_(ctx, &type_ids.metadata_type_id);
 
         let last_metadata_cell = HMetadata::get_cell(ckb, metadata_type.clone())
             .await
@@ -791,6 +797,7 @@ where
         );
         Self {
             ckb,
+            ctx,
             kicker,
             type_ids,
             last_checkpoint,
@@ -802,7 +809,7 @@ where
         }
     }
 
-    async fn build_tx(self) -> Result<TransactionView> {
+    async fn build_tx(self) -> Result<UnsignedTx> {
         self.build_tx().await
     }
 }
@@ -850,28 +857,5 @@ fn load_file(dir: &PathBuf) -> Option<std::io::BufReader<File>> {
 }
 
 fn dump_to_dir(context: &MetadataContext, dir: &PathBuf) {
-    create_dir_all(dir).unwrap();
-    let tmp_dir = dir.join("tmp");
-    create_dir_all(&tmp_dir).unwrap();
-
-    let tmp_file = tmp_dir.join(DEFAULT_CONTEXT_PATH);
-    let mut file = std::fs::OpenOptions::new()
-        .write(true)
-        .create(true)
-        .append(false)
-        .open(&tmp_file)
-        .unwrap();
-    file.set_len(0)
-        .and_then(|_| serde_json::to_string(&context).map_err(Into::into))
-        .and_then(|json_string| file.write_all(json_string.as_bytes()))
-        .and_then(|_| file.sync_all())
-        .unwrap();
-    move_file(tmp_file, dir.join(DEFAULT_CONTEXT_PATH));
-}
-
-fn move_file<P: AsRef<std::path::Path>>(src: P, dst: P) {
-    if rename(&src, &dst).is_err() {
-        copy(&src, &dst).unwrap();
-        remove_file(&src).unwrap();
-    }
+    dump_json(dir, DEFAULT_CONTEXT_PATH, context).unwrap();
 }
@@ -1,7 +1,6 @@
 use std::collections::HashMap;
 
 use anyhow::Result;
-use ckb_sdk::{ScriptGroup, ScriptGroupType};
 use ckb_types::{
     bytes::Bytes,
     core::{Capacity, TransactionBuilder, TransactionView},
@@ -19,10 +18,13 @@ use common::utils::convert::{to_u128, to_uint128};
 
 use crate::ckb::define::error::CkbTxErr;
 use crate::ckb::helper::{Issue, OmniEth, Secp256k1, Selection, Tx, Xudt};
+use crate::ckb::keystore::SignerKey;
+use crate::ckb::signer::{OmniEthSigner, TxSigner};
 
 pub struct MintTxBuilder<'a, C: CkbRpc> {
     ckb:               &'a C,
-    seeder_key:        PrivateKey,
+    ctx:               &'a ChainContext,
+    seeder:            OmniEthSigner,
     stakers:           HashMap<StakerEthAddr, Amount>,
     selection_type_id: H256,
     issue_type_id:     H256,
@@ -31,14 +33,16 @@ pub struct MintTxBuilder<'a, C: CkbRpc> {
 impl<'a, C: CkbRpc> MintTxBuilder<'a, C> {
     pub fn new(
         ckb: &'a C,
-        seeder_key: PrivateKey,
+        ctx: &'a ChainContext,
+        seeder_key: SignerKey,
         stakers: HashMap<StakerEthAddr, Amount>,
         selection_type_id: H256,
         issue_type_id: H256,
     ) -> Self {
         Self {
             ckb,
-            seeder_key,
+            ctx,
+            seeder: OmniEthSigner::new(seeder_key),
             stakers,
             selection_type_id,
             issue_type_id,
@@ -46,11 +50,11 @@ impl<'a, C: CkbRpc> MintTxBuilder<'a, C> {
     }
 
     pub async fn build_tx(self) -> Result<TransactionView> {
-        let omni_eth = OmniEth::new(self.seeder_key.clone());
-        let seeder_lock = OmniEth::lock(&omni_eth.address()?);
+        let seeder_lock = OmniEth::lock(self.ctx, &self.seeder.address()?);
 
         let selection_cell = Selection::get_cell(self.ckb, &self.selection_type_id).await?;
         let issue_cell = Issue::get_cell(self.ckb, &self.issue_type_id).await?;
+        let issue_lock: Script = issue_cell.output.lock.clone().into();
 
         let inputs = vec![
             // selection cell
@@ -66,10 +70,10 @@ impl<'a, C: CkbRpc> MintTxBuilder<'a, C> {
         let (outputs, outputs_data) = self.fill_outputs(selection_cell, issue_cell)?;
 
         let cell_deps = vec![
-            OmniEth::lock_dep(),
-            Secp256k1::lock_dep(),
-            Xudt::type_dep(),
-            Selection::lock_dep(),
+            OmniEth::lock_dep(self.ctx),
+            Secp256k1::lock_dep(self.ctx),
+            Xudt::type_dep(self.ctx),
+            Selection::lock_dep(self.ctx),
         ];
 
         let witnesses = vec![
@@ -87,23 +91,13 @@ impl<'a, C: CkbRpc> MintTxBuilder<'a, C> {
             .build();
 
         let mut tx = Tx::new(self.ckb, tx);
-        tx.balance(seeder_lock.clone()).await?;
-
-        let signer = OmniEth::new(self.seeder_key.clone()).signer()?;
-        tx.sign(&signer, &ScriptGroup {
-            script:         seeder_lock.clone(),
-            group_type:     ScriptGroupType::Lock,
-            input_indices:  vec![1],
-            output_indices: vec![],
-        })?;
-        tx.sign(&signer, &ScriptGroup {
-            script:         seeder_lock.clone(),
-            group_type:     ScriptGroupType::Lock,
-            input_indices:  vec![2],
-            output_indices: vec![],
-        })?;
-
-        Ok(tx.inner())
+        tx.balance(self.ctx, seeder_lock.clone()).await?;
+
+        let script_groups = vec![
+            tx.lock_script_group(&issue_lock).await?,
+            tx.lock_script_group(&seeder_lock).await?,
+        ];
+        self.seeder.sign(tx.inner(), &script_groups).await
     }
 
     fn fill_outputs(
@@ -115,7 +109,7 @@ impl<'a, C: CkbRpc> MintTxBuilder<'a, C> {
         let mut outputs = vec![];
 
         let selection_lock: Script = selection_cell.output.lock.clone().into();
-        let xudt = Xudt::type_(&selection_lock.calc_script_hash());
+        let xudt = Xudt::type_(self.ctx, &selection_lock.calc_script_hash());
 
         let issue_data = IssueCellData::new_unchecked(issue_cell.output_data.unwrap().into_bytes());
 
@@ -128,7 +122,7 @@ impl<'a, C: CkbRpc> MintTxBuilder<'a, C> {
             outputs_data.push(amount.pack().as_bytes());
             outputs.push(
                 CellOutput::new_builder()
-                    .lock(OmniEth::lock(staker))
+                    .lock(OmniEth::lock(self.ctx, staker))
                     .type_(Some(xudt.clone()).pack())
                     .build_exact_capacity(Capacity::bytes(16)?)?,
             );
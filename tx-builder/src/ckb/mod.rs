@@ -1,21 +1,70 @@
+pub mod batch;
 pub mod checkpoint;
 mod define;
 pub mod delegate;
 pub mod delegate_smt;
+pub mod dry_run;
 pub mod faucet;
 pub mod helper;
 pub mod init;
+pub mod keystore;
 pub mod metadata;
 pub mod mint;
+pub mod registry;
+pub mod requirement;
 pub mod reward;
+pub mod signer;
+pub mod smt_rebuild;
 pub mod stake;
 pub mod stake_smt;
 mod tests;
 pub mod withdraw;
 
+use anyhow::Result;
 use arc_swap::ArcSwap;
-use common::types::tx_builder::NetworkType;
+use ckb_sdk::{Address, AddressPayload};
+use common::types::tx_builder::{
+    ChainContext, FeeParams, NetworkParams, NetworkType, ScriptRegistry, TypeIds,
+};
+
+pub use define::constants::{INAUGURATION, MAX_TX_CYCLES, TOKEN_BYTES};
+pub use define::error::CkbTxErr;
+use registry::builtin_scripts;
 
 lazy_static::lazy_static! {
+    // deprecated, the network and the scripts are in the chain context
     pub static ref NETWORK_TYPE: ArcSwap<NetworkType> = ArcSwap::from_pointee(NetworkType::Testnet);
+    pub static ref SCRIPT_REGISTRY: ArcSwap<ScriptRegistry> = ArcSwap::from_pointee(
+        builtin_scripts(&NetworkType::Testnet).unwrap()
+    );
+}
+
+/// The chain context of the network set by `set_network_type`.
+#[deprecated(note = "build a ChainContext and pass it explicitly")]
+pub fn default_chain_context(type_ids: TypeIds) -> ChainContext {
+    let network = (**NETWORK_TYPE.load()).clone();
+    ChainContext {
+        params: NetworkParams::builtin(&network)
+            .unwrap_or_else(|| NetworkParams::builtin(&NetworkType::Devnet).unwrap()),
+        network,
+        scripts: SCRIPT_REGISTRY.load_full(),
+        type_ids,
+        fee: FeeParams::default(),
+    }
+}
+
+// a custom network is taken as the devnet by the sdk
+pub(crate) fn address_network(ctx: &ChainContext) -> ckb_sdk::NetworkType {
+    match ctx.network {
+        NetworkType::Mainnet => ckb_sdk::NetworkType::Mainnet,
+        NetworkType::Testnet => ckb_sdk::NetworkType::Testnet,
+        NetworkType::Devnet | NetworkType::Custom(_) => ckb_sdk::NetworkType::Dev,
+    }
+}
+
+/// The full address with the prefix of the network.
+pub(crate) fn encode_address(ctx: &ChainContext, payload: AddressPayload) -> Result<String> {
+    let address = Address::new(address_network(ctx), payload, true).to_string();
+    let (_, data, variant) = bech32::decode(&address)?;
+    Ok(bech32::encode(&ctx.params.address_prefix, data, variant)?)
 }
@@ -0,0 +1,321 @@
+use std::path::Path;
+
+use anyhow::Result;
+use ckb_jsonrpc_types::{DepType, ScriptHashType};
+use ckb_types::{bytes::Bytes, prelude::Entity, H256};
+use serde::{Deserialize, Serialize};
+
+use common::traits::ckb_rpc_client::CkbRpc;
+use common::types::axon_types::metadata::MetadataCellData;
+use common::types::tx_builder::{
+    CheckpointTypeIds, DelegateSmtTypeIds, MetadataTypeIds, NetworkType, RewardTypeIds, ScriptInfo,
+    ScriptRegistry, StakeSmtTypeIds, StakeTypeIds, TypeIds,
+};
+
+use crate::ckb::define::error::CkbTxErr;
+use crate::ckb::define::scripts::*;
+use crate::ckb::helper::ckb::cell_collector::get_cell_by_type;
+
+/// Where a contract is deployed.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScriptDeployment {
+    pub code_hash: H256,
+    #[serde(default = "default_hash_type")]
+    pub hash_type: ScriptHashType,
+    pub tx_hash:   H256,
+    #[serde(default)]
+    pub index:     u32,
+    #[serde(default = "default_dep_type")]
+    pub dep_type:  DepType,
+}
+
+fn default_hash_type() -> ScriptHashType {
+    ScriptHashType::Type
+}
+
+fn default_dep_type() -> DepType {
+    DepType::Code
+}
+
+impl From<ScriptDeployment> for ScriptInfo {
+    fn from(deployment: ScriptDeployment) -> Self {
+        ScriptInfo {
+            code_hash: deployment.code_hash,
+            hash_type: deployment.hash_type.into(),
+            tx_hash:   deployment.tx_hash,
+            index:     deployment.index,
+            dep_type:  deployment.dep_type.into(),
+        }
+    }
+}
+
+/// The deployment manifest, a TOML or JSON file. A deployed script overrides
+/// the builtin one of the network, a custom network needs all of them. A
+/// misspelt entry is rejected rather than leaving the builtin script in use.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Deployment {
+    pub omni_lock:                 Option<ScriptDeployment>,
+    pub secp256k1_blake160:        Option<ScriptDeployment>,
+    pub xudt_type:                 Option<ScriptDeployment>,
+    pub always_success_lock:       Option<ScriptDeployment>,
+    pub selection_lock:            Option<ScriptDeployment>,
+    pub checkpoint_type:           Option<ScriptDeployment>,
+    pub metadata_type:             Option<ScriptDeployment>,
+    pub stake_lock:                Option<ScriptDeployment>,
+    pub stake_smt_type:            Option<ScriptDeployment>,
+    pub delegate_requirement_type: Option<ScriptDeployment>,
+    pub delegate_lock:             Option<ScriptDeployment>,
+    pub delegate_smt_type:         Option<ScriptDeployment>,
+    pub withdraw_lock:             Option<ScriptDeployment>,
+    pub reward_smt_type:           Option<ScriptDeployment>,
+}
+
+impl Deployment {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        Ok(match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::from_str(&content)?,
+            _ => toml::from_str(&content)?,
+        })
+    }
+}
+
+/// The deployed scripts override the builtin ones of the network.
+pub fn deployed_scripts(
+    network_type: &NetworkType,
+    deployment: Deployment,
+) -> Result<ScriptRegistry> {
+    let builtin = builtin_scripts(network_type);
+    let builtin = builtin.as_ref();
+
+    Ok(ScriptRegistry {
+        omni_lock:                 pick(
+            "omni_lock",
+            deployment.omni_lock,
+            builtin.map(|r| &r.omni_lock),
+        )?,
+        secp256k1_blake160:        pick(
+            "secp256k1_blake160",
+            deployment.secp256k1_blake160,
+            builtin.map(|r| &r.secp256k1_blake160),
+        )?,
+        xudt_type:                 pick(
+            "xudt_type",
+            deployment.xudt_type,
+            builtin.map(|r| &r.xudt_type),
+        )?,
+        always_success_lock:       pick(
+            "always_success_lock",
+            deployment.always_success_lock,
+            builtin.map(|r| &r.always_success_lock),
+        )?,
+        selection_lock:            pick(
+            "selection_lock",
+            deployment.selection_lock,
+            builtin.map(|r| &r.selection_lock),
+        )?,
+        checkpoint_type:           pick(
+            "checkpoint_type",
+            deployment.checkpoint_type,
+            builtin.map(|r| &r.checkpoint_type),
+        )?,
+        metadata_type:             pick(
+            "metadata_type",
+            deployment.metadata_type,
+            builtin.map(|r| &r.metadata_type),
+        )?,
+        stake_lock:                pick(
+            "stake_lock",
+            deployment.stake_lock,
+            builtin.map(|r| &r.stake_lock),
+        )?,
+        stake_smt_type:            pick(
+            "stake_smt_type",
+            deployment.stake_smt_type,
+            builtin.map(|r| &r.stake_smt_type),
+        )?,
+        delegate_requirement_type: pick(
+            "delegate_requirement_type",
+            deployment.delegate_requirement_type,
+            builtin.map(|r| &r.delegate_requirement_type),
+        )?,
+        delegate_lock:             pick(
+            "delegate_lock",
+            deployment.delegate_lock,
+            builtin.map(|r| &r.delegate_lock),
+        )?,
+        delegate_smt_type:         pick(
+            "delegate_smt_type",
+            deployment.delegate_smt_type,
+            builtin.map(|r| &r.delegate_smt_type),
+        )?,
+        withdraw_lock:             pick(
+            "withdraw_lock",
+            deployment.withdraw_lock,
+            builtin.map(|r| &r.withdraw_lock),
+        )?,
+        reward_smt_type:           pick(
+            "reward_smt_type",
+            deployment.reward_smt_type,
+            builtin.map(|r| &r.reward_smt_type),
+        )?,
+    })
+}
+
+pub fn load_scripts(network_type: &NetworkType, path: impl AsRef<Path>) -> Result<ScriptRegistry> {
+    deployed_scripts(network_type, Deployment::load(path)?)
+}
+
+/// The scripts of the network, a custom network needs the deployment manifest.
+pub fn network_scripts(
+    network_type: &NetworkType,
+    deployment: Option<&Path>,
+) -> Result<ScriptRegistry> {
+    match deployment {
+        Some(path) => load_scripts(network_type, path),
+        None => deployed_scripts(network_type, Deployment::default()),
+    }
+}
+
+/// The scripts compiled in for the network, none for a custom network.
+pub fn builtin_scripts(network_type: &NetworkType) -> Option<ScriptRegistry> {
+    match network_type {
+        NetworkType::Mainnet => Some(ScriptRegistry {
+            omni_lock:                 OMNI_LOCK_MAINNET.clone(),
+            secp256k1_blake160:        SECP2561_BLAKE160_MAINNET.clone(),
+            xudt_type:                 XUDT_TYPE_MAINNET.clone(),
+            always_success_lock:       ALWAYS_SUCCESS_LOCK_MAINNET.clone(),
+            selection_lock:            SELECTION_LOCK_MAINNET.clone(),
+            checkpoint_type:           CHECKPOINT_TYPE_MAINNET.clone(),
+            metadata_type:             METADATA_TYPE_MAINNET.clone(),
+            stake_lock:                STAKE_LOCK_MAINNET.clone(),
+            stake_smt_type:            STAKE_SMT_TYPE_MAINNET.clone(),
+            delegate_requirement_type: DELEGATE_REQUIREMENT_TYPE_MAINNET.clone(),
+            delegate_lock:             DELEGATE_LOCK_MAINNET.clone(),
+            delegate_smt_type:         DELEGATE_SMT_TYPE_MAINNET.clone(),
+            withdraw_lock:             WITHDRAW_LOCK_MAINNET.clone(),
+            reward_smt_type:           REWARD_SMT_TYPE_MAINNET.clone(),
+        }),
+        NetworkType::Testnet => Some(ScriptRegistry {
+            omni_lock:                 OMNI_LOCK_TESTNET.clone(),
+            secp256k1_blake160:        SECP2561_BLAKE160_TESTNET.clone(),
+            xudt_type:                 XUDT_TYPE_TESTNET.clone(),
+            always_success_lock:       ALWAYS_SUCCESS_LOCK_TESTNET.clone(),
+            selection_lock:            SELECTION_LOCK_TESTNET.clone(),
+            checkpoint_type:           CHECKPOINT_TYPE_TESTNET.clone(),
+            metadata_type:             METADATA_TYPE_TESTNET.clone(),
+            stake_lock:                STAKE_LOCK_TESTNET.clone(),
+            stake_smt_type:            STAKE_SMT_TYPE_TESTNET.clone(),
+            delegate_requirement_type: DELEGATE_REQUIREMENT_TYPE_TESTNET.clone(),
+            delegate_lock:             DELEGATE_LOCK_TESTNET.clone(),
+            delegate_smt_type:         DELEGATE_SMT_TYPE_TESTNET.clone(),
+            withdraw_lock:             WITHDRAW_LOCK_TESTNET.clone(),
+            reward_smt_type:           REWARD_SMT_TYPE_TESTNET.clone(),
+        }),
+        NetworkType::Devnet => Some(ScriptRegistry {
+            omni_lock:                 OMNI_LOCK_DEVNET.clone(),
+            secp256k1_blake160:        SECP2561_BLAKE160_DEVNET.clone(),
+            xudt_type:                 XUDT_TYPE_DEVNET.clone(),
+            always_success_lock:       ALWAYS_SUCCESS_LOCK_DEVNET.clone(),
+            selection_lock:            SELECTION_LOCK_DEVNET.clone(),
+            checkpoint_type:           CHECKPOINT_TYPE_DEVNET.clone(),
+            metadata_type:             METADATA_TYPE_DEVNET.clone(),
+            stake_lock:                STAKE_LOCK_DEVNET.clone(),
+            stake_smt_type:            STAKE_SMT_TYPE_DEVNET.clone(),
+            delegate_requirement_type: DELEGATE_REQUIREMENT_TYPE_DEVNET.clone(),
+            delegate_lock:             DELEGATE_LOCK_DEVNET.clone(),
+            delegate_smt_type:         DELEGATE_SMT_TYPE_DEVNET.clone(),
+            withdraw_lock:             WITHDRAW_LOCK_DEVNET.clone(),
+            reward_smt_type:           REWARD_SMT_TYPE_DEVNET.clone(),
+        }),
+        NetworkType::Custom(_) => None,
+    }
+}
+
+fn pick(
+    name: &'static str,
+    deployed: Option<ScriptDeployment>,
+    builtin: Option<&ScriptInfo>,
+) -> Result<ScriptInfo> {
+    match (deployed, builtin) {
+        (Some(deployed), _) => Ok(deployed.into()),
+        (None, Some(builtin)) => Ok(builtin.clone()),
+        (None, None) => Err(CkbTxErr::ScriptNotDeployed(name).into()),
+    }
+}
+
+/// The type ids of the deployed contracts, from which the type ids of every tx
+/// builder are derived.
+#[derive(Clone, Debug)]
+pub struct TypeIdRegistry {
+    type_ids: TypeIds,
+}
+
+impl From<TypeIds> for TypeIdRegistry {
+    fn from(type_ids: TypeIds) -> Self {
+        TypeIdRegistry { type_ids }
+    }
+}
+
+impl TypeIdRegistry {
+    /// Reads the type ids recorded in the metadata cell, so that the metadata
+    /// type id is the only one to be configured.
+    pub async fn discover(
+        ckb: &impl CkbRpc,
+        scripts: &ScriptRegistry,
+        metadata_type_id: &H256,
+    ) -> Result<Self> {
+        let metadata_type = scripts
+            .metadata_type
+            .script(Bytes::from(metadata_type_id.as_bytes().to_vec()));
+        let cell = get_cell_by_type(ckb, metadata_type).await?;
+        let data =
+            MetadataCellData::new_unchecked(cell.output_data.unwrap_or_default().into_bytes());
+
+        let type_ids: TypeIds = data.type_ids().into();
+        if &type_ids.metadata_type_id != metadata_type_id {
+            return Err(CkbTxErr::MetadataTypeIdMismatch {
+                expected: metadata_type_id.clone(),
+                found:    type_ids.metadata_type_id,
+            }
+            .into());
+        }
+
+        Ok(TypeIdRegistry { type_ids })
+    }
+
+    pub fn type_ids(&self) -> &TypeIds {
+        &self.type_ids
+    }
+
+    pub fn into_type_ids(self) -> TypeIds {
+        self.type_ids
+    }
+
+    pub fn stake(&self) -> StakeTypeIds {
+        (&self.type_ids).into()
+    }
+
+    pub fn reward(&self) -> RewardTypeIds {
+        (&self.type_ids).into()
+    }
+
+    pub fn stake_smt(&self) -> StakeSmtTypeIds {
+        (&self.type_ids).into()
+    }
+
+    pub fn delegate_smt(&self) -> DelegateSmtTypeIds {
+        (&self.type_ids).into()
+    }
+
+    pub fn metadata(&self) -> MetadataTypeIds {
+        (&self.type_ids).into()
+    }
+
+    pub fn checkpoint(&self) -> CheckpointTypeIds {
+        (&self.type_ids).into()
+    }
+}
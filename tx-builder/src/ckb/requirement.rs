@@ -0,0 +1,133 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use ckb_types::{
+    core::{TransactionBuilder, TransactionView},
+    packed::{CellInput, CellOutput, Script},
+    prelude::{Builder, Entity, Pack},
+};
+
+use common::traits::ckb_rpc_client::CkbRpc;
+use common::traits::tx_builder::IRequirementTxBuilder;
+use common::types::axon_types::delegate::DelegateCellData;
+use common::types::tx_builder::{ChainContext, EthAddress, StakeTypeIds};
+
+use crate::ckb::define::error::CkbTxErr;
+use crate::ckb::helper::{Delegate, Metadata, OmniEth, Secp256k1, Stake, Tx};
+
+pub struct RequirementTxBuilder<'a, C: CkbRpc> {
+    ckb:             &'a C,
+    ctx:             &'a ChainContext,
+    type_ids:        StakeTypeIds,
+    staker:          EthAddress,
+    commission_rate: u8,
+    token_lock:      Script,
+}
+
+#[async_trait]
+impl<'a, C: CkbRpc> IRequirementTxBuilder<'a, C> for RequirementTxBuilder<'a, C> {
+    fn new(
+        ckb: &'a C,
+        ctx: &'a ChainContext,
+        type_ids: StakeTypeIds,
+        staker: EthAddress,
+        commission_rate: u8,
+    ) -> Self {
+        let token_lock = OmniEth::lock(ctx, &staker);
+
+        Self {
+            ckb,
+            ctx,
+            type_ids,
+            staker,
+            commission_rate,
+            token_lock,
+        }
+    }
+
+    async fn build_tx(self) -> Result<TransactionView> {
+        if self.commission_rate > 100 {
+            return Err(CkbTxErr::CommissionRate(self.commission_rate).into());
+        }
+
+        let (requirement_type_id, _) = Stake::get_delegate_requirement_type_id(
+            self.ckb,
+            self.ctx,
+            &self.type_ids.metadata_type_id,
+            &self.staker,
+            &self.type_ids.xudt_owner,
+        )
+        .await?;
+        let requirement_type = Delegate::requirement_type(
+            self.ctx,
+            &self.type_ids.metadata_type_id,
+            &requirement_type_id,
+        );
+        let requirement_cell =
+            Delegate::get_requirement_cell(self.ckb, requirement_type.clone()).await?;
+
+        let requirement_data = DelegateCellData::from_slice(
+            &requirement_cell
+                .output_data
+                .clone()
+                .unwrap_or_default()
+                .into_bytes(),
+        )
+        .map_err(|_| CkbTxErr::MalformedCellData("delegate requirement"))?;
+
+        log::info!(
+            "[requirement] staker: {}, old commission rate: {}, new commission rate: {}",
+            self.staker.to_string(),
+            u8::from(requirement_data.delegate_requirement().commission_rate()),
+            self.commission_rate,
+        );
+
+        let inner_requirement = requirement_data.delegate_requirement();
+        let output_data = requirement_data
+            .as_builder()
+            .delegate_requirement(
+                inner_requirement
+                    .as_builder()
+                    .commission_rate(self.commission_rate.into())
+                    .build(),
+            )
+            .build()
+            .as_bytes();
+
+        // delegate requirement cell
+        let inputs = vec![CellInput::new_builder()
+            .previous_output(requirement_cell.out_point.into())
+            .build()];
+
+        // the capacity is kept since the data has the same size
+        let outputs = vec![CellOutput::from(requirement_cell.output)
+            .as_builder()
+            .lock(self.token_lock.clone())
+            .type_(Some(requirement_type).pack())
+            .build()];
+
+        let cell_deps = vec![
+            OmniEth::lock_dep(self.ctx),
+            Secp256k1::lock_dep(self.ctx),
+            Delegate::requriement_type_dep(self.ctx),
+            Metadata::cell_dep(self.ckb, self.ctx, &self.type_ids.metadata_type_id).await?,
+        ];
+
+        let witnesses = vec![
+            OmniEth::witness_placeholder().as_bytes(), // delegate requirement cell lock
+            OmniEth::witness_placeholder().as_bytes(), // capacity provider lock
+        ];
+
+        let tx = TransactionBuilder::default()
+            .inputs(inputs)
+            .outputs(outputs)
+            .outputs_data(vec![output_data].pack())
+            .cell_deps(cell_deps)
+            .witnesses(witnesses.pack())
+            .build();
+
+        let mut tx = Tx::new(self.ckb, tx);
+        tx.balance(self.ctx, self.token_lock.clone()).await?;
+
+        Ok(tx.inner())
+    }
+}
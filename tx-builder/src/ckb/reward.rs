@@ -21,7 +21,7 @@ use common::{
         metadata::MetadataCellData,
         reward::{RewardSmtCellData as ARewardSmtCellData, RewardWitness as ARewardWitness},
     },
-    types::tx_builder::{Amount, Epoch, EthAddress, RewardMeta, RewardTypeIds},
+    types::tx_builder::{Amount, ChainContext, Epoch, EthAddress, RewardMeta, RewardTypeIds},
     utils::convert::{to_ckb_h160, to_eth_h160},
 };
 
@@ -32,8 +32,8 @@ use crate::ckb::define::types::{
     RewardWitness,
 };
 use crate::ckb::helper::{
-    AlwaysSuccess, Checkpoint, Delegate, Metadata, OmniEth, Reward, Secp256k1, Selection, Stake,
-    Tx, Xudt,
+    reward_calculator::proposed_validators, AlwaysSuccess, Checkpoint, Delegate, Metadata, OmniEth,
+    Reward, RewardCalculator, Secp256k1, Selection, Stake, Tx, Xudt,
 };
 
 pub struct RewardTxBuilder<'a, C, S>
@@ -42,6 +42,7 @@ where
     S: RewardSmtStorage + StakeSmtStorage + DelegateSmtStorage + ProposalSmtStorage,
 {
     ckb:                   &'a C,
+    ctx:                   &'a ChainContext,
     type_ids:              RewardTypeIds,
     reward_meta:           RewardMeta,
     smt:                   S,
@@ -63,18 +64,22 @@ where
 {
     async fn new(
         ckb: &'a C,
+        ctx: &'a ChainContext,
         type_ids: RewardTypeIds,
         smt: S,
         user: EthAddress,
         current_epoch: Epoch,
         epoch_count: u64,
-    ) -> Self {
-        let metadata_cell = Metadata::get_cell(ckb, Metadata::type_(&type_ids.metadata_type_id))
-            .await
-            .expect("Metadata cell not found");
-        let metadata_cell_data = MetadataCellData::new_unchecked(
-            metadata_cell.output_data.clone().unwrap().into_bytes(),
-        );
+    ) -> Result<Self> {
+        let metadata_cell =
+            Metadata::get_cell(ckb, Metadata::type_(ctx, &type_ids.metadata_type_id)).await?;
+        let metadata_cell_data = metadata_cell
+            .output_data
+            .clone()
+            .ok_or(CkbTxErr::MalformedCellData("metadata"))?
+            .into_bytes();
+        let metadata_cell_data = MetadataCellData::from_compatible_slice(&metadata_cell_data)
+            .map_err(|_| CkbTxErr::MalformedCellData("metadata"))?;
 
         let minimum_propose_count = Metadata::calc_minimum_propose_count(&metadata_cell_data);
         log::info!("[reward] minimum propose count: {}", minimum_propose_count);
@@ -82,8 +87,9 @@ where
         let reward_metadata = Metadata::parse_reward_meta(&metadata_cell_data);
         log::info!("[reward] reward metadata: {:?}", reward_metadata);
 
-        Self {
+        Ok(Self {
             ckb,
+            ctx,
             type_ids,
             reward_meta: Metadata::parse_reward_meta(&metadata_cell_data),
             smt,
@@ -95,7 +101,7 @@ where
             commission_rates: HashMap::new(),
             stake_cell_deps: Vec::new(),
             requirement_cell_deps: Vec::new(),
-        }
+        })
     }
 
     async fn build_tx(mut self) -> Result<TransactionView> {
@@ -103,7 +109,8 @@ where
             return Err(CkbTxErr::RewardCurrentEpoch(self.current_epoch).into());
         }
 
-        let reward_smt_cell = Reward::get_cell(self.ckb, &self.type_ids.reward_smt_type_id).await?;
+        let reward_smt_cell =
+            Reward::get_cell(self.ckb, self.ctx, &self.type_ids.reward_smt_type_id).await?;
         let selection_cell =
             Selection::get_cell(self.ckb, &self.type_ids.selection_type_id).await?;
 
@@ -131,8 +138,14 @@ where
         let outputs = vec![
             // reward smt cell
             CellOutput::new_builder()
-                .lock(AlwaysSuccess::lock())
-                .type_(Some(Reward::smt_type(&self.type_ids.reward_smt_type_id)).pack())
+                .lock(AlwaysSuccess::lock(self.ctx))
+                .type_(
+                    Some(Reward::smt_type(
+                        self.ctx,
+                        &self.type_ids.reward_smt_type_id,
+                    ))
+                    .pack(),
+                )
                 .build_exact_capacity(Capacity::bytes(outputs_data[0].len())?)?,
             // selection cell
             CellOutput::new_builder()
@@ -141,21 +154,21 @@ where
                 .build_exact_capacity(Capacity::bytes(outputs_data[1].len())?)?,
             // AT cell
             CellOutput::new_builder()
-                .lock(Secp256k1::lock(Bytes::from(self.user.0.to_vec())))
-                .type_(Some(Xudt::type_(&self.type_ids.xudt_owner.pack())).pack())
+                .lock(Secp256k1::lock(self.ctx, Bytes::from(self.user.0.to_vec())))
+                .type_(Some(Xudt::type_(self.ctx, &self.type_ids.xudt_owner.pack())).pack())
                 .build_exact_capacity(Capacity::bytes(outputs_data[2].len())?)?,
         ];
 
         let mut cell_deps = vec![
-            OmniEth::lock_dep(),
-            Secp256k1::lock_dep(),
-            AlwaysSuccess::lock_dep(),
-            Xudt::type_dep(),
-            Reward::smt_type_dep(),
-            Selection::lock_dep(),
-            Checkpoint::cell_dep(self.ckb, &self.type_ids.checkpoint_type_id).await?,
-            Stake::smt_cell_dep(self.ckb, &self.type_ids.stake_smt_type_id).await?,
-            Delegate::smt_cell_dep(self.ckb, &self.type_ids.delegate_smt_type_id).await?,
+            OmniEth::lock_dep(self.ctx),
+            Secp256k1::lock_dep(self.ctx),
+            AlwaysSuccess::lock_dep(self.ctx),
+            Xudt::type_dep(self.ctx),
+            Reward::smt_type_dep(self.ctx),
+            Selection::lock_dep(self.ctx),
+            Checkpoint::cell_dep(self.ckb, self.ctx, &self.type_ids.checkpoint_type_id).await?,
+            Stake::smt_cell_dep(self.ckb, self.ctx, &self.type_ids.stake_smt_type_id).await?,
+            Delegate::smt_cell_dep(self.ckb, self.ctx, &self.type_ids.delegate_smt_type_id).await?,
             // metadata cell dep
             CellDep::new_builder()
                 .out_point(self.metadata_outpoint.clone().into())
@@ -185,7 +198,8 @@ where
             .build();
 
         let mut tx = Tx::new(self.ckb, tx);
-        tx.balance(OmniEth::lock(&self.user)).await?;
+        tx.balance(self.ctx, OmniEth::lock(self.ctx, &self.user))
+            .await?;
 
         Ok(tx.inner())
     }
@@ -199,8 +213,8 @@ where
     async fn add_token_to_inputs(&self, inputs: &mut Vec<CellInput>) -> Result<Option<Amount>> {
         let (token_cells, amount) = Xudt::collect(
             self.ckb,
-            OmniEth::lock(&self.user),
-            Xudt::type_(&self.type_ids.xudt_owner.pack()),
+            OmniEth::lock(self.ctx, &self.user),
+            Xudt::type_(self.ctx, &self.type_ids.xudt_owner.pack()),
             1,
         )
         .await?;
@@ -254,26 +268,66 @@ where
             end_reward_epoch,
         );
 
+        // The commission rates are read before the calculation, which adds the
+        // stake and requirement cells of the validators to the cell deps.
+        let mut commission_rates = HashMap::new();
+        for validator in
+            proposed_validators(&self.smt, start_reward_epoch..=end_reward_epoch).await?
+        {
+            let commission_rate = self.commission_rate(&to_ckb_h160(&validator)).await?;
+            commission_rates.insert(validator, commission_rate);
+        }
+        let calculator = RewardCalculator::new(
+            &self.smt,
+            self.reward_meta.clone(),
+            self.minimum_propose_count,
+            self.current_epoch,
+            commission_rates,
+        );
+
         let mut total_reward_amount = 0_u128;
         let user = to_eth_h160(&self.user);
 
         for epoch in start_reward_epoch..=end_reward_epoch {
-            let propose_counts = ProposalSmtStorage::get_sub_leaves(&self.smt, epoch)
-                .await
-                .unwrap();
+            let epoch_reward = calculator.calc_epoch_reward(user, epoch).await?;
+            total_reward_amount += epoch_reward.amount;
 
             let mut epoch_reward_witness = EpochRewardStakeInfo::default();
             let mut validators = vec![];
 
-            total_reward_amount += self
-                .calc_epoch_reward(
-                    user,
-                    epoch,
-                    propose_counts,
-                    &mut epoch_reward_witness,
-                    &mut validators,
-                )
-                .await?;
+            for reward in epoch_reward.validators.into_iter() {
+                log::info!(
+                    "[reward] epoch: {}, validator: {}, propose count: {}, stake amount: {}, total delegate amount: {}, total reward: {}, reward: {}",
+                    epoch, reward.validator.to_string(), reward.propose_count, reward.stake_amount,
+                    reward.total_delegate_amount, reward.total_reward, reward.reward,
+                );
+
+                validators.push(reward.validator);
+
+                let all_delegates =
+                    DelegateSmtStorage::get_sub_leaves(&self.smt, epoch, reward.validator).await?;
+                epoch_reward_witness
+                    .reward_stake_infos
+                    .push(RewardStakeInfo {
+                        validator:            to_ckb_h160(&reward.validator),
+                        propose_count:        reward.propose_count,
+                        stake_amount:         reward.stake_amount,
+                        delegate_infos:       all_delegates
+                            .into_iter()
+                            .map(|(delegator_addr, amount)| RewardDelegateInfo {
+                                delegator_addr,
+                                amount,
+                            })
+                            .collect(),
+                        delegate_epoch_proof: DelegateSmtStorage::generate_top_proof(
+                            &self.smt,
+                            vec![epoch],
+                            reward.validator,
+                        )
+                        .await
+                        .unwrap(),
+                    });
+            }
 
             epoch_reward_witness.count_proof =
                 ProposalSmtStorage::generate_sub_proof(&self.smt, epoch, validators.clone())
@@ -326,7 +380,7 @@ where
                 // reward smt cell data
                 ARewardSmtCellData::from(RewardSmtCellData {
                     claim_smt_root:     reward_smt_root,
-                    metadata_type_hash: Metadata::type_(&self.type_ids.metadata_type_id)
+                    metadata_type_hash: Metadata::type_(self.ctx, &self.type_ids.metadata_type_id)
                         .calc_script_hash(),
                 })
                 .as_bytes(),
@@ -371,6 +425,7 @@ where
 
         let (requirement_type_id, stake_cell_outpoint) = Stake::get_delegate_requirement_type_id(
             self.ckb,
+            self.ctx,
             &self.type_ids.metadata_type_id,
             staker,
             &self.type_ids.xudt_owner,
@@ -379,7 +434,11 @@ where
 
         let delegate_requirement_cell = Delegate::get_requirement_cell(
             self.ckb,
-            Delegate::requirement_type(&self.type_ids.metadata_type_id, &requirement_type_id),
+            Delegate::requirement_type(
+                self.ctx,
+                &self.type_ids.metadata_type_id,
+                &requirement_type_id,
+            ),
         )
         .await?;
 
@@ -403,119 +462,4 @@ where
             .commission_rate()
             .into())
     }
-
-    async fn calc_epoch_reward(
-        &mut self,
-        user: ethereum_types::H160,
-        epoch: u64,
-        propose_counts: HashMap<ethereum_types::H160, u64>,
-        epoch_reward_witness: &mut EpochRewardStakeInfo,
-        validators: &mut Vec<ethereum_types::H160>,
-    ) -> Result<u128> {
-        let mut epoch_reward = 0;
-
-        for (validator, propose_count) in propose_counts.into_iter() {
-            log::info!(
-                "[reward] epoch: {}, validator: {}",
-                epoch,
-                validator.to_string(),
-            );
-
-            validators.push(validator);
-
-            let is_validator = user == validator;
-
-            let delegate_amount =
-                DelegateSmtStorage::get_amount(&self.smt, epoch + INAUGURATION, validator, user)
-                    .await
-                    .unwrap();
-
-            let in_delegate_smt = delegate_amount.is_some();
-
-            let commission_rate = self.commission_rate(&to_ckb_h160(&validator)).await? as u128;
-
-            log::info!(
-                "[reward] commission_rate: {}, propose count: {}",
-                commission_rate,
-                propose_count,
-            );
-
-            let mut total_reward = self.reward_meta.base_reward
-                / (2_u64.pow((self.current_epoch / self.reward_meta.half_reward_cycle) as u32))
-                    as u128;
-
-            if propose_count < self.minimum_propose_count {
-                total_reward = total_reward * self.reward_meta.propose_discount_rate as u128 / 100;
-            }
-
-            let stake_amount = StakeSmtStorage::get_amount(&self.smt, epoch, validator).await?;
-            if stake_amount.is_none() {
-                return Err(CkbTxErr::StakeAmountNotFound(epoch, validator).into());
-            }
-            let stake_amount = stake_amount.unwrap();
-
-            let all_delegates =
-                DelegateSmtStorage::get_sub_leaves(&self.smt, epoch, validator).await?;
-            let total_delegate_amount = all_delegates.values().sum::<Amount>();
-
-            let total_amount = stake_amount + total_delegate_amount;
-            let staker_reward = total_reward * stake_amount / total_amount;
-            let delegators_reward = total_reward - staker_reward;
-
-            log::info!(
-                "[reward] epoch: {}, stake amount: {}, total delegate amount: {}, total reward: {}, staker reward: {}, delegators reward: {}",
-                epoch, stake_amount, total_delegate_amount, total_reward, staker_reward, delegators_reward,
-            );
-
-            if is_validator {
-                let staker_fee_reward = delegators_reward * commission_rate / 100;
-                epoch_reward += staker_reward + staker_fee_reward;
-                log::info!(
-                    "[reward] epoch: {}, is a validator, reward: {}",
-                    epoch,
-                    staker_reward + staker_fee_reward,
-                );
-            } else if in_delegate_smt {
-                let delegate_reward = delegators_reward * delegate_amount.unwrap()
-                    / total_delegate_amount
-                    * (100 - commission_rate)
-                    / 100;
-                epoch_reward += delegate_reward;
-                log::info!(
-                    "[reward] epoch: {}, delegate amount: {}, reward: {}",
-                    epoch,
-                    delegate_amount.unwrap(),
-                    delegate_reward,
-                );
-            }
-
-            log::info!(
-                "[reward] validator: {:?}, propose count: {}, stake amount: {}, delegators count: {}",
-                validator.to_string(), propose_count, stake_amount, all_delegates.len()
-            );
-
-            epoch_reward_witness
-                .reward_stake_infos
-                .push(RewardStakeInfo {
-                    validator: to_ckb_h160(&validator),
-                    propose_count,
-                    stake_amount,
-                    delegate_infos: all_delegates
-                        .into_iter()
-                        .map(|(delegator_addr, amount)| RewardDelegateInfo {
-                            delegator_addr,
-                            amount,
-                        })
-                        .collect(),
-                    delegate_epoch_proof: DelegateSmtStorage::generate_top_proof(
-                        &self.smt,
-                        vec![epoch],
-                        validator,
-                    )
-                    .await
-                    .unwrap(),
-                });
-        }
-        Ok(epoch_reward)
-    }
 }
@@ -0,0 +1,493 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use ckb_jsonrpc_types::{
+    Script as JsonScript, Transaction, TransactionView as JsonTransactionView,
+};
+use ckb_sdk::unlock::{
+    MultisigConfig, OmniLockConfig, OmniLockScriptSigner, OmniUnlockMode, ScriptSigner,
+};
+use ckb_sdk::{ScriptGroup, ScriptGroupType};
+use ckb_types::{core::TransactionView, packed, prelude::*, H160, H256};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use common::types::tx_builder::{ChainContext, SignerLock, UnsignedTx};
+
+use crate::ckb::helper::{OmniEth, Secp256k1, Sighash};
+use crate::ckb::keystore::{KeySigner, KeystoreConfig, SignerKey};
+
+const EXTERNAL_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Signs the txs built with its [`SignerLock`]. The builders only leave the
+/// witness placeholder of the lock, so the keys can be kept out of the
+/// process.
+#[async_trait]
+pub trait TxSigner: Send + Sync {
+    fn signer_lock(&self, ctx: &ChainContext) -> Result<SignerLock>;
+
+    /// Every script group is signed with the lock of the signer.
+    async fn sign(
+        &self,
+        tx: TransactionView,
+        script_groups: &[ScriptGroup],
+    ) -> Result<TransactionView>;
+
+    async fn sign_unsigned(&self, unsigned_tx: UnsignedTx) -> Result<TransactionView> {
+        self.sign(unsigned_tx.tx, &unsigned_tx.script_groups).await
+    }
+}
+
+/// The lock of a signer, which is known without its keys.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LockConfig {
+    OmniEth {
+        address: H160,
+    },
+    Sighash {
+        pubkey_hash: H160,
+    },
+    OmniMultisig {
+        sighash_addresses: Vec<H160>,
+        require_first_n:   u8,
+        threshold:         u8,
+    },
+}
+
+impl LockConfig {
+    pub fn signer_lock(&self, ctx: &ChainContext) -> Result<SignerLock> {
+        Ok(match self {
+            Self::OmniEth { address } => SignerLock {
+                lock:                OmniEth::lock(ctx, address),
+                cell_deps:           vec![OmniEth::lock_dep(ctx), Secp256k1::lock_dep(ctx)],
+                witness_placeholder: OmniEth::witness_placeholder(),
+            },
+            Self::Sighash { pubkey_hash } => SignerLock {
+                lock:                Sighash::pubkey_hash_lock(ctx, pubkey_hash),
+                cell_deps:           vec![Sighash::lock_dep(ctx)],
+                witness_placeholder: Sighash::witness_placeholder(),
+            },
+            Self::OmniMultisig {
+                sighash_addresses,
+                require_first_n,
+                threshold,
+            } => {
+                let config =
+                    multisig_config(sighash_addresses.clone(), *require_first_n, *threshold)?;
+                SignerLock {
+                    lock:                OmniEth::config_lock(ctx, &config),
+                    cell_deps:           vec![OmniEth::lock_dep(ctx), Secp256k1::lock_dep(ctx)],
+                    witness_placeholder: config.placeholder_witness(OmniUnlockMode::Normal)?,
+                }
+            }
+        })
+    }
+}
+
+fn multisig_config(
+    sighash_addresses: Vec<H160>,
+    require_first_n: u8,
+    threshold: u8,
+) -> Result<OmniLockConfig> {
+    Ok(OmniLockConfig::new_multisig(MultisigConfig::new_with(
+        sighash_addresses,
+        require_first_n,
+        threshold,
+    )?))
+}
+
+/// How a signer is set in the config, the local signers use the key of the
+/// config if they have none of their own.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SignerConfig {
+    #[default]
+    OmniEth,
+    Sighash,
+    OmniMultisig {
+        sighash_addresses: Vec<H160>,
+        require_first_n:   u8,
+        threshold:         u8,
+        // the keys of the signatures, at least `threshold` of them
+        keystores:         Vec<KeystoreConfig>,
+    },
+    External {
+        lock:         LockConfig,
+        transport:    ExternalTransport,
+        // seconds to wait for the signed tx
+        timeout_secs: u64,
+    },
+}
+
+impl SignerConfig {
+    pub fn build(&self, key: SignerKey) -> Result<Arc<dyn TxSigner>> {
+        Ok(match self {
+            Self::OmniEth => Arc::new(OmniEthSigner::new(key)),
+            Self::Sighash => Arc::new(SighashSigner::new(key)),
+            Self::OmniMultisig {
+                sighash_addresses,
+                require_first_n,
+                threshold,
+                keystores,
+            } => Arc::new(OmniMultisigSigner::new(
+                sighash_addresses.clone(),
+                *require_first_n,
+                *threshold,
+                keystores
+                    .iter()
+                    .map(KeystoreConfig::load)
+                    .collect::<Result<_>>()?,
+            )),
+            Self::External {
+                lock,
+                transport,
+                timeout_secs,
+            } => Arc::new(ExternalSigner::new(
+                lock.clone(),
+                transport.clone(),
+                Duration::from_secs(*timeout_secs),
+            )),
+        })
+    }
+}
+
+pub struct OmniEthSigner {
+    key: SignerKey,
+}
+
+impl OmniEthSigner {
+    pub fn new(key: SignerKey) -> Self {
+        Self { key }
+    }
+
+    pub fn address(&self) -> Result<H160> {
+        self.key.with_key(|key| OmniEth::new(key.clone()).address())
+    }
+}
+
+#[async_trait]
+impl TxSigner for OmniEthSigner {
+    fn signer_lock(&self, ctx: &ChainContext) -> Result<SignerLock> {
+        LockConfig::OmniEth {
+            address: self.address()?,
+        }
+        .signer_lock(ctx)
+    }
+
+    async fn sign(
+        &self,
+        tx: TransactionView,
+        script_groups: &[ScriptGroup],
+    ) -> Result<TransactionView> {
+        self.key.with_key(|key| {
+            let signer = OmniEth::new(key.clone()).signer()?;
+            sign_script_groups(&signer, tx, script_groups)
+        })
+    }
+}
+
+pub struct SighashSigner {
+    key: SignerKey,
+}
+
+impl SighashSigner {
+    pub fn new(key: SignerKey) -> Self {
+        Self { key }
+    }
+}
+
+#[async_trait]
+impl TxSigner for SighashSigner {
+    fn signer_lock(&self, ctx: &ChainContext) -> Result<SignerLock> {
+        let lock = self
+            .key
+            .with_key(|key| Sighash::new(key.clone()).lock(ctx))?;
+        Ok(SignerLock {
+            lock,
+            cell_deps: vec![Sighash::lock_dep(ctx)],
+            witness_placeholder: Sighash::witness_placeholder(),
+        })
+    }
+
+    async fn sign(
+        &self,
+        tx: TransactionView,
+        script_groups: &[ScriptGroup],
+    ) -> Result<TransactionView> {
+        self.key.with_key(|key| {
+            let signer = Sighash::new(key.clone()).signer()?;
+            sign_script_groups(&signer, tx, script_groups)
+        })
+    }
+}
+
+/// An Omni lock multisig of secp256k1 sighash keys. All the signatures are
+/// made locally, so the keys should reach the threshold.
+pub struct OmniMultisigSigner {
+    sighash_addresses: Vec<H160>,
+    require_first_n:   u8,
+    threshold:         u8,
+    keys:              Vec<SignerKey>,
+}
+
+impl OmniMultisigSigner {
+    pub fn new(
+        sighash_addresses: Vec<H160>,
+        require_first_n: u8,
+        threshold: u8,
+        keys: Vec<SignerKey>,
+    ) -> Self {
+        Self {
+            sighash_addresses,
+            require_first_n,
+            threshold,
+            keys,
+        }
+    }
+}
+
+#[async_trait]
+impl TxSigner for OmniMultisigSigner {
+    fn signer_lock(&self, ctx: &ChainContext) -> Result<SignerLock> {
+        LockConfig::OmniMultisig {
+            sighash_addresses: self.sighash_addresses.clone(),
+            require_first_n:   self.require_first_n,
+            threshold:         self.threshold,
+        }
+        .signer_lock(ctx)
+    }
+
+    async fn sign(
+        &self,
+        tx: TransactionView,
+        script_groups: &[ScriptGroup],
+    ) -> Result<TransactionView> {
+        let signer = OmniLockScriptSigner::new(
+            Box::new(KeySigner::new(self.keys.clone())?),
+            multisig_config(
+                self.sighash_addresses.clone(),
+                self.require_first_n,
+                self.threshold,
+            )?,
+            OmniUnlockMode::Normal,
+        );
+        sign_script_groups(&signer, tx, script_groups)
+    }
+}
+
+fn sign_script_groups(
+    signer: &impl ScriptSigner,
+    mut tx: TransactionView,
+    script_groups: &[ScriptGroup],
+) -> Result<TransactionView> {
+    for script_group in script_groups.iter() {
+        tx = signer.sign_tx(&tx, script_group)?;
+    }
+    Ok(tx)
+}
+
+/// Where the external signer is reached.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExternalTransport {
+    // a unix socket, one json line is written and one json line is read back
+    Socket(PathBuf),
+    // a directory, `<tx hash>.json` is written and `<tx hash>.signed.json` is
+    // waited for, both are renamed into place from a `.tmp` file
+    Dir(PathBuf),
+}
+
+/// The request sent to the external signer.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SignRequest {
+    pub tx:            JsonTransactionView,
+    pub script_groups: Vec<SignScriptGroup>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SignScriptGroup {
+    pub script:         JsonScript,
+    // lock or type
+    pub group_type:     String,
+    pub input_indices:  Vec<usize>,
+    pub output_indices: Vec<usize>,
+}
+
+impl From<&ScriptGroup> for SignScriptGroup {
+    fn from(group: &ScriptGroup) -> Self {
+        Self {
+            script:         group.script.clone().into(),
+            group_type:     match group.group_type {
+                ScriptGroupType::Lock => "lock".to_string(),
+                ScriptGroupType::Type => "type".to_string(),
+            },
+            input_indices:  group.input_indices.clone(),
+            output_indices: group.output_indices.clone(),
+        }
+    }
+}
+
+/// Hands the tx to a signer out of the process, such as a hardware wallet
+/// bridge. The signed tx replies with the whole tx, whose hash must be the
+/// same as the unsigned one.
+pub struct ExternalSigner {
+    lock:      LockConfig,
+    transport: ExternalTransport,
+    timeout:   Duration,
+}
+
+impl ExternalSigner {
+    pub fn new(lock: LockConfig, transport: ExternalTransport, timeout: Duration) -> Self {
+        Self {
+            lock,
+            transport,
+            timeout,
+        }
+    }
+
+    async fn sign_by_socket(&self, path: &Path, request: &str) -> Result<Transaction> {
+        let mut stream = tokio::net::UnixStream::connect(path).await?;
+        stream.write_all(request.as_bytes()).await?;
+        stream.write_all(b"\n").await?;
+
+        let mut response = String::new();
+        BufReader::new(stream).read_line(&mut response).await?;
+        Ok(serde_json::from_str(response.trim())?)
+    }
+
+    async fn sign_by_dir(&self, dir: &Path, tx_hash: &str, request: &str) -> Result<Transaction> {
+        // renamed into place, so the request is never read half written
+        let tmp_path = dir.join(format!("{}.json.tmp", tx_hash));
+        tokio::fs::write(&tmp_path, request).await?;
+        tokio::fs::rename(&tmp_path, request_path(dir, tx_hash)).await?;
+
+        loop {
+            match tokio::fs::read_to_string(signed_path(dir, tx_hash)).await {
+                // a response which is not renamed into place may be half written
+                Ok(response) => match serde_json::from_str(response.trim()) {
+                    Ok(signed) => return Ok(signed),
+                    Err(e) => log::warn!("[signer] read the signed tx {} again: {}", tx_hash, e),
+                },
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+            tokio::time::sleep(EXTERNAL_POLL_INTERVAL).await;
+        }
+    }
+}
+
+fn request_path(dir: &Path, tx_hash: &str) -> PathBuf {
+    dir.join(format!("{}.json", tx_hash))
+}
+
+fn signed_path(dir: &Path, tx_hash: &str) -> PathBuf {
+    dir.join(format!("{}.signed.json", tx_hash))
+}
+
+async fn remove_files(dir: &Path, tx_hash: &str) {
+    for path in [request_path(dir, tx_hash), signed_path(dir, tx_hash)] {
+        if let Err(e) = tokio::fs::remove_file(&path).await {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                log::warn!("[signer] remove {} failed: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+/// Only the lock fields of the witnesses of the signed groups may be changed
+/// by the signer, the hash of the tx does not cover the witnesses.
+fn check_witnesses(
+    tx: &TransactionView,
+    signed: &TransactionView,
+    script_groups: &[ScriptGroup],
+) -> Result<()> {
+    let tx_hash: H256 = tx.hash().unpack();
+    if signed.witnesses().len() != tx.witnesses().len() {
+        return Err(anyhow!(
+            "external signer changed the witness count, tx: 0x{}",
+            tx_hash
+        ));
+    }
+
+    let signed_indices = script_groups
+        .iter()
+        .filter_map(|group| group.input_indices.first().copied())
+        .collect::<HashSet<_>>();
+    for (i, (witness, signed_witness)) in tx
+        .witnesses()
+        .into_iter()
+        .zip(signed.witnesses().into_iter())
+        .enumerate()
+    {
+        if witness.raw_data() == signed_witness.raw_data() {
+            continue;
+        }
+
+        let changed = || {
+            anyhow!(
+                "external signer changed the witness {}, tx: 0x{}",
+                i,
+                tx_hash
+            )
+        };
+        if !signed_indices.contains(&i) {
+            return Err(changed());
+        }
+        let witness = packed::WitnessArgs::from_slice(&witness.raw_data())?;
+        let signed_witness = packed::WitnessArgs::from_slice(&signed_witness.raw_data())?;
+        if witness.input_type().as_slice() != signed_witness.input_type().as_slice()
+            || witness.output_type().as_slice() != signed_witness.output_type().as_slice()
+        {
+            return Err(changed());
+        }
+    }
+
+    Ok(())
+}
+
+#[async_trait]
+impl TxSigner for ExternalSigner {
+    fn signer_lock(&self, ctx: &ChainContext) -> Result<SignerLock> {
+        self.lock.signer_lock(ctx)
+    }
+
+    async fn sign(
+        &self,
+        tx: TransactionView,
+        script_groups: &[ScriptGroup],
+    ) -> Result<TransactionView> {
+        let tx_hash: H256 = tx.hash().unpack();
+        let tx_hash = format!("0x{}", tx_hash);
+        let request = serde_json::to_string(&SignRequest {
+            tx:            tx.clone().into(),
+            script_groups: script_groups.iter().map(Into::into).collect(),
+        })?;
+
+        let signed = match &self.transport {
+            ExternalTransport::Socket(path) => {
+                tokio::time::timeout(self.timeout, self.sign_by_socket(path, &request)).await
+            }
+            ExternalTransport::Dir(dir) => {
+                let signed =
+                    tokio::time::timeout(self.timeout, self.sign_by_dir(dir, &tx_hash, &request))
+                        .await;
+                remove_files(dir, &tx_hash).await;
+                signed
+            }
+        }
+        .map_err(|_| anyhow!("external signer timed out, tx: {}", tx_hash))??;
+
+        let signed = packed::Transaction::from(signed).into_view();
+        if signed.hash() != tx.hash() {
+            return Err(anyhow!("external signer changed the tx: {}", tx_hash));
+        }
+        check_witnesses(&tx, &signed, script_groups)?;
+
+        Ok(signed)
+    }
+}
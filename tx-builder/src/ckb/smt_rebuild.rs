@@ -0,0 +1,566 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use anyhow::{anyhow, Result};
+use ckb_jsonrpc_types::{
+    CellOutput as JsonCellOutput, Either, OutPoint, Script as JsonScript, TransactionView,
+};
+use ckb_types::{
+    bytes::Bytes,
+    packed::{Script, WitnessArgs},
+    prelude::Entity,
+    H160, H256,
+};
+
+use common::traits::{
+    ckb_rpc_client::CkbRpc,
+    smt::{DelegateSmtStorage, ProposalSmtStorage, RewardSmtStorage, StakeSmtStorage},
+};
+use common::types::axon_types::{
+    checkpoint::CheckpointCellData,
+    delegate::{DelegateAtCellData, DelegateCellData, DelegateSmtCellData, DelegateSmtWitness},
+    metadata::{MetadataCellData, MetadataWitness},
+    reward::{RewardSmtCellData, RewardWitness},
+    stake::{StakeAtCellData, StakeSmtCellData, StakeSmtWitness},
+};
+use common::types::smt::{Address, Amount, Delegator, Staker, UserAmount};
+use common::types::tx_builder::{ChainContext, Checkpoint as CheckpointData, DelegateItem, Epoch};
+use common::utils::convert::{to_ckb_h160, to_eth_h160, to_h160, to_u128, to_u64, to_usize};
+
+use crate::ckb::define::constants::{INAUGURATION, TOKEN_BYTES};
+use crate::ckb::helper::ckb::cell_collector::get_cell_by_type;
+use crate::ckb::helper::{Checkpoint, Delegate, Metadata, Reward, Stake};
+
+// The AT lock args are the metadata type hash followed by the owner address.
+const OWNER_OFFSET: usize = 32;
+
+/// Rebuilds the SMTs from the history of the stake smt, delegate smt and reward
+/// smt cells on CKB. Every smt tx since the init tx is replayed with the old
+/// leaves in its witness and the deltas of its input AT cells. After each tx
+/// the rebuilt roots must match the roots in the tx outputs.
+///
+/// The SMT storage should be empty before the rebuild.
+pub struct SmtRebuilder<'a, C, S> {
+    ckb: &'a C,
+    ctx: &'a ChainContext,
+    smt: &'a S,
+    txs: Mutex<HashMap<H256, TransactionView>>,
+}
+
+struct HistoryTx {
+    hash:   H256,
+    tx:     TransactionView,
+    inputs: Vec<(JsonCellOutput, Bytes)>,
+}
+
+impl HistoryTx {
+    fn input_index(&self, type_: &JsonScript) -> Option<usize> {
+        self.inputs
+            .iter()
+            .position(|(output, _)| output.type_.as_ref() == Some(type_))
+    }
+
+    fn output_data(&self, type_: &JsonScript) -> Result<Bytes> {
+        self.tx
+            .inner
+            .outputs
+            .iter()
+            .position(|output| output.type_.as_ref() == Some(type_))
+            .and_then(|i| self.tx.inner.outputs_data.get(i))
+            .map(|data| data.clone().into_bytes())
+            .ok_or_else(|| anyhow!("smt cell not found in outputs of tx 0x{}", self.hash))
+    }
+
+    // the type witness of the smt cell input
+    fn witness(&self, type_: &JsonScript) -> Result<Bytes> {
+        let witness = self
+            .input_index(type_)
+            .and_then(|i| self.tx.inner.witnesses.get(i))
+            .ok_or_else(|| anyhow!("smt witness not found in tx 0x{}", self.hash))?;
+
+        WitnessArgs::from_slice(witness.as_bytes())?
+            .input_type()
+            .to_opt()
+            .map(|w| w.raw_data())
+            .ok_or_else(|| anyhow!("empty smt witness in tx 0x{}", self.hash))
+    }
+}
+
+impl<'a, C, S> SmtRebuilder<'a, C, S>
+where
+    C: CkbRpc,
+    S: StakeSmtStorage + DelegateSmtStorage + RewardSmtStorage + ProposalSmtStorage,
+{
+    pub fn new(ckb: &'a C, ctx: &'a ChainContext, smt: &'a S) -> Self {
+        Self {
+            ckb,
+            ctx,
+            smt,
+            txs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn rebuild(&self) -> Result<()> {
+        let stake_smt_type = Stake::smt_type(self.ctx, &self.ctx.type_ids.stake_smt_type_id);
+        let delegate_smt_type =
+            Delegate::smt_type(self.ctx, &self.ctx.type_ids.delegate_smt_type_id);
+        let metadata_type: JsonScript =
+            Metadata::type_(self.ctx, &self.ctx.type_ids.metadata_type_id).into();
+
+        // The metadata txs update both the stake smt and the delegate smt cells. The
+        // stake smt txs and the delegate smt txs between two metadata txs do not
+        // depend on each other.
+        let mut stake_txs = self.history(stake_smt_type).await?.into_iter();
+        let mut delegate_txs = self.history(delegate_smt_type).await?.into_iter();
+        loop {
+            let mut metadata_tx = None;
+            for tx in stake_txs.by_ref() {
+                if tx.input_index(&metadata_type).is_some() {
+                    metadata_tx = Some(tx);
+                    break;
+                }
+                self.replay_stake_smt(&tx).await?;
+            }
+            for tx in delegate_txs.by_ref() {
+                if tx.input_index(&metadata_type).is_some() {
+                    break;
+                }
+                self.replay_delegate_smt(&tx).await?;
+            }
+
+            match metadata_tx {
+                Some(tx) => self.replay_metadata(&tx).await?,
+                None => break,
+            }
+        }
+
+        for tx in self
+            .history(Reward::smt_type(
+                self.ctx,
+                &self.ctx.type_ids.reward_smt_type_id,
+            ))
+            .await?
+        {
+            self.replay_reward(&tx).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn replay_stake_smt(&self, tx: &HistoryTx) -> Result<()> {
+        let epoch = self.checkpoint(tx).await?.epoch + INAUGURATION;
+        let quorum = Metadata::parse_quorum(&MetadataCellData::new_unchecked(
+            self.dep_data(
+                tx,
+                &Metadata::type_(self.ctx, &self.ctx.type_ids.metadata_type_id).into(),
+            )
+            .await?,
+        ));
+
+        let stake_smt_type: JsonScript =
+            Stake::smt_type(self.ctx, &self.ctx.type_ids.stake_smt_type_id).into();
+        let witness = StakeSmtWitness::new_unchecked(tx.witness(&stake_smt_type)?);
+        let mut new_smt = witness
+            .update_info()
+            .all_stake_infos()
+            .into_iter()
+            .map(|info| (to_eth_h160(&to_h160(&info.addr())), to_u128(&info.amount())))
+            .collect::<HashMap<Staker, Amount>>();
+
+        let stake_lock = Stake::lock_prefix(self.ctx, &self.ctx.type_ids.metadata_type_id);
+        for (output, data) in tx.inputs.iter() {
+            let staker = match parse_owner(&output.lock, &stake_lock) {
+                Some(staker) if data.len() > TOKEN_BYTES => staker,
+                _ => continue,
+            };
+            let stake_data = StakeAtCellData::new_unchecked(data.slice(TOKEN_BYTES..));
+            let delta = Stake::item(&stake_data.lock().delta());
+            if delta.inauguration_epoch < epoch {
+                continue;
+            }
+
+            let amount = apply_stake_delta(
+                &staker,
+                new_smt.get(&staker).copied(),
+                delta.is_increase,
+                delta.amount,
+            )?;
+            new_smt.insert(staker, amount);
+        }
+        remove_lowest(&mut new_smt, 3 * quorum as usize);
+
+        StakeSmtStorage::insert(self.smt, epoch, to_user_amounts(new_smt)).await?;
+        self.verify_stake_root(tx).await
+    }
+
+    async fn replay_delegate_smt(&self, tx: &HistoryTx) -> Result<()> {
+        let epoch = self.checkpoint(tx).await?.epoch + INAUGURATION;
+
+        let delegate_smt_type: JsonScript =
+            Delegate::smt_type(self.ctx, &self.ctx.type_ids.delegate_smt_type_id).into();
+        let witness = DelegateSmtWitness::new_unchecked(tx.witness(&delegate_smt_type)?);
+        let deltas = self.delegate_deltas(tx, epoch)?;
+
+        for group in witness.update_info().all_stake_group_infos().into_iter() {
+            let staker = to_eth_h160(&to_h160(&group.staker()));
+            let mut new_smt = group
+                .delegate_infos()
+                .into_iter()
+                .map(|info| {
+                    (
+                        to_eth_h160(&to_h160(&info.delegator_addr())),
+                        to_u128(&info.amount()),
+                    )
+                })
+                .collect::<HashMap<Delegator, Amount>>();
+
+            for (delegator, delta) in deltas.get(&staker).into_iter().flatten() {
+                let amount = apply_delegate_delta(
+                    delegator,
+                    new_smt.get(delegator).copied(),
+                    delta.is_increase,
+                    delta.amount,
+                )?;
+                new_smt.insert(*delegator, amount);
+            }
+            let maximum_delegators = self.maximum_delegators(tx, &staker).await?;
+            remove_lowest(&mut new_smt, maximum_delegators);
+
+            DelegateSmtStorage::insert(self.smt, epoch, staker, to_user_amounts(new_smt)).await?;
+        }
+
+        self.verify_delegate_roots(tx).await
+    }
+
+    async fn replay_metadata(&self, tx: &HistoryTx) -> Result<()> {
+        let checkpoint = self.checkpoint(tx).await?;
+        let epoch = checkpoint.epoch + INAUGURATION;
+
+        let metadata_type: JsonScript =
+            Metadata::type_(self.ctx, &self.ctx.type_ids.metadata_type_id).into();
+        let witness = MetadataWitness::new_unchecked(tx.witness(&metadata_type)?);
+        let metadata = MetadataCellData::new_unchecked(tx.output_data(&metadata_type)?);
+
+        let validators = metadata
+            .metadata()
+            .get(1)
+            .ok_or_else(|| anyhow!("next metadata not found in tx 0x{}", tx.hash))?
+            .validators()
+            .into_iter()
+            .map(|v| to_eth_h160(&to_h160(&v.address())))
+            .collect::<HashSet<_>>();
+
+        let mut no_top_stakers = vec![];
+        let mut no_top_delegators = vec![];
+        for miner in witness.smt_election_info().n2().miners().into_iter() {
+            let staker = to_eth_h160(&to_h160(&miner.staker()));
+            if validators.contains(&staker) {
+                continue;
+            }
+
+            no_top_stakers.push(staker);
+            for delegate in miner.delegate_infos().into_iter() {
+                no_top_delegators.push((staker, to_eth_h160(&to_h160(&delegate.addr()))));
+            }
+        }
+
+        StakeSmtStorage::remove(self.smt, epoch, no_top_stakers).await?;
+        StakeSmtStorage::new_epoch(self.smt, epoch + 1).await?;
+        DelegateSmtStorage::remove(self.smt, epoch, no_top_delegators).await?;
+        DelegateSmtStorage::new_epoch(self.smt, epoch + 1).await?;
+        ProposalSmtStorage::insert(
+            self.smt,
+            checkpoint.epoch,
+            checkpoint
+                .propose_count
+                .iter()
+                .map(|p| (to_eth_h160(&p.proposer), p.count))
+                .collect(),
+        )
+        .await?;
+
+        let proposal_root = ProposalSmtStorage::get_top_root(self.smt).await?;
+        if metadata.propose_count_smt_root().as_slice() != proposal_root.as_slice() {
+            return Err(anyhow!("proposal smt root mismatch in tx 0x{}", tx.hash));
+        }
+        self.verify_stake_root(tx).await?;
+        self.verify_delegate_roots(tx).await
+    }
+
+    async fn replay_reward(&self, tx: &HistoryTx) -> Result<()> {
+        let reward_smt_type: JsonScript =
+            Reward::smt_type(self.ctx, &self.ctx.type_ids.reward_smt_type_id).into();
+        let witness = RewardWitness::new_unchecked(tx.witness(&reward_smt_type)?);
+
+        RewardSmtStorage::insert(
+            self.smt,
+            to_u64(&witness.new_not_claim_info().epoch()),
+            to_eth_h160(&to_h160(&witness.miner())),
+        )
+        .await?;
+
+        let data = RewardSmtCellData::new_unchecked(tx.output_data(&reward_smt_type)?);
+        let root = RewardSmtStorage::get_root(self.smt).await?;
+        if data.claim_smt_root().as_slice() != root.as_slice() {
+            return Err(anyhow!("reward smt root mismatch in tx 0x{}", tx.hash));
+        }
+        Ok(())
+    }
+
+    async fn verify_stake_root(&self, tx: &HistoryTx) -> Result<()> {
+        let stake_smt_type = Stake::smt_type(self.ctx, &self.ctx.type_ids.stake_smt_type_id).into();
+        let data = StakeSmtCellData::new_unchecked(tx.output_data(&stake_smt_type)?);
+        let root = StakeSmtStorage::get_top_root(self.smt).await?;
+
+        if data.smt_root().as_slice() != root.as_slice() {
+            return Err(anyhow!("stake smt root mismatch in tx 0x{}", tx.hash));
+        }
+        Ok(())
+    }
+
+    async fn verify_delegate_roots(&self, tx: &HistoryTx) -> Result<()> {
+        let delegate_smt_type =
+            Delegate::smt_type(self.ctx, &self.ctx.type_ids.delegate_smt_type_id).into();
+        let data = DelegateSmtCellData::new_unchecked(tx.output_data(&delegate_smt_type)?);
+
+        for staker_root in data.smt_roots().into_iter() {
+            let staker = to_eth_h160(&to_h160(&staker_root.staker()));
+            let root = DelegateSmtStorage::get_top_root(self.smt, staker).await?;
+            if staker_root.root().as_slice() != root.as_slice() {
+                return Err(anyhow!(
+                    "delegate smt root of {} mismatch in tx 0x{}",
+                    staker,
+                    tx.hash
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // The same as the delegate smt tx builder, the items of a delegate AT cell
+    // before the first expired one take effect.
+    fn delegate_deltas(
+        &self,
+        tx: &HistoryTx,
+        epoch: Epoch,
+    ) -> Result<HashMap<Staker, HashMap<Delegator, DelegateItem>>> {
+        let delegate_lock = Delegate::lock_prefix(self.ctx, &self.ctx.type_ids.metadata_type_id);
+        let mut deltas: HashMap<Staker, HashMap<Delegator, DelegateItem>> = HashMap::new();
+
+        for (output, data) in tx.inputs.iter() {
+            let delegator = match parse_owner(&output.lock, &delegate_lock) {
+                Some(delegator) if data.len() > TOKEN_BYTES => delegator,
+                _ => continue,
+            };
+            let delegate_data = DelegateAtCellData::new_unchecked(data.slice(TOKEN_BYTES..));
+
+            for info in delegate_data.lock().delegator_infos().into_iter() {
+                let item = Delegate::item(&info);
+                if item.inauguration_epoch < epoch {
+                    break;
+                }
+                deltas
+                    .entry(to_eth_h160(&item.staker))
+                    .or_default()
+                    .insert(delegator, item);
+            }
+        }
+
+        Ok(deltas)
+    }
+
+    // The stake AT cell and the delegate requirement cell of the staker are the
+    // cell deps of the delegate smt tx.
+    async fn maximum_delegators(&self, tx: &HistoryTx, staker: &Staker) -> Result<usize> {
+        let metadata_type_id = &self.ctx.type_ids.metadata_type_id;
+        let stake_data = self
+            .dep_data_by(tx, |output| {
+                output.lock == Stake::lock(self.ctx, metadata_type_id, &to_ckb_h160(staker)).into()
+            })
+            .await?;
+        if stake_data.len() < TOKEN_BYTES {
+            return Err(anyhow!("invalid stake AT cell of {}", staker));
+        }
+
+        let requirement_type_id = StakeAtCellData::new_unchecked(stake_data.slice(TOKEN_BYTES..))
+            .lock()
+            .requirement_info()
+            .requirement()
+            .requirement_type_id();
+        let requirement_type = Delegate::requirement_type(
+            self.ctx,
+            metadata_type_id,
+            &H256::from_slice(&requirement_type_id.as_bytes())?,
+        );
+        let requirement_data = self.dep_data(tx, &requirement_type.into()).await?;
+
+        Ok(to_usize(
+            DelegateCellData::new_unchecked(requirement_data)
+                .delegate_requirement()
+                .max_delegator_size(),
+        ))
+    }
+
+    async fn checkpoint(&self, tx: &HistoryTx) -> Result<CheckpointData> {
+        let checkpoint_type =
+            Checkpoint::type_(self.ctx, &self.ctx.type_ids.checkpoint_type_id).into();
+        let data = self.dep_data(tx, &checkpoint_type).await?;
+        Ok(CheckpointCellData::new_unchecked(data).into())
+    }
+
+    async fn dep_data(&self, tx: &HistoryTx, type_: &JsonScript) -> Result<Bytes> {
+        self.dep_data_by(tx, |output| output.type_.as_ref() == Some(type_))
+            .await
+    }
+
+    async fn dep_data_by<F>(&self, tx: &HistoryTx, f: F) -> Result<Bytes>
+    where
+        F: Fn(&JsonCellOutput) -> bool,
+    {
+        for dep in tx.tx.inner.cell_deps.iter() {
+            let (output, data) = self.get_output(&dep.out_point).await?;
+            if f(&output) {
+                return Ok(data);
+            }
+        }
+        Err(anyhow!("cell dep not found in tx 0x{}", tx.hash))
+    }
+
+    // Returns the txs that spent an older smt cell, from the oldest one to the
+    // latest one.
+    async fn history(&self, type_: Script) -> Result<Vec<HistoryTx>> {
+        let json_type: JsonScript = type_.clone().into();
+        let mut tx_hash = get_cell_by_type(self.ckb, type_).await?.out_point.tx_hash;
+        let mut history = vec![];
+
+        loop {
+            let tx = self.get_tx(&tx_hash).await?;
+            let mut inputs = Vec::with_capacity(tx.inner.inputs.len());
+            for input in tx.inner.inputs.iter() {
+                inputs.push(self.get_output(&input.previous_output).await?);
+            }
+
+            let tx = HistoryTx {
+                hash: tx_hash,
+                tx,
+                inputs,
+            };
+            // the first smt cell is created by the init tx
+            let prev_tx_hash = match tx.input_index(&json_type) {
+                Some(i) => tx.tx.inner.inputs[i].previous_output.tx_hash.clone(),
+                None => break,
+            };
+
+            history.push(tx);
+            tx_hash = prev_tx_hash;
+        }
+
+        history.reverse();
+        Ok(history)
+    }
+
+    async fn get_output(&self, out_point: &OutPoint) -> Result<(JsonCellOutput, Bytes)> {
+        let tx = self.get_tx(&out_point.tx_hash).await?;
+        let index = out_point.index.value() as usize;
+
+        match (
+            tx.inner.outputs.get(index),
+            tx.inner.outputs_data.get(index),
+        ) {
+            (Some(output), Some(data)) => Ok((output.clone(), data.clone().into_bytes())),
+            _ => Err(anyhow!("cell not found: {:?}", out_point)),
+        }
+    }
+
+    async fn get_tx(&self, tx_hash: &H256) -> Result<TransactionView> {
+        if let Some(tx) = self.txs.lock().unwrap().get(tx_hash) {
+            return Ok(tx.clone());
+        }
+
+        let tx = self
+            .ckb
+            .get_transaction(tx_hash.clone())
+            .await?
+            .and_then(|tx| tx.transaction)
+            .ok_or_else(|| anyhow!("tx not found: 0x{}", tx_hash))?;
+        let tx = match tx.inner {
+            Either::Left(tx) => tx,
+            Either::Right(_) => return Err(anyhow!("tx in bytes: 0x{}", tx_hash)),
+        };
+
+        self.txs.lock().unwrap().insert(tx_hash.clone(), tx.clone());
+        Ok(tx)
+    }
+}
+
+fn parse_owner(lock: &JsonScript, lock_prefix: &Script) -> Option<H160> {
+    let prefix = lock_prefix.args().raw_data();
+    let args = lock.args.as_bytes();
+
+    if lock.code_hash.as_bytes() != lock_prefix.code_hash().as_slice()
+        || args.len() < OWNER_OFFSET + 20
+        || args[..OWNER_OFFSET] != prefix[..]
+    {
+        return None;
+    }
+    Some(to_eth_h160(
+        &H160::from_slice(&args[OWNER_OFFSET..OWNER_OFFSET + 20]).ok()?,
+    ))
+}
+
+// The amount of a staker after the delta. The same as `StakeSmtTxBuilder`, a
+// decrease over the amount leaves the amount unchanged. The first delta of a
+// staker must be an increase, otherwise the history can not be replayed.
+pub(crate) fn apply_stake_delta(
+    staker: &Address,
+    amount: Option<Amount>,
+    is_increase: bool,
+    delta: Amount,
+) -> Result<Amount> {
+    match amount {
+        Some(amount) if is_increase => Ok(amount + delta),
+        Some(amount) => Ok(amount.checked_sub(delta).unwrap_or(amount)),
+        None if is_increase => Ok(delta),
+        None => Err(anyhow!("the first delta of {} is a decrease", staker)),
+    }
+}
+
+// The amount of a delegator after the delta. The same as
+// `DelegateSmtTxBuilder`, a decrease over the amount redeems the whole amount.
+pub(crate) fn apply_delegate_delta(
+    delegator: &Address,
+    amount: Option<Amount>,
+    is_increase: bool,
+    delta: Amount,
+) -> Result<Amount> {
+    match amount {
+        Some(amount) if is_increase => Ok(amount + delta),
+        Some(amount) => Ok(amount.saturating_sub(delta)),
+        None if is_increase => Ok(delta),
+        None => Err(anyhow!("the first delta of {} is a decrease", delegator)),
+    }
+}
+
+// The same as the smt tx builders, the users with the lowest amounts are
+// removed if there are more than `limit` users.
+fn remove_lowest(smt: &mut HashMap<Address, Amount>, limit: usize) {
+    if smt.len() <= limit {
+        return;
+    }
+
+    let mut amounts = smt.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>();
+    amounts.sort_unstable_by_key(|v| v.1);
+    for (user, _) in amounts[..amounts.len() - limit].iter() {
+        smt.remove(user);
+    }
+}
+
+fn to_user_amounts(smt: HashMap<Address, Amount>) -> Vec<UserAmount> {
+    smt.into_iter()
+        .map(|(user, amount)| UserAmount {
+            user,
+            amount,
+            is_increase: true,
+        })
+        .collect()
+}
@@ -21,10 +21,6 @@ use common::utils::convert::*;
 
 use crate::ckb::define::constants::*;
 use crate::ckb::define::error::{CkbTxErr, CkbTxResult};
-use crate::ckb::define::scripts::{
-    DELEGATE_REQUIREMENT_TYPE_DEVNET, DELEGATE_REQUIREMENT_TYPE_MAINNET,
-    DELEGATE_REQUIREMENT_TYPE_TESTNET,
-};
 use crate::ckb::define::types::{
     DelegateRequirementArgs, DelegateRequirementInfo, StakeAtCellData, StakeAtCellLockData,
 };
@@ -32,10 +28,10 @@ use crate::ckb::helper::{
     amount_calculator::*, token_cell_data, Checkpoint, Delegate, Metadata, OmniEth, Secp256k1,
     Stake, Tx, TypeId, Withdraw, Xudt,
 };
-use crate::ckb::NETWORK_TYPE;
 
 pub struct StakeTxBuilder<'a, C: CkbRpc> {
     ckb:              &'a C,
+    ctx:              &'a ChainContext,
     type_ids:         StakeTypeIds,
     staker:           EthAddress,
     current_epoch:    Epoch,
@@ -51,19 +47,21 @@ pub struct StakeTxBuilder<'a, C: CkbRpc> {
 impl<'a, C: CkbRpc> IStakeTxBuilder<'a, C> for StakeTxBuilder<'a, C> {
     fn new(
         ckb: &'a C,
+        ctx: &'a ChainContext,
         type_ids: StakeTypeIds,
         staker: EthAddress,
         current_epoch: Epoch,
         stake_item: StakeItem,
         first_stake_info: Option<FirstStakeInfo>,
     ) -> Self {
-        let stake_lock = Stake::lock(&type_ids.metadata_type_id, &staker);
-        let withdraw_lock = Withdraw::lock(&type_ids.metadata_type_id, &staker);
-        let token_lock = OmniEth::lock(&staker);
-        let xudt = Xudt::type_(&type_ids.xudt_owner.pack());
+        let stake_lock = Stake::lock(ctx, &type_ids.metadata_type_id, &staker);
+        let withdraw_lock = Withdraw::lock(ctx, &type_ids.metadata_type_id, &staker);
+        let token_lock = OmniEth::lock(ctx, &staker);
+        let xudt = Xudt::type_(ctx, &type_ids.xudt_owner.pack());
 
         Self {
             ckb,
+            ctx,
             type_ids,
             staker,
             current_epoch,
@@ -121,6 +119,7 @@ impl<'a, C: CkbRpc> StakeTxBuilder<'a, C> {
                 .lock(self.token_lock.clone())
                 .type_(
                     Some(Delegate::requirement_type(
+                        self.ctx,
                         &self.type_ids.metadata_type_id,
                         &H256::default(),
                     ))
@@ -133,10 +132,10 @@ impl<'a, C: CkbRpc> StakeTxBuilder<'a, C> {
             .await?;
 
         let cell_deps = vec![
-            OmniEth::lock_dep(),
-            Secp256k1::lock_dep(),
-            Xudt::type_dep(),
-            Delegate::requriement_type_dep(),
+            OmniEth::lock_dep(self.ctx),
+            Secp256k1::lock_dep(self.ctx),
+            Xudt::type_dep(self.ctx),
+            Delegate::requriement_type_dep(self.ctx),
         ];
 
         let witnesses = vec![
@@ -153,7 +152,7 @@ impl<'a, C: CkbRpc> StakeTxBuilder<'a, C> {
             .build();
 
         let mut tx = Tx::new(self.ckb, tx);
-        tx.balance(self.token_lock.clone()).await?;
+        tx.balance(self.ctx, self.token_lock.clone()).await?;
 
         let tx = tx.inner();
         let mut outputs = tx.outputs().into_iter().collect::<Vec<_>>();
@@ -161,7 +160,6 @@ impl<'a, C: CkbRpc> StakeTxBuilder<'a, C> {
 
         let first_input = tx.inputs().get(0).unwrap();
         let requirement_type_id = TypeId::calc(&first_input, 2);
-        let network_type = NETWORK_TYPE.load();
 
         outputs_data[1] = {
             let total_stake_amount = new_u128(&stake_data[..TOKEN_BYTES]);
@@ -176,15 +174,15 @@ impl<'a, C: CkbRpc> StakeTxBuilder<'a, C> {
                             .as_builder()
                             .requirement_info(
                                 DelegateRequirementInfo {
-                                    code_hash:   if **network_type == NetworkType::Mainnet {
-                                        DELEGATE_REQUIREMENT_TYPE_MAINNET.code_hash.clone()
-                                    } else if **network_type == NetworkType::Testnet {
-                                        DELEGATE_REQUIREMENT_TYPE_TESTNET.code_hash.clone()
-                                    } else {
-                                        DELEGATE_REQUIREMENT_TYPE_DEVNET.code_hash.clone()
-                                    },
+                                    code_hash:   self
+                                        .ctx
+                                        .scripts
+                                        .delegate_requirement_type
+                                        .code_hash
+                                        .clone(),
                                     requirement: DelegateRequirementArgs {
                                         metadata_type_hash:  Metadata::type_(
+                                            self.ctx,
                                             &self.type_ids.metadata_type_id,
                                         )
                                         .calc_script_hash(),
@@ -207,6 +205,7 @@ impl<'a, C: CkbRpc> StakeTxBuilder<'a, C> {
             .as_builder()
             .type_(
                 Some(Delegate::requirement_type(
+                    self.ctx,
                     &self.type_ids.metadata_type_id,
                     &requirement_type_id,
                 ))
@@ -249,12 +248,12 @@ impl<'a, C: CkbRpc> StakeTxBuilder<'a, C> {
         ];
 
         let cell_deps = vec![
-            OmniEth::lock_dep(),
-            Secp256k1::lock_dep(),
-            Xudt::type_dep(),
-            Stake::lock_dep(),
-            Checkpoint::cell_dep(self.ckb, &self.type_ids.checkpoint_type_id).await?,
-            Metadata::cell_dep(self.ckb, &self.type_ids.metadata_type_id).await?,
+            OmniEth::lock_dep(self.ctx),
+            Secp256k1::lock_dep(self.ctx),
+            Xudt::type_dep(self.ctx),
+            Stake::lock_dep(self.ctx),
+            Checkpoint::cell_dep(self.ckb, self.ctx, &self.type_ids.checkpoint_type_id).await?,
+            Metadata::cell_dep(self.ckb, self.ctx, &self.type_ids.metadata_type_id).await?,
         ];
 
         let witnesses = vec![
@@ -272,7 +271,7 @@ impl<'a, C: CkbRpc> StakeTxBuilder<'a, C> {
             .build();
 
         let mut tx = Tx::new(self.ckb, tx);
-        tx.balance(self.token_lock.clone()).await?;
+        tx.balance(self.ctx, self.token_lock.clone()).await?;
 
         Ok(tx.inner())
     }
@@ -376,7 +375,7 @@ impl<'a, C: CkbRpc> StakeTxBuilder<'a, C> {
         ])
     }
 
-    fn update_stake_data(
+    pub(crate) fn update_stake_data(
         &self,
         mut wallet_amount: Amount,
         stake_data: Bytes,
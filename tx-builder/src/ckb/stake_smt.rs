@@ -2,10 +2,9 @@ use std::collections::HashMap;
 
 use anyhow::Result;
 use async_trait::async_trait;
-use ckb_sdk::{ScriptGroup, ScriptGroupType};
 use ckb_types::{
     bytes::Bytes,
-    core::{Capacity, TransactionBuilder, TransactionView},
+    core::{Capacity, TransactionBuilder},
     packed::{CellDep, CellInput, CellOutput, WitnessArgs},
     prelude::{Entity, Pack},
     H160,
@@ -21,8 +20,8 @@ use common::types::axon_types::stake::{StakeArgs, StakeSmtCellData};
 use common::types::ckb_rpc_client::Cell;
 use common::types::smt::{Root, Staker as SmtStaker, UserAmount};
 use common::types::tx_builder::{
-    Amount, Epoch, InStakeSmt, NonTopStakers, PrivateKey, StakeItem, StakeSmtTypeIds,
-    Staker as TxStaker,
+    Amount, ChainContext, Epoch, InStakeSmt, NonTopStakers, SignerLock, StakeItem, StakeSmtTypeIds,
+    Staker as TxStaker, UnsignedTx,
 };
 use common::utils::convert::{to_u128, to_u64};
 
@@ -34,7 +33,8 @@ use crate::ckb::helper::{
 
 pub struct StakeSmtTxBuilder<'a, C: CkbRpc, S: StakeSmtStorage + Send + Sync> {
     ckb:               &'a C,
-    kicker:            PrivateKey,
+    ctx:               &'a ChainContext,
+    kicker:            SignerLock,
     current_epoch:     Epoch,
     stake_cells:       Vec<Cell>,
     stake_smt_storage: S,
@@ -47,7 +47,8 @@ impl<'a, C: CkbRpc, S: StakeSmtStorage + Send + Sync> IStakeSmtTxBuilder<'a, C,
 {
     fn new(
         ckb: &'a C,
-        kicker: PrivateKey,
+        ctx: &'a ChainContext,
+        kicker: SignerLock,
         current_epoch: Epoch,
         type_ids: StakeSmtTypeIds,
         stake_cells: Vec<Cell>,
@@ -55,6 +56,7 @@ impl<'a, C: CkbRpc, S: StakeSmtStorage + Send + Sync> IStakeSmtTxBuilder<'a, C,
     ) -> Self {
         Self {
             ckb,
+            ctx,
             kicker,
             current_epoch,
             stake_cells,
@@ -63,17 +65,19 @@ impl<'a, C: CkbRpc, S: StakeSmtStorage + Send + Sync> IStakeSmtTxBuilder<'a, C,
         }
     }
 
-    async fn build_tx(self) -> Result<(TransactionView, NonTopStakers)> {
-        let metadata_cell =
-            Metadata::get_cell(self.ckb, Metadata::type_(&self.type_ids.metadata_type_id))
-                .await
-                .expect("Metadata cell not found");
+    async fn build_tx(self) -> Result<(UnsignedTx, NonTopStakers)> {
+        let metadata_cell = Metadata::get_cell(
+            self.ckb,
+            Metadata::type_(self.ctx, &self.type_ids.metadata_type_id),
+        )
+        .await
+        .expect("Metadata cell not found");
         let metadata_cell_data = MetadataCellData::new_unchecked(
             metadata_cell.output_data.clone().unwrap().into_bytes(),
         );
         let quorum = Metadata::parse_quorum(&metadata_cell_data);
 
-        let stake_smt_type = Stake::smt_type(&self.type_ids.stake_smt_type_id);
+        let stake_smt_type = Stake::smt_type(self.ctx, &self.type_ids.stake_smt_type_id);
         let stake_smt_cell = Stake::get_smt_cell(self.ckb, stake_smt_type.clone()).await?;
 
         let mut inputs = vec![
@@ -96,7 +100,7 @@ impl<'a, C: CkbRpc, S: StakeSmtStorage + Send + Sync> IStakeSmtTxBuilder<'a, C,
         let mut outputs = vec![
             // stake smt cell
             CellOutput::new_builder()
-                .lock(AlwaysSuccess::lock())
+                .lock(AlwaysSuccess::lock(self.ctx))
                 .type_(Some(stake_smt_type).pack())
                 .build_exact_capacity(Capacity::bytes(new_stake_smt_cell_data.len())?)?,
         ];
@@ -115,16 +119,16 @@ impl<'a, C: CkbRpc, S: StakeSmtStorage + Send + Sync> IStakeSmtTxBuilder<'a, C,
         )
         .await?;
 
-        witnesses.push(OmniEth::witness_placeholder().as_bytes()); // capacity provider lock
+        witnesses.push(self.kicker.witness_placeholder.as_bytes()); // capacity provider lock
 
         let mut cell_deps = vec![
-            OmniEth::lock_dep(),
-            Secp256k1::lock_dep(),
-            Xudt::type_dep(),
-            AlwaysSuccess::lock_dep(),
-            Stake::lock_dep(),
-            Stake::smt_type_dep(),
-            Checkpoint::cell_dep(self.ckb, &self.type_ids.checkpoint_type_id).await?,
+            OmniEth::lock_dep(self.ctx),
+            Secp256k1::lock_dep(self.ctx),
+            Xudt::type_dep(self.ctx),
+            AlwaysSuccess::lock_dep(self.ctx),
+            Stake::lock_dep(self.ctx),
+            Stake::smt_type_dep(self.ctx),
+            Checkpoint::cell_dep(self.ckb, self.ctx, &self.type_ids.checkpoint_type_id).await?,
             // metadata cell dep
             CellDep::new_builder()
                 .out_point(metadata_cell.out_point.into())
@@ -132,8 +136,9 @@ impl<'a, C: CkbRpc, S: StakeSmtStorage + Send + Sync> IStakeSmtTxBuilder<'a, C,
         ];
 
         if !statistics.withdraw_amounts.is_empty() {
-            cell_deps.push(Withdraw::lock_dep());
+            cell_deps.push(Withdraw::lock_dep(self.ctx));
         }
+        self.kicker.add_cell_deps(&mut cell_deps);
 
         let tx = TransactionBuilder::default()
             .inputs(inputs)
@@ -143,20 +148,15 @@ impl<'a, C: CkbRpc, S: StakeSmtStorage + Send + Sync> IStakeSmtTxBuilder<'a, C,
             .witnesses(witnesses.pack())
             .build();
 
-        let omni_eth = OmniEth::new(self.kicker.clone());
-        let kicker_lock = OmniEth::lock(&omni_eth.address()?);
-
         let mut tx = Tx::new(self.ckb, tx);
-        tx.balance(kicker_lock.clone()).await?;
+        tx.balance(self.ctx, self.kicker.lock.clone()).await?;
 
-        tx.sign(&omni_eth.signer()?, &ScriptGroup {
-            script:         kicker_lock,
-            group_type:     ScriptGroupType::Lock,
-            input_indices:  vec![tx.inner_ref().witnesses().len() - 1],
-            output_indices: vec![],
-        })?;
+        let unsigned_tx = UnsignedTx {
+            script_groups: vec![tx.lock_script_group(&self.kicker.lock).await?],
+            tx:            tx.inner(),
+        };
 
-        Ok((tx.inner(), statistics.non_top_stakers))
+        Ok((unsigned_tx, statistics.non_top_stakers))
     }
 }
 
@@ -175,7 +175,7 @@ impl<'a, C: CkbRpc, S: StakeSmtStorage + Send + Sync> StakeSmtTxBuilder<'a, C, S
         outputs_data: &mut Vec<Bytes>,
         witnesses: &mut Vec<Bytes>,
     ) -> Result<()> {
-        let xudt = Xudt::type_(&self.type_ids.xudt_owner.pack());
+        let xudt = Xudt::type_(self.ctx, &self.type_ids.xudt_owner.pack());
         for (staker, stake_cell) in inputs_stake_cells.iter() {
             // inputs: stake AT cell
             inputs.push(
@@ -197,7 +197,7 @@ impl<'a, C: CkbRpc, S: StakeSmtStorage + Send + Sync> StakeSmtTxBuilder<'a, C, S
                 to_u64(&old_stake_data.lock().delta().inauguration_epoch()),
             );
 
-            let withdraw_lock = Withdraw::lock(&self.type_ids.metadata_type_id, staker);
+            let withdraw_lock = Withdraw::lock(self.ctx, &self.type_ids.metadata_type_id, staker);
             let mut new_total_stake_amount = old_total_stake_amount;
 
             if statistics.withdraw_amounts.contains_key(staker) {
@@ -261,7 +261,11 @@ impl<'a, C: CkbRpc, S: StakeSmtStorage + Send + Sync> StakeSmtTxBuilder<'a, C, S
             outputs_data.push(token_cell_data(new_total_stake_amount, new_stake_data));
             outputs.push(
                 CellOutput::new_builder()
-                    .lock(Stake::lock(&self.type_ids.metadata_type_id, staker))
+                    .lock(Stake::lock(
+                        self.ctx,
+                        &self.type_ids.metadata_type_id,
+                        staker,
+                    ))
                     .type_(Some(xudt.clone()).pack())
                     .build_exact_capacity(Capacity::bytes(outputs_data.last().unwrap().len())?)?,
             );
@@ -286,7 +290,7 @@ impl<'a, C: CkbRpc, S: StakeSmtStorage + Send + Sync> StakeSmtTxBuilder<'a, C, S
             );
         }
 
-        let xudt = Xudt::type_(&self.type_ids.xudt_owner.pack());
+        let xudt = Xudt::type_(self.ctx, &self.type_ids.xudt_owner.pack());
 
         let mut new_smt = old_smt.clone();
         let mut withdraw_amounts = HashMap::new(); // records all the stakers' withdraw amounts
@@ -339,7 +343,7 @@ impl<'a, C: CkbRpc, S: StakeSmtStorage + Send + Sync> StakeSmtTxBuilder<'a, C, S
                 if !inputs_stake_cells.contains_key(staker) {
                     let stake_cell = Stake::get_cell(
                         self.ckb,
-                        Stake::lock(&self.type_ids.metadata_type_id, staker),
+                        Stake::lock(self.ctx, &self.type_ids.metadata_type_id, staker),
                         xudt.clone(),
                     )
                     .await?
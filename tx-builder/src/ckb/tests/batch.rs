@@ -0,0 +1,194 @@
+use std::sync::Arc;
+
+use ckb_types::bytes::Bytes;
+use ckb_types::core::{Capacity, TransactionBuilder};
+use ckb_types::packed::{CellOutput, Script};
+use ckb_types::prelude::{Builder, Entity, Pack};
+use ckb_types::{h160, h256, H160};
+
+use common::traits::tx_builder::IBatchTxBuilder;
+use common::types::axon_types::stake::StakeAtCellData as AStakeAtCellData;
+use common::types::tx_builder::{
+    BatchItems, ChainContext, DelegateItem, FeeParams, NetworkParams, NetworkType, StakeItem,
+    StakeTypeIds, TypeIds,
+};
+use common::utils::mock::MockCkbRpc;
+use storage::SmtManager;
+
+use crate::ckb::batch::BatchTxBuilder;
+use crate::ckb::define::error::CkbTxErr;
+use crate::ckb::define::types::{StakeAtCellData, StakeAtCellLockData};
+use crate::ckb::helper::{token_cell_data, Checkpoint, Metadata, OmniEth, Stake, Xudt};
+use crate::ckb::registry::builtin_scripts;
+
+const USER: H160 = h160!("0x88fc88b65d9a613a1ad53fb2f41e5cb9c741fb6d");
+const STAKER: H160 = h160!("0x7e1c2a1f0ab7d8c7b1e8b1c5d4e3f2a1b0c9d8e7");
+
+const CKB: u64 = 100_000_000;
+const EPOCH: u64 = 1;
+
+fn chain_context() -> ChainContext {
+    ChainContext {
+        network:  NetworkType::Testnet,
+        params:   NetworkParams::builtin(&NetworkType::Testnet).unwrap(),
+        scripts:  Arc::new(builtin_scripts(&NetworkType::Testnet).unwrap()),
+        type_ids: TypeIds::default(),
+        fee:      FeeParams::default(),
+    }
+}
+
+fn type_ids() -> StakeTypeIds {
+    StakeTypeIds {
+        metadata_type_id:   h256!("0x1"),
+        checkpoint_type_id: h256!("0x2"),
+        xudt_owner:         h256!("0x3"),
+    }
+}
+
+fn smt_storage(name: &str) -> SmtManager {
+    let path = std::env::temp_dir().join("spark-batch-test").join(name);
+    let _ = std::fs::remove_dir_all(&path);
+    SmtManager::new(path)
+}
+
+fn output(lock: Script, type_: Option<Script>, capacity: u64) -> CellOutput {
+    CellOutput::new_builder()
+        .lock(lock)
+        .type_(type_.pack())
+        .capacity(Capacity::shannons(capacity * CKB).pack())
+        .build()
+}
+
+fn stake(epoch: u64) -> StakeItem {
+    StakeItem {
+        is_increase:        true,
+        amount:             100,
+        inauguration_epoch: epoch,
+    }
+}
+
+fn builder<'a>(
+    ckb: &'a MockCkbRpc,
+    ctx: &'a ChainContext,
+    batch: BatchItems,
+    name: &str,
+) -> BatchTxBuilder<'a, MockCkbRpc, SmtManager> {
+    BatchTxBuilder::new(ckb, ctx, type_ids(), USER, EPOCH, batch, smt_storage(name))
+}
+
+// The stake AT cell of the user, the checkpoint cell, the metadata cell, the
+// AT cells of the wallet and the capacity cells of the user.
+fn commit_cells(ckb: &MockCkbRpc, ctx: &ChainContext, wallet_amounts: &[u128]) {
+    let type_ids = type_ids();
+    let xudt = Xudt::type_(ctx, &type_ids.xudt_owner.pack());
+    let token_lock = OmniEth::lock(ctx, &USER);
+
+    let stake_data = AStakeAtCellData::from(StakeAtCellData {
+        lock: StakeAtCellLockData {
+            l2_address: USER,
+            ..Default::default()
+        },
+    });
+
+    let mut tx = TransactionBuilder::default()
+        .output(output(
+            Stake::lock(ctx, &type_ids.metadata_type_id, &USER),
+            Some(xudt.clone()),
+            1000,
+        ))
+        .output_data(token_cell_data(0, stake_data.as_bytes()).pack())
+        .output(output(
+            Script::default(),
+            Some(Checkpoint::type_(ctx, &type_ids.checkpoint_type_id)),
+            1000,
+        ))
+        .output_data(Bytes::new().pack())
+        .output(output(
+            Script::default(),
+            Some(Metadata::type_(ctx, &type_ids.metadata_type_id)),
+            1000,
+        ))
+        .output_data(Bytes::new().pack());
+
+    for amount in wallet_amounts.iter() {
+        tx = tx
+            .output(output(token_lock.clone(), Some(xudt.clone()), 1000))
+            .output_data(amount.pack().as_bytes().pack());
+    }
+    for _ in 0..10 {
+        tx = tx
+            .output(output(token_lock.clone(), None, 1000))
+            .output_data(Bytes::new().pack());
+    }
+    ckb.commit(tx.build(), 1, 1_000);
+}
+
+#[tokio::test]
+async fn check_batch() {
+    let ctx = chain_context();
+    let ckb = MockCkbRpc::new();
+
+    let err = builder(&ckb, &ctx, BatchItems::default(), "empty")
+        .build_tx()
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<CkbTxErr>(),
+        Some(CkbTxErr::EmptyBatch)
+    ));
+
+    // the delegate takes effect later than the inauguration epoch
+    let batch = BatchItems {
+        stake:     Some(stake(EPOCH + 2)),
+        delegates: vec![DelegateItem::new(STAKER, true, 100, EPOCH + 3)],
+        withdraw:  false,
+    };
+    let err = builder(&ckb, &ctx, batch, "late")
+        .build_tx()
+        .await
+        .unwrap_err();
+    match err.downcast_ref::<CkbTxErr>() {
+        Some(CkbTxErr::InaugurationEpoch { expected, found }) => {
+            assert_eq!(*expected, EPOCH + 2);
+            assert_eq!(*found, EPOCH + 3);
+        }
+        _ => panic!("unexpected error: {}", err),
+    }
+}
+
+// The stake of 100 collects the three AT cells of 40, which are in one lock
+// group with the capacity cells. The first AT cell carries the placeholder of
+// the group, the other AT cells have empty witnesses.
+#[tokio::test]
+async fn batch_witnesses_of_wallet_cells() {
+    let ctx = chain_context();
+    let ckb = MockCkbRpc::new();
+    commit_cells(&ckb, &ctx, &[40, 40, 40]);
+
+    let batch = BatchItems {
+        stake: Some(stake(EPOCH + 2)),
+        ..Default::default()
+    };
+    let tx = builder(&ckb, &ctx, batch, "witnesses")
+        .build_tx()
+        .await
+        .unwrap();
+
+    // the stake AT cell, the AT cells and at least a capacity cell
+    assert!(tx.inputs().len() > 4);
+
+    let witnesses = tx.witnesses();
+    let placeholder = OmniEth::witness_placeholder().as_bytes();
+    assert_eq!(
+        witnesses.get(0).unwrap().raw_data(),
+        Stake::witness(0u8).as_bytes()
+    );
+    assert_eq!(witnesses.get(1).unwrap().raw_data(), placeholder);
+    assert!(witnesses.get(2).unwrap().raw_data().is_empty());
+    assert!(witnesses.get(3).unwrap().raw_data().is_empty());
+    assert_eq!(witnesses.get(4).unwrap().raw_data(), placeholder);
+
+    // the wallet keeps the 20 left
+    let wallet_data = tx.outputs_data().get(1).unwrap().raw_data();
+    assert_eq!(wallet_data, 20u128.pack().as_bytes());
+}
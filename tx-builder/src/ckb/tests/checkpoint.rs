@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ckb_types::bytes::Bytes;
+use ckb_types::core::{Capacity, TransactionBuilder};
+use ckb_types::packed::{CellOutput, Script};
+use ckb_types::prelude::{Builder, Entity, Pack};
+use ckb_types::{h160, h256};
+
+use common::traits::smt::{ProposalSmtStorage, StakeSmtStorage};
+use common::traits::tx_builder::IRewardTxBuilder;
+use common::types::axon_types::checkpoint::CheckpointCellData;
+use common::types::smt::{Address, UserAmount};
+use common::types::tx_builder::{
+    ChainContext, Epoch, FeeParams, NetworkParams, NetworkType, RewardMeta, RewardTypeIds, TypeIds,
+};
+use common::utils::convert::to_uint64;
+use common::utils::mock::MockCkbRpc;
+use storage::SmtManager;
+
+use crate::ckb::define::error::CkbTxErr;
+use crate::ckb::helper::reward_calculator::fetch_metadata;
+use crate::ckb::helper::{Checkpoint, Metadata, RewardCalculator};
+use crate::ckb::registry::builtin_scripts;
+use crate::ckb::reward::RewardTxBuilder;
+
+fn chain_context() -> ChainContext {
+    ChainContext {
+        network:  NetworkType::Testnet,
+        params:   NetworkParams::builtin(&NetworkType::Testnet).unwrap(),
+        scripts:  Arc::new(builtin_scripts(&NetworkType::Testnet).unwrap()),
+        type_ids: TypeIds {
+            metadata_type_id: h256!("0x1"),
+            checkpoint_type_id: h256!("0x2"),
+            ..Default::default()
+        },
+        fee:      FeeParams::default(),
+    }
+}
+
+fn commit_cell(ckb: &MockCkbRpc, type_: Script, data: Bytes) {
+    let tx = TransactionBuilder::default()
+        .output(
+            CellOutput::new_builder()
+                .type_(Some(type_).pack())
+                .capacity(Capacity::shannons(1000).pack())
+                .build(),
+        )
+        .output_data(data.pack())
+        .build();
+    ckb.commit(tx, 1, 1_000);
+}
+
+fn assert_malformed(err: anyhow::Error, cell: &str) {
+    match err.downcast_ref::<CkbTxErr>() {
+        Some(CkbTxErr::MalformedCellData(name)) => assert_eq!(*name, cell),
+        _ => panic!("unexpected error: {}", err),
+    }
+}
+
+#[tokio::test]
+async fn get_epoch() {
+    let ctx = chain_context();
+    let ckb = MockCkbRpc::new();
+    let checkpoint_type_id = &ctx.type_ids.checkpoint_type_id;
+    let data = CheckpointCellData::new_builder()
+        .epoch(to_uint64(3))
+        .build()
+        .as_bytes();
+    commit_cell(&ckb, Checkpoint::type_(&ctx, checkpoint_type_id), data);
+
+    let epoch = Checkpoint::get_epoch(&ckb, &ctx, checkpoint_type_id).await;
+    assert_eq!(epoch.unwrap(), 3);
+}
+
+#[tokio::test]
+async fn get_epoch_without_checkpoint_cell() {
+    let ctx = chain_context();
+    let ckb = MockCkbRpc::new();
+
+    let err = Checkpoint::get_epoch(&ckb, &ctx, &ctx.type_ids.checkpoint_type_id)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<CkbTxErr>(),
+        Some(CkbTxErr::CellNotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn get_epoch_of_malformed_checkpoint_cell() {
+    let ctx = chain_context();
+    let ckb = MockCkbRpc::new();
+    let checkpoint_type_id = &ctx.type_ids.checkpoint_type_id;
+    commit_cell(
+        &ckb,
+        Checkpoint::type_(&ctx, checkpoint_type_id),
+        Bytes::from(vec![1u8, 2, 3]),
+    );
+
+    let err = Checkpoint::get_epoch(&ckb, &ctx, checkpoint_type_id)
+        .await
+        .unwrap_err();
+    assert_malformed(err, "checkpoint");
+}
+
+async fn new_reward_tx_builder(name: &str, ckb: &MockCkbRpc) -> anyhow::Result<()> {
+    let ctx = chain_context();
+    let smt = SmtManager::new(std::env::temp_dir().join("spark-reward-test").join(name));
+    RewardTxBuilder::new(
+        ckb,
+        &ctx,
+        RewardTypeIds {
+            metadata_type_id: ctx.type_ids.metadata_type_id.clone(),
+            ..Default::default()
+        },
+        smt,
+        h160!("0x88fc88b65d9a613a1ad53fb2f41e5cb9c741fb6d"),
+        3,
+        1,
+    )
+    .await
+    .map(|_| ())
+}
+
+#[tokio::test]
+async fn reward_tx_builder_without_metadata_cell() {
+    let ckb = MockCkbRpc::new();
+
+    let err = new_reward_tx_builder("without_metadata", &ckb)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<CkbTxErr>(),
+        Some(CkbTxErr::CellNotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn reward_tx_builder_of_malformed_metadata_cell() {
+    let ctx = chain_context();
+    let ckb = MockCkbRpc::new();
+    commit_cell(
+        &ckb,
+        Metadata::type_(&ctx, &ctx.type_ids.metadata_type_id),
+        Bytes::from(vec![1u8, 2, 3]),
+    );
+
+    let err = new_reward_tx_builder("malformed_metadata", &ckb)
+        .await
+        .unwrap_err();
+    assert_malformed(err, "metadata");
+}
+
+#[tokio::test]
+async fn fetch_malformed_metadata_cell() {
+    let ctx = chain_context();
+    let ckb = MockCkbRpc::new();
+    let metadata_type_id = &ctx.type_ids.metadata_type_id;
+    commit_cell(
+        &ckb,
+        Metadata::type_(&ctx, metadata_type_id),
+        Bytes::from(vec![1u8, 2, 3]),
+    );
+
+    let err = fetch_metadata(&ckb, &ctx, metadata_type_id)
+        .await
+        .unwrap_err();
+    assert_malformed(err, "metadata");
+}
+
+#[tokio::test]
+async fn halve_epoch_reward() {
+    let validator: Address = [1u8; 20].into();
+    let smt = SmtManager::new(
+        std::env::temp_dir()
+            .join("spark-reward-test")
+            .join("halving"),
+    );
+    ProposalSmtStorage::insert(&smt, 1, vec![(validator, 10)])
+        .await
+        .unwrap();
+    StakeSmtStorage::insert(&smt, 1, vec![UserAmount {
+        user:        validator,
+        amount:      100,
+        is_increase: true,
+    }])
+    .await
+    .unwrap();
+
+    let epoch_reward = |half_reward_cycle: Epoch, current_epoch: Epoch| {
+        let calculator = RewardCalculator::new(
+            &smt,
+            RewardMeta {
+                base_reward: 1000,
+                half_reward_cycle,
+                propose_minimum_rate: 0,
+                propose_discount_rate: 100,
+            },
+            0,
+            current_epoch,
+            HashMap::from([(validator, 0)]),
+        );
+        async move {
+            calculator
+                .calc_epoch_reward(validator, 1)
+                .await
+                .map(|reward| reward.amount)
+        }
+    };
+
+    assert_eq!(epoch_reward(2, 5).await.unwrap(), 250);
+    // the reward is halved to zero instead of overflowing
+    assert_eq!(epoch_reward(1, 200).await.unwrap(), 0);
+    assert_eq!(epoch_reward(1, u64::MAX).await.unwrap(), 0);
+
+    let err = epoch_reward(0, 5).await.unwrap_err();
+    assert_malformed(err, "metadata");
+}
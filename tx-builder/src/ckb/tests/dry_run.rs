@@ -0,0 +1,184 @@
+use std::sync::Arc;
+
+use ckb_types::bytes::Bytes;
+use ckb_types::core::Capacity;
+use ckb_types::packed::{Byte32, CellOutput, OutPoint, Script};
+use ckb_types::prelude::{Builder, Entity, Pack, Unpack};
+use ckb_types::{h160, h256, H256};
+
+use common::types::axon_types::{
+    delegate::DelegateSmtCellData as ADelegateSmtCellData,
+    stake::StakeSmtCellData as AStakeSmtCellData,
+};
+use common::types::smt::Root as SmtRoot;
+use common::types::tx_builder::{ChainContext, FeeParams, NetworkParams, NetworkType, TypeIds};
+
+use crate::ckb::define::types::{DelegateSmtCellData, StakeSmtCellData, StakerSmtRoot};
+use crate::ckb::dry_run::{smt_roots, summarize, token_deltas, ResolvedCell};
+use crate::ckb::helper::{token_cell_data, Delegate, Stake, Xudt};
+use crate::ckb::registry::builtin_scripts;
+
+fn chain_context() -> ChainContext {
+    ChainContext {
+        network:  NetworkType::Testnet,
+        params:   NetworkParams::builtin(&NetworkType::Testnet).unwrap(),
+        scripts:  Arc::new(builtin_scripts(&NetworkType::Testnet).unwrap()),
+        type_ids: TypeIds {
+            stake_smt_type_id: h256!("0x1"),
+            delegate_smt_type_id: h256!("0x2"),
+            reward_smt_type_id: h256!("0x3"),
+            xudt_owner: h256!("0x4"),
+            ..Default::default()
+        },
+        fee:      FeeParams::default(),
+    }
+}
+
+fn lock(arg: u8) -> Script {
+    Script::new_builder().args(vec![arg].pack()).build()
+}
+
+fn cell(lock: Script, type_: Option<Script>, capacity: u64, data: Bytes) -> ResolvedCell {
+    let output = CellOutput::new_builder()
+        .lock(lock)
+        .type_(type_.pack())
+        .capacity(Capacity::shannons(capacity).pack())
+        .build();
+    (Some(OutPoint::new(Byte32::default(), 0)), output, data)
+}
+
+fn xudt(ctx: &ChainContext) -> Script {
+    Xudt::type_(ctx, &ctx.type_ids.xudt_owner.pack())
+}
+
+fn root(byte: u8) -> SmtRoot {
+    [byte; 32].into()
+}
+
+#[test]
+fn summarize_cells() {
+    let ctx = chain_context();
+    let cells = vec![
+        cell(
+            lock(1),
+            Some(xudt(&ctx)),
+            100,
+            token_cell_data(7, Bytes::new()),
+        ),
+        // too short to be a token cell
+        cell(lock(1), Some(xudt(&ctx)), 200, Bytes::from(vec![1u8; 8])),
+        cell(lock(2), None, 300, Bytes::new()),
+    ];
+
+    let summaries = summarize(&cells, &xudt(&ctx));
+    assert_eq!(summaries.len(), 3);
+
+    let lock_hash: H256 = lock(1).calc_script_hash().unpack();
+    let xudt_hash: H256 = xudt(&ctx).calc_script_hash().unpack();
+    assert_eq!(summaries[0].lock_hash, lock_hash);
+    assert_eq!(summaries[0].type_hash, Some(xudt_hash));
+    assert_eq!(summaries[0].capacity, 100);
+    assert_eq!(summaries[0].token_amount, Some(7));
+    assert_eq!(summaries[1].token_amount, None);
+    assert_eq!(summaries[2].type_hash, None);
+    assert_eq!(summaries[2].token_amount, None);
+}
+
+#[test]
+fn token_deltas_by_lock() {
+    let ctx = chain_context();
+    let token = |lock_arg, amount| {
+        cell(
+            lock(lock_arg),
+            Some(xudt(&ctx)),
+            100,
+            token_cell_data(amount, Bytes::new()),
+        )
+    };
+
+    let consumed = summarize(&[token(1, 10), token(1, 5), token(2, 8)], &xudt(&ctx));
+    let created = summarize(
+        &[
+            token(1, 12),
+            token(3, 11),
+            cell(lock(2), None, 100, Bytes::new()),
+        ],
+        &xudt(&ctx),
+    );
+
+    let mut deltas = token_deltas(&consumed, &created)
+        .into_iter()
+        .map(|delta| (delta.lock_hash, delta.consumed, delta.created))
+        .collect::<Vec<_>>();
+    deltas.sort();
+
+    let hash = |lock_arg| -> H256 { lock(lock_arg).calc_script_hash().unpack() };
+    let mut expected = vec![(hash(1), 15, 12), (hash(2), 8, 0), (hash(3), 0, 11)];
+    expected.sort();
+    assert_eq!(deltas, expected);
+}
+
+#[test]
+fn smt_root_changes() {
+    let ctx = chain_context();
+    let stake_smt = Stake::smt_type(&ctx, &ctx.type_ids.stake_smt_type_id);
+    let delegate_smt = Delegate::smt_type(&ctx, &ctx.type_ids.delegate_smt_type_id);
+    let stake_data = |byte| {
+        AStakeSmtCellData::from(StakeSmtCellData {
+            smt_root: root(byte),
+            ..Default::default()
+        })
+        .as_bytes()
+    };
+    let delegate_data = |roots: Vec<(ckb_types::H160, u8)>| {
+        ADelegateSmtCellData::from(DelegateSmtCellData {
+            smt_roots: roots
+                .into_iter()
+                .map(|(staker, byte)| StakerSmtRoot {
+                    staker,
+                    root: root(byte),
+                })
+                .collect(),
+            ..Default::default()
+        })
+        .as_bytes()
+    };
+    let staker_a = h160!("0x1");
+    let staker_b = h160!("0x2");
+    let staker_c = h160!("0x3");
+
+    let inputs = vec![
+        cell(lock(0), Some(stake_smt.clone()), 100, stake_data(1)),
+        cell(
+            lock(0),
+            Some(delegate_smt.clone()),
+            100,
+            delegate_data(vec![(staker_a.clone(), 1), (staker_b.clone(), 2)]),
+        ),
+    ];
+    let outputs = vec![
+        cell(lock(0), Some(stake_smt), 100, stake_data(2)),
+        cell(
+            lock(0),
+            Some(delegate_smt),
+            100,
+            delegate_data(vec![(staker_a.clone(), 3), (staker_c.clone(), 4)]),
+        ),
+    ];
+
+    let changes = smt_roots(&ctx, &inputs, &outputs)
+        .into_iter()
+        .map(|change| (change.smt, change.staker, change.before, change.after))
+        .collect::<Vec<_>>();
+    let some_root = |byte: u8| Some(H256::from([byte; 32]));
+    assert_eq!(changes, vec![
+        ("stake", None, some_root(1), some_root(2)),
+        ("delegate", Some(staker_a), some_root(1), some_root(3)),
+        ("delegate", Some(staker_b), some_root(2), None),
+        ("delegate", Some(staker_c), None, some_root(4)),
+    ]);
+
+    // the cells of no smt
+    let cells = vec![cell(lock(0), None, 100, Bytes::new())];
+    assert!(smt_roots(&ctx, &cells, &cells).is_empty());
+}
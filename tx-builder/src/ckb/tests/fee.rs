@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use ckb_types::bytes::Bytes;
+use ckb_types::core::{Capacity, TransactionBuilder, TransactionView};
+use ckb_types::h256;
+use ckb_types::packed::{CellOutput, Script};
+use ckb_types::prelude::{Builder, Entity, Pack, Unpack};
+
+use common::types::tx_builder::{
+    ChainContext, FeeParams, FeeStrategy, NetworkParams, NetworkType, TypeIds,
+};
+use common::utils::mock::MockCkbRpc;
+
+use crate::ckb::define::constants::FEE_RATE;
+use crate::ckb::define::error::CkbTxErr;
+use crate::ckb::helper::Tx;
+use crate::ckb::registry::builtin_scripts;
+
+const CKB: u64 = 100_000_000;
+
+fn chain_context(strategy: FeeStrategy, balance_retry: u32) -> ChainContext {
+    ChainContext {
+        network:  NetworkType::Testnet,
+        params:   NetworkParams::builtin(&NetworkType::Testnet).unwrap(),
+        scripts:  Arc::new(builtin_scripts(&NetworkType::Testnet).unwrap()),
+        type_ids: TypeIds::default(),
+        fee:      FeeParams {
+            strategy,
+            balance_retry,
+        },
+    }
+}
+
+// no witness placeholder is filled for the lock
+fn capacity_provider() -> Script {
+    Script::new_builder()
+        .code_hash(h256!("0x99").pack())
+        .args(Bytes::from(vec![1u8; 20]).pack())
+        .build()
+}
+
+// the capacity provider owns `count` cells of `capacity` CKB each
+fn commit_capacity_cells(ckb: &MockCkbRpc, count: usize, capacity: u64) {
+    let output = CellOutput::new_builder()
+        .lock(capacity_provider())
+        .capacity(Capacity::shannons(capacity * CKB).pack())
+        .build();
+    let tx = TransactionBuilder::default()
+        .outputs(vec![output; count])
+        .outputs_data(vec![Bytes::new().pack(); count])
+        .build();
+    ckb.commit(tx, 1, 1_000);
+}
+
+// a tx paying 100 CKB to someone else
+fn payment_tx() -> TransactionView {
+    TransactionBuilder::default()
+        .output(
+            CellOutput::new_builder()
+                .capacity(Capacity::shannons(100 * CKB).pack())
+                .build(),
+        )
+        .output_data(Bytes::new().pack())
+        .build()
+}
+
+fn paid_fee(tx: &TransactionView, capacity: u64) -> u64 {
+    let inputs_capacity = tx.inputs().len() as u64 * capacity * CKB;
+    let outputs_capacity: u64 = tx
+        .outputs()
+        .into_iter()
+        .map(|output| -> u64 { output.capacity().unpack() })
+        .sum();
+    inputs_capacity - outputs_capacity
+}
+
+fn expected_fee(tx: &TransactionView, fee_rate: u64) -> u64 {
+    tx.data().as_reader().serialized_size_in_block() as u64 * fee_rate / 1000
+}
+
+#[tokio::test]
+async fn fee_rate() {
+    let ckb = MockCkbRpc::new();
+    let tx = Tx::new(&ckb, payment_tx());
+
+    ckb.set_fee_rate(Some(5000));
+    assert_eq!(tx.fee_rate(&FeeStrategy::Fixed(3000)).await.unwrap(), 3000);
+    assert_eq!(tx.fee_rate(&FeeStrategy::Estimated).await.unwrap(), 5000);
+    assert_eq!(tx.fee_rate(&FeeStrategy::Capped(2000)).await.unwrap(), 2000);
+    assert_eq!(tx.fee_rate(&FeeStrategy::Capped(8000)).await.unwrap(), 5000);
+
+    // a cap lower than the minimum fee rate is raised to it
+    assert_eq!(
+        tx.fee_rate(&FeeStrategy::Capped(500)).await.unwrap(),
+        FEE_RATE
+    );
+
+    // the estimation is never lower than the minimum fee rate
+    ckb.set_fee_rate(Some(500));
+    assert_eq!(
+        tx.fee_rate(&FeeStrategy::Estimated).await.unwrap(),
+        FEE_RATE
+    );
+    ckb.set_fee_rate(None);
+    assert_eq!(
+        tx.fee_rate(&FeeStrategy::Estimated).await.unwrap(),
+        FEE_RATE
+    );
+    assert_eq!(
+        tx.fee_rate(&FeeStrategy::Capped(500)).await.unwrap(),
+        FEE_RATE
+    );
+}
+
+#[tokio::test]
+async fn balance_pays_fee_of_tx_size() {
+    for (strategy, fee_rate) in [
+        (FeeStrategy::Fixed(1000), 1000),
+        (FeeStrategy::Fixed(3000), 3000),
+        (FeeStrategy::Estimated, 2000),
+        (FeeStrategy::Capped(1500), 1500),
+    ] {
+        let ctx = chain_context(strategy, 0);
+        let ckb = MockCkbRpc::new();
+        ckb.set_fee_rate(Some(2000));
+        commit_capacity_cells(&ckb, 10, 100);
+
+        let mut tx = Tx::new(&ckb, payment_tx());
+        tx.balance(&ctx, capacity_provider()).await.unwrap();
+        let tx = tx.inner();
+
+        assert_eq!(tx.outputs().len(), 2);
+        assert_eq!(
+            tx.output(1).unwrap().lock().as_bytes(),
+            capacity_provider().as_bytes()
+        );
+        assert_eq!(paid_fee(&tx, 100), expected_fee(&tx, fee_rate));
+    }
+}
+
+// The first collected cells pay the outputs but not the fee of 50 CKB per KB,
+// so more cells are collected in the retry.
+#[tokio::test]
+async fn balance_retry() {
+    let fee_rate = 50 * CKB;
+    let ckb = MockCkbRpc::new();
+    commit_capacity_cells(&ckb, 20, 20);
+
+    let ctx = chain_context(FeeStrategy::Fixed(fee_rate), 0);
+    let mut tx = Tx::new(&ckb, payment_tx());
+    let err = tx.balance(&ctx, capacity_provider()).await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<CkbTxErr>(),
+        Some(CkbTxErr::InsufficientCapacity { .. })
+    ));
+
+    let ctx = chain_context(FeeStrategy::Fixed(fee_rate), 2);
+    let mut tx = Tx::new(&ckb, payment_tx());
+    tx.balance(&ctx, capacity_provider()).await.unwrap();
+    let tx = tx.inner();
+
+    assert_eq!(tx.outputs().len(), 2);
+    assert_eq!(paid_fee(&tx, 20), expected_fee(&tx, fee_rate));
+}
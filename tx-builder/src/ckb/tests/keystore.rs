@@ -0,0 +1,137 @@
+use std::path::PathBuf;
+
+use ckb_hash::blake2b_256;
+use ckb_sdk::traits::{SecpCkbRawKeySigner, Signer};
+use ckb_types::core::TransactionBuilder;
+use ckb_types::{h256, H160};
+
+use crate::ckb::helper::ckb::omni::OmniEth;
+use crate::ckb::keystore::{KeySigner, SignerKey};
+
+// The test vectors of the Web3 Secret Storage Definition.
+const PASSWORD: &str = "testpassword";
+const PRIVATE_KEY: &str = "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d";
+
+const PBKDF2_KEYSTORE: &str = r#"{
+    "crypto" : {
+        "cipher" : "aes-128-ctr",
+        "cipherparams" : {
+            "iv" : "6087dab2f9fdbbfaddc31a909735c1e6"
+        },
+        "ciphertext" : "5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46",
+        "kdf" : "pbkdf2",
+        "kdfparams" : {
+            "c" : 262144,
+            "dklen" : 32,
+            "prf" : "hmac-sha256",
+            "salt" : "ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd"
+        },
+        "mac" : "517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2"
+    },
+    "id" : "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+    "version" : 3
+}"#;
+
+const SCRYPT_KEYSTORE: &str = r#"{
+    "crypto" : {
+        "cipher" : "aes-128-ctr",
+        "cipherparams" : {
+            "iv" : "83dbcc02d8ccb40e466191a123791e0e"
+        },
+        "ciphertext" : "d172bf743a674da9cdad04534d56926ef8358534d458fffccd4e6ad2fbde479c",
+        "kdf" : "scrypt",
+        "kdfparams" : {
+            "dklen" : 32,
+            "n" : 262144,
+            "p" : 8,
+            "r" : 1,
+            "salt" : "ab0c7876052600dd703518d6fc3fe8984592145b591fc8fb5c6d43190334ba19"
+        },
+        "mac" : "2103ac29920d71da29f15d75b4a16dbe95cfd7ff8faea1056c33131d846e3097"
+    },
+    "id" : "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+    "version" : 3
+}"#;
+
+fn keystore_file(name: &str, keystore: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("spark-keystore-test");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    std::fs::write(&path, keystore).unwrap();
+    path
+}
+
+fn assert_test_key(key: &SignerKey) {
+    key.with_key(|key| {
+        assert_eq!(
+            key,
+            &h256!("0x7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d")
+        )
+    });
+}
+
+#[test]
+fn decrypt_pbkdf2_keystore() {
+    let path = keystore_file("pbkdf2.json", PBKDF2_KEYSTORE);
+    assert_test_key(&SignerKey::from_keystore(&path, PASSWORD).unwrap());
+}
+
+#[test]
+fn decrypt_scrypt_keystore() {
+    let path = keystore_file("scrypt.json", SCRYPT_KEYSTORE);
+    assert_test_key(&SignerKey::from_keystore(&path, PASSWORD).unwrap());
+}
+
+#[test]
+fn reject_wrong_password() {
+    let path = keystore_file("wrong-password.json", PBKDF2_KEYSTORE);
+    assert!(SignerKey::from_keystore(&path, "wrongpassword").is_err());
+}
+
+#[test]
+fn parse_hex_key() {
+    assert_test_key(&SignerKey::from_hex(PRIVATE_KEY).unwrap());
+    assert_test_key(&SignerKey::from_hex(&format!("0x{}", PRIVATE_KEY)).unwrap());
+    assert!(SignerKey::from_hex(&PRIVATE_KEY[2..]).is_err());
+    assert!(SignerKey::from_hex("").is_err());
+}
+
+// The signatures are the same as the ones of `SecpCkbRawKeySigner`.
+#[test]
+fn sign_as_raw_key_signer() {
+    let key = SignerKey::from_hex(PRIVATE_KEY).unwrap();
+    let secret_key = key.with_secret_key(|key| *key).unwrap();
+    let pubkey_hash =
+        H160::from_slice(&blake2b_256(&key.pubkey().unwrap().serialize()[..])[0..20]).unwrap();
+    let eth_address = key.with_key(|key| OmniEth::new(key.clone()).address().unwrap());
+    let tx = TransactionBuilder::default().build();
+    let message = [7u8; 32];
+
+    let signers = vec![
+        (
+            pubkey_hash,
+            KeySigner::new(vec![key.clone()]).unwrap(),
+            SecpCkbRawKeySigner::new_with_secret_keys(vec![secret_key]),
+        ),
+        (
+            eth_address,
+            KeySigner::new_ethereum(vec![key]).unwrap(),
+            SecpCkbRawKeySigner::new_with_ethereum_secret_keys(vec![secret_key]),
+        ),
+    ];
+
+    for (id, signer, raw_signer) in signers {
+        assert!(signer.match_id(id.as_bytes()));
+        assert!(!signer.match_id(H160::default().as_bytes()));
+
+        for recoverable in [true, false] {
+            let signature = signer
+                .sign(id.as_bytes(), &message, recoverable, &tx)
+                .unwrap();
+            let raw_signature = raw_signer
+                .sign(id.as_bytes(), &message, recoverable, &tx)
+                .unwrap();
+            assert_eq!(signature, raw_signature);
+        }
+    }
+}
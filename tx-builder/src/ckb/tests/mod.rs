@@ -1,4 +1,24 @@
 #[cfg(test)]
 mod amount;
 #[cfg(test)]
+mod batch;
+#[cfg(test)]
+mod checkpoint;
+#[cfg(test)]
+mod dry_run;
+#[cfg(test)]
+mod fee;
+#[cfg(test)]
+mod keystore;
+#[cfg(test)]
 mod omni;
+#[cfg(test)]
+mod registry;
+#[cfg(test)]
+mod requirement;
+#[cfg(test)]
+mod signer;
+#[cfg(test)]
+mod smt_rebuild;
+#[cfg(test)]
+mod verifier;
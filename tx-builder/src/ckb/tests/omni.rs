@@ -1,6 +1,11 @@
+use std::sync::Arc;
+
 use ckb_types::{h160, h256};
 
+use common::types::tx_builder::{ChainContext, FeeParams, NetworkParams, NetworkType, TypeIds};
+
 use crate::ckb::helper::ckb::omni::OmniEth;
+use crate::ckb::registry::builtin_scripts;
 
 #[test]
 fn omni() {
@@ -8,8 +13,15 @@ fn omni() {
 
     let test_key = h256!("0x13b08bb054d5dd04013156dced8ba2ce4d8cc5973e10d905a228ea1abc267e60");
     let omni_eth = OmniEth::new(test_key);
+    let ctx = ChainContext {
+        network:  NetworkType::Testnet,
+        params:   NetworkParams::builtin(&NetworkType::Testnet).unwrap(),
+        scripts:  Arc::new(builtin_scripts(&NetworkType::Testnet).unwrap()),
+        type_ids: TypeIds::default(),
+        fee:      FeeParams::default(),
+    };
 
-    let ckb_addr = omni_eth.ckb_address().unwrap();
+    let ckb_addr = omni_eth.ckb_address(&ctx).unwrap();
     assert_eq!(
         &ckb_addr,
         "ckt1qrejnmlar3r452tcg57gvq8patctcgy8acync0hxfnyka35ywafvkqgp3r7g3djanfsn5xk487e0g8juh8r5r7mdqqkzm504",
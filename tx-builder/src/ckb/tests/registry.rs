@@ -0,0 +1,242 @@
+use std::path::PathBuf;
+
+use ckb_jsonrpc_types::{DepType as JsonDepType, ScriptHashType as JsonScriptHashType};
+use ckb_types::bytes::Bytes;
+use ckb_types::core::{Capacity, DepType, ScriptHashType, TransactionBuilder};
+use ckb_types::packed::{CellOutput, Script};
+use ckb_types::prelude::{Builder, Entity, Pack};
+use ckb_types::{h256, H256};
+
+use common::types::axon_types::metadata::MetadataCellData as AMetadataCellData;
+use common::types::tx_builder::{NetworkType, ScriptRegistry, TypeIds};
+use common::utils::mock::MockCkbRpc;
+
+use crate::ckb::define::error::CkbTxErr;
+use crate::ckb::define::types::MetadataCellData;
+use crate::ckb::registry::{builtin_scripts, deployed_scripts, Deployment, TypeIdRegistry};
+
+const ENTRIES: [&str; 14] = [
+    "omni_lock",
+    "secp256k1_blake160",
+    "xudt_type",
+    "always_success_lock",
+    "selection_lock",
+    "checkpoint_type",
+    "metadata_type",
+    "stake_lock",
+    "stake_smt_type",
+    "delegate_requirement_type",
+    "delegate_lock",
+    "delegate_smt_type",
+    "withdraw_lock",
+    "reward_smt_type",
+];
+
+const STAKE_LOCK: &str = r#"
+[stake_lock]
+code_hash = "0x0000000000000000000000000000000000000000000000000000000000000011"
+hash_type = "data1"
+tx_hash = "0x0000000000000000000000000000000000000000000000000000000000000022"
+index = 3
+dep_type = "dep_group"
+"#;
+
+fn write_manifest(name: &str, content: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("spark-registry-test");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+fn full_manifest() -> String {
+    ENTRIES
+        .iter()
+        .map(|name| {
+            format!(
+                "[{}]\ncode_hash = \"0x{:064x}\"\ntx_hash = \"0x{:064x}\"\n",
+                name, 1, 2
+            )
+        })
+        .collect()
+}
+
+// the metadata cell of the type id args, recording `type_ids`
+fn commit_metadata_cell(
+    ckb: &MockCkbRpc,
+    scripts: &ScriptRegistry,
+    args: &H256,
+    type_ids: TypeIds,
+) {
+    let data = AMetadataCellData::from(MetadataCellData {
+        type_ids,
+        ..Default::default()
+    });
+    let output = CellOutput::new_builder()
+        .lock(Script::default())
+        .type_(
+            Some(
+                scripts
+                    .metadata_type
+                    .script(Bytes::from(args.as_bytes().to_vec())),
+            )
+            .pack(),
+        )
+        .capacity(Capacity::shannons(1000).pack())
+        .build();
+    let tx = TransactionBuilder::default()
+        .output(output)
+        .output_data(data.as_bytes().pack())
+        .build();
+    ckb.commit(tx, 1, 1_000);
+}
+
+fn assert_not_deployed(err: anyhow::Error, script: &str) {
+    match err.downcast_ref::<CkbTxErr>() {
+        Some(CkbTxErr::ScriptNotDeployed(name)) => assert_eq!(*name, script),
+        _ => panic!("unexpected error: {}", err),
+    }
+}
+
+#[test]
+fn load_toml_deployment() {
+    let deployment = Deployment::load(write_manifest("stake_lock.toml", STAKE_LOCK)).unwrap();
+
+    let stake_lock = deployment.stake_lock.unwrap();
+    assert_eq!(stake_lock.code_hash, h256!("0x11"));
+    assert_eq!(stake_lock.tx_hash, h256!("0x22"));
+    assert_eq!(stake_lock.index, 3);
+    assert!(deployment.omni_lock.is_none());
+}
+
+#[test]
+fn load_json_deployment() {
+    let manifest = r#"{
+        "stake_lock": {
+            "code_hash": "0x0000000000000000000000000000000000000000000000000000000000000011",
+            "tx_hash": "0x0000000000000000000000000000000000000000000000000000000000000022"
+        }
+    }"#;
+    let deployment = Deployment::load(write_manifest("stake_lock.json", manifest)).unwrap();
+
+    // the hash type, the index and the dep type are defaulted
+    let stake_lock = deployment.stake_lock.unwrap();
+    assert_eq!(stake_lock.code_hash, h256!("0x11"));
+    assert_eq!(stake_lock.hash_type, JsonScriptHashType::Type);
+    assert_eq!(stake_lock.index, 0);
+    assert_eq!(stake_lock.dep_type, JsonDepType::Code);
+}
+
+#[test]
+fn load_deployment_with_unknown_fields() {
+    // a misspelt entry
+    let manifest = STAKE_LOCK.replace("[stake_lock]", "[stake_locks]");
+    assert!(Deployment::load(write_manifest("unknown_entry.toml", &manifest)).is_err());
+
+    // a misspelt field of an entry
+    let manifest = STAKE_LOCK.replace("dep_type", "deptype");
+    assert!(Deployment::load(write_manifest("unknown_field.toml", &manifest)).is_err());
+}
+
+#[test]
+fn deployed_scripts_override_builtin() {
+    let deployment = Deployment::load(write_manifest("override.toml", STAKE_LOCK)).unwrap();
+    let scripts = deployed_scripts(&NetworkType::Testnet, deployment).unwrap();
+    let builtin = builtin_scripts(&NetworkType::Testnet).unwrap();
+
+    assert_eq!(scripts.stake_lock.code_hash, h256!("0x11"));
+    assert_eq!(scripts.stake_lock.hash_type, ScriptHashType::Data1);
+    assert_eq!(scripts.stake_lock.tx_hash, h256!("0x22"));
+    assert_eq!(scripts.stake_lock.index, 3);
+    assert_eq!(scripts.stake_lock.dep_type, DepType::DepGroup);
+
+    assert_eq!(scripts.omni_lock.code_hash, builtin.omni_lock.code_hash);
+    assert_eq!(scripts.omni_lock.tx_hash, builtin.omni_lock.tx_hash);
+}
+
+#[test]
+fn deployed_scripts_of_custom_network() {
+    let network = NetworkType::Custom("my-net".to_owned());
+
+    let deployment = Deployment::load(write_manifest("full.toml", &full_manifest())).unwrap();
+    let scripts = deployed_scripts(&network, deployment).unwrap();
+    assert_eq!(scripts.reward_smt_type.code_hash, h256!("0x1"));
+    assert_eq!(scripts.reward_smt_type.hash_type, ScriptHashType::Type);
+    assert_eq!(scripts.reward_smt_type.dep_type, DepType::Code);
+
+    // nothing is compiled in for a custom network
+    let err = deployed_scripts(&network, Deployment::default()).unwrap_err();
+    assert_not_deployed(err, "omni_lock");
+
+    let mut deployment =
+        Deployment::load(write_manifest("partial.toml", &full_manifest())).unwrap();
+    deployment.withdraw_lock = None;
+    let err = deployed_scripts(&network, deployment).unwrap_err();
+    assert_not_deployed(err, "withdraw_lock");
+}
+
+#[tokio::test]
+async fn discover_type_ids() {
+    let scripts = builtin_scripts(&NetworkType::Testnet).unwrap();
+    let ckb = MockCkbRpc::new();
+    commit_metadata_cell(&ckb, &scripts, &h256!("0x1"), TypeIds {
+        metadata_type_id: h256!("0x1"),
+        checkpoint_type_id: h256!("0x2"),
+        stake_smt_type_id: h256!("0x3"),
+        xudt_owner: h256!("0x4"),
+        ..Default::default()
+    });
+
+    let registry = TypeIdRegistry::discover(&ckb, &scripts, &h256!("0x1"))
+        .await
+        .unwrap();
+    let type_ids = registry.type_ids();
+    assert_eq!(type_ids.metadata_type_id, h256!("0x1"));
+    assert_eq!(type_ids.checkpoint_type_id, h256!("0x2"));
+    assert_eq!(type_ids.stake_smt_type_id, h256!("0x3"));
+
+    let stake = registry.stake();
+    assert_eq!(stake.metadata_type_id, h256!("0x1"));
+    assert_eq!(stake.checkpoint_type_id, h256!("0x2"));
+    assert_eq!(stake.xudt_owner, h256!("0x4"));
+}
+
+#[tokio::test]
+async fn discover_type_ids_of_another_metadata() {
+    let scripts = builtin_scripts(&NetworkType::Testnet).unwrap();
+    let ckb = MockCkbRpc::new();
+    // the cell of type id 0x1 records another metadata type id
+    commit_metadata_cell(&ckb, &scripts, &h256!("0x1"), TypeIds {
+        metadata_type_id: h256!("0x5"),
+        ..Default::default()
+    });
+
+    let err = TypeIdRegistry::discover(&ckb, &scripts, &h256!("0x1"))
+        .await
+        .unwrap_err();
+    match err.downcast_ref::<CkbTxErr>() {
+        Some(CkbTxErr::MetadataTypeIdMismatch { expected, found }) => {
+            assert_eq!(*expected, h256!("0x1"));
+            assert_eq!(*found, h256!("0x5"));
+        }
+        _ => panic!("unexpected error: {}", err),
+    }
+}
+
+#[tokio::test]
+async fn discover_type_ids_without_metadata_cell() {
+    let scripts = builtin_scripts(&NetworkType::Testnet).unwrap();
+    let ckb = MockCkbRpc::new();
+    commit_metadata_cell(&ckb, &scripts, &h256!("0x1"), TypeIds {
+        metadata_type_id: h256!("0x1"),
+        ..Default::default()
+    });
+
+    let err = TypeIdRegistry::discover(&ckb, &scripts, &h256!("0x2"))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<CkbTxErr>(),
+        Some(CkbTxErr::CellNotFound(_))
+    ));
+}
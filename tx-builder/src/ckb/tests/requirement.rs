@@ -0,0 +1,163 @@
+use std::sync::Arc;
+
+use ckb_types::bytes::Bytes;
+use ckb_types::core::{Capacity, TransactionBuilder, TransactionView};
+use ckb_types::packed::{CellOutput, OutPoint, Script};
+use ckb_types::prelude::{Builder, Entity, Pack};
+use ckb_types::{h160, h256, H160, H256};
+
+use common::traits::tx_builder::IRequirementTxBuilder;
+use common::types::axon_types::delegate::DelegateCellData;
+use common::types::axon_types::stake::StakeAtCellData as AStakeAtCellData;
+use common::types::tx_builder::{
+    ChainContext, DelegateRequirement, FeeParams, NetworkParams, NetworkType, StakeTypeIds, TypeIds,
+};
+use common::utils::convert::to_u128;
+use common::utils::mock::MockCkbRpc;
+
+use crate::ckb::define::error::CkbTxErr;
+use crate::ckb::define::types::{
+    DelegateRequirementArgs, DelegateRequirementInfo, StakeAtCellData, StakeAtCellLockData,
+};
+use crate::ckb::helper::{token_cell_data, Delegate, Metadata, OmniEth, Stake, Xudt};
+use crate::ckb::registry::builtin_scripts;
+use crate::ckb::requirement::RequirementTxBuilder;
+
+const STAKER: H160 = h160!("0x88fc88b65d9a613a1ad53fb2f41e5cb9c741fb6d");
+const REQUIREMENT_TYPE_ID: H256 = h256!("0x4");
+
+const CKB: u64 = 100_000_000;
+
+fn chain_context() -> ChainContext {
+    ChainContext {
+        network:  NetworkType::Testnet,
+        params:   NetworkParams::builtin(&NetworkType::Testnet).unwrap(),
+        scripts:  Arc::new(builtin_scripts(&NetworkType::Testnet).unwrap()),
+        type_ids: TypeIds::default(),
+        fee:      FeeParams::default(),
+    }
+}
+
+fn type_ids() -> StakeTypeIds {
+    StakeTypeIds {
+        metadata_type_id:   h256!("0x1"),
+        checkpoint_type_id: h256!("0x2"),
+        xudt_owner:         h256!("0x3"),
+    }
+}
+
+fn output(lock: Script, type_: Option<Script>, capacity: u64) -> CellOutput {
+    CellOutput::new_builder()
+        .lock(lock)
+        .type_(type_.pack())
+        .capacity(Capacity::shannons(capacity * CKB).pack())
+        .build()
+}
+
+// The stake AT cell of the staker, its delegate requirement cell, the metadata
+// cell and the capacity cells of the staker.
+fn commit_cells(ckb: &MockCkbRpc, ctx: &ChainContext) -> TransactionView {
+    let type_ids = type_ids();
+    let token_lock = OmniEth::lock(ctx, &STAKER);
+
+    let stake_data = AStakeAtCellData::from(StakeAtCellData {
+        lock: StakeAtCellLockData {
+            l2_address: STAKER,
+            requirement_info: DelegateRequirementInfo {
+                requirement: DelegateRequirementArgs {
+                    requirement_type_id: REQUIREMENT_TYPE_ID,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    });
+    let requirement_data = DelegateCellData::new_builder()
+        .delegate_requirement(
+            DelegateRequirement {
+                commission_rate:    20,
+                maximum_delegators: 10,
+                threshold:          1000,
+            }
+            .into(),
+        )
+        .build();
+
+    let mut tx = TransactionBuilder::default()
+        .output(output(
+            Stake::lock(ctx, &type_ids.metadata_type_id, &STAKER),
+            Some(Xudt::type_(ctx, &type_ids.xudt_owner.pack())),
+            1000,
+        ))
+        .output_data(token_cell_data(0, stake_data.as_bytes()).pack())
+        .output(output(
+            token_lock.clone(),
+            Some(Delegate::requirement_type(
+                ctx,
+                &type_ids.metadata_type_id,
+                &REQUIREMENT_TYPE_ID,
+            )),
+            1000,
+        ))
+        .output_data(requirement_data.as_bytes().pack())
+        .output(output(
+            Script::default(),
+            Some(Metadata::type_(ctx, &type_ids.metadata_type_id)),
+            1000,
+        ))
+        .output_data(Bytes::new().pack());
+
+    for _ in 0..10 {
+        tx = tx
+            .output(output(token_lock.clone(), None, 1000))
+            .output_data(Bytes::new().pack());
+    }
+    let tx = tx.build();
+    ckb.commit(tx.clone(), 1, 1_000);
+    tx
+}
+
+#[tokio::test]
+async fn set_commission_rate() {
+    let ctx = chain_context();
+    let ckb = MockCkbRpc::new();
+    let cells_tx = commit_cells(&ckb, &ctx);
+
+    let tx = RequirementTxBuilder::new(&ckb, &ctx, type_ids(), STAKER, 30)
+        .build_tx()
+        .await
+        .unwrap();
+
+    // the requirement cell is updated in place
+    assert_eq!(
+        tx.inputs().get(0).unwrap().previous_output(),
+        OutPoint::new(cells_tx.hash(), 1)
+    );
+    assert_eq!(
+        tx.outputs().get(0).unwrap(),
+        cells_tx.outputs().get(1).unwrap()
+    );
+
+    let data = DelegateCellData::from_slice(&tx.outputs_data().get(0).unwrap().raw_data())
+        .unwrap()
+        .delegate_requirement();
+    assert_eq!(u8::from(data.commission_rate()), 30);
+    assert_eq!(to_u128(&data.threshold()), 1000);
+}
+
+#[tokio::test]
+async fn reject_commission_rate_over_100() {
+    let ctx = chain_context();
+    let ckb = MockCkbRpc::new();
+    commit_cells(&ckb, &ctx);
+
+    let err = RequirementTxBuilder::new(&ckb, &ctx, type_ids(), STAKER, 101)
+        .build_tx()
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<CkbTxErr>(),
+        Some(CkbTxErr::CommissionRate(101))
+    ));
+}
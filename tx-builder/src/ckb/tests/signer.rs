@@ -0,0 +1,212 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use ckb_jsonrpc_types::Transaction;
+use ckb_sdk::{ScriptGroup, ScriptGroupType};
+use ckb_types::bytes::Bytes;
+use ckb_types::core::{Capacity, TransactionBuilder, TransactionView};
+use ckb_types::packed::{self, Byte32, CellInput, CellOutput, OutPoint, Script, WitnessArgs};
+use ckb_types::prelude::{Builder, Entity, Pack, Unpack};
+use ckb_types::{H160, H256};
+
+use common::utils::mock::MockCkbRpc;
+
+use crate::ckb::helper::Tx;
+use crate::ckb::signer::{ExternalSigner, ExternalTransport, LockConfig, SignRequest, TxSigner};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn signer_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("spark-signer-test").join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// The witness 0 is of a type script, the witness 1 is of the signed lock.
+fn unsigned_tx() -> TransactionView {
+    TransactionBuilder::default()
+        .input(CellInput::new(OutPoint::new(Byte32::default(), 0), 0))
+        .input(CellInput::new(OutPoint::new(Byte32::default(), 1), 0))
+        .witness(
+            WitnessArgs::new_builder()
+                .input_type(Some(Bytes::from(vec![2u8; 8])).pack())
+                .build()
+                .as_bytes()
+                .pack(),
+        )
+        .witness(
+            WitnessArgs::new_builder()
+                .lock(Some(Bytes::from(vec![0u8; 65])).pack())
+                .build()
+                .as_bytes()
+                .pack(),
+        )
+        .build()
+}
+
+fn script_groups() -> Vec<ScriptGroup> {
+    vec![ScriptGroup {
+        script:         Script::default(),
+        group_type:     ScriptGroupType::Lock,
+        input_indices:  vec![1],
+        output_indices: vec![],
+    }]
+}
+
+fn witness(tx: &TransactionView, index: usize) -> WitnessArgs {
+    WitnessArgs::from_slice(&tx.witnesses().get(index).unwrap().raw_data()).unwrap()
+}
+
+fn set_witness(tx: TransactionView, index: usize, witness: WitnessArgs) -> TransactionView {
+    let mut witnesses = tx.witnesses().into_iter().collect::<Vec<_>>();
+    witnesses[index] = witness.as_bytes().pack();
+    tx.as_advanced_builder().set_witnesses(witnesses).build()
+}
+
+fn sign_lock(tx: TransactionView, index: usize) -> TransactionView {
+    let signed = witness(&tx, index)
+        .as_builder()
+        .lock(Some(Bytes::from(vec![1u8; 65])).pack())
+        .build();
+    set_witness(tx, index, signed)
+}
+
+fn sign_and_change_type(tx: TransactionView, index: usize) -> TransactionView {
+    let tx = sign_lock(tx, index);
+    let changed = witness(&tx, index)
+        .as_builder()
+        .output_type(Some(Bytes::from(vec![3u8; 8])).pack())
+        .build();
+    set_witness(tx, index, changed)
+}
+
+fn sign_and_change_other(tx: TransactionView, index: usize) -> TransactionView {
+    let tx = sign_lock(tx, index);
+    set_witness(tx, 0, WitnessArgs::default())
+}
+
+// Plays the external signer of a directory.
+async fn respond(
+    dir: PathBuf,
+    tx_hash: String,
+    sign: fn(TransactionView, usize) -> TransactionView,
+    half_written: bool,
+) {
+    let request_path = dir.join(format!("{}.json", tx_hash));
+    let request = loop {
+        match tokio::fs::read_to_string(&request_path).await {
+            Ok(request) => break request,
+            Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+        }
+    };
+    let request: SignRequest = serde_json::from_str(&request).unwrap();
+    let tx = packed::Transaction::from(request.tx.inner).into_view();
+    let signed = sign(tx, request.script_groups[0].input_indices[0]);
+    let signed = serde_json::to_string(&Transaction::from(signed.data())).unwrap();
+
+    let signed_path = dir.join(format!("{}.signed.json", tx_hash));
+    if half_written {
+        tokio::fs::write(&signed_path, &signed[..signed.len() / 2])
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+    }
+    let tmp_path = dir.join(format!("{}.signed.json.tmp", tx_hash));
+    tokio::fs::write(&tmp_path, signed).await.unwrap();
+    tokio::fs::rename(&tmp_path, &signed_path).await.unwrap();
+}
+
+async fn sign_by_dir(
+    name: &str,
+    sign: fn(TransactionView, usize) -> TransactionView,
+    half_written: bool,
+) -> (anyhow::Result<TransactionView>, PathBuf) {
+    let dir = signer_dir(name);
+    let tx = unsigned_tx();
+    let tx_hash: H256 = tx.hash().unpack();
+    let responder = tokio::spawn(respond(
+        dir.clone(),
+        format!("0x{}", tx_hash),
+        sign,
+        half_written,
+    ));
+
+    let signer = ExternalSigner::new(
+        LockConfig::OmniEth {
+            address: H160::default(),
+        },
+        ExternalTransport::Dir(dir.clone()),
+        TIMEOUT,
+    );
+    let signed = signer.sign(tx, &script_groups()).await;
+    responder.await.unwrap();
+    (signed, dir)
+}
+
+#[tokio::test]
+async fn sign_by_dir_and_clean_up() {
+    let (signed, dir) = sign_by_dir("clean_up", sign_lock, false).await;
+
+    let signed = signed.unwrap();
+    assert_eq!(signed.hash(), unsigned_tx().hash());
+    assert_eq!(
+        witness(&signed, 1).lock().to_opt().unwrap().raw_data(),
+        Bytes::from(vec![1u8; 65])
+    );
+    // the request and the response are removed
+    assert_eq!(std::fs::read_dir(dir).unwrap().count(), 0);
+}
+
+#[tokio::test]
+async fn read_half_written_response_again() {
+    let (signed, dir) = sign_by_dir("half_written", sign_lock, true).await;
+
+    assert!(signed.is_ok());
+    assert_eq!(std::fs::read_dir(dir).unwrap().count(), 0);
+}
+
+#[tokio::test]
+async fn reject_changed_type_witness() {
+    let (signed, _) = sign_by_dir("changed_type", sign_and_change_type, false).await;
+    assert!(signed.is_err());
+}
+
+#[tokio::test]
+async fn reject_changed_unsigned_witness() {
+    let (signed, _) = sign_by_dir("changed_other", sign_and_change_other, false).await;
+    assert!(signed.is_err());
+}
+
+#[tokio::test]
+async fn lock_script_group_of_all_inputs() {
+    let ckb = MockCkbRpc::new();
+    let kicker_lock = Script::new_builder().args(vec![1u8].pack()).build();
+    let other_lock = Script::new_builder().args(vec![2u8].pack()).build();
+    let cell = |lock: &Script| {
+        CellOutput::new_builder()
+            .lock(lock.clone())
+            .capacity(Capacity::shannons(1000).pack())
+            .build()
+    };
+
+    let genesis = TransactionBuilder::default()
+        .output(cell(&kicker_lock))
+        .output(cell(&other_lock))
+        .output(cell(&kicker_lock))
+        .outputs_data(vec![Bytes::new().pack(); 3])
+        .build();
+    ckb.commit(genesis.clone(), 1, 1_000);
+
+    let tx = TransactionBuilder::default()
+        .inputs((0..3).map(|i| CellInput::new(OutPoint::new(genesis.hash(), i), 0)))
+        .build();
+    let tx = Tx::new(&ckb, tx);
+
+    let group = tx.lock_script_group(&kicker_lock).await.unwrap();
+    assert_eq!(group.input_indices, vec![0, 2]);
+    assert!(matches!(group.group_type, ScriptGroupType::Lock));
+
+    let missing_lock = Script::new_builder().args(vec![3u8].pack()).build();
+    assert!(tx.lock_script_group(&missing_lock).await.is_err());
+}
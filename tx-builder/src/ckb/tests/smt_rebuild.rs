@@ -0,0 +1,300 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use ckb_types::bytes::Bytes;
+use ckb_types::core::{Capacity, TransactionBuilder, TransactionView};
+use ckb_types::packed::{CellDep, CellInput, CellOutput, OutPoint, Script, WitnessArgs};
+use ckb_types::prelude::{Builder, Entity, Pack};
+use ckb_types::{h160, h256, H160};
+
+use common::traits::smt::StakeSmtStorage;
+use common::types::axon_types::{
+    checkpoint::CheckpointCellData, metadata::MetadataCellData as AMetadataCellData,
+    stake::StakeAtCellData as AStakeAtCellData, stake::StakeSmtCellData as AStakeSmtCellData,
+    stake::StakeSmtWitness as AStakeSmtWitness,
+};
+use common::types::smt::{Root, UserAmount};
+use common::types::tx_builder::{
+    ChainContext, FeeParams, Metadata as MetadataInfo, NetworkParams, NetworkType, StakeItem,
+    TypeIds,
+};
+use common::utils::convert::{to_eth_h160, to_uint64};
+use common::utils::mock::MockCkbRpc;
+use storage::SmtManager;
+
+use crate::ckb::define::types::{
+    MetadataCellData, StakeAtCellData, StakeAtCellLockData, StakeInfo, StakeSmtCellData,
+    StakeSmtUpdateInfo, StakeSmtWitness,
+};
+use crate::ckb::helper::{token_cell_data, Checkpoint, Delegate, Metadata, Reward, Stake};
+use crate::ckb::registry::builtin_scripts;
+use crate::ckb::smt_rebuild::{apply_delegate_delta, apply_stake_delta, SmtRebuilder};
+
+const STAKER_A: H160 = h160!("0x88fc88b65d9a613a1ad53fb2f41e5cb9c741fb6d");
+const STAKER_B: H160 = h160!("0x7e1c2a1f0ab7d8c7b1e8b1c5d4e3f2a1b0c9d8e7");
+
+// the checkpoint is of epoch 1, so the deltas of epoch 3 take effect
+const EPOCH: u64 = 3;
+
+fn chain_context() -> ChainContext {
+    ChainContext {
+        network:  NetworkType::Testnet,
+        params:   NetworkParams::builtin(&NetworkType::Testnet).unwrap(),
+        scripts:  Arc::new(builtin_scripts(&NetworkType::Testnet).unwrap()),
+        type_ids: TypeIds {
+            metadata_type_id: h256!("0x1"),
+            checkpoint_type_id: h256!("0x2"),
+            stake_smt_type_id: h256!("0x3"),
+            delegate_smt_type_id: h256!("0x4"),
+            reward_smt_type_id: h256!("0x5"),
+            ..Default::default()
+        },
+        fee:      FeeParams::default(),
+    }
+}
+
+fn smt_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join("spark-rebuild-test").join(name);
+    let _ = std::fs::remove_dir_all(&path);
+    path
+}
+
+fn output(lock: Script, type_: Option<Script>) -> CellOutput {
+    CellOutput::new_builder()
+        .lock(lock)
+        .type_(type_.pack())
+        .capacity(Capacity::shannons(1000).pack())
+        .build()
+}
+
+fn stake_smt_data(root: Root) -> Bytes {
+    AStakeSmtCellData::from(StakeSmtCellData {
+        smt_root: root,
+        ..Default::default()
+    })
+    .as_bytes()
+}
+
+fn stake_at_data(staker: &H160, is_increase: bool, amount: u128) -> Bytes {
+    let data = AStakeAtCellData::from(StakeAtCellData {
+        lock: StakeAtCellLockData {
+            l2_address: staker.clone(),
+            stake_info: StakeItem {
+                is_increase,
+                amount,
+                inauguration_epoch: EPOCH,
+            },
+            ..Default::default()
+        },
+    });
+    token_cell_data(amount, data.as_bytes())
+}
+
+// The stake smt cell, the delegate smt cell, the reward smt cell, the
+// checkpoint cell, the metadata cell and a stake AT cell of every delta.
+fn init_tx(ctx: &ChainContext, deltas: &[(H160, bool, u128)]) -> TransactionView {
+    let type_ids = &ctx.type_ids;
+    let checkpoint = CheckpointCellData::new_builder()
+        .epoch(to_uint64(EPOCH - 2))
+        .build();
+    let metadata = AMetadataCellData::from(MetadataCellData {
+        metadata: vec![MetadataInfo::default(), MetadataInfo {
+            quorum: 2,
+            ..Default::default()
+        }],
+        ..Default::default()
+    });
+
+    let mut tx = TransactionBuilder::default()
+        .output(output(
+            Script::default(),
+            Some(Stake::smt_type(ctx, &type_ids.stake_smt_type_id)),
+        ))
+        .output_data(stake_smt_data(Root::zero()).pack())
+        .output(output(
+            Script::default(),
+            Some(Delegate::smt_type(ctx, &type_ids.delegate_smt_type_id)),
+        ))
+        .output_data(Bytes::new().pack())
+        .output(output(
+            Script::default(),
+            Some(Reward::smt_type(ctx, &type_ids.reward_smt_type_id)),
+        ))
+        .output_data(Bytes::new().pack())
+        .output(output(
+            Script::default(),
+            Some(Checkpoint::type_(ctx, &type_ids.checkpoint_type_id)),
+        ))
+        .output_data(checkpoint.as_bytes().pack())
+        .output(output(
+            Script::default(),
+            Some(Metadata::type_(ctx, &type_ids.metadata_type_id)),
+        ))
+        .output_data(metadata.as_bytes().pack());
+
+    for (staker, is_increase, amount) in deltas.iter() {
+        tx = tx
+            .output(output(
+                Stake::lock(ctx, &type_ids.metadata_type_id, staker),
+                None,
+            ))
+            .output_data(stake_at_data(staker, *is_increase, *amount).pack());
+    }
+    tx.build()
+}
+
+// A stake smt tx spending the stake smt cell and the stake AT cells, with the
+// old stake smt in its witness.
+fn stake_smt_tx(
+    ctx: &ChainContext,
+    stake_smt_cell: OutPoint,
+    init_tx: &TransactionView,
+    stake_at_cells: &[u32],
+    old_smt: &[(H160, u128)],
+    root: Root,
+) -> TransactionView {
+    let witness = AStakeSmtWitness::from(StakeSmtWitness {
+        mode:        1,
+        update_info: StakeSmtUpdateInfo {
+            all_stake_infos: old_smt
+                .iter()
+                .map(|(addr, amount)| StakeInfo {
+                    addr:   addr.clone(),
+                    amount: *amount,
+                })
+                .collect(),
+            ..Default::default()
+        },
+    });
+    let witness = WitnessArgs::new_builder()
+        .input_type(Some(witness.as_bytes()).pack())
+        .build();
+    let dep = |index| {
+        CellDep::new_builder()
+            .out_point(OutPoint::new(init_tx.hash(), index))
+            .build()
+    };
+
+    let mut tx = TransactionBuilder::default()
+        .input(CellInput::new(stake_smt_cell, 0))
+        .witness(witness.as_bytes().pack())
+        .cell_dep(dep(3))
+        .cell_dep(dep(4))
+        .output(output(
+            Script::default(),
+            Some(Stake::smt_type(ctx, &ctx.type_ids.stake_smt_type_id)),
+        ))
+        .output_data(stake_smt_data(root).pack());
+    for index in stake_at_cells.iter() {
+        tx = tx.input(CellInput::new(OutPoint::new(init_tx.hash(), *index), 0));
+    }
+    tx.build()
+}
+
+// The stake smt root of the amounts in the epoch.
+async fn expected_root(name: &str, amounts: &[(H160, u128)]) -> Root {
+    let smt = SmtManager::new(smt_path(name));
+    let amounts = amounts
+        .iter()
+        .map(|(staker, amount)| UserAmount {
+            user:        to_eth_h160(staker),
+            amount:      *amount,
+            is_increase: true,
+        })
+        .collect();
+    StakeSmtStorage::insert(&smt, EPOCH, amounts).await.unwrap();
+    StakeSmtStorage::get_top_root(&smt).await.unwrap()
+}
+
+// The stakers A and B stake 100 and 50, then A unstakes `decrease`.
+async fn rebuild(name: &str, decrease: u128, root: Root) -> anyhow::Result<Root> {
+    let ctx = chain_context();
+    let ckb = MockCkbRpc::new();
+
+    let init_tx = init_tx(&ctx, &[
+        (STAKER_A, true, 100),
+        (STAKER_B, true, 50),
+        (STAKER_A, false, decrease),
+    ]);
+    ckb.commit(init_tx.clone(), 1, 1_000);
+
+    let first_root = expected_root(&format!("{}-first", name), &[
+        (STAKER_A, 100),
+        (STAKER_B, 50),
+    ])
+    .await;
+    let first_tx = stake_smt_tx(
+        &ctx,
+        OutPoint::new(init_tx.hash(), 0),
+        &init_tx,
+        &[5, 6],
+        &[],
+        first_root,
+    );
+    ckb.commit(first_tx.clone(), 2, 2_000);
+
+    let second_tx = stake_smt_tx(
+        &ctx,
+        OutPoint::new(first_tx.hash(), 0),
+        &init_tx,
+        &[7],
+        &[(STAKER_A, 100), (STAKER_B, 50)],
+        root,
+    );
+    ckb.commit(second_tx, 3, 3_000);
+
+    let smt = SmtManager::new(smt_path(name));
+    SmtRebuilder::new(&ckb, &ctx, &smt).rebuild().await?;
+    StakeSmtStorage::get_top_root(&smt).await
+}
+
+#[tokio::test]
+async fn rebuild_stake_smt() {
+    let root = expected_root("expected", &[(STAKER_A, 70), (STAKER_B, 50)]).await;
+
+    let rebuilt_root = rebuild("rebuild", 30, root).await.unwrap();
+    assert_eq!(rebuilt_root, root);
+}
+
+#[tokio::test]
+async fn rebuild_stake_smt_with_root_mismatch() {
+    // the root of A unstaking 20 rather than 30
+    let root = expected_root("mismatch-expected", &[(STAKER_A, 80), (STAKER_B, 50)]).await;
+
+    let err = rebuild("mismatch", 30, root).await.unwrap_err();
+    assert!(err.to_string().contains("stake smt root mismatch"));
+}
+
+// The same as the stake smt tx builder, A unstaking more than its amount is
+// skipped.
+#[tokio::test]
+async fn rebuild_stake_smt_with_decrease_over_amount() {
+    let root = expected_root("over-expected", &[(STAKER_A, 100), (STAKER_B, 50)]).await;
+
+    let rebuilt_root = rebuild("over", 130, root).await.unwrap();
+    assert_eq!(rebuilt_root, root);
+}
+
+#[test]
+fn replay_over_redeem() {
+    let user = to_eth_h160(&STAKER_A);
+
+    // a staker keeps its amount, a delegator redeems the whole amount
+    assert_eq!(
+        apply_stake_delta(&user, Some(100), false, 130).unwrap(),
+        100
+    );
+    assert_eq!(
+        apply_delegate_delta(&user, Some(100), false, 130).unwrap(),
+        0
+    );
+
+    assert_eq!(apply_stake_delta(&user, Some(100), false, 30).unwrap(), 70);
+    assert_eq!(
+        apply_delegate_delta(&user, Some(100), false, 30).unwrap(),
+        70
+    );
+
+    assert!(apply_stake_delta(&user, None, false, 30).is_err());
+    assert!(apply_delegate_delta(&user, None, false, 30).is_err());
+}
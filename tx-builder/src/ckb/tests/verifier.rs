@@ -0,0 +1,166 @@
+use ckb_types::bytes::Bytes;
+use ckb_types::core::{Capacity, ScriptHashType, TransactionBuilder, TransactionView};
+use ckb_types::packed::{CellDep, CellInput, CellOutput, OutPoint, Script};
+use ckb_types::prelude::{Builder, Entity, Pack, Unpack};
+use ckb_types::H256;
+
+use common::utils::mock::MockCkbRpc;
+
+use crate::ckb::define::constants::MAX_TX_CYCLES;
+use crate::ckb::define::error::CkbTxErr;
+use crate::ckb::helper::Tx;
+
+const CODE_VADDR: u64 = 0x10000;
+// the elf header and a program header
+const CODE_OFFSET: u64 = 64 + 56;
+
+// A RISC-V ELF exiting with `code`:
+//   li a0, code
+//   li a7, 93
+//   ecall
+fn exit_script(code: u8) -> Bytes {
+    let text = [
+        0x0000_0513 | ((code as u32) << 20), // addi a0, zero, code
+        0x05d0_0893,                         // addi a7, zero, 93
+        0x0000_0073,                         // ecall
+    ]
+    .iter()
+    .flat_map(|inst| inst.to_le_bytes())
+    .collect::<Vec<u8>>();
+    let size = CODE_OFFSET + text.len() as u64;
+
+    let mut elf = vec![0x7f, b'E', b'L', b'F', 2, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    elf.extend_from_slice(&2u16.to_le_bytes()); // executable
+    elf.extend_from_slice(&0xf3u16.to_le_bytes()); // RISC-V
+    elf.extend_from_slice(&1u32.to_le_bytes());
+    elf.extend_from_slice(&(CODE_VADDR + CODE_OFFSET).to_le_bytes()); // entry
+    elf.extend_from_slice(&64u64.to_le_bytes()); // program headers
+    elf.extend_from_slice(&0u64.to_le_bytes()); // no section headers
+    elf.extend_from_slice(&0u32.to_le_bytes());
+    elf.extend_from_slice(&64u16.to_le_bytes());
+    elf.extend_from_slice(&56u16.to_le_bytes());
+    elf.extend_from_slice(&1u16.to_le_bytes());
+    elf.extend_from_slice(&64u16.to_le_bytes());
+    elf.extend_from_slice(&0u16.to_le_bytes());
+    elf.extend_from_slice(&0u16.to_le_bytes());
+
+    // a loadable segment of the whole file, readable and executable
+    elf.extend_from_slice(&1u32.to_le_bytes());
+    elf.extend_from_slice(&5u32.to_le_bytes());
+    elf.extend_from_slice(&0u64.to_le_bytes());
+    elf.extend_from_slice(&CODE_VADDR.to_le_bytes());
+    elf.extend_from_slice(&CODE_VADDR.to_le_bytes());
+    elf.extend_from_slice(&size.to_le_bytes());
+    elf.extend_from_slice(&size.to_le_bytes());
+    elf.extend_from_slice(&0x1000u64.to_le_bytes());
+
+    elf.extend_from_slice(&text);
+    Bytes::from(elf)
+}
+
+fn lock(code: &Bytes) -> Script {
+    Script::new_builder()
+        .code_hash(CellOutput::calc_data_hash(code))
+        .hash_type(ScriptHashType::Data1.into())
+        .build()
+}
+
+fn output(lock: Script, data: &Bytes) -> CellOutput {
+    CellOutput::new_builder()
+        .lock(lock)
+        .build_exact_capacity(Capacity::bytes(data.len()).unwrap())
+        .unwrap()
+}
+
+// The code cells of the scripts exiting with 0 and 1, and a cell locked by each
+// of them.
+fn deploy_tx() -> TransactionView {
+    let success = exit_script(0);
+    let failure = exit_script(1);
+
+    TransactionBuilder::default()
+        .output(output(Script::default(), &success))
+        .output_data(success.pack())
+        .output(output(Script::default(), &failure))
+        .output_data(failure.pack())
+        .output(output(lock(&success), &Bytes::new()))
+        .output_data(Bytes::new().pack())
+        .output(output(lock(&failure), &Bytes::new()))
+        .output_data(Bytes::new().pack())
+        .build()
+}
+
+fn spend_tx(deploy_tx: &TransactionView, deps: &[u32], inputs: &[u32]) -> TransactionView {
+    let out_point = |index| OutPoint::new(deploy_tx.hash(), index);
+
+    let mut tx = TransactionBuilder::default()
+        .output(output(Script::default(), &Bytes::new()))
+        .output_data(Bytes::new().pack());
+    for index in deps.iter() {
+        tx = tx.cell_dep(CellDep::new_builder().out_point(out_point(*index)).build());
+    }
+    for index in inputs.iter() {
+        tx = tx
+            .input(CellInput::new(out_point(*index), 0))
+            .witness(Bytes::new().pack());
+    }
+    tx.build()
+}
+
+fn assert_failed_script(err: anyhow::Error, lock: &Script, code: Option<i8>) {
+    match err.downcast_ref::<CkbTxErr>() {
+        Some(CkbTxErr::ScriptVerification {
+            script_hash,
+            exit_code,
+            ..
+        }) => {
+            let lock_hash: H256 = lock.calc_script_hash().unpack();
+            assert_eq!(*script_hash, lock_hash);
+            assert_eq!(*exit_code, code);
+        }
+        _ => panic!("unexpected error: {}", err),
+    }
+}
+
+#[tokio::test]
+async fn verify_tx() {
+    let ckb = MockCkbRpc::new();
+    let deploy_tx = deploy_tx();
+    ckb.commit(deploy_tx.clone(), 1, 1_000);
+
+    let tx = spend_tx(&deploy_tx, &[0], &[2]);
+    let verification = Tx::new(&ckb, tx).verify(MAX_TX_CYCLES).await.unwrap();
+
+    let lock_hash: H256 = lock(&exit_script(0)).calc_script_hash().unpack();
+    assert_eq!(verification.groups.len(), 1);
+    assert_eq!(verification.groups[0].script_hash, lock_hash);
+    assert!(verification.cycles > 0);
+    assert_eq!(verification.cycles, verification.groups[0].cycles);
+}
+
+#[tokio::test]
+async fn verify_tampered_tx() {
+    let ckb = MockCkbRpc::new();
+    let deploy_tx = deploy_tx();
+    ckb.commit(deploy_tx.clone(), 1, 1_000);
+
+    // the input is replaced by a cell of the failing lock
+    let tx = spend_tx(&deploy_tx, &[0, 1], &[2]);
+    let tx = tx
+        .as_advanced_builder()
+        .set_inputs(vec![CellInput::new(OutPoint::new(deploy_tx.hash(), 3), 0)])
+        .build();
+    let err = Tx::new(&ckb, tx).verify(MAX_TX_CYCLES).await.unwrap_err();
+    assert_failed_script(err, &lock(&exit_script(1)), Some(1));
+
+    // the code cell of the lock is removed from the cell deps
+    let tx = spend_tx(&deploy_tx, &[0], &[2]);
+    let tx = tx.as_advanced_builder().set_cell_deps(vec![]).build();
+    let err = Tx::new(&ckb, tx).verify(MAX_TX_CYCLES).await.unwrap_err();
+    assert_failed_script(err, &lock(&exit_script(0)), None);
+
+    // the cycles run out
+    let tx = spend_tx(&deploy_tx, &[0], &[2]);
+    let err = Tx::new(&ckb, tx).verify(1).await.unwrap_err();
+    assert_failed_script(err, &lock(&exit_script(0)), None);
+}
@@ -11,7 +11,7 @@ use common::traits::ckb_rpc_client::CkbRpc;
 use common::traits::tx_builder::IWithdrawTxBuilder;
 use common::types::axon_types::withdraw::*;
 use common::types::ckb_rpc_client::Cell;
-use common::types::tx_builder::{Amount, Epoch, EthAddress, StakeTypeIds};
+use common::types::tx_builder::{Amount, ChainContext, Epoch, EthAddress, StakeTypeIds};
 use common::utils::convert::*;
 
 use crate::ckb::define::constants::TOKEN_BYTES;
@@ -24,6 +24,7 @@ use super::define::error::CkbTxErr;
 
 pub struct WithdrawTxBuilder<'a, C: CkbRpc> {
     ckb:           &'a C,
+    ctx:           &'a ChainContext,
     type_ids:      StakeTypeIds,
     current_epoch: Epoch,
     user:          EthAddress,
@@ -34,13 +35,20 @@ pub struct WithdrawTxBuilder<'a, C: CkbRpc> {
 
 #[async_trait]
 impl<'a, C: CkbRpc> IWithdrawTxBuilder<'a, C> for WithdrawTxBuilder<'a, C> {
-    fn new(ckb: &'a C, type_ids: StakeTypeIds, user: EthAddress, current_epoch: Epoch) -> Self {
-        let withdraw_lock = Withdraw::lock(&type_ids.metadata_type_id, &user);
-        let token_lock = OmniEth::lock(&user);
-        let xudt = Xudt::type_(&type_ids.xudt_owner.pack());
+    fn new(
+        ckb: &'a C,
+        ctx: &'a ChainContext,
+        type_ids: StakeTypeIds,
+        user: EthAddress,
+        current_epoch: Epoch,
+    ) -> Self {
+        let withdraw_lock = Withdraw::lock(ctx, &type_ids.metadata_type_id, &user);
+        let token_lock = OmniEth::lock(ctx, &user);
+        let xudt = Xudt::type_(ctx, &type_ids.xudt_owner.pack());
 
         Self {
             ckb,
+            ctx,
             type_ids,
             current_epoch,
             user,
@@ -78,12 +86,12 @@ impl<'a, C: CkbRpc> IWithdrawTxBuilder<'a, C> for WithdrawTxBuilder<'a, C> {
         ];
 
         let cell_deps = vec![
-            OmniEth::lock_dep(),
-            Secp256k1::lock_dep(),
-            Xudt::type_dep(),
-            Withdraw::lock_dep(),
-            Checkpoint::cell_dep(self.ckb, &self.type_ids.checkpoint_type_id).await?,
-            Metadata::cell_dep(self.ckb, &self.type_ids.metadata_type_id).await?,
+            OmniEth::lock_dep(self.ctx),
+            Secp256k1::lock_dep(self.ctx),
+            Xudt::type_dep(self.ctx),
+            Withdraw::lock_dep(self.ctx),
+            Checkpoint::cell_dep(self.ckb, self.ctx, &self.type_ids.checkpoint_type_id).await?,
+            Metadata::cell_dep(self.ckb, self.ctx, &self.type_ids.metadata_type_id).await?,
         ];
 
         let witnesses = vec![
@@ -101,7 +109,7 @@ impl<'a, C: CkbRpc> IWithdrawTxBuilder<'a, C> for WithdrawTxBuilder<'a, C> {
             .build();
 
         let mut tx = Tx::new(self.ckb, tx);
-        tx.balance(self.token_lock.clone()).await?;
+        tx.balance(self.ctx, self.token_lock.clone()).await?;
 
         Ok(tx.inner())
     }
@@ -137,7 +145,7 @@ impl<'a, C: CkbRpc> WithdrawTxBuilder<'a, C> {
         Ok(amount)
     }
 
-    async fn build_data(
+    pub(crate) async fn build_data(
         &self,
         mut wallet_amount: Amount,
         mut withdraw_data: Bytes,
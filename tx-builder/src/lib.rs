@@ -1,9 +1,22 @@
 pub mod axon;
 pub mod ckb;
 
-use common::types::tx_builder::NetworkType;
+use common::types::tx_builder::{NetworkType, ScriptRegistry};
 use std::sync::Arc;
 
+use ckb::registry::builtin_scripts;
+
+/// Sets the network and its builtin scripts of `default_chain_context`. The
+/// scripts of a custom network are set by `set_script_registry`.
+#[deprecated(note = "build a ChainContext and pass it explicitly")]
 pub fn set_network_type(network_type: NetworkType) {
+    if let Some(registry) = builtin_scripts(&network_type) {
+        (*ckb::SCRIPT_REGISTRY).swap(Arc::new(registry));
+    }
     (*ckb::NETWORK_TYPE).swap(Arc::new(network_type));
 }
+
+#[deprecated(note = "build a ChainContext and pass it explicitly")]
+pub fn set_script_registry(registry: ScriptRegistry) {
+    (*ckb::SCRIPT_REGISTRY).swap(Arc::new(registry));
+}